@@ -4,10 +4,11 @@
 //! This is the single source of truth for device information, status, and capabilities.
 
 use std::collections::HashMap;
+use std::sync::RwLock;
 use serde::{Deserialize, Serialize};
 
 /// Unified Device State — The canonical representation of a connected device
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UnifiedDeviceState {
     /// Unique device identifier (serial number or UUID)
@@ -49,7 +50,7 @@ pub struct UnifiedDeviceState {
 }
 
 /// Device identity information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeviceIdentity {
     /// Manufacturer name
@@ -81,7 +82,7 @@ pub struct DeviceIdentity {
 }
 
 /// Connection state
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConnectionState {
     /// Connection type
@@ -144,7 +145,7 @@ pub enum ConnectionSpeed {
 }
 
 /// Hardware information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HardwareInfo {
     /// CPU architecture
@@ -180,7 +181,7 @@ pub enum CpuArchitecture {
 }
 
 /// Screen information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ScreenInfo {
     pub width: u32,
@@ -189,7 +190,7 @@ pub struct ScreenInfo {
 }
 
 /// Software information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SoftwareInfo {
     /// Operating system
@@ -229,7 +230,7 @@ pub enum OperatingSystem {
 }
 
 /// Security state
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SecurityState {
     /// Bootloader lock state
@@ -257,6 +258,34 @@ pub struct SecurityState {
     pub rooted: Option<bool>,
 }
 
+impl SecurityState {
+    /// Security state from fastboot variables (`unlocked`, `verified-boot-state`).
+    /// Everything fastboot cannot report is left unknown.
+    pub fn from_fastboot_vars(vars: &HashMap<String, String>) -> Self {
+        let get = |key: &str| vars.get(key).map(|v| v.trim().to_ascii_lowercase());
+        Self {
+            bootloader_locked: get("unlocked").and_then(|v| match v.as_str() {
+                "yes" | "true" | "1" => Some(false),
+                "no" | "false" | "0" => Some(true),
+                _ => None,
+            }),
+            verified_boot: get("verified-boot-state").and_then(|v| match v.as_str() {
+                "green" => Some(VerifiedBootState::Green),
+                "yellow" => Some(VerifiedBootState::Yellow),
+                "orange" => Some(VerifiedBootState::Orange),
+                "red" => Some(VerifiedBootState::Red),
+                _ => None,
+            }),
+            encrypted: None,
+            frp_enabled: None,
+            knox_enrolled: None,
+            mdm_enrolled: None,
+            activation_lock: None,
+            rooted: None,
+        }
+    }
+}
+
 /// Verified boot state
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -268,7 +297,7 @@ pub enum VerifiedBootState {
 }
 
 /// Storage partition
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StoragePartition {
     /// Partition name
@@ -294,7 +323,7 @@ pub struct StoragePartition {
 }
 
 /// Battery state
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BatteryState {
     /// Battery level (0-100)
@@ -327,7 +356,7 @@ pub enum BatteryHealth {
 }
 
 /// Current operation state
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OperationState {
     /// Operation ID
@@ -353,7 +382,7 @@ pub struct OperationState {
 }
 
 /// Device capabilities
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct DeviceCapabilities {
     /// Supports ADB
@@ -400,10 +429,62 @@ pub struct DeviceCapabilities {
     
     /// Supports diagnostics
     pub diagnostics: bool,
+    
+    /// Has A/B slots
+    #[serde(default)]
+    pub ab_slots: bool,
+    
+    /// Fastboot is served from userspace (fastbootd) rather than the bootloader
+    #[serde(default)]
+    pub fastbootd: bool,
+    
+    /// Flash storage is raw NAND
+    #[serde(default)]
+    pub raw_nand: bool,
+}
+
+impl DeviceCapabilities {
+    /// Capabilities implied by `fastboot getvar all` output.
+    /// 
+    /// `max-download-size` means fastboot flashing works, a `slot-count` of 2
+    /// or more means A/B, `is-userspace: yes` means fastbootd, and
+    /// `nand-total-size` means raw NAND. Everything else stays false.
+    pub fn from_fastboot_vars(vars: &HashMap<String, String>) -> Self {
+        let get = |key: &str| vars.get(key).map(|v| v.trim().to_ascii_lowercase());
+        Self {
+            fastboot: get("max-download-size").is_some_and(|v| !v.is_empty()),
+            ab_slots: get("slot-count").and_then(|v| v.parse::<u32>().ok()).is_some_and(|n| n >= 2),
+            fastbootd: get("is-userspace").is_some_and(|v| matches!(v.as_str(), "yes" | "true" | "1")),
+            raw_nand: get("nand-total-size").is_some_and(|v| !v.is_empty()),
+            ..Self::default()
+        }
+    }
+    
+    /// Turn on every capability `other` has; nothing is turned off.
+    pub fn merge(&mut self, other: &Self) {
+        self.adb |= other.adb;
+        self.fastboot |= other.fastboot;
+        self.edl |= other.edl;
+        self.dfu |= other.dfu;
+        self.download_mode |= other.download_mode;
+        self.ota |= other.ota;
+        self.sideload |= other.sideload;
+        self.backup |= other.backup;
+        self.restore |= other.restore;
+        self.screenshot |= other.screenshot;
+        self.screen_record |= other.screen_record;
+        self.shell |= other.shell;
+        self.file_transfer |= other.file_transfer;
+        self.install_app |= other.install_app;
+        self.diagnostics |= other.diagnostics;
+        self.ab_slots |= other.ab_slots;
+        self.fastbootd |= other.fastbootd;
+        self.raw_nand |= other.raw_nand;
+    }
 }
 
 /// Device timestamps
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeviceTimestamps {
     /// First seen (Unix timestamp)
@@ -528,10 +609,151 @@ impl UnifiedDeviceState {
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str(json)
     }
+    
+    /// Compare two states of the same device
+    pub fn diff(old: &Self, new: &Self) -> DeviceStateDiff {
+        let mut changed_fields = Vec::new();
+        push_changed_fields("/id", &old.id, &new.id, &mut changed_fields);
+        push_changed_fields("/identity", &old.identity, &new.identity, &mut changed_fields);
+        push_changed_fields("/connection", &old.connection, &new.connection, &mut changed_fields);
+        push_changed_fields("/hardware", &old.hardware, &new.hardware, &mut changed_fields);
+        push_changed_fields("/software", &old.software, &new.software, &mut changed_fields);
+        push_changed_fields("/security", &old.security, &new.security, &mut changed_fields);
+        push_changed_fields("/storage", &old.storage, &new.storage, &mut changed_fields);
+        push_changed_fields("/battery", &old.battery, &new.battery, &mut changed_fields);
+        push_changed_fields("/operation", &old.operation, &new.operation, &mut changed_fields);
+        push_changed_fields("/capabilities", &old.capabilities, &new.capabilities, &mut changed_fields);
+        push_changed_fields("/timestamps", &old.timestamps, &new.timestamps, &mut changed_fields);
+        push_changed_fields("/custom", &old.custom, &new.custom, &mut changed_fields);
+        
+        DeviceStateDiff {
+            changed_fields,
+            mode_changed: old.connection.mode != new.connection.mode,
+            capabilities_changed: old.capabilities != new.capabilities,
+            software_changed: old.software != new.software,
+            security_changed: old.security != new.security,
+        }
+    }
+}
+
+/// Differences between two `UnifiedDeviceState` snapshots
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceStateDiff {
+    /// JSON pointer paths (RFC 6901) of every changed leaf value
+    pub changed_fields: Vec<String>,
+    
+    /// Connection mode changed
+    pub mode_changed: bool,
+    
+    /// Capability flags changed
+    pub capabilities_changed: bool,
+    
+    /// Software information changed
+    pub software_changed: bool,
+    
+    /// Security state changed
+    pub security_changed: bool,
+}
+
+impl DeviceStateDiff {
+    /// Whether the change is worth reporting (mode or capabilities moved)
+    pub fn is_significant(&self) -> bool {
+        self.mode_changed || self.capabilities_changed
+    }
+}
+
+/// Append the JSON pointer of every leaf that differs between `old` and `new`.
+fn push_changed_fields<T: Serialize + PartialEq>(pointer: &str, old: &T, new: &T, out: &mut Vec<String>) {
+    if old == new {
+        return;
+    }
+    match (serde_json::to_value(old), serde_json::to_value(new)) {
+        (Ok(old), Ok(new)) => push_changed_values(pointer, &old, &new, out),
+        _ => out.push(pointer.to_string()),
+    }
+}
+
+fn push_changed_values(pointer: &str, old: &serde_json::Value, new: &serde_json::Value, out: &mut Vec<String>) {
+    use serde_json::Value;
+    
+    if old == new {
+        return;
+    }
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+                push_changed_values(
+                    &child,
+                    old.get(key).unwrap_or(&Value::Null),
+                    new.get(key).unwrap_or(&Value::Null),
+                    out,
+                );
+            }
+        }
+        (Value::Array(old), Value::Array(new)) if old.len() == new.len() => {
+            for (index, (old, new)) in old.iter().zip(new).enumerate() {
+                push_changed_values(&format!("{}/{}", pointer, index), old, new, out);
+            }
+        }
+        _ => out.push(pointer.to_string()),
+    }
+}
+
+/// Device State Store — latest known `UnifiedDeviceState` per device id
+#[derive(Debug, Default)]
+pub struct DeviceStateStore {
+    states: RwLock<HashMap<String, UnifiedDeviceState>>,
+}
+
+impl DeviceStateStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Insert or replace the state for `state.id`
+    pub fn upsert(&self, state: UnifiedDeviceState) {
+        let mut states = self.states.write().unwrap_or_else(|e| e.into_inner());
+        states.insert(state.id.clone(), state);
+    }
+    
+    /// Apply `f` to the stored state for `id`. Returns false if there is none.
+    pub fn update(&self, id: &str, f: impl FnOnce(&mut UnifiedDeviceState)) -> bool {
+        let mut states = self.states.write().unwrap_or_else(|e| e.into_inner());
+        match states.get_mut(id) {
+            Some(state) => {
+                f(state);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    pub fn get(&self, id: &str) -> Option<UnifiedDeviceState> {
+        let states = self.states.read().unwrap_or_else(|e| e.into_inner());
+        states.get(id).cloned()
+    }
+    
+    pub fn remove(&self, id: &str) -> Option<UnifiedDeviceState> {
+        let mut states = self.states.write().unwrap_or_else(|e| e.into_inner());
+        states.remove(id)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.states.read().map(|s| s.len()).unwrap_or(0)
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 /// JSON Schema for validation (can be used by external tools)
-pub const DEVICE_STATE_JSON_SCHEMA: &str = r#"{
+pub const DEVICE_STATE_JSON_SCHEMA: &str = r##"{
     "$schema": "http://json-schema.org/draft-07/schema#",
     "$id": "https://phoenixforge.dev/schemas/unified-device-state.json",
     "title": "Unified Device State",
@@ -681,7 +903,10 @@ pub const DEVICE_STATE_JSON_SCHEMA: &str = r#"{
                 "shell": { "type": "boolean" },
                 "fileTransfer": { "type": "boolean" },
                 "installApp": { "type": "boolean" },
-                "diagnostics": { "type": "boolean" }
+                "diagnostics": { "type": "boolean" },
+                "abSlots": { "type": "boolean" },
+                "fastbootd": { "type": "boolean" },
+                "rawNand": { "type": "boolean" }
             }
         },
         "DeviceTimestamps": {
@@ -695,7 +920,7 @@ pub const DEVICE_STATE_JSON_SCHEMA: &str = r#"{
             }
         }
     }
-}"#;
+}"##;
 
 #[cfg(test)]
 mod tests {
@@ -716,6 +941,60 @@ mod tests {
         assert!(!state.is_flashable());
     }
 
+    #[test]
+    fn test_security_state_from_fastboot_vars() {
+        let vars: HashMap<String, String> = [
+            ("unlocked".to_string(), "yes".to_string()),
+            ("verified-boot-state".to_string(), "orange".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let security = SecurityState::from_fastboot_vars(&vars);
+        assert_eq!(security.bootloader_locked, Some(false));
+        assert_eq!(security.verified_boot, Some(VerifiedBootState::Orange));
+
+        let security = SecurityState::from_fastboot_vars(&HashMap::new());
+        assert_eq!(security.bootloader_locked, None);
+        assert_eq!(security.verified_boot, None);
+    }
+
+    #[test]
+    fn test_capabilities_from_pixel7_fastboot_vars() {
+        // Pixel 7 (panther) bootloader, `fastboot getvar all`
+        let vars: HashMap<String, String> = [
+            ("product", "panther"),
+            ("variant", "GS201 EVT1.1"),
+            ("version-bootloader", "cloudripper-14.0-10758130"),
+            ("version-baseband", "g5300q-230626-230818-B-10646188"),
+            ("max-download-size", "0x10000000"),
+            ("slot-count", "2"),
+            ("current-slot", "a"),
+            ("slot-successful:a", "yes"),
+            ("slot-unbootable:b", "no"),
+            ("is-userspace", "no"),
+            ("secure", "yes"),
+            ("unlocked", "no"),
+            ("off-mode-charge", "1"),
+            ("partition-type:super", "raw"),
+            ("partition-size:super", "0x220000000"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        
+        let caps = DeviceCapabilities::from_fastboot_vars(&vars);
+        assert!(caps.fastboot && caps.ab_slots);
+        assert!(!caps.fastbootd && !caps.raw_nand && !caps.adb);
+        
+        let userspace: HashMap<String, String> = [("is-userspace", "yes"), ("nand-total-size", "0x20000000")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let mut merged = caps.clone();
+        merged.merge(&DeviceCapabilities::from_fastboot_vars(&userspace));
+        assert!(merged.fastboot && merged.ab_slots && merged.fastbootd && merged.raw_nand);
+    }
+
     #[test]
     fn test_serialize_deserialize() {
         let state = UnifiedDeviceState::new(
@@ -750,4 +1029,58 @@ mod tests {
         assert!(!state.is_flashable());
         assert!(state.is_normal());
     }
+
+    #[test]
+    fn test_diff_reports_changed_fields() {
+        let old = UnifiedDeviceState::new(
+            "ABC123".to_string(),
+            "Google".to_string(),
+            "Pixel 8".to_string(),
+            0x18D1,
+            0x4EE7,
+        );
+        let diff = UnifiedDeviceState::diff(&old, &old.clone());
+        assert!(diff.changed_fields.is_empty());
+        assert!(!diff.is_significant());
+        
+        let mut new = old.clone();
+        new.connection.quality = Some(80);
+        new.software.os_version = "14".to_string();
+        let diff = UnifiedDeviceState::diff(&old, &new);
+        assert_eq!(diff.changed_fields, vec!["/connection/quality", "/software/osVersion"]);
+        assert!(diff.software_changed);
+        assert!(!diff.is_significant());
+        
+        new.connection.mode = DeviceMode::Fastboot;
+        new.capabilities.fastboot = true;
+        let diff = UnifiedDeviceState::diff(&old, &new);
+        assert_eq!(
+            diff.changed_fields,
+            vec!["/connection/mode", "/connection/quality", "/software/osVersion", "/capabilities/fastboot"]
+        );
+        assert!(diff.mode_changed && diff.capabilities_changed && !diff.security_changed);
+        assert!(diff.is_significant());
+    }
+
+    #[test]
+    fn test_state_store_upsert_and_update() {
+        let store = DeviceStateStore::new();
+        assert!(store.is_empty());
+        
+        store.upsert(UnifiedDeviceState::new(
+            "ABC123".to_string(),
+            "Google".to_string(),
+            "Pixel 8".to_string(),
+            0x18D1,
+            0x4EE7,
+        ));
+        assert!(store.update("ABC123", |s| s.set_mode(DeviceMode::Fastboot)));
+        assert!(!store.update("MISSING", |s| s.set_mode(DeviceMode::Fastboot)));
+        
+        let state = store.get("ABC123").unwrap();
+        assert!(state.is_flashable());
+        assert_eq!(store.len(), 1);
+        assert!(store.remove("ABC123").is_some());
+        assert!(store.get("ABC123").is_none());
+    }
 }
@@ -4,10 +4,18 @@
 //! Ensures devices are properly recognized across Windows, macOS, and Linux.
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::trapdoor::verification::compute_file_hash;
+
+/// File name of the manifest written into every bundle root
+pub const BUNDLE_MANIFEST_FILE: &str = "manifest.json";
 
 /// Supported operating systems for driver bundling
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TargetOS {
     Windows,
     MacOS,
@@ -68,6 +76,31 @@ pub struct DriverPackRegistry {
     installed: HashMap<String, InstalledDriver>,
 }
 
+/// One pack in a remote update manifest (the manifest is a JSON array of these)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriverPackManifestEntry {
+    pub pack_id: String,
+    /// Semver version of the pack at `download_url`
+    pub version: String,
+    /// https URL of the pack ZIP
+    pub download_url: String,
+    /// SHA-256 of the ZIP, hex
+    pub sha256: String,
+    #[serde(default)]
+    pub changelog: Option<String>,
+}
+
+/// A registered pack with a newer version in the remote manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DriverPackUpdate {
+    pub pack_id: String,
+    pub current_version: String,
+    pub latest_version: String,
+    pub changelog: Option<String>,
+    pub download_url: String,
+    pub sha256: String,
+}
+
 /// Installed driver record
 #[derive(Debug, Clone)]
 pub struct InstalledDriver {
@@ -222,6 +255,93 @@ impl DriverPackRegistry {
         
         required
     }
+
+    /// Fetch the update manifest at `manifest_url` (https only) and list the
+    /// packs for the current OS that have a newer version. Blocking.
+    pub fn check_updates(&self, manifest_url: &str) -> Result<Vec<DriverPackUpdate>, String> {
+        require_https(manifest_url, "Driver manifest")?;
+        let manifest: Vec<DriverPackManifestEntry> = reqwest::blocking::get(manifest_url)
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Failed to fetch driver manifest {}: {}", manifest_url, e))?
+            .json()
+            .map_err(|e| format!("Invalid driver manifest {}: {}", manifest_url, e))?;
+        self.updates_from_manifest(&manifest)
+    }
+
+    /// Compare the manifest against the registered packs for the current OS.
+    ///
+    /// Only strictly newer versions are returned, ordered by pack id. Entries
+    /// for unknown packs are ignored; an update without an https URL or a
+    /// SHA-256 is an error.
+    pub fn updates_from_manifest(&self, manifest: &[DriverPackManifestEntry]) -> Result<Vec<DriverPackUpdate>, String> {
+        let mut updates = Vec::new();
+        for pack in self.get_packs_for_current_os() {
+            let Some(entry) = manifest.iter().find(|e| e.pack_id == pack.id) else {
+                continue;
+            };
+            let current = semver::Version::parse(&pack.version)
+                .map_err(|e| format!("Invalid version '{}' for driver pack {}: {}", pack.version, pack.id, e))?;
+            let latest = semver::Version::parse(&entry.version)
+                .map_err(|e| format!("Invalid manifest version '{}' for driver pack {}: {}", entry.version, pack.id, e))?;
+            if latest > current {
+                require_https(&entry.download_url, &format!("Download for driver pack {}", pack.id))?;
+                if entry.sha256.len() != 64 || !entry.sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(format!("Manifest entry for driver pack {} has no valid sha256", pack.id));
+                }
+                updates.push(DriverPackUpdate {
+                    pack_id: pack.id.clone(),
+                    current_version: pack.version.clone(),
+                    latest_version: entry.version.clone(),
+                    changelog: entry.changelog.clone(),
+                    download_url: entry.download_url.clone(),
+                    sha256: entry.sha256.clone(),
+                });
+            }
+        }
+        updates.sort_by(|a, b| a.pack_id.cmp(&b.pack_id));
+        Ok(updates)
+    }
+
+    /// Download the ZIP for `update` into `dest_dir` as
+    /// `<pack_id>-<latest_version>.zip`. Blocking.
+    ///
+    /// The URL must be https, and the file is only written once its SHA-256
+    /// matches the manifest.
+    pub fn download_pack_update(&self, pack_id: &str, update: &DriverPackUpdate, dest_dir: &Path) -> Result<PathBuf, String> {
+        if update.pack_id != pack_id {
+            return Err(format!("Update is for driver pack {}, not {}", update.pack_id, pack_id));
+        }
+        if !self.packs.contains_key(pack_id) {
+            return Err(format!("Unknown driver pack: {}", pack_id));
+        }
+        require_https(&update.download_url, &format!("Download for driver pack {}", pack_id))?;
+
+        let bytes = reqwest::blocking::get(&update.download_url)
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.bytes())
+            .map_err(|e| format!("Failed to download {}: {}", update.download_url, e))?;
+        let actual = hex::encode(Sha256::digest(&bytes));
+        if !actual.eq_ignore_ascii_case(&update.sha256) {
+            return Err(format!(
+                "Checksum mismatch for {} {}: expected {}, got {}",
+                pack_id, update.latest_version, update.sha256, actual
+            ));
+        }
+
+        std::fs::create_dir_all(dest_dir)
+            .map_err(|e| format!("Failed to create {}: {}", dest_dir.display(), e))?;
+        let path = dest_dir.join(format!("{}-{}.zip", pack_id, update.latest_version));
+        std::fs::write(&path, &bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+}
+
+/// `what` is named in the error when `url` is not an https URL
+fn require_https(url: &str, what: &str) -> Result<(), String> {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if parsed.scheme() == "https" => Ok(()),
+        _ => Err(format!("{} must be an https URL: {}", what, url)),
+    }
 }
 
 impl Default for DriverPackRegistry {
@@ -239,21 +359,72 @@ pub struct DriverBundler {
 impl DriverBundler {
     /// Create new bundler
     pub fn new(bundle_path: PathBuf) -> Self {
+        Self::with_registry(DriverPackRegistry::new(), bundle_path)
+    }
+
+    /// Create a bundler over an existing registry
+    pub fn with_registry(registry: DriverPackRegistry, bundle_path: PathBuf) -> Self {
         Self {
-            registry: DriverPackRegistry::new(),
+            registry,
             bundle_path,
         }
     }
 
     /// Bundle all required drivers for target OS
+    ///
+    /// Copies every pack file into `<bundle_path>/<pack.id>/` after verifying its
+    /// SHA-256 against `DriverFile::checksum`, then writes `manifest.json` into the
+    /// bundle root. Missing optional files are skipped; a missing required file or
+    /// any checksum mismatch aborts the bundle.
     pub fn bundle_for_os(&self, target_os: TargetOS) -> Result<BundleManifest, String> {
-        let packs: Vec<_> = self.registry.packs.values()
+        let mut packs: Vec<_> = self.registry.packs.values()
             .filter(|p| p.target_os == target_os)
             .collect();
+        packs.sort_by(|a, b| a.id.cmp(&b.id));
+
+        std::fs::create_dir_all(&self.bundle_path)
+            .map_err(|e| format!("Failed to create bundle directory {}: {}", self.bundle_path.display(), e))?;
+
+        let mut files = Vec::new();
+        for pack in &packs {
+            let pack_dir = self.bundle_path.join(&pack.id);
+            std::fs::create_dir_all(&pack_dir)
+                .map_err(|e| format!("Failed to create {}: {}", pack_dir.display(), e))?;
+
+            for file in &pack.files {
+                if !file.path.is_file() {
+                    if file.required {
+                        return Err(format!("Required driver file missing for {}: {}", pack.id, file.path.display()));
+                    }
+                    log::warn!("Skipping missing optional driver file for {}: {}", pack.id, file.path.display());
+                    continue;
+                }
+
+                let actual = compute_file_hash(&file.path).map_err(|e| e.to_string())?;
+                if !actual.eq_ignore_ascii_case(&file.checksum) {
+                    return Err(format!(
+                        "Checksum mismatch for {}: expected {}, got {}",
+                        file.path.display(), file.checksum, actual
+                    ));
+                }
+
+                let file_name = file.path.file_name()
+                    .ok_or_else(|| format!("Driver file has no file name: {}", file.path.display()))?;
+                std::fs::copy(&file.path, pack_dir.join(file_name))
+                    .map_err(|e| format!("Failed to copy {}: {}", file.path.display(), e))?;
+
+                files.push(BundledFile {
+                    pack_id: pack.id.clone(),
+                    relative_path: Path::new(&pack.id).join(file_name),
+                    checksum: actual,
+                });
+            }
+        }
 
         let manifest = BundleManifest {
             target_os,
             packs: packs.iter().map(|p| p.id.clone()).collect(),
+            files,
             created_at: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
@@ -261,19 +432,72 @@ impl DriverBundler {
             bundle_path: self.bundle_path.clone(),
         };
 
+        let json = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize bundle manifest: {}", e))?;
+        std::fs::write(self.bundle_path.join(BUNDLE_MANIFEST_FILE), json)
+            .map_err(|e| format!("Failed to write bundle manifest: {}", e))?;
+
         Ok(manifest)
     }
+
+    /// Re-check every file listed in a bundle's manifest
+    ///
+    /// Returns `Ok(false)` if any file is missing or its checksum no longer matches,
+    /// and `Err` if the manifest cannot be read or lists a path outside the bundle.
+    pub fn verify_bundle(bundle_path: &Path) -> Result<bool, String> {
+        let manifest_path = bundle_path.join(BUNDLE_MANIFEST_FILE);
+        let json = std::fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
+        let manifest: BundleManifest = serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid bundle manifest: {}", e))?;
+
+        for file in &manifest.files {
+            if !is_contained_relative_path(&file.relative_path) {
+                return Err(format!(
+                    "Bundle manifest lists a path outside the bundle: {}",
+                    file.relative_path.display()
+                ));
+            }
+            let path = bundle_path.join(&file.relative_path);
+            if !path.is_file() {
+                log::warn!("Bundled driver file missing: {}", path.display());
+                return Ok(false);
+            }
+            let actual = compute_file_hash(&path).map_err(|e| e.to_string())?;
+            if !actual.eq_ignore_ascii_case(&file.checksum) {
+                log::warn!("Bundled driver file checksum mismatch: {}", path.display());
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+/// `path` is relative and only descends: no root, prefix, `.` or `..`
+fn is_contained_relative_path(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path.components().all(|c| matches!(c, std::path::Component::Normal(_)))
 }
 
 /// Bundle manifest
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BundleManifest {
     pub target_os: TargetOS,
     pub packs: Vec<String>,
+    pub files: Vec<BundledFile>,
     pub created_at: u64,
     pub bundle_path: PathBuf,
 }
 
+/// File copied into a bundle, relative to the bundle root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundledFile {
+    pub pack_id: String,
+    pub relative_path: PathBuf,
+    pub checksum: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -291,4 +515,140 @@ mod tests {
         // Should find Android USB drivers on Windows
         assert!(packs.len() >= 0); // Depends on current OS
     }
+
+    fn manifest_entry(pack_id: &str, version: &str) -> DriverPackManifestEntry {
+        DriverPackManifestEntry {
+            pack_id: pack_id.to_string(),
+            version: version.to_string(),
+            download_url: format!("https://example.com/{}.zip", pack_id),
+            sha256: "ab".repeat(32),
+            changelog: Some("Adds new device IDs".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_updates_from_manifest_only_lists_newer_versions() {
+        let mut registry = DriverPackRegistry::new();
+        for (id, version) in [("pack-a", "1.2.0"), ("pack-b", "2.0.0"), ("pack-c", "1.0.0")] {
+            registry.register_pack(DriverPack {
+                id: id.to_string(),
+                name: id.to_string(),
+                version: version.to_string(),
+                vendor: "Test".to_string(),
+                target_os: TargetOS::current(),
+                devices: vec![],
+                files: vec![],
+                install_script: None,
+                uninstall_script: None,
+            });
+        }
+        let manifest = vec![
+            manifest_entry("pack-c", "1.10.0"),
+            manifest_entry("pack-a", "1.2.0"),
+            manifest_entry("pack-b", "1.9.9"),
+            manifest_entry("not-registered", "9.0.0"),
+        ];
+
+        let updates = registry.updates_from_manifest(&manifest).unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].pack_id, "pack-c");
+        assert_eq!(updates[0].current_version, "1.0.0");
+        assert_eq!(updates[0].latest_version, "1.10.0");
+
+        let bad = vec![manifest_entry("pack-a", "latest")];
+        assert!(registry.updates_from_manifest(&bad).is_err());
+
+        let mut plain_http = manifest_entry("pack-c", "1.10.0");
+        plain_http.download_url = "http://example.com/pack-c.zip".to_string();
+        assert!(registry.updates_from_manifest(&[plain_http]).is_err());
+        let mut unchecked = manifest_entry("pack-c", "1.10.0");
+        unchecked.sha256 = String::new();
+        assert!(registry.updates_from_manifest(&[unchecked]).is_err());
+        assert!(registry.check_updates("http://example.com/manifest.json").unwrap_err().contains("https"));
+        let mut update = updates[0].clone();
+        update.download_url = "file:///tmp/pack-c.zip".to_string();
+        let err = registry.download_pack_update("pack-c", &update, Path::new("/nonexistent")).unwrap_err();
+        assert!(err.contains("https"), "{err}");
+    }
+
+    fn bundler_with_file(dir: &Path, contents: &[u8], checksum: Option<&str>, required: bool) -> DriverBundler {
+        let source = dir.join("source").join("driver.inf");
+        std::fs::create_dir_all(source.parent().unwrap()).unwrap();
+        std::fs::write(&source, contents).unwrap();
+        let checksum = match checksum {
+            Some(c) => c.to_string(),
+            None => compute_file_hash(&source).unwrap(),
+        };
+
+        let mut registry = DriverPackRegistry::new();
+        registry.register_pack(DriverPack {
+            id: "test-pack".to_string(),
+            name: "Test Pack".to_string(),
+            version: "1.0.0".to_string(),
+            vendor: "Test".to_string(),
+            target_os: TargetOS::Linux,
+            devices: vec![],
+            files: vec![DriverFile { path: source, checksum, required }],
+            install_script: None,
+            uninstall_script: None,
+        });
+        DriverBundler::with_registry(registry, dir.join("bundle"))
+    }
+
+    #[test]
+    fn test_bundle_copies_files_and_writes_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundler = bundler_with_file(dir.path(), b"[Version]", None, true);
+
+        let manifest = bundler.bundle_for_os(TargetOS::Linux).unwrap();
+        assert_eq!(manifest.packs, vec!["test-pack".to_string()]);
+        assert_eq!(manifest.files.len(), 1);
+
+        let bundle = dir.path().join("bundle");
+        assert_eq!(std::fs::read(bundle.join("test-pack").join("driver.inf")).unwrap(), b"[Version]");
+        assert!(bundle.join(BUNDLE_MANIFEST_FILE).is_file());
+        assert!(DriverBundler::verify_bundle(&bundle).unwrap());
+    }
+
+    #[test]
+    fn test_bundle_rejects_checksum_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundler = bundler_with_file(dir.path(), b"[Version]", Some("deadbeef"), true);
+        assert!(bundler.bundle_for_os(TargetOS::Linux).is_err());
+    }
+
+    #[test]
+    fn test_bundle_rejects_missing_required_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundler = bundler_with_file(dir.path(), b"[Version]", None, true);
+        std::fs::remove_file(dir.path().join("source").join("driver.inf")).unwrap();
+        assert!(bundler.bundle_for_os(TargetOS::Linux).is_err());
+    }
+
+    #[test]
+    fn test_verify_bundle_detects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundler = bundler_with_file(dir.path(), b"[Version]", None, true);
+        bundler.bundle_for_os(TargetOS::Linux).unwrap();
+
+        let bundle = dir.path().join("bundle");
+        std::fs::write(bundle.join("test-pack").join("driver.inf"), b"tampered").unwrap();
+        assert!(!DriverBundler::verify_bundle(&bundle).unwrap());
+    }
+
+    #[test]
+    fn test_verify_bundle_rejects_paths_outside_the_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundler = bundler_with_file(dir.path(), b"[Version]", None, true);
+        let mut manifest = bundler.bundle_for_os(TargetOS::Linux).unwrap();
+        let bundle = dir.path().join("bundle");
+        let outside = dir.path().join("source").join("driver.inf");
+
+        for relative in [PathBuf::from("../source/driver.inf"), outside.clone(), PathBuf::from("./test-pack/../../x")] {
+            manifest.files[0].relative_path = relative;
+            manifest.files[0].checksum = compute_file_hash(&outside).unwrap();
+            std::fs::write(bundle.join(BUNDLE_MANIFEST_FILE), serde_json::to_string(&manifest).unwrap()).unwrap();
+            assert!(DriverBundler::verify_bundle(&bundle).is_err());
+        }
+    }
 }
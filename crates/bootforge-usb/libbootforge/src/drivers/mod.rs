@@ -10,4 +10,6 @@ pub use android::AndroidDriver;
 pub use samsung::SamsungDriver;
 pub use qualcomm::QualcommDriver;
 pub use mediatek::MediaTekDriver;
-pub use driver_packs::{DriverPackRegistry, DriverPack, DriverBundler, TargetOS};
+pub use driver_packs::{
+    DriverPackRegistry, DriverPack, DriverBundler, BundleManifest, TargetOS, DriverPackManifestEntry, DriverPackUpdate,
+};
@@ -4,9 +4,82 @@
 //! Profiles contain partition layouts, boot sequences, and recovery options.
 
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use bootforgeusb::model::{ConfirmedDeviceRecord, Platform};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use super::engine::ImagingEngine;
+
+/// Assumed sustained fastboot transfer rate over USB 2.0
+pub const USB2_ASSUMED_SPEED_BPS: u64 = 25 * 1024 * 1024; // 25MB/s
+
+/// Assumed sustained fastboot transfer rate over USB 3.0
+pub const USB3_ASSUMED_SPEED_BPS: u64 = 300 * 1024 * 1024; // 300MB/s
+
+/// Version of the profile bundle layout written by `export_bundle`
+pub const PROFILE_BUNDLE_VERSION: u32 = 1;
+
+const BUNDLE_MANIFEST: &str = "manifest.json";
+
+/// Largest uncompressed bundle entry `import_bundle` will read; profiles are
+/// a few KiB of JSON
+pub const MAX_BUNDLE_ENTRY_BYTES: u64 = 1024 * 1024;
+/// Largest total uncompressed size of the entries of one bundle
+pub const MAX_BUNDLE_TOTAL_BYTES: u64 = 16 * 1024 * 1024;
+
+/// ext4 and f2fs both keep their superblock 1024 bytes into the image
+const SUPERBLOCK_OFFSET: usize = 1024;
+const EXT4_MAGIC: u16 = 0xef53;
+const F2FS_MAGIC: u32 = 0xf2f5_2010;
+/// Block size assumed when an ext4/f2fs partition's image has no readable superblock
+const DEFAULT_FS_BLOCK_SIZE: u64 = 4096;
+
+/// Errors from reading, writing and sharing boot profiles
+#[derive(Error, Debug)]
+pub enum ProfileError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid profile JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("ZIP error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("Unknown boot profile: {0}")]
+    UnknownProfile(String),
+    #[error("Invalid profile bundle: {0}")]
+    InvalidBundle(String),
+    #[error("Boot profile already exists: {0}; import with overwrite to replace it")]
+    DuplicateProfile(String),
+}
+
+/// `manifest.json` of a profile bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub version: u32,
+    /// User that exported the bundle, when known
+    pub author: Option<String>,
+    pub created_at: String,
+    pub profiles: Vec<BundleEntry>,
+}
+
+/// One profile file inside a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleEntry {
+    pub id: String,
+    pub file: String,
+    /// Hex SHA-256 of `file`
+    pub sha256: String,
+}
 
 /// Boot profile for a specific OS/device combination
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BootProfile {
     pub id: String,
     pub name: String,
@@ -19,7 +92,7 @@ pub struct BootProfile {
 }
 
 /// Operating system type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum OSType {
     Android,
     IOS,
@@ -30,7 +103,7 @@ pub enum OSType {
 }
 
 /// Device family for profile matching
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DeviceFamily {
     // Android
     GooglePixel,
@@ -51,7 +124,7 @@ pub enum DeviceFamily {
 }
 
 /// Partition definition
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PartitionDef {
     pub name: String,
     pub label: String,
@@ -63,7 +136,7 @@ pub struct PartitionDef {
 }
 
 /// Partition filesystem types
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PartitionFS {
     Ext4,
     F2FS,
@@ -78,7 +151,7 @@ pub enum PartitionFS {
 }
 
 /// Partition flags
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PartitionFlag {
     Bootable,
     System,
@@ -99,7 +172,7 @@ pub enum PartitionFlag {
 }
 
 /// Boot sequence step
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BootStep {
     pub order: u32,
     pub name: String,
@@ -110,7 +183,7 @@ pub struct BootStep {
 }
 
 /// Boot action types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BootAction {
     FlashPartition { partition: String, image: String },
     ErasePartition { partition: String },
@@ -125,7 +198,7 @@ pub enum BootAction {
 }
 
 /// Reboot modes
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RebootMode {
     Normal,
     Recovery,
@@ -137,7 +210,7 @@ pub enum RebootMode {
 }
 
 /// Wait conditions
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum WaitCondition {
     DeviceConnected,
     ModeChange { target: RebootMode },
@@ -146,7 +219,7 @@ pub enum WaitCondition {
 }
 
 /// Recovery options
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecoveryOption {
     pub id: String,
     pub name: String,
@@ -156,7 +229,7 @@ pub struct RecoveryOption {
 }
 
 /// Risk levels
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RiskLevel {
     Safe,
     Low,
@@ -166,7 +239,7 @@ pub enum RiskLevel {
 }
 
 /// Verified boot configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VerifiedBootConfig {
     pub version: u8,  // AVB 1.0, 2.0, etc
     pub rollback_index: u64,
@@ -175,13 +248,135 @@ pub struct VerifiedBootConfig {
 }
 
 /// Chain partition for verified boot
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChainPartition {
     pub partition: String,
     pub rollback_index_slot: u32,
     pub public_key: String,
 }
 
+impl PartitionDef {
+    /// Bytes `image_path` occupies once flashed to this partition: the
+    /// expanded size of a sparse image, otherwise the file size, rounded up
+    /// to the filesystem block size for ext4 and f2fs images.
+    pub fn minimum_flash_size_from_image(&self, image_path: &Path) -> crate::Result<u64> {
+        minimum_image_flash_size(image_path, self.filesystem)
+    }
+}
+
+/// [`PartitionDef::minimum_flash_size_from_image`] for callers that only have
+/// an image path. The filesystem is read from the image's superblock;
+/// `filesystem` is the fallback when the superblock is missing.
+pub fn minimum_image_flash_size(image_path: &Path, filesystem: PartitionFS) -> crate::Result<u64> {
+    if ImagingEngine::has_sparse_magic(image_path) {
+        return ImagingEngine::sparse_expanded_size(image_path);
+    }
+    let size = std::fs::metadata(image_path)?.len();
+    let block_size = match superblock_block_size(image_path)? {
+        Some(block_size) => block_size,
+        None if matches!(filesystem, PartitionFS::Ext4 | PartitionFS::F2FS) => DEFAULT_FS_BLOCK_SIZE,
+        None => return Ok(size),
+    };
+    Ok(size.next_multiple_of(block_size))
+}
+
+/// Block size from an ext4 or f2fs superblock, `None` for other images.
+fn superblock_block_size(image_path: &Path) -> crate::Result<Option<u64>> {
+    let mut superblock = Vec::with_capacity(SUPERBLOCK_OFFSET + 64);
+    File::open(image_path)?
+        .take((SUPERBLOCK_OFFSET + 64) as u64)
+        .read_to_end(&mut superblock)?;
+    if superblock.len() < SUPERBLOCK_OFFSET + 64 {
+        return Ok(None);
+    }
+    let sb = &superblock[SUPERBLOCK_OFFSET..];
+    let u32_at = |i: usize| u32::from_le_bytes([sb[i], sb[i + 1], sb[i + 2], sb[i + 3]]);
+    // ext4: s_magic at 0x38, s_log_block_size (log2 of size / 1024) at 0x18
+    if u16::from_le_bytes([sb[0x38], sb[0x39]]) == EXT4_MAGIC && u32_at(0x18) <= 6 {
+        return Ok(Some(1024 << u32_at(0x18)));
+    }
+    // f2fs: magic at 0, log_blocksize at 0x10
+    if u32_at(0) == F2FS_MAGIC && (9..=16).contains(&u32_at(0x10)) {
+        return Ok(Some(1 << u32_at(0x10)));
+    }
+    Ok(None)
+}
+
+impl BootProfile {
+    /// Sum of [`PartitionDef::minimum_flash_size_from_image`] over the
+    /// flashable partitions whose image is present in `image_dir`. A
+    /// partition's image is the one its `FlashPartition` step names, or
+    /// `<partition>.img` when no step does.
+    pub fn total_minimum_size(&self, image_dir: &Path) -> crate::Result<u64> {
+        let mut total = 0;
+        for partition in self.partitions.iter().filter(|p| p.flashable) {
+            let image = self
+                .boot_sequence
+                .iter()
+                .find_map(|step| match &step.action {
+                    BootAction::FlashPartition { partition: name, image } if *name == partition.name => Some(image.clone()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| format!("{}.img", partition.name));
+            let path = image_dir.join(image);
+            if path.is_file() {
+                total += partition.minimum_flash_size_from_image(&path)?;
+            }
+        }
+        Ok(total)
+    }
+
+    /// Estimate how long running this profile's boot sequence will take.
+    ///
+    /// Sums the image transfer time for `total_bytes` at `assumed_speed_bps`
+    /// and the fixed overhead of every step in `boot_sequence`. A speed of 0
+    /// falls back to [`USB2_ASSUMED_SPEED_BPS`].
+    pub fn estimate_duration(&self, total_bytes: u64, assumed_speed_bps: u64) -> Duration {
+        let speed = if assumed_speed_bps == 0 { USB2_ASSUMED_SPEED_BPS } else { assumed_speed_bps };
+        let transfer = Duration::from_secs_f64(total_bytes as f64 / speed as f64);
+        let overhead: Duration = self.boot_sequence.iter().map(|step| step.action.fixed_overhead()).sum();
+        transfer + overhead
+    }
+}
+
+impl BootProfile {
+    /// Write the profile as pretty-printed JSON.
+    pub fn to_json_file(&self, path: &Path) -> Result<(), ProfileError> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    fn to_json(&self) -> Result<Vec<u8>, ProfileError> {
+        Ok(serde_json::to_vec_pretty(self)?)
+    }
+
+    fn from_json(bytes: &[u8]) -> Result<Self, ProfileError> {
+        let profile: BootProfile = serde_json::from_slice(bytes)?;
+        if profile.id.trim().is_empty() {
+            return Err(ProfileError::InvalidBundle("profile id is empty".to_string()));
+        }
+        Ok(profile)
+    }
+}
+
+impl BootAction {
+    /// Fixed time cost of the action, excluding image transfer
+    pub fn fixed_overhead(&self) -> Duration {
+        match self {
+            BootAction::FlashPartition { .. } => Duration::from_secs(1),
+            BootAction::ErasePartition { .. } => Duration::from_secs(2),
+            BootAction::SetActive { .. } => Duration::from_secs(1),
+            BootAction::Reboot { .. } => Duration::from_secs(5),
+            BootAction::Wait { condition: WaitCondition::Timeout { ms } } => Duration::from_millis(u64::from(*ms)),
+            BootAction::Wait { .. } => Duration::from_secs(5),
+            BootAction::Verify { .. } => Duration::from_secs(2),
+            BootAction::UnlockBootloader | BootAction::LockBootloader => Duration::from_secs(10),
+            BootAction::FormatData => Duration::from_secs(30),
+            BootAction::Custom { .. } => Duration::from_secs(1),
+        }
+    }
+}
+
 /// Boot profile registry
 pub struct BootProfileRegistry {
     profiles: HashMap<String, BootProfile>,
@@ -376,6 +571,66 @@ impl BootProfileRegistry {
             verified_boot: None,
         });
 
+        // Any other Android device (fastboot)
+        self.register_profile(BootProfile {
+            id: "generic-android".to_string(),
+            name: "Android (Generic Fastboot)".to_string(),
+            os_type: OSType::Android,
+            device_family: DeviceFamily::GenericAndroid,
+            partitions: vec![
+                PartitionDef {
+                    name: "boot".to_string(),
+                    label: "Boot".to_string(),
+                    size_bytes: None,
+                    filesystem: PartitionFS::Raw,
+                    flags: vec![PartitionFlag::Boot, PartitionFlag::Bootable],
+                    flashable: true,
+                    critical: true,
+                },
+                PartitionDef {
+                    name: "recovery".to_string(),
+                    label: "Recovery".to_string(),
+                    size_bytes: None,
+                    filesystem: PartitionFS::Raw,
+                    flags: vec![PartitionFlag::Recovery],
+                    flashable: true,
+                    critical: true,
+                },
+                PartitionDef {
+                    name: "userdata".to_string(),
+                    label: "User Data".to_string(),
+                    size_bytes: None,
+                    filesystem: PartitionFS::Ext4,
+                    flags: vec![PartitionFlag::Userdata],
+                    flashable: false,
+                    critical: false,
+                },
+            ],
+            boot_sequence: vec![
+                BootStep {
+                    order: 1,
+                    name: "Flash boot".to_string(),
+                    action: BootAction::FlashPartition {
+                        partition: "boot".to_string(),
+                        image: "boot.img".to_string(),
+                    },
+                    timeout_ms: 30000,
+                    required: true,
+                    fallback: None,
+                },
+                BootStep {
+                    order: 2,
+                    name: "Reboot to system".to_string(),
+                    action: BootAction::Reboot { mode: RebootMode::Normal },
+                    timeout_ms: 60000,
+                    required: true,
+                    fallback: None,
+                },
+            ],
+            recovery_options: vec![],
+            verified_boot: None,
+        });
+
         // iPhone (iOS)
         self.register_profile(BootProfile {
             id: "iphone-ios".to_string(),
@@ -466,6 +721,206 @@ impl BootProfileRegistry {
     pub fn all_profiles(&self) -> Vec<&BootProfile> {
         self.profiles.values().collect()
     }
+
+    /// Write the given profiles to a ZIP bundle: one `<id>.json` per profile
+    /// and a `manifest.json` with their checksums. Returns the bundle size.
+    pub fn export_bundle(&self, profile_ids: &[String], output_zip: &Path) -> Result<u64, ProfileError> {
+        if profile_ids.is_empty() {
+            return Err(ProfileError::InvalidBundle("no profiles selected".to_string()));
+        }
+        let mut files = Vec::with_capacity(profile_ids.len());
+        for id in profile_ids {
+            let profile = self
+                .get_profile(id)
+                .ok_or_else(|| ProfileError::UnknownProfile(id.clone()))?;
+            files.push((profile.id.clone(), format!("{}.json", bundle_file_stem(&profile.id)), profile.to_json()?));
+        }
+
+        let manifest = BundleManifest {
+            version: PROFILE_BUNDLE_VERSION,
+            author: std::env::var("USER").or_else(|_| std::env::var("USERNAME")).ok(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            profiles: files
+                .iter()
+                .map(|(id, file, json)| BundleEntry {
+                    id: id.clone(),
+                    file: file.clone(),
+                    sha256: hex::encode(Sha256::digest(json)),
+                })
+                .collect(),
+        };
+
+        let mut zip = ZipWriter::new(File::create(output_zip)?);
+        let options = SimpleFileOptions::default();
+        zip.start_file(BUNDLE_MANIFEST, options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+        for (_, file, json) in &files {
+            zip.start_file(file.as_str(), options)?;
+            zip.write_all(json)?;
+        }
+        zip.finish()?;
+        Ok(std::fs::metadata(output_zip)?.len())
+    }
+
+    /// Register every profile of a bundle written by `export_bundle`. All
+    /// entries are checked against the manifest before any is registered.
+    /// A profile whose id is already registered, built-in or imported, is
+    /// rejected unless `overwrite` is set. Returns the imported ids.
+    pub fn import_bundle(&mut self, zip_path: &Path, overwrite: bool) -> Result<Vec<String>, ProfileError> {
+        let mut archive = ZipArchive::new(File::open(zip_path)?)?;
+        let mut budget = MAX_BUNDLE_TOTAL_BYTES;
+        let manifest: BundleManifest =
+            serde_json::from_slice(&read_zip_entry(&mut archive, BUNDLE_MANIFEST, &mut budget)?)?;
+        if manifest.version > PROFILE_BUNDLE_VERSION {
+            return Err(ProfileError::InvalidBundle(format!(
+                "bundle version {} is newer than supported version {}",
+                manifest.version, PROFILE_BUNDLE_VERSION
+            )));
+        }
+
+        let mut profiles = Vec::with_capacity(manifest.profiles.len());
+        for entry in &manifest.profiles {
+            let json = read_zip_entry(&mut archive, &entry.file, &mut budget)?;
+            if !hex::encode(Sha256::digest(&json)).eq_ignore_ascii_case(&entry.sha256) {
+                return Err(ProfileError::InvalidBundle(format!("checksum mismatch for {}", entry.file)));
+            }
+            let profile = BootProfile::from_json(&json)?;
+            if profile.id != entry.id {
+                return Err(ProfileError::InvalidBundle(format!(
+                    "{} contains profile '{}', manifest lists '{}'",
+                    entry.file, profile.id, entry.id
+                )));
+            }
+            if !overwrite && self.profiles.contains_key(&profile.id) {
+                return Err(ProfileError::DuplicateProfile(profile.id));
+            }
+            profiles.push(profile);
+        }
+
+        let ids = profiles.iter().map(|p| p.id.clone()).collect();
+        for profile in profiles {
+            self.register_profile(profile);
+        }
+        Ok(ids)
+    }
+
+    /// Write the given profiles to `dir` as `<id>.json`, for
+    /// [`Self::load_profile_dir`] to restore on the next start.
+    pub fn save_profiles(&self, profile_ids: &[String], dir: &Path) -> Result<(), ProfileError> {
+        std::fs::create_dir_all(dir)?;
+        for id in profile_ids {
+            let profile = self
+                .get_profile(id)
+                .ok_or_else(|| ProfileError::UnknownProfile(id.clone()))?;
+            profile.to_json_file(&dir.join(format!("{}.json", bundle_file_stem(&profile.id))))?;
+        }
+        Ok(())
+    }
+
+    /// Register every `*.json` profile in `dir`, replacing built-ins with
+    /// the same id. A missing directory loads nothing. Returns the ids.
+    pub fn load_profile_dir(&mut self, dir: &Path) -> Result<Vec<String>, ProfileError> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json") && path.is_file())
+            .collect();
+        paths.sort();
+        let mut ids = Vec::with_capacity(paths.len());
+        for path in paths {
+            let profile = BootProfile::from_json(&std::fs::read(&path)?)?;
+            ids.push(profile.id.clone());
+            self.register_profile(profile);
+        }
+        Ok(ids)
+    }
+
+    /// Best profile for a scanned device: its detected family first, then the
+    /// generic family for its platform. `None` for unknown platforms.
+    pub fn for_device(&self, record: &ConfirmedDeviceRecord) -> Option<&BootProfile> {
+        let (os_type, fallback) = match record.platform {
+            Platform::Android => (OSType::Android, DeviceFamily::GenericAndroid),
+            Platform::Ios => (OSType::IOS, DeviceFamily::IPhone),
+            Platform::Unknown => return None,
+        };
+        let usb = &record.evidence.usb;
+        let detected = [usb.product.as_deref(), usb.manufacturer.as_deref()]
+            .into_iter()
+            .flatten()
+            .find_map(|s| family_from_usb_string(s, os_type));
+
+        detected
+            .into_iter()
+            .chain(std::iter::once(fallback))
+            .find_map(|family| {
+                // Several profiles may share a family; pick by id so the
+                // answer does not depend on HashMap order.
+                self.profiles
+                    .values()
+                    .filter(|p| p.os_type == os_type && p.device_family == family)
+                    .min_by(|a, b| a.id.cmp(&b.id))
+            })
+    }
+}
+
+/// Profile id reduced to characters that are safe in a ZIP entry name
+fn bundle_file_stem(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// Read one entry, refusing entries over [`MAX_BUNDLE_ENTRY_BYTES`] or past
+/// what is left of the bundle's `budget`. The declared size is checked
+/// first and the read is capped too, since the header can lie.
+fn read_zip_entry(archive: &mut ZipArchive<File>, name: &str, budget: &mut u64) -> Result<Vec<u8>, ProfileError> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|_| ProfileError::InvalidBundle(format!("missing {}", name)))?;
+    let limit = MAX_BUNDLE_ENTRY_BYTES.min(*budget);
+    let too_large = || ProfileError::InvalidBundle(format!("{} is larger than {} bytes", name, limit));
+    if entry.size() > limit {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::new();
+    entry.take(limit + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > limit {
+        return Err(too_large());
+    }
+    *budget -= bytes.len() as u64;
+    Ok(bytes)
+}
+
+/// Lowercase keywords found in USB manufacturer/product strings
+const FAMILY_KEYWORDS: &[(&str, DeviceFamily)] = &[
+    ("pixel", DeviceFamily::GooglePixel),
+    ("google", DeviceFamily::GooglePixel),
+    ("samsung", DeviceFamily::Samsung),
+    ("galaxy", DeviceFamily::Samsung),
+    ("xiaomi", DeviceFamily::Xiaomi),
+    ("redmi", DeviceFamily::Xiaomi),
+    ("poco", DeviceFamily::Xiaomi),
+    ("oneplus", DeviceFamily::OnePlus),
+    ("motorola", DeviceFamily::Motorola),
+    ("moto ", DeviceFamily::Motorola),
+    ("huawei", DeviceFamily::Huawei),
+    ("ipad", DeviceFamily::IPad),
+    ("iphone", DeviceFamily::IPhone),
+];
+
+fn family_from_usb_string(value: &str, os_type: OSType) -> Option<DeviceFamily> {
+    let value = value.to_lowercase();
+    FAMILY_KEYWORDS
+        .iter()
+        .filter(|(_, family)| {
+            matches!(family, DeviceFamily::IPhone | DeviceFamily::IPad) == (os_type == OSType::IOS)
+        })
+        .find(|(keyword, _)| value.contains(keyword))
+        .map(|(_, family)| *family)
 }
 
 impl Default for BootProfileRegistry {
@@ -492,4 +947,193 @@ mod tests {
         let android_profiles = registry.find_by_os(OSType::Android);
         assert!(!android_profiles.is_empty());
     }
+
+    fn record(platform: Platform, manufacturer: &str, product: &str) -> ConfirmedDeviceRecord {
+        use bootforgeusb::model::{Evidence, UsbTransportEvidence};
+        ConfirmedDeviceRecord {
+            device_uid: "ABC123".to_string(),
+            platform,
+            mode: String::new(),
+            confidence: 0.9,
+            evidence: Evidence {
+                usb: UsbTransportEvidence {
+                    vid: "0000".to_string(),
+                    pid: "0000".to_string(),
+                    manufacturer: Some(manufacturer.to_string()),
+                    product: Some(product.to_string()),
+                    bus: 1,
+                    address: 2,
+                    ..Default::default()
+                },
+                tools: HashMap::new(),
+            },
+            notes: vec![],
+            matched_tool_ids: vec![],
+            marketing_name: None,
+            driver_status: None,
+        }
+    }
+
+    #[test]
+    fn test_for_device_matches_family_keywords() {
+        let registry = BootProfileRegistry::new();
+        let id = |r: &ConfirmedDeviceRecord| registry.for_device(r).map(|p| p.id.as_str());
+
+        assert_eq!(id(&record(Platform::Android, "Google", "Pixel 8")), Some("google-pixel-android14"));
+        assert_eq!(id(&record(Platform::Android, "SAMSUNG", "SAMSUNG_Android")), Some("samsung-android"));
+        assert_eq!(id(&record(Platform::Ios, "Apple Inc.", "iPhone")), Some("iphone-ios"));
+        // iPad has no profile of its own; falls back to the iPhone profile
+        assert_eq!(id(&record(Platform::Ios, "Apple Inc.", "iPad")), Some("iphone-ios"));
+        // No Xiaomi profile is built in; falls back to the generic Android profile
+        assert_eq!(id(&record(Platform::Android, "Xiaomi", "Redmi Note 12")), Some("generic-android"));
+        assert_eq!(id(&record(Platform::Unknown, "Google", "Pixel 8")), None);
+    }
+
+    #[test]
+    fn test_profile_bundle_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("profiles.zip");
+        let registry = BootProfileRegistry::new();
+        let ids = vec!["google-pixel-android14".to_string(), "iphone-ios".to_string()];
+        assert!(registry.export_bundle(&ids, &bundle).unwrap() > 0);
+
+        let mut imported = BootProfileRegistry { profiles: HashMap::new() };
+        assert_eq!(imported.import_bundle(&bundle, false).unwrap(), ids);
+        let pixel = imported.get_profile("google-pixel-android14").unwrap();
+        assert_eq!(pixel.device_family, DeviceFamily::GooglePixel);
+        assert_eq!(pixel.partitions.len(), 7);
+        assert!(imported.get_profile("samsung-android").is_none());
+
+        let single = dir.path().join("pixel.json");
+        pixel.to_json_file(&single).unwrap();
+        let parsed = BootProfile::from_json(&std::fs::read(&single).unwrap()).unwrap();
+        assert_eq!(parsed.boot_sequence.len(), pixel.boot_sequence.len());
+
+        assert!(matches!(
+            registry.export_bundle(&["missing".to_string()], &bundle),
+            Err(ProfileError::UnknownProfile(_))
+        ));
+    }
+
+    #[test]
+    fn test_import_rejects_tampered_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("tampered.zip");
+        let registry = BootProfileRegistry::new();
+        let json = registry.get_profile("samsung-android").unwrap().to_json().unwrap();
+        let manifest = BundleManifest {
+            version: PROFILE_BUNDLE_VERSION,
+            author: None,
+            created_at: String::new(),
+            profiles: vec![BundleEntry {
+                id: "samsung-android".to_string(),
+                file: "samsung-android.json".to_string(),
+                sha256: "00".repeat(32),
+            }],
+        };
+        let mut zip = ZipWriter::new(File::create(&bundle).unwrap());
+        zip.start_file(BUNDLE_MANIFEST, SimpleFileOptions::default()).unwrap();
+        zip.write_all(&serde_json::to_vec(&manifest).unwrap()).unwrap();
+        zip.start_file("samsung-android.json", SimpleFileOptions::default()).unwrap();
+        zip.write_all(&json).unwrap();
+        zip.finish().unwrap();
+
+        let mut imported = BootProfileRegistry { profiles: HashMap::new() };
+        assert!(matches!(imported.import_bundle(&bundle, false), Err(ProfileError::InvalidBundle(_))));
+        assert!(imported.all_profiles().is_empty());
+    }
+
+    #[test]
+    fn test_import_rejects_id_collisions_unless_overwriting() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("pixel.zip");
+        let ids = vec!["google-pixel-android14".to_string()];
+        let mut registry = BootProfileRegistry::new();
+        registry.export_bundle(&ids, &bundle).unwrap();
+
+        assert!(matches!(
+            registry.import_bundle(&bundle, false),
+            Err(ProfileError::DuplicateProfile(id)) if id == "google-pixel-android14"
+        ));
+        assert_eq!(registry.import_bundle(&bundle, true).unwrap(), ids);
+
+        let saved = dir.path().join("imported");
+        registry.save_profiles(&ids, &saved).unwrap();
+        let mut restored = BootProfileRegistry { profiles: HashMap::new() };
+        assert_eq!(restored.load_profile_dir(&saved).unwrap(), ids);
+        assert!(restored.load_profile_dir(&dir.path().join("missing")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_import_rejects_oversized_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("huge.zip");
+        let mut zip = ZipWriter::new(File::create(&bundle).unwrap());
+        zip.start_file(BUNDLE_MANIFEST, SimpleFileOptions::default()).unwrap();
+        zip.write_all(&vec![b' '; MAX_BUNDLE_ENTRY_BYTES as usize + 1]).unwrap();
+        zip.finish().unwrap();
+
+        let mut registry = BootProfileRegistry { profiles: HashMap::new() };
+        assert!(matches!(registry.import_bundle(&bundle, false), Err(ProfileError::InvalidBundle(_))));
+    }
+
+    #[test]
+    fn test_estimate_duration_sums_transfer_and_step_overhead() {
+        let step = |order, action| BootStep {
+            order,
+            name: format!("step-{}", order),
+            action,
+            timeout_ms: 0,
+            required: true,
+            fallback: None,
+        };
+        let mut profile = BootProfileRegistry::new().get_profile("google-pixel-android14").unwrap().clone();
+        profile.boot_sequence = vec![
+            step(1, BootAction::ErasePartition { partition: "userdata".to_string() }),
+            step(2, BootAction::FormatData),
+            step(3, BootAction::Reboot { mode: RebootMode::Normal }),
+        ];
+
+        // 50MB at 25MB/s = 2s transfer + 2s erase + 30s format + 5s reboot
+        let estimate = profile.estimate_duration(50 * 1024 * 1024, USB2_ASSUMED_SPEED_BPS);
+        assert_eq!(estimate, Duration::from_secs(39));
+
+        // Zero speed falls back to the USB 2.0 assumption
+        assert_eq!(profile.estimate_duration(50 * 1024 * 1024, 0), estimate);
+    }
+
+    #[test]
+    fn test_minimum_flash_size_from_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let profile = BootProfileRegistry::new().get_profile("google-pixel-android14").unwrap().clone();
+        let partition = |name: &str| profile.partitions.iter().find(|p| p.name == name).unwrap().clone();
+
+        // ext4 superblock with 4K blocks: 5000 bytes occupy two blocks
+        let mut ext4 = vec![0u8; 5000];
+        ext4[1024 + 0x18..1024 + 0x1c].copy_from_slice(&2u32.to_le_bytes());
+        ext4[1024 + 0x38..1024 + 0x3a].copy_from_slice(&EXT4_MAGIC.to_le_bytes());
+        std::fs::write(dir.path().join("vendor.img"), &ext4).unwrap();
+        assert_eq!(partition("vendor").minimum_flash_size_from_image(&dir.path().join("vendor.img")).unwrap(), 8192);
+
+        // Sparse header: 10 blocks of 4096 bytes
+        let mut sparse = Vec::new();
+        sparse.extend_from_slice(&0xed26_ff3au32.to_le_bytes());
+        sparse.extend_from_slice(&1u16.to_le_bytes());
+        sparse.extend_from_slice(&0u16.to_le_bytes());
+        sparse.extend_from_slice(&28u16.to_le_bytes());
+        sparse.extend_from_slice(&12u16.to_le_bytes());
+        sparse.extend_from_slice(&4096u32.to_le_bytes());
+        sparse.extend_from_slice(&10u32.to_le_bytes());
+        sparse.extend_from_slice(&0u32.to_le_bytes());
+        sparse.extend_from_slice(&0u32.to_le_bytes());
+        std::fs::write(dir.path().join("system.img"), &sparse).unwrap();
+        assert_eq!(partition("system").minimum_flash_size_from_image(&dir.path().join("system.img")).unwrap(), 40960);
+
+        // Raw images are taken at face value
+        std::fs::write(dir.path().join("boot.img"), vec![0u8; 5000]).unwrap();
+        assert_eq!(partition("boot").minimum_flash_size_from_image(&dir.path().join("boot.img")).unwrap(), 5000);
+
+        // Partitions without an image in the directory are skipped
+        assert_eq!(profile.total_minimum_size(dir.path()).unwrap(), 8192 + 40960 + 5000);
+    }
 }
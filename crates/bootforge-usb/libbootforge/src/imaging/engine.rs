@@ -1,8 +1,19 @@
 use crate::Result;
 use crate::BootforgeError;
 use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 
+/// Android sparse image magic (stored little-endian)
+const SPARSE_HEADER_MAGIC: u32 = 0xed26_ff3a;
+const SPARSE_HEADER_LEN: u16 = 28;
+const SPARSE_CHUNK_HEADER_LEN: u16 = 12;
+const CHUNK_TYPE_RAW: u16 = 0xcac1;
+const CHUNK_TYPE_FILL: u16 = 0xcac2;
+const CHUNK_TYPE_DONT_CARE: u16 = 0xcac3;
+const CHUNK_TYPE_CRC32: u16 = 0xcac4;
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum ImageFormat {
     Raw,
@@ -10,6 +21,8 @@ pub enum ImageFormat {
     Wim,
     Iso,
     Img,
+    /// Android sparse image, detected from its header rather than the extension
+    AndroidSparse,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +37,10 @@ pub struct ImagingEngine;
 
 impl ImagingEngine {
     pub fn detect_format(path: &Path) -> Result<ImageFormat> {
+        if Self::has_sparse_magic(path) {
+            return Ok(ImageFormat::AndroidSparse);
+        }
+
         let ext = path
             .extension()
             .and_then(|s| s.to_str())
@@ -39,12 +56,124 @@ impl ImagingEngine {
         }
     }
 
+    pub(crate) fn has_sparse_magic(path: &Path) -> bool {
+        let mut magic = [0u8; 4];
+        File::open(path)
+            .and_then(|mut file| file.read_exact(&mut magic))
+            .is_ok_and(|_| u32::from_le_bytes(magic) == SPARSE_HEADER_MAGIC)
+    }
+
+    /// Size of the raw image a sparse image expands to, read from its header.
+    pub fn sparse_expanded_size(sparse_path: &Path) -> Result<u64> {
+        let header = SparseHeader::read(&mut BufReader::new(File::open(sparse_path)?))?;
+        Ok(header.total_blks as u64 * header.blk_sz as u64)
+    }
+
+    /// Expand an Android sparse image into a raw image at `output_path`.
+    /// Don't-care chunks are left as holes. Returns the raw size in bytes
+    /// (`total_blks * blk_sz`).
+    pub fn unsparse_image(sparse_path: &Path, output_path: &Path) -> Result<u64> {
+        let mut input = BufReader::new(File::open(sparse_path)?);
+        let header = SparseHeader::read(&mut input)?;
+        let total_size = header.total_blks as u64 * header.blk_sz as u64;
+
+        let file = File::create(output_path)?;
+        let mut output = BufWriter::new(file);
+        let mut blocks_written: u64 = 0;
+        for index in 0..header.total_chunks {
+            let mut chunk = [0u8; SPARSE_CHUNK_HEADER_LEN as usize];
+            input.read_exact(&mut chunk)?;
+            skip_bytes(&mut input, (header.chunk_hdr_sz - SPARSE_CHUNK_HEADER_LEN) as u64)?;
+            let chunk_type = u16::from_le_bytes([chunk[0], chunk[1]]);
+            let chunk_blocks = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]) as u64;
+            let chunk_bytes = chunk_blocks * header.blk_sz as u64;
+
+            blocks_written += chunk_blocks;
+            if blocks_written > header.total_blks as u64 {
+                return Err(BootforgeError::Imaging(format!(
+                    "Sparse chunk {} runs past the {} blocks in the header",
+                    index, header.total_blks
+                )));
+            }
+
+            match chunk_type {
+                CHUNK_TYPE_RAW => {
+                    let copied = std::io::copy(&mut (&mut input).take(chunk_bytes), &mut output)?;
+                    if copied != chunk_bytes {
+                        return Err(BootforgeError::Imaging(format!("Sparse chunk {} is truncated", index)));
+                    }
+                }
+                CHUNK_TYPE_FILL => {
+                    let mut pattern = [0u8; 4];
+                    input.read_exact(&mut pattern)?;
+                    let block: Vec<u8> = pattern.iter().copied().cycle().take(header.blk_sz as usize).collect();
+                    for _ in 0..chunk_blocks {
+                        output.write_all(&block)?;
+                    }
+                }
+                CHUNK_TYPE_DONT_CARE => {
+                    output.seek(SeekFrom::Current(chunk_bytes as i64))?;
+                }
+                CHUNK_TYPE_CRC32 => {
+                    // Checksum of the data so far (chunk_sz is 0); not verified
+                    skip_bytes(&mut input, 4)?;
+                }
+                other => {
+                    return Err(BootforgeError::Imaging(format!(
+                        "Unknown sparse chunk type {:#06x} in chunk {}",
+                        other, index
+                    )));
+                }
+            }
+        }
+
+        if blocks_written != header.total_blks as u64 {
+            return Err(BootforgeError::Imaging(format!(
+                "Sparse image covers {} of {} blocks",
+                blocks_written, header.total_blks
+            )));
+        }
+        let file = output.into_inner().map_err(|e| BootforgeError::Io(e.into_error()))?;
+        // A trailing don't-care chunk only moved the cursor; extend regular files to full size.
+        if file.metadata()?.is_file() {
+            file.set_len(total_size)?;
+        }
+        Ok(total_size)
+    }
+
+    /// Apply image `image_index` of a WIM to `target_drive` (a mounted,
+    /// formatted volume). Returns the final progress.
+    pub fn apply_wim(wim_path: &Path, target_drive: &Path, image_index: u32) -> Result<ImagingProgress> {
+        Self::apply_wim_with_progress(wim_path, target_drive, image_index, |_| {})
+    }
+
+    /// `apply_wim`, calling `on_progress` each time the apply tool reports progress.
+    pub fn apply_wim_with_progress(
+        wim_path: &Path,
+        target_drive: &Path,
+        image_index: u32,
+        mut on_progress: impl FnMut(&ImagingProgress),
+    ) -> Result<ImagingProgress> {
+        super::wim::apply_wim(wim_path, target_drive, image_index, &mut on_progress)
+    }
+
     pub async fn write_image(
         &self,
-        _image_path: &Path,
-        _target: &str,
-        _format: ImageFormat,
+        image_path: &Path,
+        target: &str,
+        format: ImageFormat,
     ) -> Result<()> {
+        if let ImageFormat::AndroidSparse = format {
+            let written = Self::unsparse_image(image_path, Path::new(target))?;
+            log::info!("Expanded sparse image {} to {} ({} bytes)", image_path.display(), target, written);
+            return Ok(());
+        }
+        if let ImageFormat::Wim = format {
+            // Windows installation media carries its default edition as image 1
+            let progress = Self::apply_wim(image_path, Path::new(target), 1)?;
+            log::info!("Applied {} to {} ({})", image_path.display(), target, progress.status);
+            return Ok(());
+        }
         log::warn!("Image write operation not yet implemented");
         // TODO: Implement actual imaging logic using dd or specialized tool
         // For now, return error to prevent silent failure
@@ -62,3 +191,99 @@ impl ImagingEngine {
         Err(BootforgeError::Imaging("Image verification not yet implemented. Cannot validate image integrity.".to_string()))
     }
 }
+
+/// Fields of the sparse file header that unsparsing needs
+struct SparseHeader {
+    chunk_hdr_sz: u16,
+    blk_sz: u32,
+    total_blks: u32,
+    total_chunks: u32,
+}
+
+impl SparseHeader {
+    fn read(input: &mut impl Read) -> Result<Self> {
+        let mut raw = [0u8; SPARSE_HEADER_LEN as usize];
+        input.read_exact(&mut raw)?;
+        let u16_at = |i: usize| u16::from_le_bytes([raw[i], raw[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes([raw[i], raw[i + 1], raw[i + 2], raw[i + 3]]);
+
+        if u32_at(0) != SPARSE_HEADER_MAGIC {
+            return Err(BootforgeError::Imaging("Not an Android sparse image".to_string()));
+        }
+        if u16_at(4) != 1 {
+            return Err(BootforgeError::Imaging(format!("Unsupported sparse format version {}", u16_at(4))));
+        }
+        let file_hdr_sz = u16_at(8);
+        let header = Self { chunk_hdr_sz: u16_at(10), blk_sz: u32_at(12), total_blks: u32_at(16), total_chunks: u32_at(20) };
+        if file_hdr_sz < SPARSE_HEADER_LEN || header.chunk_hdr_sz < SPARSE_CHUNK_HEADER_LEN {
+            return Err(BootforgeError::Imaging("Sparse header sizes are too small".to_string()));
+        }
+        if header.blk_sz == 0 || header.blk_sz % 4 != 0 {
+            return Err(BootforgeError::Imaging(format!("Invalid sparse block size {}", header.blk_sz)));
+        }
+        skip_bytes(input, (file_hdr_sz - SPARSE_HEADER_LEN) as u64)?;
+        Ok(header)
+    }
+}
+
+fn skip_bytes(input: &mut impl Read, count: u64) -> Result<()> {
+    let skipped = std::io::copy(&mut input.take(count), &mut std::io::sink())?;
+    if skipped != count {
+        return Err(BootforgeError::Imaging("Sparse image is truncated".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(chunk_type: u16, blocks: u32, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&chunk_type.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        bytes.extend_from_slice(&blocks.to_le_bytes());
+        bytes.extend_from_slice(&(12 + data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn unsparses_raw_fill_dont_care_and_crc_chunks() {
+        let blk_sz = 8u32;
+        let chunks = [
+            chunk(CHUNK_TYPE_RAW, 1, b"ABCDEFGH"),
+            chunk(CHUNK_TYPE_FILL, 2, &[0xaa, 0xbb, 0xcc, 0xdd]),
+            chunk(CHUNK_TYPE_DONT_CARE, 1, &[]),
+            chunk(CHUNK_TYPE_CRC32, 0, &[0; 4]),
+            chunk(CHUNK_TYPE_DONT_CARE, 1, &[]),
+        ];
+        let mut image = Vec::new();
+        image.extend_from_slice(&SPARSE_HEADER_MAGIC.to_le_bytes());
+        image.extend_from_slice(&1u16.to_le_bytes());
+        image.extend_from_slice(&0u16.to_le_bytes());
+        image.extend_from_slice(&SPARSE_HEADER_LEN.to_le_bytes());
+        image.extend_from_slice(&SPARSE_CHUNK_HEADER_LEN.to_le_bytes());
+        image.extend_from_slice(&blk_sz.to_le_bytes());
+        image.extend_from_slice(&5u32.to_le_bytes());
+        image.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+        image.extend_from_slice(&0u32.to_le_bytes());
+        image.extend(chunks.concat());
+
+        let dir = tempfile::tempdir().unwrap();
+        let sparse = dir.path().join("system.img");
+        let raw = dir.path().join("system.raw");
+        std::fs::write(&sparse, &image).unwrap();
+
+        assert!(matches!(ImagingEngine::detect_format(&sparse).unwrap(), ImageFormat::AndroidSparse));
+        assert_eq!(ImagingEngine::unsparse_image(&sparse, &raw).unwrap(), 40);
+
+        let mut expected = b"ABCDEFGH".to_vec();
+        expected.extend([0xaa, 0xbb, 0xcc, 0xdd].repeat(4));
+        expected.extend([0u8; 16]);
+        assert_eq!(std::fs::read(&raw).unwrap(), expected);
+
+        std::fs::write(&sparse, &image[..image.len() - 20]).unwrap();
+        assert!(ImagingEngine::unsparse_image(&sparse, &raw).is_err());
+    }
+}
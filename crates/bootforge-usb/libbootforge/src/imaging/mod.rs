@@ -1,7 +1,13 @@
 pub mod engine;
 pub mod writers;
 pub mod boot_profiles;
+pub mod wim;
 
 pub use engine::{ImagingEngine, ImageFormat, ImagingProgress};
+pub use wim::{list_wim_images, WimImageInfo};
 pub use writers::{RawWriter, ApfsWriter, NtfsWriter, ExtWriter};
-pub use boot_profiles::{BootProfileRegistry, BootProfile, OSType, DeviceFamily};
+pub use boot_profiles::{
+    BootProfileRegistry, BootProfile, OSType, DeviceFamily, ProfileError, PartitionDef, PartitionFS,
+    minimum_image_flash_size,
+    USB2_ASSUMED_SPEED_BPS, USB3_ASSUMED_SPEED_BPS,
+};
@@ -0,0 +1,249 @@
+use crate::Result;
+use crate::BootforgeError;
+use super::engine::ImagingProgress;
+use serde::{Deserialize, Serialize};
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const WIMLIB: &str = "wimlib-imagex";
+#[cfg(target_os = "windows")]
+const DISM: &str = "dism";
+
+/// One image inside a WIM file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WimImageInfo {
+    pub index: u32,
+    pub name: String,
+    pub description: Option<String>,
+    /// Expanded size of the image
+    pub size_bytes: Option<u64>,
+}
+
+/// List the images in a WIM file. Windows asks DISM first and falls back to
+/// wimlib; other platforms use wimlib only.
+pub fn list_wim_images(wim_path: &Path) -> Result<Vec<WimImageInfo>> {
+    #[cfg(target_os = "windows")]
+    {
+        let mut cmd = Command::new(DISM);
+        cmd.arg("/English")
+            .arg("/Get-WimInfo")
+            .arg(format!("/WimFile:{}", wim_path.display()));
+        match run_capture(cmd) {
+            Ok(output) => return Ok(parse_image_list(&output)),
+            Err(e) => log::warn!("DISM could not read {}: {}; trying wimlib", wim_path.display(), e),
+        }
+    }
+    let mut cmd = Command::new(WIMLIB);
+    cmd.arg("info").arg(wim_path);
+    let output = run_capture(cmd)?;
+    Ok(parse_image_list(&output))
+}
+
+/// Apply image `image_index` of the WIM to `target_drive`, reporting each
+/// progress line the tool prints. Windows tries DISM first and falls back to
+/// wimlib; other platforms use wimlib only.
+pub(crate) fn apply_wim(
+    wim_path: &Path,
+    target_drive: &Path,
+    image_index: u32,
+    on_progress: &mut dyn FnMut(&ImagingProgress),
+) -> Result<ImagingProgress> {
+    let image = list_wim_images(wim_path)?
+        .into_iter()
+        .find(|image| image.index == image_index)
+        .ok_or_else(|| {
+            BootforgeError::Imaging(format!("{} has no image with index {}", wim_path.display(), image_index))
+        })?;
+    let total_bytes = image.size_bytes.unwrap_or(0);
+
+    #[cfg(target_os = "windows")]
+    {
+        let mut cmd = Command::new(DISM);
+        cmd.arg("/English")
+            .arg("/Apply-Image")
+            .arg(format!("/ImageFile:{}", wim_path.display()))
+            .arg(format!("/Index:{}", image_index))
+            .arg(format!("/ApplyDir:{}", target_drive.display()));
+        match run_with_progress(cmd, total_bytes, on_progress) {
+            Ok(progress) => return Ok(progress),
+            Err(e) => log::warn!("DISM could not apply {}: {}; trying wimlib", wim_path.display(), e),
+        }
+    }
+    let mut cmd = Command::new(WIMLIB);
+    cmd.arg("apply").arg(wim_path).arg(image_index.to_string()).arg(target_drive);
+    run_with_progress(cmd, total_bytes, on_progress)
+}
+
+fn run_capture(mut cmd: Command) -> Result<String> {
+    let program = cmd.get_program().to_string_lossy().to_string();
+    let output = cmd
+        .output()
+        .map_err(|e| BootforgeError::Imaging(format!("{} not available: {}", program, e)))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if !output.status.success() {
+        return Err(BootforgeError::Imaging(format!(
+            "{} failed: {}{}",
+            program,
+            stdout.trim(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(stdout)
+}
+
+/// Run an apply command, turning every percentage it prints into an
+/// `ImagingProgress`. Both tools redraw their progress line with `\r`.
+fn run_with_progress(
+    mut cmd: Command,
+    total_bytes: u64,
+    on_progress: &mut dyn FnMut(&ImagingProgress),
+) -> Result<ImagingProgress> {
+    let program = cmd.get_program().to_string_lossy().to_string();
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| BootforgeError::Imaging(format!("{} not available: {}", program, e)))?;
+
+    let progress_at = |percentage: f32, status: &str| ImagingProgress {
+        total_bytes,
+        written_bytes: (total_bytes as f64 * percentage as f64 / 100.0) as u64,
+        percentage,
+        status: status.to_string(),
+    };
+    // Drain stderr alongside stdout so a chatty tool cannot block on a full pipe
+    let stderr_reader = child.stderr.take().map(|mut pipe| {
+        std::thread::spawn(move || {
+            let mut stderr = String::new();
+            let _ = pipe.read_to_string(&mut stderr);
+            stderr
+        })
+    });
+    let mut last_line = String::new();
+    if let Some(stdout) = child.stdout.take() {
+        let mut line = Vec::new();
+        for byte in BufReader::new(stdout).bytes() {
+            let byte = byte?;
+            if byte != b'\r' && byte != b'\n' {
+                line.push(byte);
+                continue;
+            }
+            let text = String::from_utf8_lossy(&line).trim().to_string();
+            line.clear();
+            if let Some(percentage) = parse_percent(&text) {
+                on_progress(&progress_at(percentage, "applying"));
+            }
+            if !text.is_empty() {
+                last_line = text;
+            }
+        }
+    }
+    let stderr = stderr_reader.and_then(|reader| reader.join().ok()).unwrap_or_default();
+    let status = child.wait()?;
+    if !status.success() {
+        let detail = if stderr.trim().is_empty() { last_line } else { stderr.trim().to_string() };
+        return Err(BootforgeError::Imaging(format!("{} failed: {}", program, detail)));
+    }
+
+    let done = progress_at(100.0, "completed");
+    on_progress(&done);
+    Ok(done)
+}
+
+/// Percentage in a progress line: wimlib's `... 1024 MiB of 4096 MiB (25%) done`
+/// or DISM's `[=====     25.0%     ]`.
+fn parse_percent(line: &str) -> Option<f32> {
+    let end = line.find('%')?;
+    let start = line[..end]
+        .rfind(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .map_or(0, |i| i + 1);
+    line[start..end].parse::<f32>().ok().filter(|pct| (0.0..=100.0).contains(pct))
+}
+
+/// Image entries from `wimlib-imagex info` (`Index:  1`) or
+/// `dism /Get-WimInfo` (`Index : 1`) output. Each `Index` line starts a new image.
+fn parse_image_list(output: &str) -> Vec<WimImageInfo> {
+    let mut images: Vec<WimImageInfo> = Vec::new();
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "Index" => {
+                if let Ok(index) = value.parse() {
+                    images.push(WimImageInfo { index, name: String::new(), description: None, size_bytes: None });
+                }
+            }
+            "Name" => {
+                if let Some(image) = images.last_mut() {
+                    image.name = value.to_string();
+                }
+            }
+            "Description" => {
+                if let Some(image) = images.last_mut() {
+                    image.description = Some(value.to_string()).filter(|d| !d.is_empty() && d != "<undefined>");
+                }
+            }
+            // wimlib reports "Total Bytes", DISM "Size : 1,234 bytes"
+            "Total Bytes" | "Size" => {
+                if let Some(image) = images.last_mut() {
+                    let digits: String = value.chars().take_while(|c| c.is_ascii_digit() || *c == ',').collect();
+                    image.size_bytes = digits.replace(',', "").parse().ok();
+                }
+            }
+            _ => {}
+        }
+    }
+    images
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_wimlib_and_dism_image_lists() {
+        let wimlib = "WIM Information:\n----------------\nPath:           install.wim\nImage Count:    2\n\n\
+                      Available Images:\n-----------------\nIndex:                  1\nName:                   Windows 10 Home\n\
+                      Description:            Windows 10 Home\nTotal Bytes:            15020418562\n\n\
+                      Index:                  2\nName:                   Windows 10 Pro\nDescription:            <undefined>\n";
+        let images = parse_image_list(wimlib);
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].name, "Windows 10 Home");
+        assert_eq!(images[0].size_bytes, Some(15_020_418_562));
+        assert_eq!(images[1].description, None);
+
+        let dism = "Details for image : install.wim\n\nIndex : 3\nName : Windows 11 Pro\n\
+                    Description : Windows 11 Pro\nSize : 16,212,545,013 bytes\n\nThe operation completed successfully.\n";
+        assert_eq!(
+            parse_image_list(dism),
+            vec![WimImageInfo {
+                index: 3,
+                name: "Windows 11 Pro".to_string(),
+                description: Some("Windows 11 Pro".to_string()),
+                size_bytes: Some(16_212_545_013),
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_progress_percentages() {
+        assert_eq!(parse_percent("Extracting file data: 1024 MiB of 4096 MiB (25%) done"), Some(25.0));
+        assert_eq!(parse_percent("[=====                      10.0%                          ]"), Some(10.0));
+        assert_eq!(parse_percent("Applying image"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_progress_while_the_tool_fills_stderr() {
+        // More stderr than a pipe buffer holds, written before the last progress line
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("echo '(50%)'; head -c 200000 /dev/zero | tr '\\0' x >&2; echo '(90%)'");
+        let mut seen = Vec::new();
+        let done = run_with_progress(cmd, 1000, &mut |p| seen.push(p.percentage)).unwrap();
+        assert_eq!(seen, vec![50.0, 90.0, 100.0]);
+        assert_eq!(done.written_bytes, 1000);
+    }
+}
@@ -1,4 +1,15 @@
 use crate::Result;
+use crate::BootforgeError;
+use super::boot_profiles::USB2_ASSUMED_SPEED_BPS;
+use super::engine::ImagingProgress;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+/// Chunk size used when `RawWriter::write` is given 0
+pub const DEFAULT_RAW_CHUNK_SIZE: usize = 1024 * 1024;
 
 pub struct RawWriter;
 pub struct ApfsWriter;
@@ -10,6 +21,86 @@ impl RawWriter {
         log::info!("Writing raw image to {}", _target);
         Ok(())
     }
+
+    /// Copy `source` byte for byte onto `target` in `chunk_size` pieces (0
+    /// means [`DEFAULT_RAW_CHUNK_SIZE`]), sending progress after every chunk.
+    /// A regular-file target is created or truncated; a block device is
+    /// written in place, opened with `O_SYNC` on Unix. The target is synced
+    /// before the final "completed" progress is sent.
+    pub fn write(
+        source: &Path,
+        target: &Path,
+        chunk_size: usize,
+        progress_tx: Sender<ImagingProgress>,
+    ) -> Result<()> {
+        let chunk_size = if chunk_size == 0 { DEFAULT_RAW_CHUNK_SIZE } else { chunk_size };
+        let mut input = File::open(source).map_err(|e| {
+            BootforgeError::Imaging(format!("Cannot open {}: {}", source.display(), e))
+        })?;
+        let total_bytes = input.metadata()?.len();
+        let mut output = open_target(target)?;
+
+        let progress_at = |written_bytes: u64, status: &str| ImagingProgress {
+            total_bytes,
+            written_bytes,
+            percentage: if total_bytes == 0 { 100.0 } else { (written_bytes as f64 / total_bytes as f64 * 100.0) as f32 },
+            status: status.to_string(),
+        };
+        let mut buffer = vec![0u8; chunk_size];
+        let mut written_bytes = 0u64;
+        loop {
+            let read = input.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            output.write_all(&buffer[..read]).map_err(|e| {
+                BootforgeError::Imaging(format!("Write to {} failed at byte {}: {}", target.display(), written_bytes, e))
+            })?;
+            written_bytes += read as u64;
+            // A dropped receiver only means nobody is watching; keep writing
+            let _ = progress_tx.send(progress_at(written_bytes, "writing"));
+        }
+        output.sync_all()?;
+
+        let _ = progress_tx.send(progress_at(written_bytes, "completed"));
+        log::info!("Wrote {} bytes from {} to {}", written_bytes, source.display(), target.display());
+        Ok(())
+    }
+
+    /// How long writing `file_size` bytes takes at `target_speed_bps`; a speed
+    /// of 0 falls back to [`USB2_ASSUMED_SPEED_BPS`].
+    pub fn estimate_time(file_size: u64, target_speed_bps: u64) -> Duration {
+        let speed = if target_speed_bps == 0 { USB2_ASSUMED_SPEED_BPS } else { target_speed_bps };
+        Duration::from_secs_f64(file_size as f64 / speed as f64)
+    }
+}
+
+fn open_target(target: &Path) -> Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if is_block_device(target) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.custom_flags(libc::O_SYNC);
+        }
+    } else {
+        options.create(true).truncate(true);
+    }
+    options
+        .open(target)
+        .map_err(|e| BootforgeError::Imaging(format!("Cannot open {} for writing: {}", target.display(), e)))
+}
+
+#[cfg(unix)]
+fn is_block_device(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    std::fs::metadata(path).map(|m| m.file_type().is_block_device()).unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_block_device(_path: &Path) -> bool {
+    false
 }
 
 impl ApfsWriter {
@@ -32,3 +123,31 @@ impl ExtWriter {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn raw_write_copies_the_source_exactly() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("image.img");
+        let target = dir.path().join("target.img");
+        let data: Vec<u8> = (0..4 * 1024 * 1024u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&source, &data).unwrap();
+        std::fs::write(&target, b"stale contents longer than nothing").unwrap();
+
+        let (tx, rx) = channel();
+        RawWriter::write(&source, &target, 0, tx).unwrap();
+
+        assert_eq!(std::fs::read(&target).unwrap(), data);
+        let updates: Vec<ImagingProgress> = rx.iter().collect();
+        assert_eq!(updates.len(), 5, "four 1 MiB chunks plus completion");
+        assert_eq!(updates[1].written_bytes, 2 * 1024 * 1024);
+        let last = updates.last().unwrap();
+        assert_eq!((last.status.as_str(), last.percentage), ("completed", 100.0));
+
+        assert_eq!(RawWriter::estimate_time(50 * 1024 * 1024, 0), Duration::from_secs(2));
+    }
+}
@@ -82,5 +82,7 @@ pub use device_state::{
     OperationState,
     DeviceCapabilities,
     DeviceTimestamps,
+    DeviceStateStore,
+    DeviceStateDiff,
     DEVICE_STATE_JSON_SCHEMA,
 };
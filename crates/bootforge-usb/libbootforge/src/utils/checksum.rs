@@ -1,25 +1,62 @@
 use crate::Result;
 use crate::BootforgeError;
-use std::path::Path;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 
 pub struct ChecksumVerifier;
 
 impl ChecksumVerifier {
-    pub async fn compute_sha256(_path: &Path) -> Result<String> {
-        log::warn!("SHA256 checksum computation not yet implemented");
-        // TODO: Implement using sha2 crate:
-        // use sha2::{Sha256, Digest};
-        // let mut file = File::open(path)?;
-        // let mut hasher = Sha256::new();
-        // io::copy(&mut file, &mut hasher)?;
-        // Ok(format!("{:x}", hasher.finalize()))
-        Err(BootforgeError::Storage("Checksum computation not yet implemented. Cannot verify file integrity.".to_string()))
+    /// Lowercase hex SHA-256 of the file. The file is read on a blocking
+    /// thread so large images do not stall the runtime.
+    pub async fn compute_sha256(path: &Path) -> Result<String> {
+        let path: PathBuf = path.to_path_buf();
+        tokio::task::spawn_blocking(move || Self::hash_file(&path))
+            .await
+            .map_err(|e| BootforgeError::Storage(format!("Checksum task failed: {}", e)))?
     }
 
-    pub async fn verify(_path: &Path, _expected: &str) -> Result<bool> {
-        log::warn!("Checksum verification not yet implemented");
-        // TODO: Compute actual checksum and compare with expected
-        // For now, return error instead of fake success
-        Err(BootforgeError::Storage("Checksum verification not yet implemented. Cannot validate file integrity.".to_string()))
+    /// Compare the file's SHA-256 with `expected` (hex, case-insensitive).
+    pub async fn verify(path: &Path, expected: &str) -> Result<bool> {
+        let actual = Self::compute_sha256(path).await?;
+        Ok(actual.eq_ignore_ascii_case(expected.trim()))
+    }
+
+    fn hash_file(path: &Path) -> Result<String> {
+        let mut file = File::open(path)
+            .map_err(|e| BootforgeError::Storage(format!("Failed to open {}: {}", path.display(), e)))?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 1024 * 1024];
+        loop {
+            let n = file
+                .read(&mut buffer)
+                .map_err(|e| BootforgeError::Storage(format!("Failed to read {}: {}", path.display(), e)))?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buffer[..n]);
+        }
+        Ok(hex::encode(hasher.finalize()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    // SHA-256 of "abc"
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[tokio::test]
+    async fn test_compute_and_verify_sha256() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"abc").unwrap();
+
+        assert_eq!(ChecksumVerifier::compute_sha256(file.path()).await.unwrap(), ABC_SHA256);
+        assert!(ChecksumVerifier::verify(file.path(), &ABC_SHA256.to_uppercase()).await.unwrap());
+        assert!(!ChecksumVerifier::verify(file.path(), "00").await.unwrap());
+        assert!(ChecksumVerifier::compute_sha256(Path::new("/nonexistent/image.img")).await.is_err());
     }
 }
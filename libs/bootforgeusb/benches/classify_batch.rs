@@ -0,0 +1,68 @@
+//! Compare sequential classification against `classify_batch` for a device lab
+//! sized set of transports.
+//!
+//! Run with: cargo bench --no-default-features --features parallel --bench classify_batch
+
+use bootforgeusb::classify::{classify_batch, classify_candidate_device};
+use bootforgeusb::model::UsbTransportEvidence;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const DEVICE_COUNTS: [usize; 3] = [10, 32, 128];
+const ITERATIONS: u32 = 2_000;
+
+/// Mixed Apple/Android/unknown transports, as seen on a busy USB hub
+fn lab_transports(count: usize) -> Vec<UsbTransportEvidence> {
+    const IDS: [(&str, &str); 6] = [
+        ("05ac", "12a8"),
+        ("05ac", "1227"),
+        ("18d1", "4ee7"),
+        ("18d1", "d00d"),
+        ("04e8", "6860"),
+        ("0bda", "8153"),
+    ];
+    (0..count)
+        .map(|i| {
+            let (vid, pid) = IDS[i % IDS.len()];
+            UsbTransportEvidence {
+                vid: vid.to_string(),
+                pid: pid.to_string(),
+                serial: Some(format!("LAB{:04}", i)),
+                bus: 1 + (i / 127) as u8,
+                address: 1 + (i % 127) as u8,
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+fn time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let mode = if cfg!(feature = "parallel") { "parallel" } else { "sequential (enable `parallel`)" };
+    println!("classify_batch mode: {}", mode);
+
+    for count in DEVICE_COUNTS {
+        let transports = lab_transports(count);
+        let sequential = time(|| {
+            let out: Vec<_> = transports.iter().map(classify_candidate_device).collect();
+            black_box(out);
+        });
+        let batch = time(|| {
+            black_box(classify_batch(black_box(&transports)));
+        });
+        println!(
+            "{:>4} devices: sequential {:>10.2?}  batch {:>10.2?}  speedup {:.2}x",
+            count,
+            sequential,
+            batch,
+            sequential.as_secs_f64() / batch.as_secs_f64().max(f64::EPSILON)
+        );
+    }
+}
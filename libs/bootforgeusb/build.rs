@@ -0,0 +1,92 @@
+//! Pre-process the USB ID database into compile-time `phf` maps.
+//!
+//! Reads `data/usb.ids` (a curated subset) by default. Set `BOOTFORGEUSB_USB_IDS`
+//! to the path of a full `usb.ids` download to embed the complete database.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+fn is_hex4(s: &str) -> bool {
+    s.len() == 4 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Parse the vendor/device section of a usb.ids file.
+///
+/// Returns (vendor id -> name, "vid:pid" -> device name). Interface lines and
+/// the trailing class/language sections are ignored.
+fn parse_usb_ids(contents: &str) -> (BTreeMap<String, String>, BTreeMap<String, String>) {
+    let mut vendors = BTreeMap::new();
+    let mut devices = BTreeMap::new();
+    let mut current_vendor: Option<String> = None;
+
+    for line in contents.lines() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix('\t') {
+            if rest.starts_with('\t') {
+                continue; // interface line
+            }
+            if let (Some(vendor), Some((pid, name))) = (&current_vendor, rest.split_once("  ")) {
+                if is_hex4(pid) {
+                    devices.insert(format!("{}:{}", vendor, pid.to_ascii_lowercase()), name.trim().to_string());
+                }
+            }
+            continue;
+        }
+        match line.split_once("  ") {
+            Some((vid, name)) if is_hex4(vid) => {
+                let vid = vid.to_ascii_lowercase();
+                vendors.insert(vid.clone(), name.trim().to_string());
+                current_vendor = Some(vid);
+            }
+            // First non-vendor top-level line starts the class sections ("C 00 ...").
+            _ => break,
+        }
+    }
+
+    (vendors, devices)
+}
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let source = env::var("BOOTFORGEUSB_USB_IDS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| manifest_dir.join("data").join("usb.ids"));
+
+    println!("cargo:rerun-if-env-changed=BOOTFORGEUSB_USB_IDS");
+    println!("cargo:rerun-if-changed={}", source.display());
+
+    // usb.ids is mostly UTF-8 but historically contains a few Latin-1 bytes.
+    let bytes = fs::read(&source).unwrap_or_else(|e| panic!("failed to read {}: {}", source.display(), e));
+    let contents = String::from_utf8_lossy(&bytes);
+    let (vendors, devices) = parse_usb_ids(&contents);
+
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("usb_ids.rs");
+    let mut out = BufWriter::new(fs::File::create(&out_path).unwrap());
+
+    let mut vendor_map = phf_codegen::Map::new();
+    for (vid, name) in &vendors {
+        vendor_map.entry(vid.as_str(), &format!("{:?}", name));
+    }
+    writeln!(
+        out,
+        "static USB_VENDOR_NAMES: phf::Map<&'static str, &'static str> = {};",
+        vendor_map.build()
+    )
+    .unwrap();
+
+    let mut device_map = phf_codegen::Map::new();
+    for (key, name) in &devices {
+        device_map.entry(key.as_str(), &format!("{:?}", name));
+    }
+    writeln!(
+        out,
+        "static USB_DEVICE_NAMES: phf::Map<&'static str, &'static str> = {};",
+        device_map.build()
+    )
+    .unwrap();
+}
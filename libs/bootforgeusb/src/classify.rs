@@ -1,5 +1,195 @@
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
 use crate::model::{Classification, DeviceMode, UsbTransportEvidence, InterfaceHint};
 use crate::tools::confirmers::ToolConfirmers;
+use crate::vid_pid_db::{lookup_device_name, lookup_vendor_name};
+
+/// A USB-only classification rule.
+/// 
+/// Returns `None` when the transport is not one this classifier knows about,
+/// so the next classifier in the [`ClassifierRegistry`] gets a turn.
+pub trait DeviceClassifier {
+    fn classify(&self, transport: &UsbTransportEvidence) -> Option<Classification>;
+}
+
+/// Built-in: Apple VID 05ac (DFU, Recovery, normal mode)
+pub struct AppleClassifier;
+
+impl DeviceClassifier for AppleClassifier {
+    fn classify(&self, transport: &UsbTransportEvidence) -> Option<Classification> {
+        is_apple(transport).then(|| classify_apple_device(&transport.pid, transport))
+    }
+}
+
+/// Built-in: MediaTek BootROM (0003), Preloader (2000) and DA (2001) download ports
+pub struct MediaTekClassifier;
+
+impl DeviceClassifier for MediaTekClassifier {
+    fn classify(&self, transport: &UsbTransportEvidence) -> Option<Classification> {
+        let stage = match (transport.vid.to_ascii_lowercase().as_str(), transport.pid.to_ascii_lowercase().as_str()) {
+            ("0e8d", "0003") => "BootROM",
+            ("0e8d", "2000") => "Preloader",
+            ("0e8d", "2001") => "DA",
+            _ => return None,
+        };
+        Some(Classification {
+            mode: DeviceMode::AndroidMtkDownloadLikely,
+            confidence: 0.80,
+            notes: vec![format!(
+                "USB signature matches MediaTek {} download port (VID:0E8D PID:{})",
+                stage, transport.pid
+            )],
+        })
+    }
+}
+
+/// Built-in: known Android OEM VIDs
+pub struct AndroidClassifier;
+
+impl DeviceClassifier for AndroidClassifier {
+    fn classify(&self, transport: &UsbTransportEvidence) -> Option<Classification> {
+        is_android_vendor(&transport.vid).then(|| classify_android_device(&transport.pid, transport))
+    }
+}
+
+/// Built-in fallback: always matches with `UnknownUsb`
+pub struct UnknownUsbClassifier;
+
+impl DeviceClassifier for UnknownUsbClassifier {
+    fn classify(&self, _transport: &UsbTransportEvidence) -> Option<Classification> {
+        Some(Classification {
+            mode: DeviceMode::UnknownUsb,
+            confidence: 0.5,
+            notes: vec!["USB device detected but not classified as mobile device".to_string()],
+        })
+    }
+}
+
+/// User-supplied classification for a device the built-in classifiers get
+/// wrong, matched by VID and optionally PID (hex, case-insensitive).
+/// `forced_mode` is a USB-level mode; tool correlation can still promote it
+/// (see [`DeviceMode::is_tool_confirmed`]).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassificationOverride {
+    pub vid: String,
+    /// `None` matches every PID of the vendor
+    #[serde(default)]
+    pub pid: Option<String>,
+    pub forced_mode: DeviceMode,
+    pub forced_confidence: f32,
+    pub note: String,
+}
+
+impl ClassificationOverride {
+    pub fn matches(&self, transport: &UsbTransportEvidence) -> bool {
+        self.vid.eq_ignore_ascii_case(&transport.vid)
+            && match self.pid.as_deref() {
+                Some(pid) => pid.eq_ignore_ascii_case(&transport.pid),
+                None => true,
+            }
+    }
+}
+
+/// Applies a list of [`ClassificationOverride`]s; the first match wins.
+pub struct OverrideClassifier(pub Vec<ClassificationOverride>);
+
+impl DeviceClassifier for OverrideClassifier {
+    fn classify(&self, transport: &UsbTransportEvidence) -> Option<Classification> {
+        self.0.iter().find(|o| o.matches(transport)).map(|o| Classification {
+            mode: o.forced_mode.clone(),
+            confidence: o.forced_confidence,
+            notes: vec![format!("Classification override: {}", o.note)],
+        })
+    }
+}
+
+/// Ordered list of classifiers; the first one that returns a classification wins.
+/// 
+/// Classifiers added with [`register`](Self::register) run before the
+/// built-in Apple, MediaTek, Android and unknown classifiers, in registration order.
+pub struct ClassifierRegistry {
+    classifiers: Vec<Box<dyn DeviceClassifier + Send + Sync>>,
+    registered: usize,
+}
+
+impl Default for ClassifierRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClassifierRegistry {
+    /// Registry with only the built-in classifiers
+    pub fn new() -> Self {
+        Self {
+            classifiers: vec![
+                Box::new(AppleClassifier),
+                Box::new(MediaTekClassifier),
+                Box::new(AndroidClassifier),
+                Box::new(UnknownUsbClassifier),
+            ],
+            registered: 0,
+        }
+    }
+
+    /// Built-in classifiers, with `overrides` taking precedence over all of them.
+    pub fn with_overrides(overrides: Vec<ClassificationOverride>) -> Self {
+        let mut registry = Self::new();
+        if !overrides.is_empty() {
+            registry.register(Box::new(OverrideClassifier(overrides)));
+        }
+        registry
+    }
+
+    /// Shared registry with only the built-in classifiers, used by [`crate::scan`].
+    pub fn builtin() -> &'static ClassifierRegistry {
+        static BUILTIN: OnceLock<ClassifierRegistry> = OnceLock::new();
+        BUILTIN.get_or_init(ClassifierRegistry::new)
+    }
+
+    /// Add a classifier that takes precedence over the built-in ones.
+    pub fn register(&mut self, classifier: Box<dyn DeviceClassifier + Send + Sync>) {
+        self.classifiers.insert(self.registered, classifier);
+        self.registered += 1;
+    }
+
+    /// Stage 2: Classify a candidate USB transport with the first matching classifier.
+    pub fn classify_first_match(&self, transport: &UsbTransportEvidence) -> Classification {
+        let mut classification = self
+            .classifiers
+            .iter()
+            .find_map(|c| c.classify(transport))
+            .unwrap_or_else(|| UnknownUsbClassifier.classify(transport).expect("fallback always matches"));
+        
+        // No product descriptor (typical in DFU/EDL): fall back to the USB ID database.
+        if transport.product.is_none() {
+            if let Some(note) = usb_id_database_note(&transport.vid, &transport.pid) {
+                classification.notes.push(note);
+            }
+        }
+        
+        classification
+    }
+
+    /// Stage 2 (batch): Classify many candidate transports at once.
+    /// 
+    /// With the `parallel` feature this fans out over rayon's thread pool, which
+    /// pays off in device labs with 10+ simultaneously attached devices. Without
+    /// it, transports are classified sequentially. Output order matches input order.
+    pub fn classify_batch(&self, transports: &[UsbTransportEvidence]) -> Vec<Classification> {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            transports.par_iter().map(|t| self.classify_first_match(t)).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            transports.iter().map(|t| self.classify_first_match(t)).collect()
+        }
+    }
+}
 
 /// Stage 2: Classify a candidate USB transport (determine platform + mode).
 /// 
@@ -8,23 +198,25 @@ use crate::tools::confirmers::ToolConfirmers;
 /// - Mode: ADB, Fastboot, DFU, Recovery, Normal, etc.
 /// - Confidence: 0.0 - 1.0 based on evidence strength
 /// 
-/// This is USB-only classification (no tool correlation yet).
+/// This is USB-only classification (no tool correlation yet), using the
+/// built-in classifiers only.
 pub fn classify_candidate_device(transport: &UsbTransportEvidence) -> Classification {
-    let vid = transport.vid.as_str();
-    let pid = transport.pid.as_str();
-    
-    if vid == "05ac" {
-        return classify_apple_device(pid, transport);
-    }
-    
-    if is_android_vendor(vid) {
-        return classify_android_device(pid, transport);
-    }
-    
-    Classification {
-        mode: DeviceMode::UnknownUsb,
-        confidence: 0.5,
-        notes: vec!["USB device detected but not classified as mobile device".to_string()],
+    ClassifierRegistry::builtin().classify_first_match(transport)
+}
+
+/// Stage 2 (batch) with the built-in classifiers. See [`ClassifierRegistry::classify_batch`].
+pub fn classify_batch(transports: &[UsbTransportEvidence]) -> Vec<Classification> {
+    ClassifierRegistry::builtin().classify_batch(transports)
+}
+
+/// Build a synthetic product note from the embedded USB ID database.
+fn usb_id_database_note(vid: &str, pid: &str) -> Option<String> {
+    let vendor = lookup_vendor_name(vid);
+    match (vendor, lookup_device_name(vid, pid)) {
+        (Some(vendor), Some(product)) => Some(format!("USB ID database: {} {} (VID:{} PID:{})", vendor, product, vid, pid)),
+        (None, Some(product)) => Some(format!("USB ID database: {} (VID:{} PID:{})", product, vid, pid)),
+        (Some(vendor), None) => Some(format!("USB ID database: vendor {} (PID:{} not listed)", vendor, pid)),
+        (None, None) => None,
     }
 }
 
@@ -42,7 +234,17 @@ pub fn resolve_device_identity_with_correlation(
     tools: &ToolConfirmers,
 ) -> (Classification, Vec<String>) {
     // Start with USB-only classification
-    let mut classification = classify_candidate_device(transport);
+    let classification = classify_candidate_device(transport);
+    correlate_classification(transport, classification, all_transports, tools)
+}
+
+/// Stage 4 for a transport that has already been classified (e.g. by [`classify_batch`]).
+pub fn correlate_classification(
+    transport: &UsbTransportEvidence,
+    mut classification: Classification,
+    all_transports: &[UsbTransportEvidence],
+    tools: &ToolConfirmers,
+) -> (Classification, Vec<String>) {
     let mut matched_tool_ids = Vec::new();
     
     // Step 4a: Direct serial match (highest confidence)
@@ -57,9 +259,36 @@ pub fn resolve_device_identity_with_correlation(
         ));
     }
     
+    // Step 4c: Apple device on USB that idevice_id does not list
+    if matched_tool_ids.is_empty() {
+        detect_untrusted_ios(transport, tools, &mut classification);
+    }
+    
     (classification, matched_tool_ids)
 }
 
+/// A normal-mode iPhone/iPad whose trust prompt has not been accepted shows
+/// up on USB but not in `idevice_id -l`. Only applies when idevice_id
+/// actually ran and reported no devices at all.
+fn detect_untrusted_ios(
+    transport: &UsbTransportEvidence,
+    tools: &ToolConfirmers,
+    classification: &mut Classification,
+) {
+    if !is_apple(transport)
+        || !matches!(classification.mode, DeviceMode::IosNormalLikely)
+        || !tools.idevice_id.present
+        || !tools.idevice_id.device_ids.is_empty()
+    {
+        return;
+    }
+    classification.mode = DeviceMode::IosUntrusted;
+    classification.confidence = 0.80;
+    classification.notes.push(
+        "iOS device detected but not trusted \u{2014} accept trust prompt on device".to_string()
+    );
+}
+
 /// Attempt single-candidate identity resolution heuristic.
 /// 
 /// When exactly one platform candidate and exactly one tool device ID,
@@ -128,10 +357,51 @@ fn has_vendor_interface(hints: &[InterfaceHint]) -> bool {
     hints.iter().any(|h| h.class == 0xff)
 }
 
+/// USB still image class (0x06, used by both PTP and MTP), or "MTP" in the product string.
+fn has_mtp_interface(transport: &UsbTransportEvidence) -> bool {
+    transport.interface_hints.iter().any(|h| h.class == 0x06)
+        || transport.product.as_deref().is_some_and(|p| p.contains("MTP"))
+}
+
 fn is_apple(transport: &UsbTransportEvidence) -> bool {
     transport.vid.eq_ignore_ascii_case("05ac")
 }
 
+/// Normal-mode Apple PIDs (VID 05ac) by device generation, from usb.ids and
+/// iDevice firmware metadata. Later generations share PIDs, so some entries
+/// name a range of models.
+static APPLE_MARKETING_NAMES: phf::Map<&'static str, &'static str> = phf::phf_map! {
+    "1290" => "iPhone",
+    "1291" => "iPod touch (1st generation)",
+    "1292" => "iPhone 3G",
+    "1293" => "iPod touch (2nd generation)",
+    "1294" => "iPhone 3GS",
+    "1297" => "iPhone 4",
+    "1299" => "iPod touch (3rd generation)",
+    "129a" => "iPad",
+    "129c" => "iPhone 4 (CDMA)",
+    "129e" => "iPod touch (4th generation)",
+    "129f" => "iPad 2",
+    "12a0" => "iPhone 4S",
+    "12a2" => "iPad 2 (3G)",
+    "12a3" => "iPad 2 (CDMA)",
+    "12a4" => "iPad (3rd generation)",
+    "12a5" => "iPad (3rd generation, CDMA)",
+    "12a6" => "iPad (3rd generation, 3G)",
+    "12a8" => "iPhone 5/5C/5S/6/SE/7/8/X/XR or later",
+    "12a9" => "iPad 2",
+    "12aa" => "iPod touch (5th generation)",
+    "12ab" => "iPad (4th generation) / iPad mini or later",
+};
+
+/// Marketing name for an Apple normal-mode PID, if known.
+///
+/// DFU (`1227`) and Recovery (`1281`) PIDs are shared by every model and
+/// are intentionally absent.
+pub fn resolve_apple_marketing_name(pid: &str) -> Option<&'static str> {
+    APPLE_MARKETING_NAMES.get(pid.to_ascii_lowercase().as_str()).copied()
+}
+
 fn is_android_likely(transport: &UsbTransportEvidence) -> bool {
     if is_apple(transport) {
         return false;
@@ -198,7 +468,29 @@ fn classify_apple_device(pid: &str, transport: &UsbTransportEvidence) -> Classif
     }
 }
 
-fn classify_android_device(_pid: &str, transport: &UsbTransportEvidence) -> Classification {
+fn classify_android_device(pid: &str, transport: &UsbTransportEvidence) -> Classification {
+    if detect_download_mode_device(transport) {
+        return Classification {
+            mode: DeviceMode::AndroidDownloadModeDetected,
+            confidence: 0.94,
+            notes: vec![
+                format!("USB signature matches Samsung Download mode (VID:04E8 PID:{})", pid),
+                "Flash with Odin or Heimdall".to_string(),
+            ],
+        };
+    }
+
+    if has_mtp_interface(transport) {
+        return Classification {
+            mode: DeviceMode::AndroidMtpDetected,
+            confidence: 0.72,
+            notes: vec![
+                "Android device in MTP/PTP file transfer mode (still image interface or MTP product string)".to_string(),
+                "Enable USB debugging to manage it with adb".to_string(),
+            ],
+        };
+    }
+
     if has_vendor_interface(&transport.interface_hints) {
         return Classification {
             mode: DeviceMode::UnknownUsb,
@@ -217,6 +509,25 @@ fn classify_android_device(_pid: &str, transport: &UsbTransportEvidence) -> Clas
     }
 }
 
+/// Samsung Download mode PIDs. 6860 is also the PID of the normal-mode
+/// MTP/adb composite device, so it only counts when the device exposes
+/// Download mode's CDC ACM serial port and neither of those interfaces.
+fn detect_download_mode_device(transport: &UsbTransportEvidence) -> bool {
+    if !transport.vid.eq_ignore_ascii_case("04e8") {
+        return false;
+    }
+    match transport.pid.to_ascii_lowercase().as_str() {
+        "685d" => true,
+        "6860" => {
+            let hints = &transport.interface_hints;
+            hints.iter().any(|h| h.class == 0x02 || h.class == 0x0a)
+                && !has_mtp_interface(transport)
+                && !has_vendor_interface(hints)
+        }
+        _ => false,
+    }
+}
+
 fn is_android_vendor(vid: &str) -> bool {
     matches!(
         vid,
@@ -248,12 +559,9 @@ mod tests {
             vid: "05ac".to_string(),
             pid: "1227".to_string(),
             manufacturer: Some("Apple Inc.".to_string()),
-            product: None,
-            serial: None,
             bus: 1,
             address: 5,
-            interface_class: None,
-            interface_hints: vec![],
+            ..Default::default()
         };
         
         let classification = classify_candidate_device(&transport);
@@ -277,6 +585,7 @@ mod tests {
                 subclass: 0x42,
                 protocol: 0x01,
             }],
+            ..Default::default()
         };
         
         let classification = classify_candidate_device(&transport);
@@ -288,13 +597,9 @@ mod tests {
         let transport = UsbTransportEvidence {
             vid: "0000".to_string(),
             pid: "0000".to_string(),
-            manufacturer: None,
-            product: None,
-            serial: None,
             bus: 1,
             address: 1,
-            interface_class: None,
-            interface_hints: vec![],
+            ..Default::default()
         };
         
         let classification = classify_candidate_device(&transport);
@@ -308,16 +613,240 @@ mod tests {
             vid: "05ac".to_string(),
             pid: "1281".to_string(),
             manufacturer: Some("Apple Inc.".to_string()),
-            product: None,
-            serial: None,
             bus: 1,
             address: 2,
-            interface_class: None,
-            interface_hints: vec![],
+            ..Default::default()
         };
         
         let classification = classify_candidate_device(&transport);
         assert_eq!(classification.mode.as_str(), "ios_recovery_likely");
         assert!(classification.confidence > 0.8);
     }
+    
+    #[test]
+    fn test_classify_adds_usb_id_note_without_product() {
+        let transport = UsbTransportEvidence {
+            vid: "05ac".to_string(),
+            pid: "1227".to_string(),
+            bus: 1,
+            address: 4,
+            ..Default::default()
+        };
+        
+        let classification = classify_candidate_device(&transport);
+        assert!(classification.notes.iter().any(|n| n.contains("Mobile Device (DFU Mode)")));
+    }
+    
+    #[test]
+    fn test_classify_batch_preserves_order() {
+        let transport = |vid: &str, pid: &str, address: u8| UsbTransportEvidence {
+            vid: vid.to_string(),
+            pid: pid.to_string(),
+            product: Some("test".to_string()),
+            bus: 1,
+            address,
+            ..Default::default()
+        };
+        let transports = vec![
+            transport("05ac", "1227", 1),
+            transport("0000", "0000", 2),
+            transport("05ac", "1281", 3),
+        ];
+        
+        let modes: Vec<&str> = classify_batch(&transports).iter().map(|c| c.mode.as_str()).collect();
+        assert_eq!(modes, vec!["ios_dfu_likely", "unknown_usb", "ios_recovery_likely"]);
+    }
+
+    struct AcmeClassifier;
+
+    impl DeviceClassifier for AcmeClassifier {
+        fn classify(&self, transport: &UsbTransportEvidence) -> Option<Classification> {
+            (transport.vid == "18d1" && transport.pid == "d00d").then(|| Classification {
+                mode: DeviceMode::AndroidFastbootConfirmed,
+                confidence: 0.7,
+                notes: vec!["Acme bootloader PID".to_string()],
+            })
+        }
+    }
+
+    #[test]
+    fn test_registered_classifier_takes_precedence() {
+        let transport = |pid: &str| UsbTransportEvidence {
+            vid: "18d1".to_string(),
+            pid: pid.to_string(),
+            manufacturer: Some("Google".to_string()),
+            product: Some("Android".to_string()),
+            bus: 1,
+            address: 9,
+            ..Default::default()
+        };
+        let mut registry = ClassifierRegistry::new();
+        let builtin = registry.classify_first_match(&transport("d00d"));
+        assert_ne!(builtin.mode.as_str(), "android_fastboot_confirmed");
+
+        registry.register(Box::new(AcmeClassifier));
+        let custom = registry.classify_first_match(&transport("d00d"));
+        assert_eq!(custom.mode.as_str(), "android_fastboot_confirmed");
+        assert_eq!(custom.notes, vec!["Acme bootloader PID".to_string()]);
+
+        // Transports the custom classifier declines fall through to the built-ins
+        let other = registry.classify_first_match(&transport("4ee7"));
+        assert_eq!(other.notes, builtin.notes);
+        let batch = registry.classify_batch(&[transport("4ee7"), transport("d00d")]);
+        assert_eq!(batch[1].mode.as_str(), "android_fastboot_confirmed");
+    }
+
+    #[test]
+    fn test_classify_mediatek_preloader() {
+        let transport = UsbTransportEvidence {
+            vid: "0e8d".to_string(),
+            pid: "2000".to_string(),
+            manufacturer: Some("MediaTek".to_string()),
+            product: Some("MT65xx Preloader".to_string()),
+            bus: 1,
+            address: 6,
+            interface_class: Some(0x02),
+            ..Default::default()
+        };
+        
+        let classification = classify_candidate_device(&transport);
+        assert_eq!(classification.mode.as_str(), "android_mtk_download_likely");
+        assert!(classification.notes[0].contains("Preloader"));
+        
+        // MediaTek phones booted into Android use other PIDs and stay generic
+        let adb = UsbTransportEvidence { pid: "201c".to_string(), ..transport };
+        assert_eq!(classify_candidate_device(&adb).mode.as_str(), "unknown_usb");
+    }
+
+    #[test]
+    fn test_classify_samsung_mtp() {
+        let transport = UsbTransportEvidence {
+            vid: "04e8".to_string(),
+            pid: "6860".to_string(),
+            manufacturer: Some("SAMSUNG".to_string()),
+            product: Some("SAMSUNG_Android".to_string()),
+            serial: Some("R58M12ABCDE".to_string()),
+            bus: 1,
+            address: 4,
+            interface_class: Some(0x06),
+            interface_hints: vec![InterfaceHint {
+                class: 0x06,
+                subclass: 0x01,
+                protocol: 0x01,
+            }],
+            ..Default::default()
+        };
+
+        let classification = classify_candidate_device(&transport);
+        assert_eq!(classification.mode.as_str(), "android_mtp_detected");
+        assert!((classification.confidence - 0.72).abs() < 1e-5);
+
+        // Enabling USB debugging adds an adb interface; adb confirmation wins
+        let tools = ToolConfirmers {
+            adb: crate::model::ToolEvidence::confirmed(String::new(), vec!["R58M12ABCDE".to_string()]),
+            fastboot: crate::model::ToolEvidence::missing(),
+            idevice_id: crate::model::ToolEvidence::missing(),
+        };
+        let (correlated, _) =
+            correlate_classification(&transport, classification, std::slice::from_ref(&transport), &tools);
+        assert_eq!(correlated.mode.as_str(), "android_adb_confirmed");
+    }
+
+    #[test]
+    fn test_classify_samsung_download_mode() {
+        let transport = |pid: &str, hints: Vec<InterfaceHint>| UsbTransportEvidence {
+            vid: "04e8".to_string(),
+            pid: pid.to_string(),
+            manufacturer: Some("SAMSUNG".to_string()),
+            product: Some("SAMSUNG USB".to_string()),
+            bus: 1,
+            address: 5,
+            interface_class: hints.first().map(|h| h.class),
+            interface_hints: hints,
+            ..Default::default()
+        };
+        // Download mode exposes a CDC ACM serial port
+        let cdc = || {
+            vec![
+                InterfaceHint { class: 0x02, subclass: 0x02, protocol: 0x01 },
+                InterfaceHint { class: 0x0a, subclass: 0x00, protocol: 0x00 },
+            ]
+        };
+
+        for pid in ["685d", "6860", "685D"] {
+            let download = transport(pid, cdc());
+            assert!(detect_download_mode_device(&download), "{pid}");
+            let classification = classify_candidate_device(&download);
+            assert_eq!(classification.mode.as_str(), "android_download_mode_detected");
+            assert!((classification.confidence - 0.94).abs() < 1e-5);
+        }
+
+        // 6860 is also the normal-mode MTP/adb PID
+        let adb = transport("6860", vec![InterfaceHint { class: 0xff, subclass: 0x42, protocol: 0x01 }]);
+        assert!(!detect_download_mode_device(&adb));
+        assert!(!detect_download_mode_device(&transport("6860", vec![])));
+
+        let normal = transport("6866", cdc());
+        assert!(!detect_download_mode_device(&normal));
+        assert_eq!(classify_candidate_device(&normal).mode.as_str(), "unknown_usb");
+        assert!(!detect_download_mode_device(&UsbTransportEvidence { vid: "18d1".to_string(), ..transport("685d", cdc()) }));
+    }
+
+    #[test]
+    fn test_override_takes_priority_over_builtin() {
+        let transport = UsbTransportEvidence {
+            vid: "18D1".to_string(),
+            pid: "4EE7".to_string(),
+            manufacturer: Some("Google".to_string()),
+            product: Some("Pixel 8".to_string()),
+            bus: 1,
+            address: 3,
+            ..Default::default()
+        };
+        let rule = |pid: Option<&str>| ClassificationOverride {
+            vid: "18d1".to_string(),
+            pid: pid.map(str::to_string),
+            forced_mode: DeviceMode::AndroidMtpDetected,
+            forced_confidence: 0.85,
+            note: "Lab bootloader board".to_string(),
+        };
+        assert_eq!(classify_candidate_device(&transport).mode.as_str(), "unknown_usb");
+
+        let registry = ClassifierRegistry::with_overrides(vec![rule(Some("4ee7"))]);
+        let classification = registry.classify_first_match(&transport);
+        assert_eq!(classification.mode.as_str(), "android_mtp_detected");
+        assert!((classification.confidence - 0.85).abs() < 1e-5);
+        assert_eq!(classification.notes[0], "Classification override: Lab bootloader board");
+
+        // A PID-less override covers the whole vendor; a different PID does not match
+        assert!(rule(None).matches(&transport));
+        let other_pid = ClassifierRegistry::with_overrides(vec![rule(Some("4ee0"))]);
+        assert_eq!(other_pid.classify_first_match(&transport).mode.as_str(), "unknown_usb");
+
+        let json = serde_json::to_value(rule(None)).unwrap();
+        assert_eq!(json["forced_mode"], "android_mtp_detected");
+
+        // The confirmers still promote an overridden device
+        let serial = UsbTransportEvidence { serial: Some("LAB01".to_string()), ..transport };
+        let tools = ToolConfirmers {
+            fastboot: crate::model::ToolEvidence::confirmed(String::new(), vec!["LAB01".to_string()]),
+            adb: crate::model::ToolEvidence::missing(),
+            idevice_id: crate::model::ToolEvidence::missing(),
+        };
+        let (correlated, _) = correlate_classification(
+            &serial,
+            registry.classify_first_match(&serial),
+            std::slice::from_ref(&serial),
+            &tools,
+        );
+        assert_eq!(correlated.mode.as_str(), "android_fastboot_confirmed");
+    }
+
+    #[test]
+    fn test_apple_marketing_name_from_pid() {
+        assert!(resolve_apple_marketing_name("12a8").unwrap().starts_with("iPhone"));
+        assert!(resolve_apple_marketing_name("12A8").is_some());
+        assert_eq!(resolve_apple_marketing_name("1297"), Some("iPhone 4"));
+        assert_eq!(resolve_apple_marketing_name("1227"), None);
+    }
 }
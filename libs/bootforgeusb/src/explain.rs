@@ -0,0 +1,237 @@
+//! Plain-English explanations of a device classification.
+//!
+//! Turns a `ConfirmedDeviceRecord` into a short summary, the evidence that
+//! supports it, a confidence label and suggested next steps for users who
+//! are not familiar with USB modes.
+
+use crate::model::{ConfirmedDeviceRecord, DeviceMode};
+use crate::vid_pid_db::lookup_device_name;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassificationExplanation {
+    pub summary: String,
+    pub evidence_list: Vec<String>,
+    /// "Very High", "High", "Medium" or "Low"
+    pub confidence_label: String,
+    pub recommended_actions: Vec<String>,
+}
+
+/// Human label for a classification confidence score
+pub fn confidence_label(confidence: f32) -> &'static str {
+    if confidence >= 0.9 {
+        "Very High"
+    } else if confidence >= 0.8 {
+        "High"
+    } else if confidence >= 0.65 {
+        "Medium"
+    } else {
+        "Low"
+    }
+}
+
+pub fn explain(record: &ConfirmedDeviceRecord) -> ClassificationExplanation {
+    let mode = DeviceMode::parse(&record.mode).unwrap_or(DeviceMode::UnknownUsb);
+    let label = confidence_label(record.confidence);
+    ClassificationExplanation {
+        summary: summary(&mode, record, label),
+        evidence_list: evidence_list(record),
+        confidence_label: label.to_string(),
+        recommended_actions: recommended_actions(&mode, record.confidence),
+    }
+}
+
+fn summary(mode: &DeviceMode, record: &ConfirmedDeviceRecord, label: &str) -> String {
+    let usb = &record.evidence.usb;
+    let name = usb
+        .product
+        .clone()
+        .or_else(|| lookup_device_name(&usb.vid, &usb.pid).map(str::to_string))
+        .unwrap_or_else(|| format!("USB device {}:{}", usb.vid, usb.pid));
+    let state = match mode {
+        DeviceMode::IosNormalLikely => "appears to be an iPhone or iPad running normally",
+        DeviceMode::IosRecoveryLikely => "appears to be an iPhone or iPad in Recovery mode",
+        DeviceMode::IosDfuLikely => "appears to be an iPhone or iPad in DFU mode",
+        DeviceMode::IosUntrusted => "is an iPhone or iPad that has not trusted this computer yet",
+        DeviceMode::AndroidAdbConfirmed => "is an Android device confirmed by adb",
+        DeviceMode::AndroidFastbootConfirmed => "is an Android device in the bootloader, confirmed by fastboot",
+        DeviceMode::AndroidRecoveryAdbConfirmed => "is an Android device in recovery, confirmed by adb",
+        DeviceMode::AndroidMtkDownloadLikely => "appears to be a MediaTek device in BootROM/Preloader download mode",
+        DeviceMode::AndroidMtpDetected => "appears to be an Android device in file transfer (MTP) mode",
+        DeviceMode::AndroidDownloadModeDetected => "appears to be a Samsung device in Download mode",
+        DeviceMode::UnknownUsb => "could not be identified as a phone or tablet in a known mode",
+    };
+    format!("{} {} ({} confidence).", name, state, label.to_lowercase())
+}
+
+fn evidence_list(record: &ConfirmedDeviceRecord) -> Vec<String> {
+    let usb = &record.evidence.usb;
+    let mut evidence = Vec::new();
+
+    match lookup_device_name(&usb.vid, &usb.pid) {
+        Some(name) => evidence.push(format!("USB ID {}:{} is listed as \"{}\"", usb.vid, usb.pid, name)),
+        None => evidence.push(format!("USB ID {}:{}", usb.vid, usb.pid)),
+    }
+    if let Some(manufacturer) = &usb.manufacturer {
+        evidence.push(format!("USB manufacturer string: {}", manufacturer));
+    }
+    if let Some(product) = &usb.product {
+        evidence.push(format!("USB product string: {}", product));
+    }
+    match &usb.serial {
+        Some(serial) => evidence.push(format!("USB serial number: {}", serial)),
+        None => evidence.push("Device did not report a USB serial number".to_string()),
+    }
+    if usb.interface_hints.iter().any(|h| h.class == 0xff) {
+        evidence.push("Exposes a vendor-specific USB interface".to_string());
+    }
+
+    let mut tools: Vec<_> = record.evidence.tools.iter().collect();
+    tools.sort_by(|a, b| a.0.cmp(b.0));
+    for (tool, tool_evidence) in tools {
+        let matched: Vec<&String> = tool_evidence
+            .device_ids
+            .iter()
+            .filter(|id| record.matched_tool_ids.contains(id))
+            .collect();
+        if !matched.is_empty() {
+            let ids: Vec<&str> = matched.iter().map(|s| s.as_str()).collect();
+            evidence.push(format!("{} reports this device as {}", tool, ids.join(", ")));
+        } else if tool_evidence.is_skipped() {
+            evidence.push(format!("{} was not run (USB-only scan)", tool));
+        } else if !tool_evidence.present {
+            evidence.push(format!("{} is not installed, so it could not confirm the device", tool));
+        }
+    }
+
+    evidence.extend(record.notes.iter().cloned());
+    evidence
+}
+
+fn recommended_actions(mode: &DeviceMode, confidence: f32) -> Vec<String> {
+    let mut actions: Vec<String> = match mode {
+        DeviceMode::IosNormalLikely => vec![
+            "Run `idevice_id -l` to confirm the device UDID".to_string(),
+            "Unlock the device and tap \"Trust This Computer\" if prompted".to_string(),
+        ],
+        DeviceMode::IosRecoveryLikely => vec![
+            "Check the device screen for the recovery (cable to computer) image".to_string(),
+            "Use Finder or iTunes to restore or update the device".to_string(),
+        ],
+        DeviceMode::IosDfuLikely => vec![
+            "A black screen is expected in DFU mode".to_string(),
+            "Use Finder or iTunes to restore the device, or hold the buttons to exit DFU".to_string(),
+        ],
+        DeviceMode::IosUntrusted => vec![
+            "Unlock the device and tap \"Trust This Computer\", then scan again".to_string(),
+            "If no prompt appears, reconnect the cable or reset Location & Privacy on the device".to_string(),
+        ],
+        DeviceMode::AndroidAdbConfirmed | DeviceMode::AndroidRecoveryAdbConfirmed => vec![
+            "Run `adb devices -l` to confirm identity".to_string(),
+            "Accept the USB debugging prompt on the device if it shows as unauthorized".to_string(),
+        ],
+        DeviceMode::AndroidFastbootConfirmed => vec![
+            "Run `fastboot devices` to confirm identity".to_string(),
+            "Run `fastboot getvar all` to check bootloader and slot state before flashing".to_string(),
+        ],
+        DeviceMode::AndroidMtkDownloadLikely => vec![
+            "Flash with the spflashtool method and the device's scatter file".to_string(),
+            "The download port only stays up for a few seconds; start the flash before plugging in if it disconnects".to_string(),
+        ],
+        DeviceMode::AndroidMtpDetected => vec![
+            "Files can be copied with the system's MTP support".to_string(),
+            "Enable USB debugging in Developer options to use adb tools".to_string(),
+        ],
+        DeviceMode::AndroidDownloadModeDetected => vec![
+            "Flash with Odin or Heimdall; adb and fastboot cannot see the device in this mode".to_string(),
+            "Hold Volume Down and Power to leave Download mode if no flash is planned".to_string(),
+        ],
+        DeviceMode::UnknownUsb => vec![
+            "Run `adb devices` and `fastboot devices` to check for an Android device".to_string(),
+            "Enable USB debugging on Android devices, or unlock and trust the computer on iOS".to_string(),
+            "Try a different cable or USB port".to_string(),
+        ],
+    };
+    if confidence < 0.65 {
+        actions.push("Reconnect the device and scan again to gather more evidence".to_string());
+    }
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Evidence, Platform, ToolEvidence, UsbTransportEvidence};
+    use std::collections::HashMap;
+
+    fn record(mode: DeviceMode, confidence: f32) -> ConfirmedDeviceRecord {
+        let mut tools = HashMap::new();
+        tools.insert(
+            "fastboot".to_string(),
+            ToolEvidence {
+                present: true,
+                seen: true,
+                raw: "ABC123\tfastboot".to_string(),
+                device_ids: vec!["ABC123".to_string()],
+                unauthorized_ids: vec![],
+            },
+        );
+        tools.insert("adb".to_string(), ToolEvidence::missing());
+        ConfirmedDeviceRecord {
+            device_uid: "ABC123".to_string(),
+            platform: Platform::from_mode(mode.as_str()),
+            mode: mode.as_str().to_string(),
+            confidence,
+            evidence: Evidence {
+                usb: UsbTransportEvidence {
+                    vid: "18d1".to_string(),
+                    pid: "4ee0".to_string(),
+                    manufacturer: Some("Google".to_string()),
+                    product: Some("Pixel 3a".to_string()),
+                    serial: Some("ABC123".to_string()),
+                    bus: 1,
+                    address: 4,
+                    ..Default::default()
+                },
+                tools,
+            },
+            notes: vec!["Correlated: fastboot device id matches USB serial".to_string()],
+            matched_tool_ids: vec!["ABC123".to_string()],
+            marketing_name: None,
+            driver_status: None,
+        }
+    }
+
+    #[test]
+    fn test_confidence_labels() {
+        assert_eq!(confidence_label(0.95), "Very High");
+        assert_eq!(confidence_label(0.86), "High");
+        assert_eq!(confidence_label(0.70), "Medium");
+        assert_eq!(confidence_label(0.5), "Low");
+    }
+
+    #[test]
+    fn test_explain_fastboot_device() {
+        let explanation = explain(&record(DeviceMode::AndroidFastbootConfirmed, 0.95));
+        assert_eq!(explanation.confidence_label, "Very High");
+        assert!(explanation.summary.starts_with("Pixel 3a is an Android device in the bootloader"));
+        assert!(explanation
+            .evidence_list
+            .contains(&"fastboot reports this device as ABC123".to_string()));
+        assert!(explanation
+            .evidence_list
+            .contains(&"adb is not installed, so it could not confirm the device".to_string()));
+        assert!(explanation.recommended_actions[0].contains("fastboot devices"));
+    }
+
+    #[test]
+    fn test_low_confidence_suggests_rescan() {
+        let explanation = explain(&record(DeviceMode::UnknownUsb, 0.6));
+        assert_eq!(explanation.confidence_label, "Low");
+        assert!(explanation
+            .recommended_actions
+            .last()
+            .unwrap()
+            .contains("scan again"));
+    }
+}
@@ -0,0 +1,185 @@
+//! Scan history: persist every scan result as JSON Lines for later audit.
+//!
+//! One file per UTC day (`scan-history-YYYY-MM-DD.jsonl`) is kept in the
+//! history directory. Files older than the retention window are deleted when
+//! the logger is created.
+
+use crate::model::ConfirmedDeviceRecord;
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const FILE_PREFIX: &str = "scan-history-";
+const FILE_SUFFIX: &str = ".jsonl";
+
+/// One recorded scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanHistoryEntry {
+    /// RFC 3339 timestamp of when the scan was recorded
+    pub timestamp: String,
+    pub devices: Vec<ConfirmedDeviceRecord>,
+}
+
+/// Appends scan results to daily-rotated JSON Lines files
+pub struct ScanHistoryLogger {
+    dir: PathBuf,
+    retention_days: i64,
+    write_lock: Mutex<()>,
+}
+
+impl ScanHistoryLogger {
+    /// Days of history kept on disk
+    pub const DEFAULT_RETENTION_DAYS: i64 = 7;
+
+    /// Create a logger writing into `dir`, creating it and pruning expired files.
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        Self::with_retention(dir, Self::DEFAULT_RETENTION_DAYS)
+    }
+
+    pub fn with_retention(dir: impl Into<PathBuf>, retention_days: i64) -> io::Result<Self> {
+        let logger = Self {
+            dir: dir.into(),
+            retention_days: retention_days.max(1),
+            write_lock: Mutex::new(()),
+        };
+        fs::create_dir_all(&logger.dir)?;
+        logger.prune_expired(Utc::now().date_naive())?;
+        Ok(logger)
+    }
+
+    /// Directory holding the history files
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// File the next entry will be appended to
+    pub fn current_path(&self) -> PathBuf {
+        self.path_for(Utc::now().date_naive())
+    }
+
+    fn path_for(&self, date: NaiveDate) -> PathBuf {
+        self.dir
+            .join(format!("{}{}{}", FILE_PREFIX, date.format("%Y-%m-%d"), FILE_SUFFIX))
+    }
+
+    /// Append one scan result to today's file
+    pub fn record(&self, devices: &[ConfirmedDeviceRecord]) -> io::Result<()> {
+        let entry = ScanHistoryEntry {
+            timestamp: Utc::now().to_rfc3339(),
+            devices: devices.to_vec(),
+        };
+        let line = serde_json::to_string(&entry)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.current_path())?;
+        writeln!(file, "{}", line)
+    }
+
+    /// Delete history files dated more than `retention_days` before `today`.
+    /// Returns the number of files removed.
+    pub fn prune_expired(&self, today: NaiveDate) -> io::Result<usize> {
+        let cutoff = today - chrono::Duration::days(self.retention_days);
+        let mut removed = 0;
+        for (date, path) in self.history_files()? {
+            if date < cutoff && fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Read back the last `limit` entries, newest first. Malformed lines are skipped.
+    pub fn recent(&self, limit: usize) -> io::Result<Vec<ScanHistoryEntry>> {
+        let mut files = self.history_files()?;
+        files.sort_by_key(|f| std::cmp::Reverse(f.0));
+
+        let mut entries = Vec::new();
+        for (_, path) in files {
+            if entries.len() >= limit {
+                break;
+            }
+            let file = fs::File::open(&path)?;
+            let mut day: Vec<ScanHistoryEntry> = BufReader::new(file)
+                .lines()
+                .map_while(Result::ok)
+                .filter_map(|line| serde_json::from_str(&line).ok())
+                .collect();
+            day.reverse();
+            entries.extend(day.into_iter().take(limit - entries.len()));
+        }
+        Ok(entries)
+    }
+
+    /// History files in the directory along with the date encoded in their name
+    fn history_files(&self) -> io::Result<Vec<(NaiveDate, PathBuf)>> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let date = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix(FILE_PREFIX))
+                .and_then(|n| n.strip_suffix(FILE_SUFFIX))
+                .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+            if let Some(date) = date {
+                files.push((date, path));
+            }
+        }
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "bootforgeusb-history-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_record_and_read_back_newest_first() {
+        let dir = temp_dir("roundtrip");
+        let logger = ScanHistoryLogger::new(&dir).unwrap();
+        logger.record(&[]).unwrap();
+        logger.record(&[]).unwrap();
+        logger.record(&[]).unwrap();
+
+        let recent = logger.recent(2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert!(recent[0].timestamp >= recent[1].timestamp);
+        assert_eq!(logger.recent(10).unwrap().len(), 3);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_prune_removes_files_past_retention() {
+        let dir = temp_dir("prune");
+        let logger = ScanHistoryLogger::new(&dir).unwrap();
+        let today = Utc::now().date_naive();
+        let old = logger.path_for(today - chrono::Duration::days(8));
+        let fresh = logger.path_for(today - chrono::Duration::days(3));
+        fs::write(&old, "").unwrap();
+        fs::write(&fresh, "").unwrap();
+
+        assert_eq!(logger.prune_expired(today).unwrap(), 1);
+        assert!(!old.exists());
+        assert!(fresh.exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -2,9 +2,18 @@ pub mod model;
 pub mod usb_scan;
 pub mod classify;
 pub mod tools;
+pub mod vid_pid_db;
+pub mod history;
+pub mod explain;
 
-use model::{ConfirmedDeviceRecord, Evidence};
-use std::collections::HashMap;
+#[cfg(test)]
+mod test_harness;
+
+use model::{ConfirmedDeviceRecord, DeviceGroup, Evidence, Platform, ScanBenchmark, ScanGroupResult};
+pub use vid_pid_db::lookup_device_name;
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 /// Main entry point: Scan USB transports and produce confirmed device records.
 /// 
@@ -17,60 +26,304 @@ use std::collections::HashMap;
 /// 
 /// Returns: Vec of confirmed devices with stable identities and confidence scores.
 pub fn scan() -> Result<Vec<ConfirmedDeviceRecord>, Box<dyn std::error::Error>> {
+    scan_with_registry(classify::ClassifierRegistry::builtin())
+}
+
+/// [`scan`] with a caller-supplied classifier registry, so downstream crates
+/// can add OEM-specific classifiers for stage 2.
+pub fn scan_with_registry(
+    registry: &classify::ClassifierRegistry,
+) -> Result<Vec<ConfirmedDeviceRecord>, Box<dyn std::error::Error>> {
+    // Stage 3: Probe tool evidence (done early for correlation)
+    scan_with(registry, tools::confirmers::ToolConfirmers::new)
+}
+
+/// Stages 1, 2 and 5 only: no adb/fastboot/idevice_id probing.
+/// 
+/// Much cheaper than [`scan`] for frequent polling; modes stay at their
+/// USB-only classification and device_uid falls back to serial/fingerprint.
+pub fn scan_usb_only() -> Result<Vec<ConfirmedDeviceRecord>, Box<dyn std::error::Error>> {
+    scan_usb_only_with_registry(classify::ClassifierRegistry::builtin())
+}
+
+/// [`scan_usb_only`] with a caller-supplied classifier registry.
+pub fn scan_usb_only_with_registry(
+    registry: &classify::ClassifierRegistry,
+) -> Result<Vec<ConfirmedDeviceRecord>, Box<dyn std::error::Error>> {
+    scan_with(registry, tools::confirmers::ToolConfirmers::skipped)
+}
+
+/// [`scan`] with the results ranked for display; see [`rank_devices`].
+pub fn scan_ranked() -> Result<Vec<ConfirmedDeviceRecord>, Box<dyn std::error::Error>> {
+    let mut devices = scan()?;
+    rank_devices(&mut devices);
+    Ok(devices)
+}
+
+/// [`scan_ranked`] plus the devices grouped by USB hub; see [`group_by_hub`].
+pub fn scan_with_groups() -> Result<ScanGroupResult, Box<dyn std::error::Error>> {
+    let devices = scan_ranked()?;
+    let groups = group_by_hub(&devices);
+    Ok(ScanGroupResult { devices, groups })
+}
+
+/// Run a full [`scan`] and time each pipeline stage.
+/// 
+/// Every tool is probed directly, so the cached results of recent scans do
+/// not hide the real tool latency.
+pub fn scan_benchmark() -> Result<ScanBenchmark, Box<dyn std::error::Error>> {
+    scan_benchmark_with_registry(classify::ClassifierRegistry::builtin())
+}
+
+/// [`scan_benchmark`] with a caller-supplied classifier registry.
+pub fn scan_benchmark_with_registry(
+    registry: &classify::ClassifierRegistry,
+) -> Result<ScanBenchmark, Box<dyn std::error::Error>> {
+    use tools::confirmers::{probe_adb_tool, probe_fastboot_tool, probe_idevice_id_tool, tool_paths};
+    let paths = tool_paths();
+    benchmark_with(
+        registry,
+        usb_scan::probe_usb_transports,
+        || probe_adb_tool(paths.adb.as_deref()),
+        || probe_fastboot_tool(paths.fastboot.as_deref()),
+        || probe_idevice_id_tool(paths.idevice_id.as_deref()),
+    )
+}
+
+fn benchmark_with(
+    registry: &classify::ClassifierRegistry,
+    probe_usb: impl FnOnce() -> Result<Vec<model::UsbTransportEvidence>, Box<dyn std::error::Error>>,
+    probe_adb: impl FnOnce() -> model::ToolEvidence,
+    probe_fastboot: impl FnOnce() -> model::ToolEvidence,
+    probe_idevice_id: impl FnOnce() -> model::ToolEvidence,
+) -> Result<ScanBenchmark, Box<dyn std::error::Error>> {
+    fn timed<T>(stage: impl FnOnce() -> T) -> (T, u64) {
+        let started = std::time::Instant::now();
+        let value = stage();
+        (value, started.elapsed().as_millis() as u64)
+    }
+
+    let started = std::time::Instant::now();
+    let (usb_transports, usb_probe_ms) = timed(probe_usb);
+    let usb_transports = usb_transports?;
+    let (adb, adb_probe_ms) = timed(probe_adb);
+    let (fastboot, fastboot_probe_ms) = timed(probe_fastboot);
+    let (idevice_id, idevice_probe_ms) = timed(probe_idevice_id);
+    let tool_confirmers = tools::confirmers::ToolConfirmers { adb, fastboot, idevice_id };
+    let (devices, classify_ms) = timed(|| scan_transports(registry, &usb_transports, &tool_confirmers));
+
+    Ok(ScanBenchmark {
+        usb_probe_ms,
+        adb_probe_ms,
+        fastboot_probe_ms,
+        idevice_probe_ms,
+        classify_ms,
+        total_ms: started.elapsed().as_millis() as u64,
+        device_count: devices.len(),
+        devices,
+    })
+}
+
+/// Group devices that sit behind the same hub (same bus and hub port chain).
+/// 
+/// Groups are ordered by `hub_path`. Devices whose port path is unknown are
+/// left out.
+pub fn group_by_hub(devices: &[ConfirmedDeviceRecord]) -> Vec<DeviceGroup> {
+    let mut groups: Vec<DeviceGroup> = Vec::new();
+    for device in devices {
+        let usb = &device.evidence.usb;
+        let Some((_, hub_ports)) = usb.hub_port_path.as_deref().and_then(|path| path.split_last()) else {
+            continue;
+        };
+        let mut hub_path = vec![usb.bus];
+        hub_path.extend_from_slice(hub_ports);
+        match groups.iter_mut().find(|g| g.hub_path == hub_path) {
+            Some(group) => group.devices.push(device.device_uid.clone()),
+            None => groups.push(DeviceGroup { hub_path, devices: vec![device.device_uid.clone()] }),
+        }
+    }
+    groups.sort_by(|a, b| a.hub_path.cmp(&b.hub_path));
+    groups
+}
+
+/// Order records for display instead of USB enumeration order:
+/// 1. higher `confidence` first
+/// 2. then platform: ios, android, unknown
+/// 3. then `device_uid`, ascending
+pub fn rank_devices(devices: &mut [ConfirmedDeviceRecord]) {
+    fn platform_rank(platform: Platform) -> u8 {
+        match platform {
+            Platform::Ios => 0,
+            Platform::Android => 1,
+            Platform::Unknown => 2,
+        }
+    }
+    devices.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then_with(|| platform_rank(a.platform).cmp(&platform_rank(b.platform)))
+            .then_with(|| a.device_uid.cmp(&b.device_uid))
+    });
+}
+
+fn scan_with(
+    registry: &classify::ClassifierRegistry,
+    tool_confirmers: impl FnOnce() -> tools::confirmers::ToolConfirmers,
+) -> Result<Vec<ConfirmedDeviceRecord>, Box<dyn std::error::Error>> {
     // Stage 1: Probe USB transports
     let usb_transports = usb_scan::probe_usb_transports()?;
     
-    // Stage 3: Probe tool evidence (done early for correlation)
-    let tool_confirmers = tools::confirmers::ToolConfirmers::new();
+    let tool_confirmers = tool_confirmers();
+    
+    Ok(scan_transports(registry, &usb_transports, &tool_confirmers))
+}
+
+/// Stages 2, 4 and 5 over transports and tool evidence that were already collected.
+fn scan_transports(
+    registry: &classify::ClassifierRegistry,
+    usb_transports: &[model::UsbTransportEvidence],
+    tool_confirmers: &tools::confirmers::ToolConfirmers,
+) -> Vec<ConfirmedDeviceRecord> {
+    // Stage 2: Classify candidates (parallel with the `parallel` feature)
+    let classifications = registry.classify_batch(usb_transports);
     
     let mut results = Vec::new();
     
-    // Stages 2, 4, 5: Classify, resolve identity, assemble records
-    for transport in &usb_transports {
-        // Stage 2: Classify candidate
+    // Stages 4, 5: Resolve identity, assemble records
+    for (transport, classification) in usb_transports.iter().zip(classifications) {
         // Stage 4: Resolve identity with correlation
-        let (classification, matched_tool_ids) = classify::resolve_device_identity_with_correlation(
+        let (classification, matched_tool_ids) = classify::correlate_classification(
             transport,
-            &usb_transports,
-            &tool_confirmers,
+            classification,
+            usb_transports,
+            tool_confirmers,
         );
         
         // Stage 5: Assemble confirmed device record
-        let device_uid = resolve_device_identity(transport, &matched_tool_ids);
-        
-        let platform_hint = match classification.mode.as_str() {
-            s if s.starts_with("ios_") => "ios",
-            s if s.starts_with("android_") => "android",
-            _ => "unknown",
-        };
-        
-        let mut tool_evidence = HashMap::new();
-        tool_evidence.insert("adb".to_string(), tool_confirmers.adb.clone());
-        tool_evidence.insert("fastboot".to_string(), tool_confirmers.fastboot.clone());
-        tool_evidence.insert("idevice_id".to_string(), tool_confirmers.idevice_id.clone());
-        
-        let record = ConfirmedDeviceRecord {
-            device_uid,
-            platform_hint: platform_hint.to_string(),
-            mode: classification.mode.as_str().to_string(),
-            confidence: classification.confidence,
-            evidence: Evidence {
-                usb: transport.clone(),
-                tools: tool_evidence,
-            },
-            notes: classification.notes,
-            matched_tool_ids,
-        };
+        let record = assemble_record(transport, classification, matched_tool_ids, tool_confirmers);
         
         results.push(record);
     }
     
-    Ok(results)
+    results
+}
+
+/// Stage 5: Build the confirmed record for one correlated transport.
+fn assemble_record(
+    transport: &model::UsbTransportEvidence,
+    classification: model::Classification,
+    matched_tool_ids: Vec<String>,
+    tool_confirmers: &tools::confirmers::ToolConfirmers,
+) -> ConfirmedDeviceRecord {
+    let device_uid = resolve_device_identity(transport, &matched_tool_ids);
+    
+    let platform = Platform::from_mode(classification.mode.as_str());
+    
+    let mut tool_evidence = HashMap::new();
+    tool_evidence.insert("adb".to_string(), tool_confirmers.adb.clone());
+    tool_evidence.insert("fastboot".to_string(), tool_confirmers.fastboot.clone());
+    tool_evidence.insert("idevice_id".to_string(), tool_confirmers.idevice_id.clone());
+    
+    ConfirmedDeviceRecord {
+        device_uid,
+        platform,
+        mode: classification.mode.as_str().to_string(),
+        confidence: classification.confidence,
+        evidence: Evidence {
+            usb: transport.clone(),
+            tools: tool_evidence,
+        },
+        notes: classification.notes,
+        matched_tool_ids,
+        marketing_name: if transport.vid.eq_ignore_ascii_case("05ac") {
+            classify::resolve_apple_marketing_name(&transport.pid).map(str::to_string)
+        } else {
+            None
+        },
+        driver_status: None,
+    }
+}
+
+/// Difference between two scans, keyed by `device_uid`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviceChangeSet {
+    /// Present in `after` only
+    pub connected: Vec<ConfirmedDeviceRecord>,
+    /// Present in `before` only
+    pub disconnected: Vec<ConfirmedDeviceRecord>,
+    /// Present in both with a different mode or confidence: `(before, after)`
+    pub changed: Vec<(ConfirmedDeviceRecord, ConfirmedDeviceRecord)>,
+    /// Disconnect/connect pairs that are the same device under a new
+    /// device_uid, filled by [`DeviceChangeSet::pair_reconnects`]: `(before, after)`
+    #[serde(default)]
+    pub reconnected: Vec<(ConfirmedDeviceRecord, ConfirmedDeviceRecord)>,
+}
+
+impl DeviceChangeSet {
+    pub fn is_empty(&self) -> bool {
+        self.connected.is_empty()
+            && self.disconnected.is_empty()
+            && self.changed.is_empty()
+            && self.reconnected.is_empty()
+    }
+
+    /// Move each disconnected device whose USB serial matches a connected
+    /// one into `reconnected`, so a replug that changed the device_uid is
+    /// reported once instead of as a disconnect plus a connect.
+    pub fn pair_reconnects(&mut self) {
+        let mut remaining = Vec::with_capacity(self.disconnected.len());
+        for before in self.disconnected.drain(..) {
+            let same = self.connected.iter().position(|after| {
+                usb_scan::merge_transport_evidence(&before.evidence.usb, &after.evidence.usb)
+                    == usb_scan::TransportMergeResult::SameDevice
+            });
+            match same {
+                Some(index) => {
+                    let after = self.connected.remove(index);
+                    self.reconnected.push((before, after));
+                }
+                None => remaining.push(before),
+            }
+        }
+        self.disconnected = remaining;
+    }
+}
+
+/// Compare two scan results.
+/// 
+/// Devices are matched by `device_uid`. A device counts as changed when its
+/// mode transitions (e.g. ADB -> fastboot) or its confidence score moves.
+/// Output preserves the order of the input slices.
+pub fn scan_compare(before: &[ConfirmedDeviceRecord], after: &[ConfirmedDeviceRecord]) -> DeviceChangeSet {
+    let before_by_uid: HashMap<&str, &ConfirmedDeviceRecord> =
+        before.iter().map(|d| (d.device_uid.as_str(), d)).collect();
+    let after_uids: HashSet<&str> = after.iter().map(|d| d.device_uid.as_str()).collect();
+    
+    let mut changes = DeviceChangeSet::default();
+    for device in after {
+        match before_by_uid.get(device.device_uid.as_str()) {
+            None => changes.connected.push(device.clone()),
+            Some(previous) => {
+                if previous.mode != device.mode || previous.confidence != device.confidence {
+                    changes.changed.push(((*previous).clone(), device.clone()));
+                }
+            }
+        }
+    }
+    changes.disconnected = before
+        .iter()
+        .filter(|d| !after_uids.contains(d.device_uid.as_str()))
+        .cloned()
+        .collect();
+    changes
 }
 
 /// Resolve stable device identity from transport and tool correlation.
 /// 
-/// Prefers serial number (most stable), falls back to transport UID.
+/// Prefers serial number (most stable), then matched tool ID, then a
+/// fingerprint of the descriptor strings and the port the device is plugged
+/// into. Falls back to the transport UID only when the device reports nothing
+/// to fingerprint.
 fn resolve_device_identity(transport: &model::UsbTransportEvidence, matched_tool_ids: &[String]) -> String {
     // Prefer serial number if available (stable across reconnections)
     if let Some(serial) = &transport.serial {
@@ -82,6 +335,12 @@ fn resolve_device_identity(transport: &model::UsbTransportEvidence, matched_tool
         return tool_id.clone();
     }
     
+    // Manufacturer/product strings survive a replug, bus:addr does not.
+    // Identical serial-less devices share those strings, so the port tells them apart.
+    if transport.manufacturer.is_some() || transport.product.is_some() {
+        return located_fingerprint(transport, matched_tool_ids);
+    }
+    
     // Fallback to transport UID (unstable across reconnections)
     format!(
         "usb:{}:{}:bus{}:addr{}",
@@ -89,6 +348,54 @@ fn resolve_device_identity(transport: &model::UsbTransportEvidence, matched_tool
     )
 }
 
+/// Stable cross-session identifier for a device.
+/// 
+/// SHA-256 over the serial number, manufacturer + product strings and first
+/// tool ID (whichever are present), truncated to 128 bits and hex encoded.
+/// Bus and address are deliberately left out so the value survives a replug.
+pub fn compute_device_fingerprint(transport: &model::UsbTransportEvidence, tool_ids: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    let fields = [
+        ("serial", transport.serial.as_deref()),
+        ("manufacturer", transport.manufacturer.as_deref()),
+        ("product", transport.product.as_deref()),
+        ("tool_id", tool_ids.first().map(String::as_str)),
+    ];
+    for (name, value) in fields {
+        if let Some(value) = value {
+            hasher.update(name.as_bytes());
+            hasher.update(b"=");
+            hasher.update(value.trim().as_bytes());
+            hasher.update([0u8]);
+        }
+    }
+    hasher.finalize()[..16]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// `compute_device_fingerprint` combined with the bus and hub port path, or
+/// the bus address when the host does not report the port path.
+fn located_fingerprint(transport: &model::UsbTransportEvidence, tool_ids: &[String]) -> String {
+    let location = match transport.hub_port_path.as_deref() {
+        Some(ports) if !ports.is_empty() => format!(
+            "bus{}:port{}",
+            transport.bus,
+            ports.iter().map(u8::to_string).collect::<Vec<_>>().join(".")
+        ),
+        _ => format!("bus{}:addr{}", transport.bus, transport.address),
+    };
+    let mut hasher = Sha256::new();
+    hasher.update(compute_device_fingerprint(transport, tool_ids).as_bytes());
+    hasher.update([0u8]);
+    hasher.update(location.as_bytes());
+    hasher.finalize()[..16]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
 
@@ -125,31 +432,432 @@ fn bootforgeusb(_py: Python, m: &PyModule) -> PyResult<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use model::{DeviceMode, UsbTransportEvidence};
+
+    fn record(uid: &str, mode: DeviceMode, confidence: f32) -> ConfirmedDeviceRecord {
+        ConfirmedDeviceRecord {
+            device_uid: uid.to_string(),
+            platform: Platform::from_mode(mode.as_str()),
+            mode: mode.as_str().to_string(),
+            confidence,
+            evidence: Evidence {
+                usb: UsbTransportEvidence {
+                    vid: "18d1".to_string(),
+                    pid: "4ee7".to_string(),
+                    serial: Some(uid.to_string()),
+                    bus: 1,
+                    address: 1,
+                    ..Default::default()
+                },
+                tools: HashMap::new(),
+            },
+            notes: vec![],
+            matched_tool_ids: vec![],
+            marketing_name: None,
+            driver_status: None,
+        }
+    }
+
+    #[test]
+    fn test_scan_compare_identical_scans_are_empty() {
+        let scan = vec![
+            record("A", DeviceMode::AndroidAdbConfirmed, 0.9),
+            record("B", DeviceMode::IosNormalLikely, 0.95),
+        ];
+        assert!(scan_compare(&scan, &scan).is_empty());
+        assert!(scan_compare(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_scan_compare_connected_and_disconnected() {
+        let before = vec![
+            record("A", DeviceMode::AndroidAdbConfirmed, 0.9),
+            record("B", DeviceMode::IosNormalLikely, 0.95),
+        ];
+        let after = vec![
+            record("B", DeviceMode::IosNormalLikely, 0.95),
+            record("C", DeviceMode::IosDfuLikely, 0.85),
+            record("D", DeviceMode::UnknownUsb, 0.5),
+        ];
+        let changes = scan_compare(&before, &after);
+        let connected: Vec<&str> = changes.connected.iter().map(|d| d.device_uid.as_str()).collect();
+        let disconnected: Vec<&str> = changes.disconnected.iter().map(|d| d.device_uid.as_str()).collect();
+        assert_eq!(connected, vec!["C", "D"]);
+        assert_eq!(disconnected, vec!["A"]);
+        assert!(changes.changed.is_empty());
+    }
+
+    #[test]
+    fn test_pair_reconnects_merges_matching_serials() {
+        let before = vec![
+            record("A", DeviceMode::AndroidAdbConfirmed, 0.9),
+            record("B", DeviceMode::AndroidAdbConfirmed, 0.9),
+        ];
+        // A came back on another port under a tool-derived uid
+        let mut replugged = record("adb:A", DeviceMode::AndroidAdbConfirmed, 0.9);
+        replugged.evidence.usb.serial = Some("A".to_string());
+        replugged.evidence.usb.bus = 2;
+        let after = vec![replugged, record("C", DeviceMode::UnknownUsb, 0.5)];
+
+        let mut changes = scan_compare(&before, &after);
+        changes.pair_reconnects();
+        let connected: Vec<&str> = changes.connected.iter().map(|d| d.device_uid.as_str()).collect();
+        let disconnected: Vec<&str> = changes.disconnected.iter().map(|d| d.device_uid.as_str()).collect();
+        assert_eq!(connected, vec!["C"]);
+        assert_eq!(disconnected, vec!["B"]);
+        assert_eq!(changes.reconnected.len(), 1);
+        assert_eq!(changes.reconnected[0].0.device_uid, "A");
+        assert_eq!(changes.reconnected[0].1.device_uid, "adb:A");
+    }
+
+    #[test]
+    fn test_scan_compare_mode_transition_is_changed() {
+        let before = vec![record("A", DeviceMode::AndroidAdbConfirmed, 0.9)];
+        let after = vec![record("A", DeviceMode::AndroidFastbootConfirmed, 0.9)];
+        let changes = scan_compare(&before, &after);
+        assert!(changes.connected.is_empty());
+        assert!(changes.disconnected.is_empty());
+        assert_eq!(changes.changed.len(), 1);
+        let (old, new) = &changes.changed[0];
+        assert_eq!(old.mode, "android_adb_confirmed");
+        assert_eq!(new.mode, "android_fastboot_confirmed");
+    }
+
+    #[test]
+    fn test_scan_compare_confidence_change_is_changed() {
+        let before = vec![record("A", DeviceMode::AndroidAdbConfirmed, 0.6)];
+        let after = vec![record("A", DeviceMode::AndroidAdbConfirmed, 0.9)];
+        let changes = scan_compare(&before, &after);
+        assert_eq!(changes.changed.len(), 1);
+        assert_eq!(changes.changed[0].1.confidence, 0.9);
+    }
+
+    #[test]
+    fn test_scan_compare_from_empty() {
+        let after = vec![record("A", DeviceMode::AndroidAdbConfirmed, 0.9)];
+        let changes = scan_compare(&[], &after);
+        assert_eq!(changes.connected.len(), 1);
+        assert!(scan_compare(&after, &[]).disconnected.len() == 1);
+    }
 
     #[test]
     fn test_full_scan() {
-        let result = scan();
-        assert!(result.is_ok(), "Scan should not panic");
-        
-        if let Ok(devices) = result {
-            println!("\n=== BootForgeUSB Scan Results ===");
-            println!("Found {} devices\n", devices.len());
-            
-            for device in devices {
-                println!("Device: {}", device.device_uid);
-                println!("  Platform: {}", device.platform_hint);
-                println!("  Mode: {}", device.mode);
-                println!("  Confidence: {:.2}%", device.confidence * 100.0);
-                println!("  USB: VID:{} PID:{}", device.evidence.usb.vid, device.evidence.usb.pid);
-                if let Some(product) = &device.evidence.usb.product {
-                    println!("  Product: {}", product);
-                }
-                println!("  Notes:");
-                for note in &device.notes {
-                    println!("    - {}", note);
-                }
-                println!();
-            }
+        let udid = "00008030-001A2B3C4D5E6F70";
+        let result = test_harness::TestHarness::new()
+            .with_device("18d1", "4ee7", Some("Google"), Some("Pixel 8"), Some("1A2B3C4D"))
+            .with_device("04e8", "6860", Some("SAMSUNG"), Some("SAMSUNG_Android"), Some("R58M12ABCDE"))
+            .with_device("05ac", "12a8", Some("Apple Inc."), Some("iPhone"), None)
+            .with_device("1234", "5678", Some("Acme"), Some("Widget"), None)
+            .with_adb_output("List of devices attached\n1A2B3C4D\tdevice usb:1-1 product:shiba\n")
+            .with_fastboot_output("R58M12ABCDE\tfastboot\n")
+            .with_idevice_id_output(udid)
+            .build();
+
+        assert_eq!(result.devices.len(), 4);
+
+        let pixel = result.device("1A2B3C4D");
+        assert_eq!(pixel.mode, DeviceMode::AndroidAdbConfirmed.as_str());
+        assert_eq!(pixel.matched_tool_ids, vec!["1A2B3C4D".to_string()]);
+
+        let samsung = result.device("R58M12ABCDE");
+        assert_eq!(samsung.mode, DeviceMode::AndroidFastbootConfirmed.as_str());
+        assert!(samsung.evidence.tools["fastboot"].seen);
+
+        let iphone = result.device(udid);
+        assert_eq!(iphone.platform, Platform::Ios);
+        assert_eq!(iphone.mode, DeviceMode::IosNormalLikely.as_str());
+
+        let widget = &result.devices[3];
+        assert_eq!(widget.platform, Platform::Unknown);
+        assert!(widget.matched_tool_ids.is_empty());
+    }
+
+    #[test]
+    fn test_scan_benchmark_times_each_stage() {
+        let benchmark = test_harness::TestHarness::new()
+            .with_device("18d1", "4ee7", Some("Google"), Some("Pixel 8"), Some("1A2B3C4D"))
+            .with_device("1234", "5678", Some("Acme"), Some("Widget"), None)
+            .with_adb_output("List of devices attached\n1A2B3C4D\tdevice\n")
+            .benchmark();
+
+        assert_eq!(benchmark.device_count, 2);
+        assert_eq!(benchmark.devices.len(), 2);
+        assert_eq!(benchmark.devices[0].mode, DeviceMode::AndroidAdbConfirmed.as_str());
+        assert!(benchmark.total_ms >= benchmark.usb_probe_ms + benchmark.classify_ms);
+        assert!(
+            benchmark.total_ms
+                >= benchmark.adb_probe_ms + benchmark.fastboot_probe_ms + benchmark.idevice_probe_ms
+        );
+    }
+
+    fn transport(serial: Option<&str>, bus: u8, address: u8) -> UsbTransportEvidence {
+        UsbTransportEvidence {
+            vid: "05ac".to_string(),
+            pid: "1281".to_string(),
+            manufacturer: Some("Apple Inc.".to_string()),
+            product: Some("Apple Mobile Device (Recovery Mode)".to_string()),
+            serial: serial.map(str::to_string),
+            bus,
+            address,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable_across_replug() {
+        let before = compute_device_fingerprint(&transport(None, 1, 4), &[]);
+        let after = compute_device_fingerprint(&transport(None, 2, 9), &[]);
+        assert_eq!(before, after);
+        assert_eq!(before.len(), 32);
+        assert!(before.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+    }
+
+    #[test]
+    fn test_fingerprint_differs_by_identity_fields() {
+        let base = compute_device_fingerprint(&transport(None, 1, 4), &[]);
+        assert_ne!(base, compute_device_fingerprint(&transport(Some("ABC123"), 1, 4), &[]));
+        assert_ne!(base, compute_device_fingerprint(&transport(None, 1, 4), &["ABC123".to_string()]));
+    }
+
+    #[test]
+    fn test_identity_uses_fingerprint_without_serial() {
+        assert_eq!(resolve_device_identity(&transport(Some("ABC123"), 1, 4), &[]), "ABC123");
+        let on_port = |bus: u8, address: u8, ports: &[u8]| {
+            let mut device = transport(None, bus, address);
+            device.hub_port_path = Some(ports.to_vec());
+            resolve_device_identity(&device, &[])
+        };
+        let uid = on_port(1, 4, &[2, 3]);
+        assert_eq!(uid.len(), 32);
+        assert_ne!(uid, compute_device_fingerprint(&transport(None, 1, 4), &[]));
+        // Same port after a replug: new address, same uid
+        assert_eq!(uid, on_port(1, 9, &[2, 3]));
+        // An identical device on the next port, or another bus, is a different device
+        assert_ne!(uid, on_port(1, 5, &[2, 4]));
+        assert_ne!(uid, on_port(2, 4, &[2, 3]));
+        // Without a port path the address keeps identical devices apart
+        assert_ne!(
+            resolve_device_identity(&transport(None, 1, 4), &[]),
+            resolve_device_identity(&transport(None, 1, 5), &[])
+        );
+
+        let mut bare = transport(None, 1, 4);
+        bare.manufacturer = None;
+        bare.product = None;
+        assert_eq!(resolve_device_identity(&bare, &[]), "usb:05ac:1281:bus1:addr4");
+    }
+
+    #[test]
+    fn test_group_by_hub_separates_buses_and_hubs() {
+        let on_port = |uid: &str, bus: u8, ports: Option<Vec<u8>>| {
+            let mut device = record(uid, DeviceMode::AndroidAdbConfirmed, 0.9);
+            device.evidence.usb.bus = bus;
+            device.evidence.usb.hub_port_path = ports;
+            device
+        };
+        let devices = vec![
+            on_port("hub1-slot2", 1, Some(vec![3, 2])),
+            on_port("bus2-slot1", 2, Some(vec![3, 1])),
+            on_port("hub1-slot1", 1, Some(vec![3, 1])),
+            on_port("root-port", 1, Some(vec![4])),
+            on_port("no-path", 1, None),
+        ];
+
+        let groups = group_by_hub(&devices);
+
+        assert_eq!(
+            groups,
+            vec![
+                DeviceGroup { hub_path: vec![1], devices: vec!["root-port".to_string()] },
+                DeviceGroup {
+                    hub_path: vec![1, 3],
+                    devices: vec!["hub1-slot2".to_string(), "hub1-slot1".to_string()],
+                },
+                DeviceGroup { hub_path: vec![2, 3], devices: vec!["bus2-slot1".to_string()] },
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+mod integration_tests {
+    //! End-to-end classify -> correlate -> assemble runs over fake USB
+    //! transports and tool output, so no hardware or adb/fastboot is needed.
+
+    use super::*;
+    use model::{DeviceMode, ToolEvidence, UsbTransportEvidence};
+    use tools::confirmers::ToolConfirmers;
+
+    fn usb(vid: &str, pid: &str, manufacturer: &str, product: &str, serial: Option<&str>) -> UsbTransportEvidence {
+        UsbTransportEvidence {
+            vid: vid.to_string(),
+            pid: pid.to_string(),
+            manufacturer: Some(manufacturer.to_string()),
+            product: Some(product.to_string()),
+            serial: serial.map(str::to_string),
+            bus: 1,
+            address: 7,
+            ..Default::default()
+        }
+    }
+
+    fn seen(raw: &str, device_ids: &[&str]) -> ToolEvidence {
+        ToolEvidence {
+            present: true,
+            seen: true,
+            raw: raw.to_string(),
+            device_ids: device_ids.iter().map(|id| id.to_string()).collect(),
+            unauthorized_ids: vec![],
         }
     }
+
+    #[test]
+    fn test_rank_devices_by_confidence_platform_and_uid() {
+        let record = |uid: &str, platform: Platform, confidence: f32| ConfirmedDeviceRecord {
+            device_uid: uid.to_string(),
+            platform,
+            mode: String::new(),
+            confidence,
+            evidence: Evidence {
+                usb: usb("0000", "0000", "", "", None),
+                tools: HashMap::new(),
+            },
+            notes: vec![],
+            matched_tool_ids: vec![],
+            marketing_name: None,
+            driver_status: None,
+        };
+        let mut devices = vec![
+            record("usb-b", Platform::Unknown, 0.5),
+            record("android-1", Platform::Android, 0.9),
+            record("usb-a", Platform::Unknown, 0.5),
+            record("ios-1", Platform::Ios, 0.9),
+            record("android-2", Platform::Android, 0.95),
+        ];
+
+        rank_devices(&mut devices);
+
+        let order: Vec<&str> = devices.iter().map(|d| d.device_uid.as_str()).collect();
+        assert_eq!(order, vec!["android-2", "ios-1", "android-1", "usb-a", "usb-b"]);
+    }
+
+    fn no_tools() -> ToolConfirmers {
+        ToolConfirmers {
+            adb: ToolEvidence::missing(),
+            fastboot: ToolEvidence::missing(),
+            idevice_id: ToolEvidence::missing(),
+        }
+    }
+
+    /// Run stages 2, 4 and 5 for every transport, as `scan()` does.
+    fn run_pipeline(transports: &[UsbTransportEvidence], tools: &ToolConfirmers) -> Vec<ConfirmedDeviceRecord> {
+        transports
+            .iter()
+            .map(|transport| {
+                let (classification, matched) =
+                    classify::resolve_device_identity_with_correlation(transport, transports, tools);
+                assemble_record(transport, classification, matched, tools)
+            })
+            .collect()
+    }
+
+    fn assert_record(record: &ConfirmedDeviceRecord, uid: &str, platform: &str, mode: DeviceMode, confidence: f32) {
+        assert_eq!(record.device_uid, uid);
+        assert_eq!(record.platform_hint(), platform);
+        assert_eq!(record.mode, mode.as_str());
+        assert!(
+            (record.confidence - confidence).abs() < 1e-5,
+            "confidence {} != {}",
+            record.confidence,
+            confidence
+        );
+    }
+
+    #[test]
+    fn test_pipeline_google_pixel_adb_serial_match() {
+        let tools = ToolConfirmers {
+            adb: seen("List of devices attached\n1A2B3C4D\tdevice\n", &["1A2B3C4D"]),
+            ..no_tools()
+        };
+        let records = run_pipeline(&[usb("18d1", "4ee7", "Google", "Pixel 8", Some("1A2B3C4D"))], &tools);
+
+        assert_record(&records[0], "1A2B3C4D", "android", DeviceMode::AndroidAdbConfirmed, 0.75);
+        assert_eq!(records[0].matched_tool_ids, vec!["1A2B3C4D".to_string()]);
+        assert!(records[0].notes.iter().any(|n| n.contains("adb device id matches USB serial")));
+    }
+
+    #[test]
+    fn test_pipeline_samsung_fastboot_correlation() {
+        let tools = ToolConfirmers {
+            fastboot: seen("R58M12ABCDE\tfastboot\n", &["R58M12ABCDE"]),
+            ..no_tools()
+        };
+        let records = run_pipeline(&[usb("04e8", "6860", "SAMSUNG", "SAMSUNG_Android", Some("R58M12ABCDE"))], &tools);
+
+        assert_record(&records[0], "R58M12ABCDE", "android", DeviceMode::AndroidFastbootConfirmed, 0.75);
+        assert!(records[0].evidence.tools["fastboot"].seen);
+    }
+
+    #[test]
+    fn test_pipeline_apple_single_candidate_udid() {
+        let udid = "00008030-001A2B3C4D5E6F70";
+        let tools = ToolConfirmers {
+            idevice_id: seen(udid, &[udid]),
+            ..no_tools()
+        };
+        // Normal-mode iPhones often hide the serial until trusted
+        let records = run_pipeline(&[usb("05ac", "12a8", "Apple Inc.", "iPhone", None)], &tools);
+
+        assert_record(&records[0], udid, "ios", DeviceMode::IosNormalLikely, 0.95);
+        assert!(records[0].marketing_name.is_some());
+    }
+
+    #[test]
+    fn test_pipeline_apple_without_udid_is_untrusted() {
+        let tools = ToolConfirmers {
+            idevice_id: ToolEvidence {
+                present: true,
+                seen: false,
+                raw: String::new(),
+                device_ids: vec![],
+                unauthorized_ids: vec![],
+            },
+            ..no_tools()
+        };
+        let transport = usb("05ac", "12a8", "Apple Inc.", "iPhone", None);
+        let records = run_pipeline(std::slice::from_ref(&transport), &tools);
+
+        let fingerprint = located_fingerprint(&transport, &[]);
+        assert_record(&records[0], &fingerprint, "ios", DeviceMode::IosUntrusted, 0.80);
+        assert!(records[0].notes.iter().any(|n| n.contains("not trusted")));
+
+        // Without idevice_id there is no evidence either way
+        let records = run_pipeline(std::slice::from_ref(&transport), &no_tools());
+        assert_eq!(records[0].mode, DeviceMode::IosNormalLikely.as_str());
+    }
+
+    #[test]
+    fn test_pipeline_usb_only_keeps_usb_classification() {
+        let records = run_pipeline(
+            &[usb("18d1", "4ee7", "Google", "Pixel 8", Some("1A2B3C4D"))],
+            &ToolConfirmers::skipped(),
+        );
+
+        assert_record(&records[0], "1A2B3C4D", "unknown", DeviceMode::UnknownUsb, 0.6);
+        assert!(records[0].evidence.tools["adb"].is_skipped());
+    }
+
+    #[test]
+    fn test_pipeline_unknown_vid_is_not_correlated() {
+        let tools = ToolConfirmers {
+            adb: seen("List of devices attached\nOTHER\tdevice\n", &["OTHER"]),
+            ..no_tools()
+        };
+        let transport = usb("1234", "5678", "Acme", "Widget", None);
+        let records = run_pipeline(std::slice::from_ref(&transport), &tools);
+
+        let fingerprint = located_fingerprint(&transport, &[]);
+        assert_record(&records[0], &fingerprint, "unknown", DeviceMode::UnknownUsb, 0.5);
+        assert!(records[0].matched_tool_ids.is_empty());
+    }
 }
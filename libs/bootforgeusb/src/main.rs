@@ -48,11 +48,15 @@ fn scan_devices(json_mode: bool) {
                 
                 for device in devices {
                     println!("Device: {}", device.device_uid);
-                    println!("  Platform: {}", device.platform_hint);
+                    println!("  Platform: {}", device.platform_hint());
                     println!("  Mode: {}", device.mode);
                     println!("  Confidence: {:.1}%", device.confidence * 100.0);
                     println!("  USB: VID:{} PID:{}", device.evidence.usb.vid, device.evidence.usb.pid);
                     
+                    if let Some(speed) = &device.evidence.usb.speed {
+                        println!("  Speed: {:?} ({})", speed, device.evidence.usb.speed_classification());
+                    }
+                    
                     if let Some(manufacturer) = &device.evidence.usb.manufacturer {
                         println!("  Manufacturer: {}", manufacturer);
                     }
@@ -10,17 +10,63 @@ use std::collections::HashMap;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfirmedDeviceRecord {
     pub device_uid: String,
-    pub platform_hint: String,
+    /// Serialized as `platform_hint` so existing JSON consumers keep working.
+    #[serde(rename = "platform_hint", alias = "platform")]
+    pub platform: Platform,
     pub mode: String,
     pub confidence: f32,
     pub evidence: Evidence,
     pub notes: Vec<String>,
     pub matched_tool_ids: Vec<String>,
+    /// Model name for the USB PID, when it identifies one (Apple devices only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub marketing_name: Option<String>,
+    /// USB driver bound to the device, when the host reports it (Windows:
+    /// "WinUSB", "libusbK", another driver name, or "not bound")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub driver_status: Option<String>,
+}
+
+impl ConfirmedDeviceRecord {
+    /// Platform as the legacy string hint (`"ios"`, `"android"`, `"unknown"`).
+    pub fn platform_hint(&self) -> &'static str {
+        self.platform.as_str()
+    }
 }
 
 /// Legacy alias for backwards compatibility
 pub type DeviceRecord = ConfirmedDeviceRecord;
 
+/// Device platform family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    Ios,
+    Android,
+    Unknown,
+}
+
+impl Platform {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Platform::Ios => "ios",
+            Platform::Android => "android",
+            Platform::Unknown => "unknown",
+        }
+    }
+
+    /// Platform implied by a `DeviceMode` string (`ios_*` / `android_*`).
+    pub fn from_mode(mode: &str) -> Self {
+        if mode.starts_with("ios_") {
+            Platform::Ios
+        } else if mode.starts_with("android_") {
+            Platform::Android
+        } else {
+            Platform::Unknown
+        }
+    }
+}
+
 /// Evidence bundle - USB transport data + tool outputs.
 /// 
 /// Contains all evidence used for device classification and identity resolution.
@@ -38,7 +84,7 @@ pub struct Evidence {
 /// descriptors (VID/PID, manufacturer, product, serial, interfaces).
 /// 
 /// Multiple transports can represent the same logical device (reconnections).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UsbTransportEvidence {
     pub vid: String,
     pub pid: String,
@@ -49,11 +95,92 @@ pub struct UsbTransportEvidence {
     pub address: u8,
     pub interface_class: Option<u8>,
     pub interface_hints: Vec<InterfaceHint>,
+    /// Time to open the device handle and read the string descriptors
+    #[serde(default)]
+    pub probe_latency_ms: Option<u32>,
+    /// 0-100 score combining probe latency and negotiated USB speed
+    #[serde(default)]
+    pub connection_quality: Option<u8>,
+    /// Negotiated USB protocol speed, when the host reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<ConnectionSpeed>,
+    /// Port numbers from the root hub down to the device (the last entry is
+    /// the device's own port), when the host reports them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hub_port_path: Option<Vec<u8>>,
+    /// Descriptor reads did not finish within the probe timeout; only the
+    /// cached device descriptor fields (VID/PID, bus, address) are filled in
+    #[serde(default)]
+    pub timed_out: bool,
+}
+
+impl UsbTransportEvidence {
+    /// USB generation implied by the negotiated speed: `"usb1"`, `"usb2"`,
+    /// `"usb3"` or `"unknown"`.
+    pub fn speed_classification(&self) -> &'static str {
+        match self.speed {
+            Some(ConnectionSpeed::Low) | Some(ConnectionSpeed::Full) => "usb1",
+            Some(ConnectionSpeed::High) => "usb2",
+            Some(ConnectionSpeed::Super) | Some(ConnectionSpeed::SuperPlus) | Some(ConnectionSpeed::SuperPlusPlus) => {
+                "usb3"
+            }
+            None => "unknown",
+        }
+    }
+
+    /// SuperSpeed (USB 3.x) or faster.
+    pub fn is_high_speed(&self) -> bool {
+        self.speed_classification() == "usb3"
+    }
+}
+
+/// Negotiated USB speed. Same variants and serialization as
+/// `libbootforge::device_state::ConnectionSpeed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionSpeed {
+    Low,           // USB 1.0 - 1.5 Mbps
+    Full,          // USB 1.1 - 12 Mbps
+    High,          // USB 2.0 - 480 Mbps
+    Super,         // USB 3.0 - 5 Gbps
+    SuperPlus,     // USB 3.1 - 10 Gbps
+    SuperPlusPlus, // USB 3.2 - 20 Gbps
 }
 
 /// Legacy alias for backwards compatibility
 pub type UsbEvidence = UsbTransportEvidence;
 
+/// Devices plugged into the same USB hub.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceGroup {
+    /// Bus number followed by the port numbers leading to the hub; just the
+    /// bus number for devices on a root port
+    pub hub_path: Vec<u8>,
+    /// `device_uid`s in the group, in scan order
+    pub devices: Vec<String>,
+}
+
+/// Scan result with the devices also grouped by hub.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanGroupResult {
+    pub devices: Vec<ConfirmedDeviceRecord>,
+    pub groups: Vec<DeviceGroup>,
+}
+
+/// Stage timings of one scan, with the records it produced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanBenchmark {
+    pub usb_probe_ms: u64,
+    pub adb_probe_ms: u64,
+    pub fastboot_probe_ms: u64,
+    pub idevice_probe_ms: u64,
+    /// Classification, correlation and record assembly
+    pub classify_ms: u64,
+    pub total_ms: u64,
+    pub device_count: usize,
+    pub devices: Vec<ConfirmedDeviceRecord>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InterfaceHint {
     pub class: u8,
@@ -67,6 +194,10 @@ pub struct ToolEvidence {
     pub seen: bool,
     pub raw: String,
     pub device_ids: Vec<String>,
+    /// Serials listed as `unauthorized` (adb only): connected, but the USB
+    /// debugging prompt has not been accepted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unauthorized_ids: Vec<String>,
 }
 
 impl ToolEvidence {
@@ -76,15 +207,32 @@ impl ToolEvidence {
             seen: false,
             raw: "missing".to_string(),
             device_ids: vec![],
+            unauthorized_ids: vec![],
         }
     }
 
+    /// Tool was deliberately not run (USB-only scan)
+    pub fn skipped() -> Self {
+        Self {
+            present: false,
+            seen: false,
+            raw: "skipped".to_string(),
+            device_ids: vec![],
+            unauthorized_ids: vec![],
+        }
+    }
+
+    pub fn is_skipped(&self) -> bool {
+        !self.present && self.raw == "skipped"
+    }
+
     pub fn present_not_seen() -> Self {
         Self {
             present: true,
             seen: false,
             raw: String::new(),
             device_ids: vec![],
+            unauthorized_ids: vec![],
         }
     }
 
@@ -94,18 +242,29 @@ impl ToolEvidence {
             seen: !device_ids.is_empty(),
             raw,
             device_ids,
+            unauthorized_ids: vec![],
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Serialized as the `as_str` value, e.g. `"android_adb_confirmed"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DeviceMode {
     IosNormalLikely,
     IosRecoveryLikely,
     IosDfuLikely,
+    /// Normal-mode Apple device that idevice_id cannot see (trust prompt not accepted)
+    IosUntrusted,
     AndroidAdbConfirmed,
     AndroidFastbootConfirmed,
     AndroidRecoveryAdbConfirmed,
+    /// MediaTek BootROM / Preloader download port (VID 0e8d), for SP Flash Tool
+    AndroidMtkDownloadLikely,
+    /// Android device exposing only its MTP/PTP file-transfer interface
+    AndroidMtpDetected,
+    /// Samsung Download mode (VID 04e8), for Odin/Heimdall
+    AndroidDownloadModeDetected,
     UnknownUsb,
 }
 
@@ -115,12 +274,47 @@ impl DeviceMode {
             DeviceMode::IosNormalLikely => "ios_normal_likely",
             DeviceMode::IosRecoveryLikely => "ios_recovery_likely",
             DeviceMode::IosDfuLikely => "ios_dfu_likely",
+            DeviceMode::IosUntrusted => "ios_untrusted",
             DeviceMode::AndroidAdbConfirmed => "android_adb_confirmed",
             DeviceMode::AndroidFastbootConfirmed => "android_fastboot_confirmed",
             DeviceMode::AndroidRecoveryAdbConfirmed => "android_recovery_adb_confirmed",
+            DeviceMode::AndroidMtkDownloadLikely => "android_mtk_download_likely",
+            DeviceMode::AndroidMtpDetected => "android_mtp_detected",
+            DeviceMode::AndroidDownloadModeDetected => "android_download_mode_detected",
             DeviceMode::UnknownUsb => "unknown_usb",
         }
     }
+
+    /// Modes only a tool confirmer may report: adb/fastboot saw the device,
+    /// or idevice_id could not. USB descriptors alone never justify them.
+    pub fn is_tool_confirmed(&self) -> bool {
+        matches!(
+            self,
+            DeviceMode::AndroidAdbConfirmed
+                | DeviceMode::AndroidFastbootConfirmed
+                | DeviceMode::AndroidRecoveryAdbConfirmed
+                | DeviceMode::IosUntrusted
+        )
+    }
+
+    /// Inverse of `as_str`.
+    pub fn parse(mode: &str) -> Option<Self> {
+        [
+            DeviceMode::IosNormalLikely,
+            DeviceMode::IosRecoveryLikely,
+            DeviceMode::IosDfuLikely,
+            DeviceMode::IosUntrusted,
+            DeviceMode::AndroidAdbConfirmed,
+            DeviceMode::AndroidFastbootConfirmed,
+            DeviceMode::AndroidRecoveryAdbConfirmed,
+            DeviceMode::AndroidMtkDownloadLikely,
+            DeviceMode::AndroidMtpDetected,
+            DeviceMode::AndroidDownloadModeDetected,
+            DeviceMode::UnknownUsb,
+        ]
+        .into_iter()
+        .find(|m| m.as_str() == mode)
+    }
 }
 
 /// Device classification result - platform, mode, and confidence.
@@ -134,3 +328,40 @@ pub struct Classification {
     pub confidence: f32,
     pub notes: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_platform_serializes_as_legacy_platform_hint() {
+        let record = ConfirmedDeviceRecord {
+            device_uid: "ABC123".to_string(),
+            platform: Platform::from_mode(DeviceMode::AndroidAdbConfirmed.as_str()),
+            mode: DeviceMode::AndroidAdbConfirmed.as_str().to_string(),
+            confidence: 0.9,
+            evidence: Evidence {
+                usb: UsbTransportEvidence {
+                    vid: "18d1".to_string(),
+                    pid: "4ee7".to_string(),
+                    serial: Some("ABC123".to_string()),
+                    bus: 1,
+                    address: 2,
+                    ..Default::default()
+                },
+                tools: HashMap::new(),
+            },
+            notes: vec![],
+            matched_tool_ids: vec![],
+            marketing_name: None,
+            driver_status: None,
+        };
+
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["platform_hint"], "android");
+        assert_eq!(record.platform_hint(), "android");
+
+        let back: ConfirmedDeviceRecord = serde_json::from_value(json).unwrap();
+        assert_eq!(back.platform, Platform::Android);
+    }
+}
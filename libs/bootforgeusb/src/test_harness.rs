@@ -0,0 +1,94 @@
+//! Deterministic scan pipeline for tests: fake USB transports and canned tool
+//! output in place of libusb and the adb/fastboot/idevice_id subprocesses.
+
+use crate::classify::ClassifierRegistry;
+use crate::model::ConfirmedDeviceRecord;
+use crate::tools::confirmers::MockToolRunner;
+use crate::usb_scan::FakeUsbDevices;
+
+/// Builder for one simulated [`crate::scan`] run.
+/// 
+/// ```ignore
+/// let result = TestHarness::new()
+///     .with_device("18d1", "4ee7", Some("Google"), Some("Pixel 8"), Some("1A2B3C4D"))
+///     .with_adb_output("List of devices attached\n1A2B3C4D\tdevice\n")
+///     .build();
+/// ```
+#[derive(Default)]
+pub(crate) struct TestHarness {
+    usb: FakeUsbDevices,
+    tools: MockToolRunner,
+}
+
+/// Records from a harness run, in transport order like [`crate::scan`].
+pub(crate) struct ScanResult {
+    pub devices: Vec<ConfirmedDeviceRecord>,
+}
+
+impl ScanResult {
+    /// Record with the given `device_uid`; panics if there is none.
+    pub fn device(&self, device_uid: &str) -> &ConfirmedDeviceRecord {
+        self.devices
+            .iter()
+            .find(|d| d.device_uid == device_uid)
+            .unwrap_or_else(|| panic!("no device {} in scan result", device_uid))
+    }
+}
+
+impl TestHarness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_device(
+        mut self,
+        vid: &str,
+        pid: &str,
+        manufacturer: Option<&str>,
+        product: Option<&str>,
+        serial: Option<&str>,
+    ) -> Self {
+        self.usb.add_device(vid, pid, manufacturer, product, serial);
+        self
+    }
+
+    /// `adb devices -l` stdout.
+    pub fn with_adb_output(mut self, stdout: &str) -> Self {
+        self.tools.adb = Some(stdout.to_string());
+        self
+    }
+
+    /// `fastboot devices` stdout.
+    pub fn with_fastboot_output(mut self, stdout: &str) -> Self {
+        self.tools.fastboot = Some(stdout.to_string());
+        self
+    }
+
+    /// `idevice_id -l` stdout.
+    pub fn with_idevice_id_output(mut self, stdout: &str) -> Self {
+        self.tools.idevice_id = Some(stdout.to_string());
+        self
+    }
+
+    /// Run stages 2-5 with the builtin classifiers.
+    pub fn build(self) -> ScanResult {
+        let transports = self.usb.probe_usb_transports();
+        let tools = self.tools.confirmers();
+        ScanResult {
+            devices: crate::scan_transports(ClassifierRegistry::builtin(), &transports, &tools),
+        }
+    }
+
+    /// Run the same pipeline through the stage timer used by [`crate::scan_benchmark`].
+    pub fn benchmark(self) -> crate::model::ScanBenchmark {
+        let tools = self.tools.confirmers();
+        crate::benchmark_with(
+            ClassifierRegistry::builtin(),
+            || Ok(self.usb.probe_usb_transports()),
+            || tools.adb.clone(),
+            || tools.fastboot.clone(),
+            || tools.idevice_id.clone(),
+        )
+        .expect("fake USB probe cannot fail")
+    }
+}
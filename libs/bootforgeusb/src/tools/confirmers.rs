@@ -1,5 +1,94 @@
 use crate::model::{Classification, DeviceMode, ToolEvidence};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::Instant;
+
+/// adb keeps device state in its server, so it is cheap to re-query
+pub const ADB_MIN_PROBE_INTERVAL_MS: u64 = 3_000;
+/// fastboot enumerates USB itself on every call
+pub const FASTBOOT_MIN_PROBE_INTERVAL_MS: u64 = 5_000;
+pub const IDEVICE_ID_MIN_PROBE_INTERVAL_MS: u64 = 3_000;
+
+static ADB_THROTTLE: ToolProbeThrottle = ToolProbeThrottle::new(ADB_MIN_PROBE_INTERVAL_MS);
+static FASTBOOT_THROTTLE: ToolProbeThrottle = ToolProbeThrottle::new(FASTBOOT_MIN_PROBE_INTERVAL_MS);
+static IDEVICE_ID_THROTTLE: ToolProbeThrottle = ToolProbeThrottle::new(IDEVICE_ID_MIN_PROBE_INTERVAL_MS);
+
+static TOOL_PATHS: RwLock<ToolPaths> = RwLock::new(ToolPaths { adb: None, fastboot: None, idevice_id: None });
+
+/// Custom tool binaries for the stage 3 probes; `None` runs the tool from PATH.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolPaths {
+    pub adb: Option<PathBuf>,
+    pub fastboot: Option<PathBuf>,
+    pub idevice_id: Option<PathBuf>,
+}
+
+/// Use `paths` for every later probe. Cached evidence from the old tools is discarded.
+pub fn set_tool_paths(paths: ToolPaths) {
+    let Ok(mut current) = TOOL_PATHS.write() else {
+        return;
+    };
+    if *current != paths {
+        *current = paths;
+        ToolConfirmers::invalidate_cache();
+    }
+}
+
+/// The tool paths set with [`set_tool_paths`]
+pub fn tool_paths() -> ToolPaths {
+    TOOL_PATHS.read().map(|paths| paths.clone()).unwrap_or_default()
+}
+
+/// Milliseconds since the first call; never returns 0 so 0 can mean "never probed".
+fn monotonic_ms() -> u64 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_millis() as u64 + 1
+}
+
+/// Limits how often a tool subprocess is spawned.
+///
+/// Within `min_interval_ms` of the last probe the cached `ToolEvidence` is
+/// returned instead of running the tool again.
+pub struct ToolProbeThrottle {
+    min_interval_ms: u64,
+    last_probe_ms: AtomicU64,
+    cached: Mutex<Option<ToolEvidence>>,
+}
+
+impl ToolProbeThrottle {
+    pub const fn new(min_interval_ms: u64) -> Self {
+        Self {
+            min_interval_ms,
+            last_probe_ms: AtomicU64::new(0),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Return the cached evidence if it is fresh enough, otherwise run `probe`.
+    pub fn probe_with(&self, probe: impl FnOnce() -> ToolEvidence) -> ToolEvidence {
+        let last = self.last_probe_ms.load(Ordering::Acquire);
+        if last != 0 && monotonic_ms().saturating_sub(last) < self.min_interval_ms {
+            if let Some(cached) = self.cached.lock().ok().and_then(|c| c.clone()) {
+                return cached;
+            }
+        }
+
+        let evidence = probe();
+        if let Ok(mut cached) = self.cached.lock() {
+            *cached = Some(evidence.clone());
+        }
+        self.last_probe_ms.store(monotonic_ms(), Ordering::Release);
+        evidence
+    }
+
+    /// Force the next `probe_with` call to run the tool.
+    pub fn invalidate(&self) {
+        self.last_probe_ms.store(0, Ordering::Release);
+    }
+}
 
 /// Tool evidence collector - probes adb, fastboot, and idevice_id for device IDs.
 /// 
@@ -14,14 +103,33 @@ impl ToolConfirmers {
     /// Create new tool confirmers by probing all tools.
     /// 
     /// Each tool is checked for availability and executed to collect device IDs.
+    /// Probes are throttled per tool; recent results are reused (see `ToolProbeThrottle`).
+    /// Tools run from the paths set with [`set_tool_paths`], or from PATH.
     pub fn new() -> Self {
+        let paths = tool_paths();
         Self {
-            adb: probe_adb_tool(),
-            fastboot: probe_fastboot_tool(),
-            idevice_id: probe_idevice_id_tool(),
+            adb: ADB_THROTTLE.probe_with(|| probe_adb_tool(paths.adb.as_deref())),
+            fastboot: FASTBOOT_THROTTLE.probe_with(|| probe_fastboot_tool(paths.fastboot.as_deref())),
+            idevice_id: IDEVICE_ID_THROTTLE.probe_with(|| probe_idevice_id_tool(paths.idevice_id.as_deref())),
         }
     }
 
+    /// Confirmers for a USB-only scan: no tool is run, nothing correlates.
+    pub fn skipped() -> Self {
+        Self {
+            adb: ToolEvidence::skipped(),
+            fastboot: ToolEvidence::skipped(),
+            idevice_id: ToolEvidence::skipped(),
+        }
+    }
+
+    /// Discard cached tool evidence so the next `new()` probes every tool.
+    pub fn invalidate_cache() {
+        ADB_THROTTLE.invalidate();
+        FASTBOOT_THROTTLE.invalidate();
+        IDEVICE_ID_THROTTLE.invalidate();
+    }
+
     /// Correlate device identity by matching USB serial to tool device IDs.
     /// 
     /// Direct serial match (highest confidence correlation method).
@@ -37,11 +145,20 @@ impl ToolConfirmers {
                 classification.notes.push("Correlated: adb device id matches USB serial".to_string());
                 matched_ids.push(serial_num.to_string());
                 
-                if matches!(classification.mode, DeviceMode::UnknownUsb) {
+                if matches!(
+                    classification.mode,
+                    DeviceMode::UnknownUsb | DeviceMode::AndroidMtpDetected | DeviceMode::AndroidDownloadModeDetected
+                ) {
                     classification.mode = DeviceMode::AndroidAdbConfirmed;
                 }
             }
             
+            if self.adb.unauthorized_ids.iter().any(|id| id == serial_num) {
+                classification.notes.push(
+                    "adb lists this device as unauthorized: accept the USB debugging prompt on the device".to_string(),
+                );
+            }
+            
             if self.fastboot.present && self.fastboot.device_ids.iter().any(|id| id == serial_num) {
                 classification.confidence = (classification.confidence + 0.15).min(0.95);
                 classification.notes.push("Correlated: fastboot device id matches USB serial".to_string());
@@ -60,24 +177,25 @@ impl ToolConfirmers {
     }
 }
 
-fn parse_adb_ids(stdout: &str) -> Vec<String> {
-    stdout
-        .lines()
-        .filter_map(|line| {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with("List of devices") {
-                return None;
-            }
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 2 {
-                let state = parts[1];
-                if state == "device" || state == "sideload" || state == "recovery" {
-                    return Some(parts[0].to_string());
-                }
+/// Returns `(device_ids, unauthorized_ids)` from `adb devices` output.
+fn parse_adb_ids(stdout: &str) -> (Vec<String>, Vec<String>) {
+    let mut device_ids = Vec::new();
+    let mut unauthorized_ids = Vec::new();
+    for line in stdout.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("List of devices") {
+            continue;
+        }
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() >= 2 {
+            match parts[1] {
+                "device" | "sideload" | "recovery" => device_ids.push(parts[0].to_string()),
+                "unauthorized" => unauthorized_ids.push(parts[0].to_string()),
+                _ => {}
             }
-            None
-        })
-        .collect()
+        }
+    }
+    (device_ids, unauthorized_ids)
 }
 
 fn parse_fastboot_ids(stdout: &str) -> Vec<String> {
@@ -109,42 +227,48 @@ fn parse_idevice_ids(stdout: &str) -> Vec<String> {
 
 /// Stage 3: Probe ADB tool for device IDs.
 /// 
-/// Executes `adb devices -l` and parses output for device serials.
+/// Executes `adb devices -l` (or `path` when given) and parses output for device serials.
 /// Used for identity correlation during device detection.
-fn probe_adb_tool() -> ToolEvidence {
-    if !is_tool_available("adb") {
+pub(crate) fn probe_adb_tool(path: Option<&Path>) -> ToolEvidence {
+    let program = path.map_or(OsStr::new("adb"), Path::as_os_str);
+    if !is_tool_available(program) {
         return ToolEvidence::missing();
     }
     
-    match Command::new("adb").args(["devices", "-l"]).output() {
+    match Command::new(program).args(["devices", "-l"]).output() {
         Ok(output) => {
             let stdout = String::from_utf8_lossy(&output.stdout);
-            let device_ids = parse_adb_ids(&stdout);
+            let (device_ids, unauthorized_ids) = parse_adb_ids(&stdout);
             let raw = format!("STDOUT:\n{}\nSTDERR:\n{}", 
                 stdout.trim(), 
                 String::from_utf8_lossy(&output.stderr).trim());
             
-            ToolEvidence::confirmed(raw, device_ids)
+            ToolEvidence {
+                unauthorized_ids,
+                ..ToolEvidence::confirmed(raw, device_ids)
+            }
         }
         Err(e) => ToolEvidence {
             present: true,
             seen: false,
             raw: format!("error: {}", e),
             device_ids: vec![],
+            unauthorized_ids: vec![],
         },
     }
 }
 
 /// Stage 3: Probe Fastboot tool for device IDs.
 /// 
-/// Executes `fastboot devices` and parses output for device serials.
+/// Executes `fastboot devices` (or `path` when given) and parses output for device serials.
 /// Used for identity correlation during device detection.
-fn probe_fastboot_tool() -> ToolEvidence {
-    if !is_tool_available("fastboot") {
+pub(crate) fn probe_fastboot_tool(path: Option<&Path>) -> ToolEvidence {
+    let program = path.map_or(OsStr::new("fastboot"), Path::as_os_str);
+    if !is_tool_available(program) {
         return ToolEvidence::missing();
     }
     
-    match Command::new("fastboot").arg("devices").output() {
+    match Command::new(program).arg("devices").output() {
         Ok(output) => {
             let stdout = String::from_utf8_lossy(&output.stdout);
             let device_ids = parse_fastboot_ids(&stdout);
@@ -159,20 +283,22 @@ fn probe_fastboot_tool() -> ToolEvidence {
             seen: false,
             raw: format!("error: {}", e),
             device_ids: vec![],
+            unauthorized_ids: vec![],
         },
     }
 }
 
 /// Stage 3: Probe idevice_id tool for UDIDs.
 /// 
-/// Executes `idevice_id -l` and parses output for iOS device UDIDs.
+/// Executes `idevice_id -l` (or `path` when given) and parses output for iOS device UDIDs.
 /// Used for identity correlation during device detection.
-fn probe_idevice_id_tool() -> ToolEvidence {
-    if !is_tool_available("idevice_id") {
+pub(crate) fn probe_idevice_id_tool(path: Option<&Path>) -> ToolEvidence {
+    let program = path.map_or(OsStr::new("idevice_id"), Path::as_os_str);
+    if !is_tool_available(program) {
         return ToolEvidence::missing();
     }
     
-    match Command::new("idevice_id").arg("-l").output() {
+    match Command::new(program).arg("-l").output() {
         Ok(output) => {
             let stdout = String::from_utf8_lossy(&output.stdout);
             let device_ids = parse_idevice_ids(&stdout);
@@ -187,11 +313,17 @@ fn probe_idevice_id_tool() -> ToolEvidence {
             seen: false,
             raw: format!("error: {}", e),
             device_ids: vec![],
+            unauthorized_ids: vec![],
         },
     }
 }
 
-fn is_tool_available(tool: &str) -> bool {
+/// A path to an existing file, or a bare program name found on PATH.
+fn is_tool_available(tool: &OsStr) -> bool {
+    if Path::new(tool).components().count() > 1 {
+        return Path::new(tool).is_file();
+    }
+
     #[cfg(target_os = "windows")]
     let which_cmd = "where";
     
@@ -205,6 +337,43 @@ fn is_tool_available(tool: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Canned tool output for tests, so `ToolConfirmers` can be built without
+/// running adb, fastboot or idevice_id.
+/// 
+/// Output is parsed exactly as the real probes parse it; a tool with no
+/// output configured is reported missing.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct MockToolRunner {
+    pub(crate) adb: Option<String>,
+    pub(crate) fastboot: Option<String>,
+    pub(crate) idevice_id: Option<String>,
+}
+
+#[cfg(test)]
+impl MockToolRunner {
+    pub(crate) fn confirmers(&self) -> ToolConfirmers {
+        fn raw(stdout: &str) -> String {
+            format!("STDOUT:\n{}\nSTDERR:\n", stdout.trim())
+        }
+        ToolConfirmers {
+            adb: self.adb.as_deref().map_or_else(ToolEvidence::missing, |stdout| {
+                let (device_ids, unauthorized_ids) = parse_adb_ids(stdout);
+                ToolEvidence {
+                    unauthorized_ids,
+                    ..ToolEvidence::confirmed(raw(stdout), device_ids)
+                }
+            }),
+            fastboot: self.fastboot.as_deref().map_or_else(ToolEvidence::missing, |stdout| {
+                ToolEvidence::confirmed(raw(stdout), parse_fastboot_ids(stdout))
+            }),
+            idevice_id: self.idevice_id.as_deref().map_or_else(ToolEvidence::missing, |stdout| {
+                ToolEvidence::confirmed(raw(stdout), parse_idevice_ids(stdout))
+            }),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -221,7 +390,7 @@ mod tests {
     #[test]
     fn test_parse_adb_ids() {
         let output = "List of devices attached\nABC123\tdevice\nDEF456\tdevice\n";
-        let ids = parse_adb_ids(output);
+        let (ids, _) = parse_adb_ids(output);
         assert_eq!(ids.len(), 2);
         assert!(ids.contains(&"ABC123".to_string()));
         assert!(ids.contains(&"DEF456".to_string()));
@@ -230,11 +399,19 @@ mod tests {
     #[test]
     fn test_parse_adb_ids_with_recovery() {
         let output = "List of devices attached\nABC123\trecovery\n";
-        let ids = parse_adb_ids(output);
+        let (ids, _) = parse_adb_ids(output);
         assert_eq!(ids.len(), 1);
         assert!(ids.contains(&"ABC123".to_string()));
     }
     
+    #[test]
+    fn test_parse_adb_ids_tracks_unauthorized() {
+        let output = "List of devices attached\nABC123\tunauthorized usb:1-1 transport_id:3\nDEF456\tdevice\nGHI789\toffline\n";
+        let (ids, unauthorized) = parse_adb_ids(output);
+        assert_eq!(ids, vec!["DEF456".to_string()]);
+        assert_eq!(unauthorized, vec!["ABC123".to_string()]);
+    }
+    
     #[test]
     fn test_parse_fastboot_ids() {
         let output = "ABC123 fastboot\nDEF456 fastboot\n";
@@ -252,6 +429,38 @@ mod tests {
         assert!(ids[0].starts_with("00008030"));
     }
     
+    #[test]
+    fn test_throttle_reuses_evidence_within_interval() {
+        let throttle = ToolProbeThrottle::new(60_000);
+        let calls = std::cell::Cell::new(0);
+        let probe = || {
+            calls.set(calls.get() + 1);
+            ToolEvidence::confirmed("ABC123\tdevice".to_string(), vec!["ABC123".to_string()])
+        };
+
+        let first = throttle.probe_with(probe);
+        let second = throttle.probe_with(probe);
+        assert_eq!(calls.get(), 1);
+        assert_eq!(second.device_ids, first.device_ids);
+
+        throttle.invalidate();
+        throttle.probe_with(probe);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_throttle_with_zero_interval_always_probes() {
+        let throttle = ToolProbeThrottle::new(0);
+        let calls = std::cell::Cell::new(0);
+        for _ in 0..3 {
+            throttle.probe_with(|| {
+                calls.set(calls.get() + 1);
+                ToolEvidence::missing()
+            });
+        }
+        assert_eq!(calls.get(), 3);
+    }
+
     #[test]
     fn test_correlate_device_identity_no_match() {
         let mut confirmers = ToolConfirmers::new();
@@ -289,4 +498,25 @@ mod tests {
         assert!(classification.confidence > 0.7); // Increased
         assert_eq!(classification.mode.as_str(), "android_adb_confirmed");
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_probe_uses_the_tool_path_override() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("bootforgeusb-tools-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let adb = dir.join("adb-custom");
+        std::fs::write(&adb, "#!/bin/sh\necho 'List of devices attached'\necho 'CUSTOM01\tdevice'\n").unwrap();
+        std::fs::set_permissions(&adb, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let evidence = probe_adb_tool(Some(&adb));
+        assert!(evidence.present && evidence.seen);
+        assert_eq!(evidence.device_ids, vec!["CUSTOM01".to_string()]);
+
+        let missing = probe_adb_tool(Some(&dir.join("no-such-adb")));
+        assert!(!missing.present);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
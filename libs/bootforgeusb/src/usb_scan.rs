@@ -1,5 +1,59 @@
-use crate::model::{UsbTransportEvidence, InterfaceHint};
-use rusb::{Context, Device, UsbContext};
+use crate::model::{ConnectionSpeed, UsbTransportEvidence, InterfaceHint};
+use rusb::{Context, Device, Speed, UsbContext};
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Default per-device deadline for descriptor reads, see [`set_probe_timeout`]
+pub const DEFAULT_PROBE_TIMEOUT_MS: u64 = 5_000;
+
+static PROBE_TIMEOUT_MS: AtomicU64 = AtomicU64::new(DEFAULT_PROBE_TIMEOUT_MS);
+
+/// `(bus, address)` of devices whose probe thread has not returned yet. A
+/// device stuck in libusb keeps that one thread; later scans report it as
+/// timed out straight away instead of piling up more threads behind it.
+static OUTSTANDING_PROBES: Mutex<BTreeSet<(u8, u8)>> = Mutex::new(BTreeSet::new());
+
+/// Set how long [`probe_usb_transports`] waits for one device's descriptor
+/// reads before reporting it as timed out. Applies to every later scan.
+pub fn set_probe_timeout(timeout: Duration) {
+    PROBE_TIMEOUT_MS.store(timeout.as_millis().min(u128::from(u64::MAX)) as u64, Ordering::Relaxed);
+}
+
+pub fn probe_timeout() -> Duration {
+    Duration::from_millis(PROBE_TIMEOUT_MS.load(Ordering::Relaxed))
+}
+
+/// How two sightings of USB transport evidence relate, see [`merge_transport_evidence`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportMergeResult {
+    /// Both report the same serial number
+    SameDevice,
+    /// Neither reports a serial, but VID/PID and descriptor strings agree
+    ProbablySameDevice,
+    DifferentDevice,
+}
+
+/// Decide whether `new` is the device `old` described, ignoring bus and
+/// address, which change when a device is replugged or moves to another port.
+pub fn merge_transport_evidence(old: &UsbTransportEvidence, new: &UsbTransportEvidence) -> TransportMergeResult {
+    fn serial(t: &UsbTransportEvidence) -> Option<&str> {
+        t.serial.as_deref().map(str::trim).filter(|s| !s.is_empty())
+    }
+    match (serial(old), serial(new)) {
+        (Some(a), Some(b)) if a == b => TransportMergeResult::SameDevice,
+        (None, None)
+            if old.vid.eq_ignore_ascii_case(&new.vid)
+                && old.pid.eq_ignore_ascii_case(&new.pid)
+                && old.manufacturer == new.manufacturer
+                && old.product == new.product =>
+        {
+            TransportMergeResult::ProbablySameDevice
+        }
+        _ => TransportMergeResult::DifferentDevice,
+    }
+}
 
 /// Stage 1: Probe all USB transports (enumerate USB devices).
 /// 
@@ -7,19 +61,130 @@ use rusb::{Context, Device, UsbContext};
 /// (VID/PID, descriptors, interfaces). This is the first stage of the detection pipeline.
 /// 
 /// Returns: Vec of USB transport evidence (raw USB layer data).
+/// 
+/// Each device gets [`probe_timeout`] to answer; an unresponsive one is
+/// reported with `timed_out` set instead of stalling the whole scan.
 pub fn probe_usb_transports() -> Result<Vec<UsbTransportEvidence>, Box<dyn std::error::Error>> {
     let context = Context::new()?;
     let devices = context.devices()?;
     
+    Ok(probe_devices(
+        devices.iter(),
+        probe_timeout(),
+        |device| (device.bus_number(), device.address()),
+        |device| extract_transport_evidence(device).ok(),
+        timed_out_evidence,
+    ))
+}
+
+/// Result of probing one device under a deadline
+#[derive(Debug)]
+enum ProbeOutcome {
+    Evidence(UsbTransportEvidence),
+    Failed,
+    TimedOut,
+}
+
+/// Marks a device's probe as outstanding until dropped, see [`OUTSTANDING_PROBES`]
+struct OutstandingProbe((u8, u8));
+
+impl OutstandingProbe {
+    /// `None` while an earlier probe of the device is still running
+    fn claim(key: (u8, u8)) -> Option<Self> {
+        let claimed = OUTSTANDING_PROBES.lock().ok()?.insert(key);
+        claimed.then(|| Self(key))
+    }
+}
+
+impl Drop for OutstandingProbe {
+    fn drop(&mut self) {
+        if let Ok(mut outstanding) = OUTSTANDING_PROBES.lock() {
+            outstanding.remove(&self.0);
+        }
+    }
+}
+
+/// Run `extract` on its own thread and wait at most `timeout` for it. A
+/// timed-out thread is left to finish (or stay blocked in libusb) on its own;
+/// until it does, the device at `key` is reported as timed out without a new thread.
+fn extract_with_timeout<D: Send + 'static>(
+    device: D,
+    key: (u8, u8),
+    timeout: Duration,
+    extract: fn(&D) -> Option<UsbTransportEvidence>,
+) -> ProbeOutcome {
+    let Some(claim) = OutstandingProbe::claim(key) else {
+        return ProbeOutcome::TimedOut;
+    };
+    let (tx, rx) = mpsc::channel();
+    let spawned = std::thread::Builder::new()
+        .name("usb-probe".to_string())
+        .spawn(move || {
+            let result = extract(&device);
+            drop(claim);
+            let _ = tx.send(result);
+        });
+    if spawned.is_err() {
+        return ProbeOutcome::Failed;
+    }
+    match rx.recv_timeout(timeout) {
+        Ok(Some(evidence)) => ProbeOutcome::Evidence(evidence),
+        Ok(None) | Err(mpsc::RecvTimeoutError::Disconnected) => ProbeOutcome::Failed,
+        Err(mpsc::RecvTimeoutError::Timeout) => ProbeOutcome::TimedOut,
+    }
+}
+
+/// Probe each device in turn, substituting `on_timeout`'s evidence for
+/// devices that miss the deadline and dropping devices that fail outright.
+fn probe_devices<D: Clone + Send + 'static>(
+    devices: impl IntoIterator<Item = D>,
+    timeout: Duration,
+    key: fn(&D) -> (u8, u8),
+    extract: fn(&D) -> Option<UsbTransportEvidence>,
+    on_timeout: fn(&D) -> Option<UsbTransportEvidence>,
+) -> Vec<UsbTransportEvidence> {
     let mut results = Vec::new();
-    
-    for device in devices.iter() {
-        if let Ok(evidence) = extract_transport_evidence(&device) {
-            results.push(evidence);
+    for device in devices {
+        match extract_with_timeout(device.clone(), key(&device), timeout, extract) {
+            ProbeOutcome::Evidence(evidence) => results.push(evidence),
+            ProbeOutcome::Failed => {}
+            ProbeOutcome::TimedOut => {
+                let evidence = on_timeout(&device);
+                log::warn!(
+                    "USB descriptor reads timed out after {} ms{}",
+                    timeout.as_millis(),
+                    evidence
+                        .as_ref()
+                        .map(|e| format!(" for {}:{} on bus {} address {}", e.vid, e.pid, e.bus, e.address))
+                        .unwrap_or_default()
+                );
+                results.extend(evidence);
+            }
         }
     }
-    
-    Ok(results)
+    results
+}
+
+/// Evidence for a device that did not answer in time: only what the cached
+/// device descriptor and topology provide, without opening the device.
+fn timed_out_evidence<T: UsbContext>(device: &Device<T>) -> Option<UsbTransportEvidence> {
+    let device_desc = device.device_descriptor().ok()?;
+    Some(UsbTransportEvidence {
+        vid: format!("{:04x}", device_desc.vendor_id()),
+        pid: format!("{:04x}", device_desc.product_id()),
+        manufacturer: None,
+        product: None,
+        serial: None,
+        bus: device.bus_number(),
+        address: device.address(),
+        interface_class: None,
+        interface_hints: vec![],
+        probe_latency_ms: None,
+        connection_quality: None,
+        speed: connection_speed(device.speed()),
+        hub_port_path: device.port_numbers().ok().filter(|ports| !ports.is_empty()),
+        timed_out: true,
+    })
 }
 
 /// Extract transport evidence from a USB device descriptor.
@@ -34,6 +199,8 @@ fn extract_transport_evidence<T: UsbContext>(device: &Device<T>) -> Result<UsbTr
     let vid = format!("{:04x}", device_desc.vendor_id());
     let pid = format!("{:04x}", device_desc.product_id());
     
+    // Time the handle open + string descriptor reads as a link health signal.
+    let probe_started = Instant::now();
     let handle = device.open();
     
     let manufacturer = handle.as_ref()
@@ -48,6 +215,13 @@ fn extract_transport_evidence<T: UsbContext>(device: &Device<T>) -> Result<UsbTr
         .ok()
         .and_then(|h| h.read_serial_number_string_ascii(&device_desc).ok());
     
+    let probe_latency_ms = handle
+        .is_ok()
+        .then(|| probe_started.elapsed().as_millis().min(u128::from(u32::MAX)) as u32);
+    let connection_quality = connection_quality(probe_latency_ms, device.speed());
+    let speed = connection_speed(device.speed());
+    let hub_port_path = device.port_numbers().ok().filter(|ports| !ports.is_empty());
+    
     let (interface_class, interface_hints) = extract_interface_descriptors(device);
     
     Ok(UsbTransportEvidence {
@@ -60,9 +234,61 @@ fn extract_transport_evidence<T: UsbContext>(device: &Device<T>) -> Result<UsbTr
         address,
         interface_class,
         interface_hints,
+        probe_latency_ms,
+        connection_quality,
+        speed,
+        hub_port_path,
+        timed_out: false,
     })
 }
 
+/// Map descriptor probe latency to a 0-100 score.
+fn latency_score(latency_ms: u32) -> u8 {
+    match latency_ms {
+        0..=4 => 100,
+        5..=20 => 80,
+        21..=100 => 60,
+        _ => 40,
+    }
+}
+
+/// Map the negotiated USB speed to a 0-100 score.
+fn speed_score(speed: Speed) -> Option<u8> {
+    match speed {
+        Speed::Super | Speed::SuperPlus => Some(100),
+        Speed::High => Some(80),
+        Speed::Full => Some(50),
+        Speed::Low => Some(30),
+        _ => None,
+    }
+}
+
+/// Map the rusb speed to the evidence enum; `None` when the host does not know it.
+pub fn connection_speed(speed: Speed) -> Option<ConnectionSpeed> {
+    match speed {
+        Speed::Low => Some(ConnectionSpeed::Low),
+        Speed::Full => Some(ConnectionSpeed::Full),
+        Speed::High => Some(ConnectionSpeed::High),
+        Speed::Super => Some(ConnectionSpeed::Super),
+        Speed::SuperPlus => Some(ConnectionSpeed::SuperPlus),
+        _ => None,
+    }
+}
+
+/// Combine probe latency (60%) and USB speed (40%) into a connection quality score.
+/// 
+/// Falls back to whichever signal is available; `None` if neither is.
+pub fn connection_quality(probe_latency_ms: Option<u32>, speed: Speed) -> Option<u8> {
+    match (probe_latency_ms.map(latency_score), speed_score(speed)) {
+        (Some(latency), Some(speed)) => {
+            Some(((u32::from(latency) * 60 + u32::from(speed) * 40) / 100) as u8)
+        }
+        (Some(latency), None) => Some(latency),
+        (None, Some(speed)) => Some(speed),
+        (None, None) => None,
+    }
+}
+
 /// Extract interface descriptors (class, subclass, protocol) from USB device.
 /// 
 /// Used for platform classification hints (e.g., vendor interface 0xff suggests Android).
@@ -88,6 +314,49 @@ fn extract_interface_descriptors<T: UsbContext>(device: &Device<T>) -> (Option<u
     (first_class, hints)
 }
 
+/// Pre-programmed USB transports for tests, "enumerated" instead of the real
+/// buses.
+/// 
+/// This is not a `rusb::UsbContext`: `rusb::Device` handles can only come
+/// from a live libusb context, so the pipeline is stubbed one step later, at
+/// the evidence that `extract_transport_evidence` would have read from each
+/// device.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct FakeUsbDevices {
+    devices: Vec<UsbTransportEvidence>,
+}
+
+#[cfg(test)]
+impl FakeUsbDevices {
+    /// Plug in a device. Devices sit on bus 1 and get consecutive addresses.
+    pub(crate) fn add_device(
+        &mut self,
+        vid: &str,
+        pid: &str,
+        manufacturer: Option<&str>,
+        product: Option<&str>,
+        serial: Option<&str>,
+    ) {
+        let address = self.devices.len() as u8 + 1;
+        self.devices.push(UsbTransportEvidence {
+            vid: vid.to_string(),
+            pid: pid.to_string(),
+            manufacturer: manufacturer.map(str::to_string),
+            product: product.map(str::to_string),
+            serial: serial.map(str::to_string),
+            bus: 1,
+            address,
+            ..Default::default()
+        });
+    }
+
+    /// Fake counterpart of [`probe_usb_transports`].
+    pub(crate) fn probe_usb_transports(&self) -> Vec<UsbTransportEvidence> {
+        self.devices.clone()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,4 +387,127 @@ mod tests {
             }
         }
     }
+    
+    #[test]
+    fn test_connection_quality_weighting() {
+        // Fast probe on a high-speed link: 100 * 0.6 + 80 * 0.4
+        assert_eq!(connection_quality(Some(3), Speed::High), Some(92));
+        // Slow probe on a full-speed link: 40 * 0.6 + 50 * 0.4
+        assert_eq!(connection_quality(Some(250), Speed::Full), Some(44));
+        // Handle could not be opened: speed alone
+        assert_eq!(connection_quality(None, Speed::Super), Some(100));
+        assert_eq!(connection_quality(Some(12), Speed::Unknown), Some(80));
+        assert_eq!(connection_quality(None, Speed::Unknown), None);
+    }
+    
+    #[test]
+    fn test_merge_transport_evidence() {
+        let evidence = |serial: Option<&str>, product: Option<&str>, bus: u8| UsbTransportEvidence {
+            vid: "18d1".to_string(),
+            pid: "4ee7".to_string(),
+            manufacturer: Some("Google".to_string()),
+            product: product.map(str::to_string),
+            serial: serial.map(str::to_string),
+            bus,
+            address: bus + 4,
+            ..Default::default()
+        };
+        let pixel = Some("Pixel 7");
+        assert_eq!(
+            merge_transport_evidence(&evidence(Some("ABC123"), pixel, 1), &evidence(Some("ABC123"), pixel, 3)),
+            TransportMergeResult::SameDevice
+        );
+        assert_eq!(
+            merge_transport_evidence(&evidence(None, pixel, 1), &evidence(None, pixel, 2)),
+            TransportMergeResult::ProbablySameDevice
+        );
+        assert_eq!(
+            merge_transport_evidence(&evidence(None, pixel, 1), &evidence(None, Some("Pixel 8"), 2)),
+            TransportMergeResult::DifferentDevice
+        );
+        assert_eq!(
+            merge_transport_evidence(&evidence(Some("ABC123"), pixel, 1), &evidence(None, pixel, 1)),
+            TransportMergeResult::DifferentDevice
+        );
+        assert_eq!(
+            merge_transport_evidence(&evidence(Some("ABC123"), pixel, 1), &evidence(Some("XYZ789"), pixel, 1)),
+            TransportMergeResult::DifferentDevice
+        );
+    }
+
+    #[test]
+    fn test_connection_speed_classification() {
+        let mut transport = UsbTransportEvidence {
+            vid: "18d1".to_string(),
+            pid: "4ee7".to_string(),
+            bus: 1,
+            address: 2,
+            speed: connection_speed(Speed::High),
+            ..Default::default()
+        };
+        assert_eq!(transport.speed_classification(), "usb2");
+        assert!(!transport.is_high_speed());
+        
+        transport.speed = connection_speed(Speed::SuperPlus);
+        assert_eq!(transport.speed_classification(), "usb3");
+        assert!(transport.is_high_speed());
+        
+        transport.speed = connection_speed(Speed::Unknown);
+        assert_eq!(transport.speed_classification(), "unknown");
+        assert!(!transport.is_high_speed());
+    }
+
+    /// Mock device: (address, how long its descriptor reads take in ms)
+    fn slow_extract(device: &(u8, u64)) -> Option<UsbTransportEvidence> {
+        std::thread::sleep(Duration::from_millis(device.1));
+        Some(UsbTransportEvidence {
+            vid: "18d1".to_string(),
+            pid: "4ee7".to_string(),
+            manufacturer: Some("Google".to_string()),
+            product: Some("Pixel 8".to_string()),
+            serial: Some("SERIAL".to_string()),
+            bus: 1,
+            address: device.0,
+            ..Default::default()
+        })
+    }
+
+    fn timed_out_mock(device: &(u8, u64)) -> Option<UsbTransportEvidence> {
+        Some(UsbTransportEvidence {
+            vid: "18d1".to_string(),
+            pid: "4ee7".to_string(),
+            bus: 1,
+            address: device.0,
+            timed_out: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_slow_device_times_out_without_stalling_the_scan() {
+        // Bus 250 keeps the mock keys apart from real devices probed by other tests
+        let key: fn(&(u8, u64)) -> (u8, u8) = |device| (250, device.0);
+        let timeout = Duration::from_millis(100);
+        assert!(matches!(extract_with_timeout((1, 0), (250, 1), timeout, slow_extract), ProbeOutcome::Evidence(_)));
+        assert!(matches!(extract_with_timeout((1, 0), (250, 1), timeout, |_| None), ProbeOutcome::Failed));
+
+        let started = Instant::now();
+        let results = probe_devices([(1, 0), (2, 10_000), (3, 0)], timeout, key, slow_extract, timed_out_mock);
+        assert!(started.elapsed() < Duration::from_secs(2), "scan waited for the slow device");
+
+        assert_eq!(results.iter().map(|e| e.address).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(!results[0].timed_out && !results[2].timed_out);
+        assert!(results[1].timed_out);
+        assert_eq!(results[1].product, None);
+        assert_eq!(results[2].product.as_deref(), Some("Pixel 8"));
+
+        // The hung probe is still outstanding: the next scan reports it at
+        // once instead of starting another thread that would hang too
+        assert!(OUTSTANDING_PROBES.lock().unwrap().contains(&(250, 2)));
+        let started = Instant::now();
+        let results = probe_devices([(2, 10_000)], timeout, key, slow_extract, timed_out_mock);
+        assert!(started.elapsed() < timeout);
+        assert!(results[0].timed_out);
+        assert!(!OUTSTANDING_PROBES.lock().unwrap().contains(&(250, 1)));
+    }
 }
@@ -0,0 +1,41 @@
+//! Embedded USB ID database lookups.
+//!
+//! Used as a fallback when a transport exposes no product string descriptor
+//! (common in DFU/EDL/preloader modes). The maps are generated by `build.rs`
+//! from `data/usb.ids`.
+
+include!(concat!(env!("OUT_DIR"), "/usb_ids.rs"));
+
+/// Look up the product name for a VID:PID pair (hex, case-insensitive).
+pub fn lookup_device_name(vid: &str, pid: &str) -> Option<&'static str> {
+    let key = format!("{}:{}", vid.to_ascii_lowercase(), pid.to_ascii_lowercase());
+    USB_DEVICE_NAMES.get(key.as_str()).copied()
+}
+
+/// Look up the vendor name for a VID (hex, case-insensitive).
+pub fn lookup_vendor_name(vid: &str) -> Option<&'static str> {
+    USB_VENDOR_NAMES.get(vid.to_ascii_lowercase().as_str()).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_apple_dfu() {
+        assert_eq!(lookup_device_name("05ac", "1227"), Some("Mobile Device (DFU Mode)"));
+        assert_eq!(lookup_device_name("05AC", "1227"), Some("Mobile Device (DFU Mode)"));
+    }
+
+    #[test]
+    fn test_lookup_vendor_only() {
+        assert_eq!(lookup_vendor_name("2a70"), Some("OnePlus Technology (Shenzhen) Co., Ltd."));
+        assert_eq!(lookup_device_name("2a70", "ffff"), None);
+    }
+
+    #[test]
+    fn test_lookup_unknown() {
+        assert_eq!(lookup_device_name("0000", "0000"), None);
+        assert_eq!(lookup_vendor_name("0000"), None);
+    }
+}
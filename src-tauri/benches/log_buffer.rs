@@ -0,0 +1,50 @@
+//! Per-line cost of keeping the last 5000 flash log lines: `RingBuffer::push`
+//! against the old `Vec` push + drain-from-front truncation.
+//!
+//! Run with: cargo bench --bench log_buffer
+
+use bobbys_secret_workshop::ring_buffer::RingBuffer;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::hint::black_box;
+
+const CAPACITY: usize = 5000;
+
+fn full_vec() -> Vec<String> {
+    (0..CAPACITY).map(|i| format!("line {}", i)).collect()
+}
+
+fn full_ring() -> RingBuffer<String> {
+    let mut ring = RingBuffer::new(CAPACITY);
+    for i in 0..CAPACITY {
+        ring.push(format!("line {}", i));
+    }
+    ring
+}
+
+fn bench_push_when_full(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_5000_entry_log");
+    group.bench_function("vec_drain", |b| {
+        b.iter_batched_ref(
+            full_vec,
+            |logs| {
+                logs.push(black_box("new line".to_string()));
+                if logs.len() > CAPACITY {
+                    let drain = logs.len() - CAPACITY;
+                    logs.drain(0..drain);
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("ring_buffer", |b| {
+        b.iter_batched_ref(
+            full_ring,
+            |logs| logs.push(black_box("new line".to_string())),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_push_when_full);
+criterion_main!(benches);
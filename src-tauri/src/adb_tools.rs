@@ -0,0 +1,1756 @@
+// ADB Device Tools
+// One-shot ADB operations against devices confirmed by the most recent scan
+
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bootforgeusb::model::{ConfirmedDeviceRecord, DeviceMode};
+use libbootforge::device_state::{
+    BatteryHealth, BatteryState, CpuArchitecture, HardwareInfo, OperatingSystem, SoftwareInfo,
+    StoragePartition,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{get_log_directory, iso_now, now_ms, run_command_with_timeout, AppState, CommandRunError};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+const SHELL_TIMEOUT: Duration = Duration::from_secs(30);
+const SCREENSHOT_TIMEOUT: Duration = Duration::from_secs(30);
+const PROP_TIMEOUT: Duration = Duration::from_secs(10);
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const INSTALL_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const AAPT_TIMEOUT: Duration = Duration::from_secs(15);
+/// Includes the time the user takes to confirm the backup on the device
+const BACKUP_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+const BACKUP_PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Android `KeyEvent` codes sent by android_lock_screen / android_unlock_screen
+const KEYCODE_POWER: u32 = 26;
+const KEYCODE_ENTER: u32 = 66;
+const KEYCODE_WAKEUP: u32 = 224;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellResult {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i32,
+}
+
+/// Full device properties collected over ADB, shaped after `UnifiedDeviceState`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepDeviceInfo {
+    pub device_uid: String,
+    pub serial: String,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub build_fingerprint: Option<String>,
+    /// Maps to `DeviceIdentity::imei`; `None` on devices without a cellular modem
+    pub imei: Option<String>,
+    pub software: SoftwareInfo,
+    pub hardware: HardwareInfo,
+    pub battery: Option<BatteryState>,
+    pub storage: Vec<StoragePartition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdbFileEntry {
+    pub name: String,
+    pub size: u64,
+    pub permissions: String,
+    pub is_dir: bool,
+    /// As printed by the device's `ls`, e.g. `2024-01-31 09:15`
+    pub modified_at: String,
+}
+
+/// Flags for `adb install`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdbInstallOptions {
+    /// `-d`
+    pub allow_downgrade: bool,
+    /// `-t`
+    pub allow_test_packages: bool,
+    /// `-g`
+    pub grant_permissions: bool,
+    /// `-r`
+    pub replace_existing: bool,
+}
+
+/// Payload of the `apk-install-result` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApkInstallResultEvent {
+    device_uid: String,
+    apk_path: String,
+    /// From `aapt2 dump badging`; `None` when aapt2 is not installed
+    package_name: Option<String>,
+    success: bool,
+    /// e.g. `INSTALL_FAILED_VERSION_DOWNGRADE`
+    failure_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupResult {
+    pub output_path: String,
+    pub backup_size_bytes: u64,
+    pub duration_ms: u64,
+    /// SHA-256 of the `.ab` file, lowercase hex
+    pub checksum: String,
+}
+
+/// Payload of the `backup-progress` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupProgressEvent {
+    device_uid: String,
+    output_path: String,
+    bytes_written: u64,
+}
+
+/// Payload of the `device-input-event` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeviceInputEvent {
+    device_uid: String,
+    keycode: u32,
+}
+
+/// Which packages `android_list_packages` returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageFilter {
+    All,
+    System,
+    User,
+    Disabled,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageInfo {
+    pub package_name: String,
+    pub version_code: Option<String>,
+    pub apk_path: Option<String>,
+    pub is_system: bool,
+}
+
+/// A device adb lists as `unauthorized`, with what the user has to do about it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnauthorizedDevice {
+    pub serial: String,
+    pub product_hint: Option<String>,
+    pub resolution_steps: Vec<String>,
+}
+
+const UNAUTHORIZED_RESOLUTION_STEPS: &[&str] = &[
+    "Unlock the device and keep the screen on",
+    "Enable USB debugging in Settings > Developer options",
+    "Accept the RSA key fingerprint in the \"Allow USB debugging?\" dialog (tick \"Always allow from this computer\")",
+    "If no dialog appears, tap \"Revoke USB debugging authorizations\" in Developer options and reconnect the cable",
+    "Still unauthorized: run `adb kill-server` and scan again",
+];
+
+/// Build an `adb -s <serial>` command with the console window hidden on Windows.
+pub(crate) fn adb_command(serial: &str) -> Command {
+    let mut cmd = Command::new(crate::config::adb_program());
+    cmd.arg("-s").arg(serial);
+    #[cfg(target_os = "windows")]
+    {
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    cmd
+}
+
+/// Resolve the adb serial for a device_uid from the most recent scan.
+///
+/// The device must have been confirmed in ADB mode; the serial is taken from
+/// the matched tool ids, falling back to the device_uid itself.
+fn resolve_adb_serial(state: &AppState, device_uid: &str) -> Result<String, String> {
+    let last = state
+        .last_scan
+        .lock()
+        .map_err(|_| "last_scan mutex poisoned".to_string())?;
+    let record = last
+        .iter()
+        .find(|d| d.device_uid == device_uid)
+        .ok_or_else(|| format!("Device {} not found in the most recent scan", device_uid))?;
+
+    if record.mode != DeviceMode::AndroidAdbConfirmed.as_str() {
+        return Err(format!(
+            "Device {} is in mode '{}', expected {}",
+            device_uid,
+            record.mode,
+            DeviceMode::AndroidAdbConfirmed.as_str()
+        ));
+    }
+
+    Ok(record
+        .matched_tool_ids
+        .first()
+        .cloned()
+        .unwrap_or_else(|| record.device_uid.clone()))
+}
+
+/// Unauthorized serials from the adb evidence of a scan. The product name
+/// comes from the USB transport with the same serial, when there is one.
+fn unauthorized_devices(records: &[ConfirmedDeviceRecord]) -> Vec<UnauthorizedDevice> {
+    let mut serials: Vec<&String> = records
+        .iter()
+        .filter_map(|r| r.evidence.tools.get("adb"))
+        .flat_map(|adb| adb.unauthorized_ids.iter())
+        .collect();
+    serials.sort();
+    serials.dedup();
+    serials
+        .into_iter()
+        .map(|serial| UnauthorizedDevice {
+            serial: serial.clone(),
+            product_hint: records
+                .iter()
+                .find(|r| r.evidence.usb.serial.as_ref() == Some(serial))
+                .and_then(|r| r.marketing_name.clone().or_else(|| r.evidence.usb.product.clone())),
+            resolution_steps: UNAUTHORIZED_RESOLUTION_STEPS.iter().map(|s| s.to_string()).collect(),
+        })
+        .collect()
+}
+
+/// Devices from the most recent scan that adb lists as `unauthorized`.
+#[tauri::command]
+pub fn get_unauthorized_adb_devices(state: tauri::State<'_, AppState>) -> Result<Vec<UnauthorizedDevice>, String> {
+    let last = state
+        .last_scan
+        .lock()
+        .map_err(|_| "last_scan mutex poisoned".to_string())?;
+    Ok(unauthorized_devices(&last))
+}
+
+/// Reboot an ADB-confirmed device into recovery's `adb sideload` mode, ready
+/// for a `sideload` flash job.
+#[tauri::command(async)]
+pub fn device_enter_sideload(state: tauri::State<'_, AppState>, device_uid: String) -> Result<(), String> {
+    let serial = resolve_adb_serial(&state, &device_uid)?;
+    let mut cmd = adb_command(&serial);
+    cmd.args(["reboot", "sideload"]);
+    let output = run_command_with_timeout(cmd, SHELL_TIMEOUT).map_err(|e| match e {
+        CommandRunError::Spawn(_) => format!("adb not available: {e}"),
+        _ => format!("adb reboot sideload failed: {e}"),
+    })?;
+    if !output.status.success() {
+        return Err(format!(
+            "adb reboot sideload failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Create `path` for a command's output, refusing to replace an existing
+/// file. Callers may delete the file on failure because this call made it.
+pub(crate) fn create_new_output(path: &Path) -> Result<std::fs::File, String> {
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => format!("{} already exists; choose another file name", path.display()),
+            _ => format!("Failed to create {}: {e}", path.display()),
+        })
+}
+
+/// Save a PNG screenshot. With an empty `output_path` it goes to a
+/// timestamped file in the log directory; otherwise `output_path` is resolved
+/// inside the transfer directory and must not exist yet.
+#[tauri::command(async)]
+pub fn device_screenshot(
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+    output_path: String,
+) -> Result<String, String> {
+    let serial = resolve_adb_serial(&state, &device_uid)?;
+
+    let target = if output_path.trim().is_empty() {
+        let log_dir = get_log_directory();
+        std::fs::create_dir_all(&log_dir)
+            .map_err(|e| format!("Failed to create log directory: {e}"))?;
+        let safe_serial: String = serial
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        log_dir.join(format!("screenshot-{}-{}.png", safe_serial, now_ms()))
+    } else {
+        resolve_local_path(&transfer_base_dir(), &output_path)?
+    };
+
+    let mut file = create_new_output(&target)?;
+    let mut cmd = adb_command(&serial);
+    cmd.args(["exec-out", "screencap", "-p"]);
+    let written = run_command_with_timeout(cmd, SCREENSHOT_TIMEOUT)
+        .map_err(|e| match e {
+            CommandRunError::Spawn(_) => format!("adb not available: {e}"),
+            _ => format!("adb screencap failed: {e}"),
+        })
+        .and_then(|output| {
+            if !output.status.success() {
+                return Err(format!(
+                    "adb screencap failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            if output.stdout.is_empty() {
+                return Err("adb screencap produced no image data".to_string());
+            }
+            file.write_all(&output.stdout)
+                .map_err(|e| format!("Failed to write {}: {e}", target.display()))
+        });
+    drop(file);
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&target);
+        return Err(e);
+    }
+
+    let absolute = std::fs::canonicalize(&target).unwrap_or(target);
+    Ok(absolute.to_string_lossy().to_string())
+}
+
+/// Append a line to `<log_dir>/device-shell.log`. Logging failures are non-fatal.
+fn log_shell_invocation(serial: &str, command: &str, outcome: &str) {
+    let log_dir = get_log_directory();
+    if std::fs::create_dir_all(&log_dir).is_err() {
+        return;
+    }
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_dir.join("device-shell.log"))
+    {
+        let _ = writeln!(file, "[{}] {} $ {} -> {}", iso_now(), serial, command, outcome);
+    }
+}
+
+/// Characters the device shell would treat as command separators,
+/// substitutions or redirections
+const SHELL_METACHARACTERS: &[char] = &[';', '|', '&', '$', '`', '<', '>', '\n', '\r'];
+
+/// Reject commands that could chain a second command past the allowlist, then
+/// check the program against the allowlist (`None` or empty allows any).
+fn validate_shell_command(command: &str, allowlist: Option<&[String]>) -> Result<(), String> {
+    if let Some(c) = command.chars().find(|c| SHELL_METACHARACTERS.contains(c)) {
+        return Err(format!(
+            "Shell metacharacter '{}' is not allowed; run one command at a time",
+            c.escape_default()
+        ));
+    }
+    if let Some(allowed) = allowlist.filter(|a| !a.is_empty()) {
+        let program = command.split_whitespace().next().unwrap_or("");
+        if !allowed.iter().any(|a| a == program) {
+            return Err(format!("Command '{}' is not in the shell allowlist", program));
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command(async)]
+pub fn device_shell(
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+    command: String,
+) -> Result<ShellResult, String> {
+    let command = command.trim().to_string();
+    if command.is_empty() {
+        return Err("command is required".to_string());
+    }
+
+    {
+        let allowlist = state
+            .shell_cmd_allowlist
+            .lock()
+            .map_err(|_| "shell_cmd_allowlist mutex poisoned".to_string())?;
+        validate_shell_command(&command, allowlist.as_deref())?;
+    }
+
+    let serial = resolve_adb_serial(&state, &device_uid)?;
+
+    let mut cmd = adb_command(&serial);
+    cmd.arg("shell").arg(&command);
+    let output = match run_command_with_timeout(cmd, SHELL_TIMEOUT) {
+        Ok(out) => out,
+        Err(e) => {
+            log_shell_invocation(&serial, &command, &e.to_string());
+            return Err(match e {
+                CommandRunError::Spawn(_) => format!("adb not available: {e}"),
+                _ => format!("adb shell failed: {e}"),
+            });
+        }
+    };
+
+    let result = ShellResult {
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        exit_code: output.status.code().unwrap_or(-1),
+    };
+    log_shell_invocation(&serial, &command, &format!("exit {}", result.exit_code));
+    Ok(result)
+}
+
+/// Replace the device_shell allowlist. `None` or an empty list allows any command.
+#[tauri::command]
+pub fn device_shell_set_allowlist(
+    state: tauri::State<'_, AppState>,
+    allowlist: Option<Vec<String>>,
+) -> Result<(), String> {
+    let mut guard = state
+        .shell_cmd_allowlist
+        .lock()
+        .map_err(|_| "shell_cmd_allowlist mutex poisoned".to_string())?;
+    *guard = allowlist.map(|list| {
+        list.into_iter()
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .collect()
+    });
+    Ok(())
+}
+
+/// Read a single system property. Empty values are reported as `None`.
+fn adb_getprop(serial: &str, key: &str) -> Result<Option<String>, String> {
+    let mut cmd = adb_command(serial);
+    cmd.args(["shell", "getprop", key]);
+    let output = run_command_with_timeout(cmd, PROP_TIMEOUT).map_err(|e| match e {
+        CommandRunError::Spawn(_) => format!("adb not available: {e}"),
+        _ => format!("adb getprop {} failed: {e}", key),
+    })?;
+    if !output.status.success() {
+        return Err(format!(
+            "adb getprop {} failed: {}",
+            key,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(if value.is_empty() { None } else { Some(value) })
+}
+
+/// Build id from a fingerprint: `brand/product/device:release/ID/incremental:type/tags`
+fn build_id_from_fingerprint(fingerprint: &str) -> Option<String> {
+    let (_, rest) = fingerprint.split_once(':')?;
+    rest.split('/').nth(1).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+fn architecture_from_abi(abi: Option<&str>) -> CpuArchitecture {
+    match abi.unwrap_or_default() {
+        a if a.starts_with("arm64") => CpuArchitecture::Arm64,
+        a if a.starts_with("armeabi") => CpuArchitecture::Arm,
+        "x86_64" => CpuArchitecture::X86_64,
+        "x86" => CpuArchitecture::X86,
+        a if a.starts_with("riscv") => CpuArchitecture::Riscv,
+        a if a.starts_with("mips") => CpuArchitecture::Mips,
+        _ => CpuArchitecture::Unknown,
+    }
+}
+
+/// Parse `dumpsys battery` output. Returns `None` when no level is reported.
+///
+/// Status and health use the `BatteryManager` integer codes; temperature is in
+/// tenths of a degree Celsius.
+fn parse_dumpsys_battery(output: &str) -> Option<BatteryState> {
+    let fields: std::collections::HashMap<&str, &str> = output
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim(), v.trim()))
+        .collect();
+
+    let level = fields.get("level")?.parse::<u8>().ok()?.min(100);
+    let charging = matches!(fields.get("status").copied(), Some("2"));
+    let temperature = fields
+        .get("temperature")
+        .and_then(|t| t.parse::<f32>().ok())
+        .map(|t| t / 10.0);
+    let health = fields.get("health").map(|h| match *h {
+        "2" => BatteryHealth::Good,
+        "3" => BatteryHealth::Overheat,
+        "4" => BatteryHealth::Dead,
+        "5" => BatteryHealth::OverVoltage,
+        "6" => BatteryHealth::UnspecifiedFailure,
+        "7" => BatteryHealth::Cold,
+        _ => BatteryHealth::Unknown,
+    });
+    let voltage_mv = fields.get("voltage").and_then(|v| v.parse::<u32>().ok());
+
+    Some(BatteryState {
+        level,
+        charging,
+        temperature,
+        health,
+        voltage_mv,
+    })
+}
+
+/// Luhn checksum over a 15-digit IMEI.
+fn is_valid_imei(candidate: &str) -> bool {
+    if candidate.len() != 15 || !candidate.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    let sum: u32 = candidate
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, b)| {
+            let digit = u32::from(b - b'0');
+            if i % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+/// Decode the String16 reply of `service call iphonesubinfo 1`.
+///
+/// The parcel dump lists 32-bit little-endian words: an exception code, the
+/// UTF-16 length, then two UTF-16 code units per word (low half first).
+/// Returns `None` for a non-zero exception code or a null string.
+fn parse_iphonesubinfo_parcel(output: &str) -> Option<String> {
+    let body = output.trim().strip_prefix("Result: Parcel(")?;
+    let words: Vec<u32> = body
+        .lines()
+        .flat_map(|line| {
+            // Drop the `0x00000000:` offset and the quoted ASCII column.
+            let line = line.split_once(':').map_or(line, |(_, rest)| rest);
+            let line = line.split('\'').next().unwrap_or("");
+            line.split_whitespace()
+                .filter(|token| token.len() == 8)
+                .filter_map(|token| u32::from_str_radix(token, 16).ok())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    let (&exception, rest) = words.split_first()?;
+    let (&length, chars) = rest.split_first()?;
+    if exception != 0 || length == 0 || length == u32::MAX {
+        return None;
+    }
+    let units: Vec<u16> = chars
+        .iter()
+        .flat_map(|w| [(*w & 0xffff) as u16, (*w >> 16) as u16])
+        .take(length as usize)
+        .collect();
+    String::from_utf16(&units).ok()
+}
+
+/// Read the IMEI via the phone subinfo service, falling back to `gsm.imei`.
+/// Anything that does not pass the Luhn check is discarded.
+fn read_imei(serial: &str) -> Option<String> {
+    let mut cmd = adb_command(serial);
+    cmd.args(["shell", "service", "call", "iphonesubinfo", "1"]);
+    let from_service = run_command_with_timeout(cmd, PROP_TIMEOUT)
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| parse_iphonesubinfo_parcel(&String::from_utf8_lossy(&out.stdout)));
+    if let Some(imei) = from_service.filter(|v| is_valid_imei(v)) {
+        return Some(imei);
+    }
+
+    // Dual-SIM devices report a comma-separated list; the first slot is primary.
+    adb_getprop(serial, "gsm.imei")
+        .ok()
+        .flatten()
+        .and_then(|v| v.split(',').next().map(|s| s.trim().to_string()))
+        .filter(|v| is_valid_imei(v))
+}
+
+/// One row of `df` output.
+#[derive(Debug, Clone, PartialEq)]
+struct DfEntry {
+    /// Backing device; absent in the pre-toybox format, which starts with the mount point
+    source: Option<String>,
+    mount_point: String,
+    size_bytes: u64,
+    used_bytes: Option<u64>,
+}
+
+/// Parse `df -k` output (toybox, Android 6+) or the older toolbox `df`
+/// format (`Filesystem Size Used Free Blksize` with K/M/G suffixes).
+fn parse_df(output: &str) -> Vec<DfEntry> {
+    let mut lines = output.lines().filter(|l| !l.trim().is_empty());
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let toybox = header.contains("1K-blocks");
+    lines
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if toybox {
+                if cols.len() < 6 {
+                    return None;
+                }
+                Some(DfEntry {
+                    source: Some(cols[0].to_string()),
+                    mount_point: cols[5..].join(" "),
+                    size_bytes: cols[1].parse::<u64>().ok()? * 1024,
+                    used_bytes: cols[2].parse::<u64>().ok().map(|k| k * 1024),
+                })
+            } else {
+                if cols.len() < 3 {
+                    return None;
+                }
+                Some(DfEntry {
+                    source: None,
+                    mount_point: cols[0].to_string(),
+                    size_bytes: parse_suffixed_size(cols[1])?,
+                    used_bytes: parse_suffixed_size(cols[2]),
+                })
+            }
+        })
+        .collect()
+}
+
+/// "446.8M" -> bytes. A bare number is taken as bytes.
+fn parse_suffixed_size(value: &str) -> Option<u64> {
+    let (number, multiplier) = match value.chars().last()? {
+        'K' | 'k' => (&value[..value.len() - 1], 1u64 << 10),
+        'M' | 'm' => (&value[..value.len() - 1], 1u64 << 20),
+        'G' | 'g' => (&value[..value.len() - 1], 1u64 << 30),
+        'T' | 't' => (&value[..value.len() - 1], 1u64 << 40),
+        _ => (value, 1),
+    };
+    let number: f64 = number.parse().ok()?;
+    (number >= 0.0).then_some((number * multiplier as f64) as u64)
+}
+
+/// One row of `mount` output.
+#[derive(Debug, Clone, PartialEq)]
+struct MountEntry {
+    source: String,
+    mount_point: String,
+    filesystem: String,
+    writable: bool,
+}
+
+/// Parse `mount` output in either the `<dev> on <dir> type <fs> (<opts>)`
+/// form (toybox) or the `/proc/mounts` form `<dev> <dir> <fs> <opts> 0 0`.
+fn parse_mounts(output: &str) -> Vec<MountEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            let (source, mount_point, filesystem, options) =
+                if cols.len() >= 6 && cols[1] == "on" && cols[3] == "type" {
+                    (cols[0], cols[2], cols[4], cols[5].trim_start_matches('(').trim_end_matches(')'))
+                } else if cols.len() >= 4 {
+                    (cols[0], cols[1], cols[2], cols[3])
+                } else {
+                    return None;
+                };
+            Some(MountEntry {
+                source: source.to_string(),
+                mount_point: mount_point.to_string(),
+                filesystem: filesystem.to_string(),
+                writable: options.split(',').any(|o| o == "rw"),
+            })
+        })
+        .collect()
+}
+
+/// Parse `/proc/partitions` into kernel block name -> size in bytes.
+fn parse_proc_partitions(output: &str) -> std::collections::HashMap<String, u64> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if cols.len() != 4 {
+                return None;
+            }
+            let blocks: u64 = cols[2].parse().ok()?;
+            Some((cols[3].to_string(), blocks * 1024))
+        })
+        .collect()
+}
+
+/// Parse `ls -l /dev/block/by-name` into (partition name, kernel block name).
+fn parse_by_name_links(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (left, target) = line.split_once(" -> ")?;
+            let name = left.split_whitespace().last()?;
+            let kernel = target.trim().rsplit('/').next()?;
+            Some((name.to_string(), kernel.to_string()))
+        })
+        .collect()
+}
+
+/// Partition name for a mount point: its last path component, with `/`
+/// reported as `system` (system-as-root).
+fn partition_name_from_mount(mount_point: &str) -> String {
+    match mount_point.trim_end_matches('/').rsplit('/').next() {
+        Some("") | None => "system".to_string(),
+        Some(last) => last.to_string(),
+    }
+}
+
+/// Real block devices only; skips tmpfs, fuse views and APEX loop mounts.
+fn is_block_backed(source: &str) -> bool {
+    (source.starts_with("/dev/block/") && !source.starts_with("/dev/block/loop")) || source == "/dev/root"
+}
+
+/// Combine mounted filesystems from `df`/`mount` with raw partitions from
+/// `/proc/partitions` that have a by-name link but are not mounted.
+fn build_storage_partitions(
+    df: &[DfEntry],
+    mounts: &[MountEntry],
+    proc_partitions: &std::collections::HashMap<String, u64>,
+    by_name: &[(String, String)],
+) -> Vec<StoragePartition> {
+    let mut storage: Vec<StoragePartition> = df
+        .iter()
+        .filter_map(|entry| {
+            let mount = mounts.iter().find(|m| m.mount_point == entry.mount_point);
+            let source = entry.source.as_deref().or(mount.map(|m| m.source.as_str()));
+            if source.is_some_and(|s| !is_block_backed(s)) {
+                return None;
+            }
+            Some(StoragePartition {
+                name: partition_name_from_mount(&entry.mount_point),
+                label: None,
+                size_bytes: entry.size_bytes,
+                used_bytes: entry.used_bytes,
+                filesystem: mount.map(|m| m.filesystem.clone()).unwrap_or_else(|| "unknown".to_string()),
+                mount_point: Some(entry.mount_point.clone()),
+                writable: mount.is_some_and(|m| m.writable),
+            })
+        })
+        .collect();
+
+    let mut raw: Vec<StoragePartition> = by_name
+        .iter()
+        .filter(|(name, _)| !storage.iter().any(|p| &p.name == name))
+        .filter_map(|(name, kernel)| {
+            Some(StoragePartition {
+                name: name.clone(),
+                label: Some(kernel.clone()),
+                size_bytes: *proc_partitions.get(kernel)?,
+                used_bytes: None,
+                filesystem: "raw".to_string(),
+                mount_point: None,
+                writable: false,
+            })
+        })
+        .collect();
+    raw.sort_by(|a, b| a.name.cmp(&b.name));
+    storage.extend(raw);
+    storage
+}
+
+/// Run `adb shell <args>` and return stdout, or `None` on any failure.
+fn adb_shell_output(serial: &str, args: &[&str]) -> Option<String> {
+    let mut cmd = adb_command(serial);
+    cmd.arg("shell").args(args);
+    run_command_with_timeout(cmd, PROP_TIMEOUT)
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).to_string())
+}
+
+/// Best-effort storage layout. Raw partitions need a readable
+/// `/dev/block/by-name` to be named, which some builds restrict to root.
+fn read_storage(serial: &str) -> Vec<StoragePartition> {
+    let df = adb_shell_output(serial, &["df", "-k"])
+        .filter(|out| out.contains("1K-blocks"))
+        .or_else(|| adb_shell_output(serial, &["df"]))
+        .map(|out| parse_df(&out))
+        .unwrap_or_default();
+    let mounts = adb_shell_output(serial, &["mount"])
+        .map(|out| parse_mounts(&out))
+        .unwrap_or_default();
+    let proc_partitions = adb_shell_output(serial, &["cat", "/proc/partitions"])
+        .map(|out| parse_proc_partitions(&out))
+        .unwrap_or_default();
+    let by_name = adb_shell_output(serial, &["ls", "-l", "/dev/block/by-name/"])
+        .or_else(|| adb_shell_output(serial, &["ls", "-l", "/dev/block/bootdevice/by-name/"]))
+        .map(|out| parse_by_name_links(&out))
+        .unwrap_or_default();
+    build_storage_partitions(&df, &mounts, &proc_partitions, &by_name)
+}
+
+#[tauri::command(async)]
+pub fn device_info_deep(
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+) -> Result<DeepDeviceInfo, String> {
+    let serial = resolve_adb_serial(&state, &device_uid)?;
+
+    let model = adb_getprop(&serial, "ro.product.model")?;
+    let manufacturer = adb_getprop(&serial, "ro.product.manufacturer")?;
+    let release = adb_getprop(&serial, "ro.build.version.release")?;
+    let security_patch = adb_getprop(&serial, "ro.build.version.security_patch")?;
+    let fingerprint = adb_getprop(&serial, "ro.build.fingerprint")?;
+    let bootloader = adb_getprop(&serial, "ro.bootloader")?;
+    let abi = adb_getprop(&serial, "ro.product.cpu.abi")?;
+
+    // Battery is best-effort: some recoveries and locked-down builds refuse dumpsys.
+    let battery = {
+        let mut cmd = adb_command(&serial);
+        cmd.args(["shell", "dumpsys", "battery"]);
+        run_command_with_timeout(cmd, PROP_TIMEOUT)
+            .ok()
+            .filter(|out| out.status.success())
+            .and_then(|out| parse_dumpsys_battery(&String::from_utf8_lossy(&out.stdout)))
+    };
+
+    let imei = read_imei(&serial);
+    let info_imei = imei.clone();
+    let storage = read_storage(&serial);
+
+    let software = SoftwareInfo {
+        os: OperatingSystem::Android,
+        os_version: release.unwrap_or_default(),
+        build_number: fingerprint.as_deref().and_then(build_id_from_fingerprint),
+        security_patch,
+        bootloader_version: bootloader,
+        kernel_version: None,
+        firmware_version: None,
+    };
+    let hardware = HardwareInfo {
+        architecture: architecture_from_abi(abi.as_deref()),
+        soc: None,
+        ram_bytes: None,
+        screen: None,
+        hardware_rev: None,
+        baseband: None,
+    };
+
+    state.device_states.update(&device_uid, |s| {
+        if let Some(m) = &manufacturer {
+            s.identity.manufacturer = m.clone();
+        }
+        if let Some(m) = &model {
+            s.identity.model = m.clone();
+        }
+        s.identity.imei = info_imei;
+        s.software = software.clone();
+        s.hardware = hardware.clone();
+        s.battery = battery.clone();
+        s.storage = storage.clone();
+        s.touch();
+    });
+
+    Ok(DeepDeviceInfo {
+        device_uid,
+        serial,
+        manufacturer,
+        model,
+        build_fingerprint: fingerprint,
+        imei,
+        software,
+        hardware,
+        battery,
+        storage,
+    })
+}
+
+/// Local directory that adb_pull/adb_push may read from or write into.
+pub(crate) fn transfer_base_dir() -> PathBuf {
+    dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .map(|d| d.join("BobbysWorkshop"))
+        .unwrap_or_else(|| get_log_directory().join("transfers"))
+}
+
+/// Resolve `path` against `base` and ensure it cannot escape it.
+///
+/// Relative paths are joined onto `base`; `..` components are rejected
+/// outright, and the nearest existing ancestor is canonicalized so symlinks
+/// cannot point outside the base either.
+pub(crate) fn resolve_local_path(base: &Path, path: &str) -> Result<PathBuf, String> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("path is required".to_string());
+    }
+    let requested = Path::new(path);
+    if requested.components().any(|c| matches!(c, Component::ParentDir)) {
+        return Err(format!("path '{}' must not contain '..'", path));
+    }
+    let candidate = if requested.is_absolute() { requested.to_path_buf() } else { base.join(requested) };
+
+    std::fs::create_dir_all(base)
+        .map_err(|e| format!("Failed to create directory {}: {e}", base.display()))?;
+    let canonical_base = std::fs::canonicalize(base)
+        .map_err(|e| format!("Failed to resolve {}: {e}", base.display()))?;
+
+    let mut existing = candidate.as_path();
+    let mut suffix = Vec::new();
+    while !existing.exists() {
+        suffix.push(existing.file_name().ok_or_else(|| format!("Invalid path '{}'", path))?);
+        existing = existing.parent().ok_or_else(|| format!("Invalid path '{}'", path))?;
+    }
+    let mut resolved = std::fs::canonicalize(existing)
+        .map_err(|e| format!("Failed to resolve {}: {e}", existing.display()))?;
+    resolved.extend(suffix.into_iter().rev());
+
+    if !resolved.starts_with(&canonical_base) {
+        return Err(format!(
+            "path must be inside {} (got {})",
+            canonical_base.display(),
+            resolved.display()
+        ));
+    }
+    Ok(resolved)
+}
+
+/// Single-quote a path for the device shell.
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+/// Parse toybox `ls -la` output. `total`, `.` and `..` lines are skipped; symlink
+/// targets are stripped from the name.
+fn parse_ls_la(output: &str) -> Vec<AdbFileEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 8 || fields[0].len() != 10 {
+                return None;
+            }
+            let permissions = fields[0];
+            let size = fields[4].parse::<u64>().ok()?;
+            let modified_at = format!("{} {}", fields[5], fields[6]);
+            // Names may contain spaces: take everything after the time column.
+            let name_start = line.find(fields[6])? + fields[6].len();
+            let name = line[name_start..].trim();
+            let name = name.split(" -> ").next().unwrap_or(name).to_string();
+            if name.is_empty() || name == "." || name == ".." {
+                return None;
+            }
+            Some(AdbFileEntry {
+                name,
+                size,
+                permissions: permissions.to_string(),
+                is_dir: permissions.starts_with('d'),
+                modified_at,
+            })
+        })
+        .collect()
+}
+
+/// Byte count from adb's transfer summary: `... (12345 bytes in 0.003s)`.
+fn parse_transferred_bytes(output: &str) -> Option<u64> {
+    let (before, _) = output.rsplit_once(" bytes in ")?;
+    before.rsplit('(').next()?.trim().parse().ok()
+}
+
+fn run_adb_transfer(serial: &str, args: &[&str]) -> Result<String, String> {
+    let mut cmd = adb_command(serial);
+    cmd.args(args);
+    let output = run_command_with_timeout(cmd, TRANSFER_TIMEOUT).map_err(|e| match e {
+        CommandRunError::Spawn(_) => format!("adb not available: {e}"),
+        _ => format!("adb {} failed: {e}", args[0]),
+    })?;
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    if !output.status.success() {
+        return Err(format!("adb {} failed: {}", args[0], combined.trim()));
+    }
+    Ok(combined)
+}
+
+#[tauri::command(async)]
+pub fn adb_list_files(
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+    remote_path: String,
+) -> Result<Vec<AdbFileEntry>, String> {
+    let remote_path = remote_path.trim();
+    if remote_path.is_empty() {
+        return Err("remote_path is required".to_string());
+    }
+    let serial = resolve_adb_serial(&state, &device_uid)?;
+
+    let mut cmd = adb_command(&serial);
+    cmd.args(["shell", &format!("ls -la {}", shell_quote(remote_path))]);
+    let output = run_command_with_timeout(cmd, SHELL_TIMEOUT).map_err(|e| match e {
+        CommandRunError::Spawn(_) => format!("adb not available: {e}"),
+        _ => format!("adb ls failed: {e}"),
+    })?;
+    if !output.status.success() {
+        return Err(format!(
+            "ls {} failed: {}",
+            remote_path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(parse_ls_la(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[tauri::command(async)]
+pub fn adb_pull(
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+    remote_path: String,
+    local_path: String,
+) -> Result<u64, String> {
+    if remote_path.trim().is_empty() {
+        return Err("remote_path is required".to_string());
+    }
+    let target = resolve_local_path(&transfer_base_dir(), &local_path)?;
+    let serial = resolve_adb_serial(&state, &device_uid)?;
+
+    let target_str = target.to_string_lossy().to_string();
+    let output = run_adb_transfer(&serial, &["pull", remote_path.trim(), &target_str])?;
+    Ok(parse_transferred_bytes(&output)
+        .or_else(|| std::fs::metadata(&target).ok().map(|m| m.len()))
+        .unwrap_or(0))
+}
+
+#[tauri::command(async)]
+pub fn adb_push(
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+    local_path: String,
+    remote_path: String,
+) -> Result<u64, String> {
+    if remote_path.trim().is_empty() {
+        return Err("remote_path is required".to_string());
+    }
+    let source = resolve_local_path(&transfer_base_dir(), &local_path)?;
+    if !source.exists() {
+        return Err(format!("Local file not found: {}", source.display()));
+    }
+    let serial = resolve_adb_serial(&state, &device_uid)?;
+
+    let source_str = source.to_string_lossy().to_string();
+    let output = run_adb_transfer(&serial, &["push", &source_str, remote_path.trim()])?;
+    Ok(parse_transferred_bytes(&output)
+        .or_else(|| std::fs::metadata(&source).ok().filter(|m| m.is_file()).map(|m| m.len()))
+        .unwrap_or(0))
+}
+
+fn install_args(options: &AdbInstallOptions) -> Vec<&'static str> {
+    let mut args = vec!["install"];
+    if options.allow_downgrade {
+        args.push("-d");
+    }
+    if options.allow_test_packages {
+        args.push("-t");
+    }
+    if options.grant_permissions {
+        args.push("-g");
+    }
+    if options.replace_existing {
+        args.push("-r");
+    }
+    args
+}
+
+/// `Ok(())` for `Success`, otherwise the reason from `Failure [<reason>]`.
+fn parse_install_output(output: &str) -> Result<(), String> {
+    if let Some((_, rest)) = output.split_once("Failure [") {
+        let reason = rest.split(']').next().unwrap_or(rest).trim();
+        return Err(reason.to_string());
+    }
+    if output.lines().any(|l| l.trim() == "Success") {
+        return Ok(());
+    }
+    Err(output.trim().lines().last().unwrap_or("unknown error").to_string())
+}
+
+/// Package name from the `package: name='...'` line of `aapt2 dump badging`.
+fn parse_badging_package_name(output: &str) -> Option<String> {
+    let line = output.lines().find(|l| l.starts_with("package:"))?;
+    let (_, rest) = line.split_once("name='")?;
+    let name = rest.split('\'').next()?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Best effort: `None` if aapt2 is missing or cannot read the APK.
+fn apk_package_name(apk: &Path) -> Option<String> {
+    let mut cmd = Command::new("aapt2");
+    cmd.args(["dump", "badging"]).arg(apk);
+    #[cfg(target_os = "windows")]
+    {
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    let output = run_command_with_timeout(cmd, AAPT_TIMEOUT).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_badging_package_name(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Android package names: dot-separated segments of letters, digits and `_`,
+/// starting with a letter.
+fn is_valid_package_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.contains('.')
+        && name.split('.').all(|segment| {
+            !segment.is_empty() && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+fn backup_args(output: &Path, packages: &[String], include_apks: bool, include_shared: bool) -> Vec<String> {
+    let mut args = vec![
+        "backup".to_string(),
+        "-f".to_string(),
+        output.to_string_lossy().to_string(),
+        if include_apks { "-apk" } else { "-noapk" }.to_string(),
+        if include_shared { "-shared" } else { "-noshared" }.to_string(),
+    ];
+    args.extend(packages.iter().map(|p| p.trim().to_string()));
+    args
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Back up app data with `adb backup` into an `.ab` file.
+///
+/// The device shows a confirmation screen; until the user accepts it nothing
+/// is written. While adb runs, the file size is polled and emitted as
+/// `backup-progress` events.
+#[tauri::command(async)]
+pub fn android_backup(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+    packages: Vec<String>,
+    output_path: String,
+    include_apks: bool,
+    include_shared: bool,
+) -> Result<BackupResult, String> {
+    if packages.is_empty() {
+        return Err("At least one package is required".to_string());
+    }
+    if let Some(bad) = packages.iter().find(|p| !is_valid_package_name(p.trim())) {
+        return Err(format!("Invalid package name: '{}'", bad));
+    }
+    let output = resolve_local_path(&transfer_base_dir(), &output_path)?;
+    let is_ab = output
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("ab"))
+        .unwrap_or(false);
+    if !is_ab {
+        return Err(format!("Backup file must have a .ab extension: {}", output.display()));
+    }
+    let serial = resolve_adb_serial(&state, &device_uid)?;
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {}: {e}", parent.display()))?;
+    }
+
+    let done = Arc::new(AtomicBool::new(false));
+    let progress = {
+        let done = done.clone();
+        let output = output.clone();
+        let device_uid = device_uid.clone();
+        std::thread::spawn(move || {
+            let mut last_size = 0;
+            while !done.load(Ordering::Acquire) {
+                std::thread::sleep(BACKUP_PROGRESS_INTERVAL);
+                let size = std::fs::metadata(&output).map(|m| m.len()).unwrap_or(0);
+                if size == last_size {
+                    continue;
+                }
+                last_size = size;
+                if let Some(window) = app_handle.get_webview_window("main") {
+                    let _ = window.emit(
+                        "backup-progress",
+                        BackupProgressEvent {
+                            device_uid: device_uid.clone(),
+                            output_path: output.to_string_lossy().to_string(),
+                            bytes_written: size,
+                        },
+                    );
+                }
+            }
+        })
+    };
+
+    let started = Instant::now();
+    let mut cmd = adb_command(&serial);
+    cmd.args(backup_args(&output, &packages, include_apks, include_shared));
+    let result = run_command_with_timeout(cmd, BACKUP_TIMEOUT);
+    done.store(true, Ordering::Release);
+    let _ = progress.join();
+
+    let result = result.map_err(|e| match e {
+        CommandRunError::Spawn(_) => format!("adb not available: {e}"),
+        _ => format!("adb backup failed: {e}"),
+    })?;
+    if !result.status.success() {
+        return Err(format!(
+            "adb backup failed: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        ));
+    }
+
+    let backup_size_bytes = std::fs::metadata(&output).map(|m| m.len()).unwrap_or(0);
+    if backup_size_bytes == 0 {
+        return Err("Backup is empty: confirm the backup on the device screen and try again".to_string());
+    }
+    Ok(BackupResult {
+        output_path: output.to_string_lossy().to_string(),
+        backup_size_bytes,
+        duration_ms: started.elapsed().as_millis() as u64,
+        checksum: sha256_file(&output)?,
+    })
+}
+
+/// Install an APK with `adb install`, returning adb's output on success.
+///
+/// The outcome is also emitted as an `apk-install-result` event.
+#[tauri::command(async)]
+pub fn adb_install_apk(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+    apk_path: String,
+    options: AdbInstallOptions,
+) -> Result<String, String> {
+    let apk = PathBuf::from(apk_path.trim());
+    let is_apk = apk
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("apk"))
+        .unwrap_or(false);
+    if !is_apk {
+        return Err(format!("Not an .apk file: {}", apk.display()));
+    }
+    if !apk.is_file() {
+        return Err(format!("APK not found: {}", apk.display()));
+    }
+    let serial = resolve_adb_serial(&state, &device_uid)?;
+
+    let mut cmd = adb_command(&serial);
+    cmd.args(install_args(&options)).arg(&apk);
+    let output = run_command_with_timeout(cmd, INSTALL_TIMEOUT).map_err(|e| match e {
+        CommandRunError::Spawn(_) => format!("adb not available: {e}"),
+        _ => format!("adb install failed: {e}"),
+    })?;
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result = parse_install_output(&combined);
+
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.emit(
+            "apk-install-result",
+            ApkInstallResultEvent {
+                device_uid: device_uid.clone(),
+                apk_path: apk.to_string_lossy().to_string(),
+                package_name: apk_package_name(&apk),
+                success: result.is_ok(),
+                failure_reason: result.as_ref().err().cloned(),
+            },
+        );
+    }
+
+    match result {
+        Ok(()) => Ok(combined.trim().to_string()),
+        Err(reason) => Err(format!("Install failed: {}", reason)),
+    }
+}
+
+fn list_packages_args(filter: PackageFilter) -> Vec<&'static str> {
+    let mut args = vec!["shell", "pm", "list", "packages", "-f"];
+    match filter {
+        PackageFilter::All => {}
+        PackageFilter::System => args.push("-s"),
+        PackageFilter::User => args.push("-3"),
+        PackageFilter::Disabled => args.push("-d"),
+    }
+    args.push("--show-versioncode");
+    args
+}
+
+/// APKs under these roots ship with the image and cannot be removed for all users.
+fn is_system_apk_path(path: &str) -> bool {
+    ["/system/", "/system_ext/", "/product/", "/vendor/", "/odm/", "/apex/"]
+        .iter()
+        .any(|root| path.starts_with(root))
+}
+
+/// Parse `package:<apk path>=<name> versionCode:<code>` lines, sorted by name.
+/// The apk path can itself contain `=`, so the name follows the last one.
+fn parse_pm_list_packages(output: &str) -> Vec<PackageInfo> {
+    let mut packages: Vec<PackageInfo> = output
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix("package:")?;
+            let (entry, version_code) = match rest.rsplit_once(" versionCode:") {
+                Some((entry, code)) => (entry, Some(code.trim().to_string()).filter(|c| !c.is_empty())),
+                None => (rest, None),
+            };
+            let (apk_path, package_name) = match entry.rsplit_once('=') {
+                Some((path, name)) => (Some(path.to_string()), name),
+                None => (None, entry),
+            };
+            let package_name = package_name.trim();
+            if package_name.is_empty() {
+                return None;
+            }
+            Some(PackageInfo {
+                package_name: package_name.to_string(),
+                version_code,
+                is_system: apk_path.as_deref().map(is_system_apk_path).unwrap_or(false),
+                apk_path,
+            })
+        })
+        .collect();
+    packages.sort_by(|a, b| a.package_name.cmp(&b.package_name));
+    packages
+}
+
+/// List installed packages with `pm list packages`, sorted by package name.
+#[tauri::command(async)]
+pub fn android_list_packages(
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+    filter: PackageFilter,
+) -> Result<Vec<PackageInfo>, String> {
+    let serial = resolve_adb_serial(&state, &device_uid)?;
+    let mut cmd = adb_command(&serial);
+    cmd.args(list_packages_args(filter));
+    let output = run_command_with_timeout(cmd, SHELL_TIMEOUT).map_err(|e| match e {
+        CommandRunError::Spawn(_) => format!("adb not available: {e}"),
+        _ => format!("pm list packages failed: {e}"),
+    })?;
+    if !output.status.success() {
+        return Err(format!(
+            "pm list packages failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let mut packages = parse_pm_list_packages(&String::from_utf8_lossy(&output.stdout));
+    if filter == PackageFilter::System {
+        for package in &mut packages {
+            package.is_system = true;
+        }
+    }
+    Ok(packages)
+}
+
+/// Uninstall a package with `pm uninstall`, optionally keeping its data and
+/// cache (`-k`). Returns pm's output on success.
+#[tauri::command(async)]
+pub fn android_uninstall_package(
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+    package_name: String,
+    keep_data: bool,
+) -> Result<String, String> {
+    let package_name = package_name.trim();
+    if !is_valid_package_name(package_name) {
+        return Err(format!("Invalid package name: {}", package_name));
+    }
+    let serial = resolve_adb_serial(&state, &device_uid)?;
+
+    let mut cmd = adb_command(&serial);
+    cmd.args(["shell", "pm", "uninstall"]);
+    if keep_data {
+        cmd.arg("-k");
+    }
+    cmd.arg(package_name);
+    let output = run_command_with_timeout(cmd, SHELL_TIMEOUT).map_err(|e| match e {
+        CommandRunError::Spawn(_) => format!("adb not available: {e}"),
+        _ => format!("pm uninstall failed: {e}"),
+    })?;
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    match parse_install_output(&combined) {
+        Ok(()) => Ok(combined.trim().to_string()),
+        Err(reason) => Err(format!("Uninstall of {} failed: {}", package_name, reason)),
+    }
+}
+
+/// PINs and passwords typed by android_unlock_screen. Limited to characters
+/// `input text` passes through the device shell unchanged.
+fn is_valid_unlock_pin(pin: &str) -> bool {
+    (4..=16).contains(&pin.len()) && pin.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn run_input(serial: &str, args: &[&str]) -> Result<(), String> {
+    let mut cmd = adb_command(serial);
+    cmd.args(["shell", "input"]).args(args);
+    let output = run_command_with_timeout(cmd, SHELL_TIMEOUT).map_err(|e| match e {
+        CommandRunError::Spawn(_) => format!("adb not available: {e}"),
+        _ => format!("adb shell input failed: {e}"),
+    })?;
+    if !output.status.success() {
+        return Err(format!(
+            "adb shell input failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Send one key event and report it as a `device-input-event`.
+fn send_keyevent(app_handle: &AppHandle, serial: &str, device_uid: &str, keycode: u32) -> Result<(), String> {
+    run_input(serial, &["keyevent", &keycode.to_string()])?;
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.emit(
+            "device-input-event",
+            DeviceInputEvent {
+                device_uid: device_uid.to_string(),
+                keycode,
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Press the power key. Turns the screen off (locking the device) when it
+/// is on, and on when it is off.
+#[tauri::command(async)]
+pub fn android_lock_screen(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+) -> Result<(), String> {
+    let serial = resolve_adb_serial(&state, &device_uid)?;
+    send_keyevent(&app_handle, &serial, &device_uid, KEYCODE_POWER)
+}
+
+/// Wake the screen and, with a `pin`, type it and press enter.
+///
+/// The PIN itself is never included in `device-input-event` payloads.
+#[tauri::command(async)]
+pub fn android_unlock_screen(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+    pin: Option<String>,
+) -> Result<(), String> {
+    let pin = pin.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if pin.as_deref().is_some_and(|p| !is_valid_unlock_pin(p)) {
+        return Err("PIN must be 4-16 letters or digits".to_string());
+    }
+    let serial = resolve_adb_serial(&state, &device_uid)?;
+
+    send_keyevent(&app_handle, &serial, &device_uid, KEYCODE_WAKEUP)?;
+    if let Some(pin) = pin {
+        run_input(&serial, &["text", &pin])?;
+        send_keyevent(&app_handle, &serial, &device_uid, KEYCODE_ENTER)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_adb_install_output() {
+        assert!(parse_install_output("Performing Streamed Install\nSuccess\n").is_ok());
+        assert_eq!(
+            parse_install_output(
+                "Performing Streamed Install\nadb: failed to install app.apk: Failure [INSTALL_FAILED_VERSION_DOWNGRADE: Downgrade detected]\n"
+            ),
+            Err("INSTALL_FAILED_VERSION_DOWNGRADE: Downgrade detected".to_string())
+        );
+        let args = install_args(&AdbInstallOptions {
+            allow_downgrade: true,
+            replace_existing: true,
+            ..AdbInstallOptions::default()
+        });
+        assert_eq!(args, vec!["install", "-d", "-r"]);
+    }
+
+    #[test]
+    fn backup_args_follow_flags_and_packages() {
+        let packages = vec!["com.example.notes".to_string(), " org.example.app ".to_string()];
+        let args = backup_args(Path::new("/tmp/notes.ab"), &packages, true, false);
+        assert_eq!(
+            args,
+            vec!["backup", "-f", "/tmp/notes.ab", "-apk", "-noshared", "com.example.notes", "org.example.app"]
+        );
+        assert!(is_valid_package_name("com.example.notes_2"));
+        assert!(!is_valid_package_name("notes"));
+        assert!(!is_valid_package_name("com.example;rm"));
+        assert!(!is_valid_package_name("com..example"));
+    }
+
+    #[test]
+    fn parses_pm_list_packages_sorted() {
+        let output = "package:/data/app/~~Qx3b==/com.whatsapp-Zm9v==/base.apk=com.whatsapp versionCode:241012\n\
+                      package:/system/priv-app/Settings/Settings.apk=com.android.settings versionCode:34\n\
+                      package:/product/app/Chrome/Chrome.apk=com.android.chrome\n";
+        let packages = parse_pm_list_packages(output);
+        let names: Vec<&str> = packages.iter().map(|p| p.package_name.as_str()).collect();
+        assert_eq!(names, vec!["com.android.chrome", "com.android.settings", "com.whatsapp"]);
+
+        let whatsapp = &packages[2];
+        assert_eq!(whatsapp.apk_path.as_deref(), Some("/data/app/~~Qx3b==/com.whatsapp-Zm9v==/base.apk"));
+        assert_eq!(whatsapp.version_code.as_deref(), Some("241012"));
+        assert!(!whatsapp.is_system);
+        assert!(packages[0].is_system && packages[0].version_code.is_none());
+
+        assert_eq!(list_packages_args(PackageFilter::User)[5], "-3");
+        assert!(!is_valid_package_name("1com.example"));
+    }
+
+    #[test]
+    fn unauthorized_serials_get_product_and_steps() {
+        use bootforgeusb::model::{Evidence, Platform, ToolEvidence, UsbTransportEvidence};
+
+        let adb = ToolEvidence {
+            unauthorized_ids: vec!["R58M12ABCDE".to_string()],
+            ..ToolEvidence::confirmed(String::new(), vec![])
+        };
+        let record = ConfirmedDeviceRecord {
+            device_uid: "usb:1:7".to_string(),
+            platform: Platform::Android,
+            mode: DeviceMode::UnknownUsb.as_str().to_string(),
+            confidence: 0.5,
+            evidence: Evidence {
+                usb: UsbTransportEvidence {
+                    vid: "04e8".to_string(),
+                    pid: "6860".to_string(),
+                    manufacturer: Some("SAMSUNG".to_string()),
+                    product: Some("SAMSUNG_Android".to_string()),
+                    serial: Some("R58M12ABCDE".to_string()),
+                    bus: 1,
+                    address: 7,
+                    ..Default::default()
+                },
+                tools: std::collections::HashMap::from([("adb".to_string(), adb)]),
+            },
+            notes: vec![],
+            matched_tool_ids: vec![],
+            marketing_name: None,
+            driver_status: None,
+        };
+
+        let devices = unauthorized_devices(&[record.clone(), record]);
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].serial, "R58M12ABCDE");
+        assert_eq!(devices[0].product_hint.as_deref(), Some("SAMSUNG_Android"));
+        assert!(devices[0].resolution_steps.iter().any(|s| s.contains("RSA key")));
+        assert!(unauthorized_devices(&[]).is_empty());
+    }
+
+    #[test]
+    fn sha256_file_matches_known_digest() {
+        let path = std::env::temp_dir().join(format!("bw-sha256-{}.ab", std::process::id()));
+        std::fs::write(&path, b"abc").unwrap();
+        let digest = sha256_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(digest, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+
+    #[test]
+    fn parses_package_name_from_badging() {
+        let badging = "package: name='com.example.app' versionCode='42' versionName='1.2'\nsdkVersion:'24'\n";
+        assert_eq!(parse_badging_package_name(badging).as_deref(), Some("com.example.app"));
+        assert_eq!(parse_badging_package_name("sdkVersion:'24'\n"), None);
+    }
+
+    #[test]
+    fn parses_dumpsys_battery() {
+        let out = "Current Battery Service state:\n  AC powered: false\n  USB powered: true\n  status: 2\n  health: 2\n  level: 85\n  voltage: 4123\n  temperature: 285\n";
+        let battery = parse_dumpsys_battery(out).unwrap();
+        assert_eq!(battery.level, 85);
+        assert!(battery.charging);
+        assert_eq!(battery.health, Some(BatteryHealth::Good));
+        assert_eq!(battery.voltage_mv, Some(4123));
+        assert_eq!(battery.temperature, Some(28.5));
+        assert!(parse_dumpsys_battery("Current Battery Service state:\n").is_none());
+    }
+
+    #[test]
+    fn extracts_build_id_from_fingerprint() {
+        let fp = "google/sargo/sargo:12/SP2A.220505.008/8782922:user/release-keys";
+        assert_eq!(build_id_from_fingerprint(fp).as_deref(), Some("SP2A.220505.008"));
+        assert_eq!(build_id_from_fingerprint("garbage"), None);
+    }
+
+    #[test]
+    fn parses_imei_from_service_call_fixture() {
+        let fixture = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/adb_service_call_iphonesubinfo.txt"
+        ));
+        let imei = parse_iphonesubinfo_parcel(fixture).unwrap();
+        assert_eq!(imei, "490154203237518");
+        assert!(is_valid_imei(&imei));
+    }
+
+    fn fixture(name: &str) -> String {
+        std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name))
+            .unwrap()
+    }
+
+    #[test]
+    fn storage_from_toybox_df_and_mount() {
+        let df = parse_df(&fixture("adb_df_k_android13.txt"));
+        assert_eq!(df.len(), 10);
+        let mounts = parse_mounts(&fixture("adb_mount_android13.txt"));
+        assert_eq!(mounts[0].filesystem, "ext4");
+        assert!(!mounts[0].writable);
+
+        let proc_partitions = parse_proc_partitions(&fixture("adb_proc_partitions.txt"));
+        assert_eq!(proc_partitions["sda11"], 65536 * 1024);
+        let by_name = parse_by_name_links(&fixture("adb_ls_by_name.txt"));
+        assert_eq!(by_name[0], ("boot_a".to_string(), "sda11".to_string()));
+
+        let storage = build_storage_partitions(&df, &mounts, &proc_partitions, &by_name);
+        let names: Vec<&str> = storage.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["system", "system_ext", "product", "vendor", "metadata", "data", "boot_a", "boot_b", "misc", "vbmeta_a"]
+        );
+        let data = &storage[5];
+        assert_eq!(data.filesystem, "f2fs");
+        assert_eq!(data.size_bytes, 112442752 * 1024);
+        assert_eq!(data.used_bytes, Some(8915684 * 1024));
+        assert!(data.writable);
+        assert_eq!(storage[2].filesystem, "erofs");
+        let boot = &storage[6];
+        assert_eq!((boot.filesystem.as_str(), boot.size_bytes), ("raw", 65536 * 1024));
+        assert_eq!(boot.mount_point, None);
+    }
+
+    #[test]
+    fn storage_from_legacy_df_and_proc_mounts() {
+        let df = parse_df(&fixture("adb_df_android4.txt"));
+        assert_eq!(df[2].mount_point, "/system");
+        assert_eq!(df[2].size_bytes, (1.5 * (1u64 << 30) as f64) as u64);
+        assert_eq!(df[2].used_bytes, Some((1022.5 * (1u64 << 20) as f64) as u64));
+
+        let mounts = parse_mounts(&fixture("adb_mount_android4.txt"));
+        let storage = build_storage_partitions(&df, &mounts, &Default::default(), &[]);
+        let summary: Vec<(&str, &str, bool)> = storage
+            .iter()
+            .map(|p| (p.name.as_str(), p.filesystem.as_str(), p.writable))
+            .collect();
+        assert_eq!(
+            summary,
+            [("system", "ext4", false), ("cache", "ext4", true), ("data", "ext4", true)]
+        );
+    }
+
+    #[test]
+    fn rejects_missing_or_invalid_imei() {
+        // Permission denied / no modem: non-zero exception code
+        let denied = "Result: Parcel(ffffffff 0000004a 00650052 ... 'R.e.q.u.i.r.e.s.')";
+        assert_eq!(parse_iphonesubinfo_parcel(denied), None);
+        assert_eq!(parse_iphonesubinfo_parcel("Result: Parcel(00000000 ffffffff   '........')"), None);
+        assert!(!is_valid_imei("490154203237519"));
+        assert!(!is_valid_imei("49015420323751"));
+    }
+
+    #[test]
+    fn parses_toybox_ls_output() {
+        let out = "total 24\n\
+drwxrwx--x  4 root sdcard_rw 3488 2024-01-31 09:15 .\n\
+drwx--x--x  4 root sdcard_rw 3488 2024-01-31 09:15 ..\n\
+drwxrwx--x  2 root sdcard_rw 3488 2024-02-01 10:00 DCIM\n\
+-rw-rw----  1 root sdcard_rw 1234 2024-02-02 11:30 my notes.txt\n\
+lrwxrwxrwx  1 root root        21 2024-02-03 12:00 sdcard -> /storage/self/primary\n";
+        let entries = parse_ls_la(out);
+        assert_eq!(entries.len(), 3);
+        assert!(entries[0].is_dir);
+        assert_eq!(entries[0].name, "DCIM");
+        assert_eq!(entries[1].name, "my notes.txt");
+        assert_eq!(entries[1].size, 1234);
+        assert_eq!(entries[1].modified_at, "2024-02-02 11:30");
+        assert_eq!(entries[2].name, "sdcard");
+    }
+
+    #[test]
+    fn parses_transfer_summary() {
+        let out = "/sdcard/a.bin: 1 file pulled, 0 skipped. 35.2 MB/s (12345 bytes in 0.003s)\n";
+        assert_eq!(parse_transferred_bytes(out), Some(12345));
+        assert_eq!(parse_transferred_bytes("error: no devices"), None);
+    }
+
+    #[test]
+    fn local_paths_are_confined_to_base() {
+        let base = std::env::temp_dir().join(format!("adb-transfer-test-{}", std::process::id()));
+        let resolved = resolve_local_path(&base, "pulled/photo.jpg").unwrap();
+        assert!(resolved.starts_with(std::fs::canonicalize(&base).unwrap()));
+        assert!(resolve_local_path(&base, "../escape.txt").is_err());
+        assert!(resolve_local_path(&base, "/etc/passwd").is_err());
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn unlock_pin_must_be_shell_safe() {
+        assert!(is_valid_unlock_pin("1234"));
+        assert!(is_valid_unlock_pin("hunter2abc"));
+        assert!(!is_valid_unlock_pin("123"));
+        assert!(!is_valid_unlock_pin("12 34"));
+        assert!(!is_valid_unlock_pin("1234;reboot"));
+        assert!(!is_valid_unlock_pin(&"1".repeat(17)));
+    }
+
+    #[test]
+    fn output_files_never_replace_existing_ones() {
+        let dir = std::env::temp_dir().join(format!("bw-output-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let existing = dir.join("photo.png");
+        std::fs::write(&existing, b"keep").unwrap();
+        assert!(create_new_output(&existing).unwrap_err().contains("already exists"));
+        assert_eq!(std::fs::read(&existing).unwrap(), b"keep");
+        assert!(create_new_output(&dir.join("screenshot.png")).is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn shell_commands_cannot_chain_past_the_allowlist() {
+        let allowlist = vec!["ls".to_string(), "getprop".to_string()];
+        assert!(validate_shell_command("ls -la /sdcard", Some(&allowlist)).is_ok());
+        assert!(validate_shell_command("reboot", Some(&allowlist)).is_err());
+        assert!(validate_shell_command("reboot", None).is_ok());
+        for injected in [
+            "ls ; reboot",
+            "ls && rm -rf /sdcard",
+            "ls | sh",
+            "ls $(reboot)",
+            "ls `reboot`",
+            "ls > /sdcard/x",
+            "ls < /dev/zero",
+            "ls\nreboot",
+        ] {
+            assert!(validate_shell_command(injected, Some(&allowlist)).is_err(), "{injected}");
+            assert!(validate_shell_command(injected, None).is_err(), "{injected}");
+        }
+    }
+}
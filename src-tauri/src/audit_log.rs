@@ -0,0 +1,187 @@
+// Event Audit Log
+// Every flash update and hotplug event the app emits, as daily JSONL files for debugging failed flashes
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use chrono::{NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::AppState;
+
+const FILE_PREFIX: &str = "audit-";
+const FILE_SUFFIX: &str = ".jsonl";
+
+/// One line of the audit log
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct AuditEntry {
+    /// RFC 3339 time the event was emitted
+    timestamp: String,
+    /// Event name as emitted, e.g. `flash-progress:<job id>` or `device-events`
+    event: String,
+    payload: serde_json::Value,
+}
+
+/// Appends emitted events to `<dir>/audit-YYYY-MM-DD.jsonl` (UTC) from a
+/// background thread, so recording never blocks the emit path. Files older
+/// than [`Self::RETENTION_DAYS`] are deleted at startup and on each new day.
+pub(crate) struct EventAuditLogger {
+    dir: PathBuf,
+    sender: Sender<AuditEntry>,
+}
+
+impl EventAuditLogger {
+    pub(crate) const RETENTION_DAYS: i64 = 7;
+
+    pub(crate) fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        prune_expired(&dir, Utc::now().date_naive())?;
+        let (sender, receiver) = channel();
+        let writer_dir = dir.clone();
+        std::thread::Builder::new()
+            .name("event-audit-log".to_string())
+            .spawn(move || write_entries(&writer_dir, receiver))?;
+        Ok(Self { dir, sender })
+    }
+
+    /// Queue an event for the log. Serialization or queue failures are dropped.
+    pub(crate) fn record(&self, event: &str, payload: &impl Serialize) {
+        let Ok(payload) = serde_json::to_value(payload) else {
+            return;
+        };
+        let _ = self.sender.send(AuditEntry {
+            timestamp: Utc::now().to_rfc3339(),
+            event: event.to_string(),
+            payload,
+        });
+    }
+
+    /// File today's events are appended to
+    pub(crate) fn current_path(&self) -> PathBuf {
+        path_for(&self.dir, Utc::now().date_naive())
+    }
+
+    /// The last `lines` lines of today's file, oldest first. Entries still
+    /// queued for the writer are not included.
+    pub(crate) fn tail(&self, lines: usize) -> io::Result<Vec<String>> {
+        let file = match File::open(self.current_path()) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let all: Vec<String> = BufReader::new(file).lines().map_while(Result::ok).collect();
+        Ok(all[all.len().saturating_sub(lines)..].to_vec())
+    }
+}
+
+fn path_for(dir: &Path, date: NaiveDate) -> PathBuf {
+    dir.join(format!("{}{}{}", FILE_PREFIX, date.format("%Y-%m-%d"), FILE_SUFFIX))
+}
+
+/// Writer thread: runs until every sender is dropped, reopening the file
+/// (and pruning old ones) when the date changes.
+fn write_entries(dir: &Path, receiver: Receiver<AuditEntry>) {
+    let mut open: Option<(NaiveDate, File)> = None;
+    for entry in receiver {
+        let Ok(line) = serde_json::to_string(&entry) else {
+            continue;
+        };
+        let today = Utc::now().date_naive();
+        if open.as_ref().map(|(date, _)| *date) != Some(today) {
+            if open.is_some() {
+                let _ = prune_expired(dir, today);
+            }
+            open = match OpenOptions::new().create(true).append(true).open(path_for(dir, today)) {
+                Ok(file) => Some((today, file)),
+                Err(e) => {
+                    eprintln!("[Tauri] Failed to open audit log: {}", e);
+                    None
+                }
+            };
+        }
+        if let Some((_, file)) = open.as_mut() {
+            if let Err(e) = writeln!(file, "{}", line) {
+                eprintln!("[Tauri] Failed to write audit log: {}", e);
+            }
+        }
+    }
+}
+
+/// Delete audit files dated more than [`EventAuditLogger::RETENTION_DAYS`]
+/// before `today`. Returns the number of files removed.
+fn prune_expired(dir: &Path, today: NaiveDate) -> io::Result<usize> {
+    let cutoff = today - chrono::Duration::days(EventAuditLogger::RETENTION_DAYS);
+    let mut removed = 0;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let date = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_prefix(FILE_PREFIX))
+            .and_then(|n| n.strip_suffix(FILE_SUFFIX))
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+        if date.is_some_and(|date| date < cutoff) && fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+#[tauri::command]
+pub fn audit_log_path(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    let logger = state
+        .audit_log
+        .as_ref()
+        .ok_or_else(|| "Audit log is not available".to_string())?;
+    Ok(logger.current_path().to_string_lossy().to_string())
+}
+
+/// The last `lines` entries of today's audit log, one JSON object per line.
+#[tauri::command]
+pub fn audit_log_tail(state: tauri::State<'_, AppState>, lines: usize) -> Result<Vec<String>, String> {
+    let logger = state
+        .audit_log
+        .as_ref()
+        .ok_or_else(|| "Audit log is not available".to_string())?;
+    logger.tail(lines).map_err(|e| format!("Failed to read audit log: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn records_events_in_the_background_and_prunes_old_days() {
+        let dir = std::env::temp_dir().join(format!("bw-audit-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let today = Utc::now().date_naive();
+        let expired = path_for(&dir, today - chrono::Duration::days(8));
+        let kept = path_for(&dir, today - chrono::Duration::days(6));
+        fs::write(&expired, "{}\n").unwrap();
+        fs::write(&kept, "{}\n").unwrap();
+
+        let logger = EventAuditLogger::new(&dir).unwrap();
+        assert!(!expired.exists());
+        assert!(kept.exists());
+
+        for i in 0..3 {
+            logger.record("flash-progress:job-1", &serde_json::json!({ "progress": i }));
+        }
+        let started = Instant::now();
+        let mut tail = Vec::new();
+        while tail.len() < 3 && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(10));
+            tail = logger.tail(10).unwrap();
+        }
+        assert_eq!(logger.tail(2).unwrap(), tail[1..]);
+        let last: serde_json::Value = serde_json::from_str(&tail[2]).unwrap();
+        assert_eq!(last["event"], "flash-progress:job-1");
+        assert_eq!(last["payload"]["progress"], 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -6,6 +6,8 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Manager, State};
 
+use crate::config::{adb_program, fastboot_program};
+
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
@@ -196,7 +198,7 @@ fn run_tool_lines(
 }
 
 fn run_adb_devices() -> Result<Vec<String>, String> {
-    let mut cmd = Command::new("adb");
+    let mut cmd = Command::new(adb_program());
     cmd.args(["devices", "-l"]);
     #[cfg(target_os = "windows")]
     {
@@ -240,7 +242,7 @@ fn run_adb_devices() -> Result<Vec<String>, String> {
 }
 
 fn run_fastboot_devices() -> Result<Vec<String>, String> {
-    let mut cmd = Command::new("fastboot");
+    let mut cmd = Command::new(fastboot_program());
     cmd.arg("devices");
     #[cfg(target_os = "windows")]
     {
@@ -554,7 +556,7 @@ fn run_fastboot_flash_job(
             },
         );
 
-        let mut cmd = Command::new("fastboot");
+        let mut cmd = Command::new(fastboot_program());
         cmd.args(["-s", &config.device_serial, "flash", &part.name, &part.image_path])
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
@@ -670,7 +672,7 @@ fn run_fastboot_flash_job(
     }
 
     if config.auto_reboot {
-        let mut cmd = Command::new("fastboot");
+        let mut cmd = Command::new(fastboot_program());
         cmd.args(["-s", &config.device_serial, "reboot"]);
         #[cfg(target_os = "windows")]
         {
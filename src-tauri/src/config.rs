@@ -0,0 +1,599 @@
+// Workshop Configuration
+// Persistent app settings stored as <app_data>/config.json
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bootforgeusb::classify::{ClassificationOverride, ClassifierRegistry};
+use bootforgeusb::tools::confirmers::ToolPaths;
+use serde::{Deserialize, Serialize};
+
+use crate::{validate_partition_name, AppState, STANDARD_PARTITIONS};
+
+pub const CONFIG_FILE_NAME: &str = "config.json";
+
+pub(crate) const POLL_MS_RANGE: (u64, u64) = (250, 60_000);
+const FLASH_HISTORY_RANGE: (usize, usize) = (10, 10_000);
+const FLASH_LOG_LINES_RANGE: (usize, usize) = (100, 50_000);
+/// Log lines kept per flash job unless `max_flash_log_lines` says otherwise
+pub(crate) const DEFAULT_MAX_FLASH_LOG_LINES: usize = 5000;
+const SCAN_TIMEOUT_MS_RANGE: (u64, u64) = (100, 60_000);
+
+/// User-editable settings. Missing keys in `config.json` take their default.
+///
+/// Environment variables (`BW_*`) override whatever is on disk; see
+/// [`WorkshopConfig::apply_env_overrides`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkshopConfig {
+    pub disable_node_backend: bool,
+    pub device_monitor_poll_ms: u64,
+    /// Completed jobs kept in the flash history, newest first
+    #[serde(alias = "max_flash_history")]
+    pub max_flash_history_entries: usize,
+    /// Log lines kept per flash job; older lines are overwritten
+    pub max_flash_log_lines: usize,
+    /// Replaces the built-in partition allowlist when set and non-empty
+    pub allowed_partition_names: Option<Vec<String>>,
+    /// Reject flash jobs that target partitions outside the allowlist
+    /// instead of only logging a warning
+    pub strict_partition_allowlist: bool,
+    /// Tool binaries to use instead of the ones on PATH
+    pub adb_path: Option<String>,
+    pub fastboot_path: Option<String>,
+    pub idevice_id_path: Option<String>,
+    /// Forced classifications for devices the built-in rules get wrong
+    pub classification_overrides: Vec<ClassificationOverride>,
+    /// Shared secret WebSocket clients must present; the server does not
+    /// start without one
+    pub ws_token: Option<String>,
+    /// How long a USB scan waits for one device's descriptor reads before
+    /// reporting it as timed out
+    pub scan_timeout_ms: u64,
+}
+
+impl Default for WorkshopConfig {
+    fn default() -> Self {
+        Self {
+            disable_node_backend: false,
+            device_monitor_poll_ms: 1500,
+            max_flash_history_entries: 200,
+            max_flash_log_lines: DEFAULT_MAX_FLASH_LOG_LINES,
+            allowed_partition_names: None,
+            strict_partition_allowlist: false,
+            adb_path: None,
+            fastboot_path: None,
+            idevice_id_path: None,
+            classification_overrides: Vec::new(),
+            ws_token: None,
+            scan_timeout_ms: bootforgeusb::usb_scan::DEFAULT_PROBE_TIMEOUT_MS,
+        }
+    }
+}
+
+impl WorkshopConfig {
+    /// Read `path`, falling back to defaults if it is missing, unreadable or
+    /// invalid, then apply environment overrides.
+    pub fn load(path: &Path) -> Self {
+        let mut config = Self::read(path);
+        config.apply_env_overrides();
+        config
+    }
+
+    /// [`Self::load`] without the environment overrides: what is on disk.
+    fn read(path: &Path) -> Self {
+        match Self::read_checked(path) {
+            Ok(config) => config.unwrap_or_default(),
+            Err(e) => {
+                eprintln!("[Config] Ignoring {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// The config at `path`, `None` if there is no file, or why it cannot be
+    /// used: unreadable, not valid JSON, or failing [`Self::validate`].
+    pub(crate) fn read_checked(path: &Path) -> Result<Option<Self>, String> {
+        let raw = match fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("{}: cannot be read: {}", path.display(), e)),
+        };
+        let config = serde_json::from_str::<WorkshopConfig>(&raw)
+            .map_err(|e| format!("{}: failed to parse: {}", path.display(), e))?;
+        config
+            .validate()
+            .map_err(|e| format!("{}: invalid: {}", path.display(), e))?;
+        Ok(Some(config))
+    }
+
+    /// Validate and write to `path`, creating the parent directory if needed.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        self.validate()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let (min_poll, max_poll) = POLL_MS_RANGE;
+        if !(min_poll..=max_poll).contains(&self.device_monitor_poll_ms) {
+            return Err(format!(
+                "device_monitor_poll_ms must be between {} and {}",
+                min_poll, max_poll
+            ));
+        }
+        let (min_history, max_history) = FLASH_HISTORY_RANGE;
+        if !(min_history..=max_history).contains(&self.max_flash_history_entries) {
+            return Err(format!(
+                "max_flash_history_entries must be between {} and {}",
+                min_history, max_history
+            ));
+        }
+        let (min_lines, max_lines) = FLASH_LOG_LINES_RANGE;
+        if !(min_lines..=max_lines).contains(&self.max_flash_log_lines) {
+            return Err(format!(
+                "max_flash_log_lines must be between {} and {}",
+                min_lines, max_lines
+            ));
+        }
+        if let Some(names) = &self.allowed_partition_names {
+            for name in names {
+                validate_partition_name(name)?;
+            }
+        }
+        validate_tool_path("adb_path", self.adb_path.as_deref())?;
+        validate_tool_path("fastboot_path", self.fastboot_path.as_deref())?;
+        validate_tool_path("idevice_id_path", self.idevice_id_path.as_deref())?;
+        for rule in &self.classification_overrides {
+            validate_classification_override(rule)?;
+        }
+        if self.ws_token.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            return Err("ws_token cannot be empty".to_string());
+        }
+        let (min_timeout, max_timeout) = SCAN_TIMEOUT_MS_RANGE;
+        if !(min_timeout..=max_timeout).contains(&self.scan_timeout_ms) {
+            return Err(format!(
+                "scan_timeout_ms must be between {} and {}",
+                min_timeout, max_timeout
+            ));
+        }
+        Ok(())
+    }
+
+    /// The active partition allowlist: the override if one is set, otherwise
+    /// the standard Android partitions.
+    pub fn allowed_partitions(&self) -> Vec<String> {
+        match &self.allowed_partition_names {
+            Some(names) if !names.is_empty() => names.clone(),
+            _ => STANDARD_PARTITIONS.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    /// Make `scan_timeout_ms` the deadline and the tool path overrides the
+    /// tools for every later USB scan.
+    pub(crate) fn apply_scan_settings(&self) {
+        bootforgeusb::usb_scan::set_probe_timeout(std::time::Duration::from_millis(self.scan_timeout_ms));
+        bootforgeusb::tools::confirmers::set_tool_paths(ToolPaths {
+            adb: self.adb_path.as_deref().map(PathBuf::from),
+            fastboot: self.fastboot_path.as_deref().map(PathBuf::from),
+            idevice_id: self.idevice_id_path.as_deref().map(PathBuf::from),
+        });
+    }
+
+    pub fn apply_env_overrides(&mut self) {
+        self.apply_overrides_from(|name| env::var(name).ok());
+    }
+
+    /// Overrides:
+    /// - `BW_DISABLE_NODE_BACKEND`, `BW_STRICT_PARTITION_ALLOWLIST` (1/true/yes/on)
+    /// - `BW_DEVICE_MONITOR_POLL_MS`, `BW_MAX_FLASH_HISTORY`, `BW_MAX_FLASH_LOG_LINES`,
+    ///   `BW_SCAN_TIMEOUT_MS`
+    /// - `BW_ALLOWED_PARTITIONS` (comma separated)
+    /// - `BW_ADB_PATH`, `BW_FASTBOOT_PATH`, `BW_IDEVICE_ID_PATH`
+    /// - `BW_WS_TOKEN`
+    ///
+    /// Values that fail to parse, and numbers outside the range
+    /// [`Self::validate`] accepts, are ignored and logged.
+    fn apply_overrides_from(&mut self, lookup: impl Fn(&str) -> Option<String>) {
+        if let Some(v) = lookup("BW_DISABLE_NODE_BACKEND") {
+            self.disable_node_backend = is_truthy(&v);
+        }
+        if let Some(v) = lookup("BW_STRICT_PARTITION_ALLOWLIST") {
+            self.strict_partition_allowlist = is_truthy(&v);
+        }
+        if let Some(v) = ranged_override(&lookup, "BW_DEVICE_MONITOR_POLL_MS", POLL_MS_RANGE) {
+            self.device_monitor_poll_ms = v;
+        }
+        if let Some(v) = ranged_override(&lookup, "BW_MAX_FLASH_HISTORY", FLASH_HISTORY_RANGE) {
+            self.max_flash_history_entries = v;
+        }
+        if let Some(v) = ranged_override(&lookup, "BW_MAX_FLASH_LOG_LINES", FLASH_LOG_LINES_RANGE) {
+            self.max_flash_log_lines = v;
+        }
+        if let Some(v) = ranged_override(&lookup, "BW_SCAN_TIMEOUT_MS", SCAN_TIMEOUT_MS_RANGE) {
+            self.scan_timeout_ms = v;
+        }
+        if let Some(v) = lookup("BW_ALLOWED_PARTITIONS") {
+            let names: Vec<String> = v
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+            self.allowed_partition_names = Some(names);
+        }
+        if let Some(v) = lookup("BW_ADB_PATH").filter(|v| !v.trim().is_empty()) {
+            self.adb_path = Some(v);
+        }
+        if let Some(v) = lookup("BW_FASTBOOT_PATH").filter(|v| !v.trim().is_empty()) {
+            self.fastboot_path = Some(v);
+        }
+        if let Some(v) = lookup("BW_IDEVICE_ID_PATH").filter(|v| !v.trim().is_empty()) {
+            self.idevice_id_path = Some(v);
+        }
+        if let Some(v) = lookup("BW_WS_TOKEN").filter(|v| !v.trim().is_empty()) {
+            self.ws_token = Some(v);
+        }
+    }
+}
+
+/// The numeric override `name`, or `None` when it is unset, does not parse or
+/// is outside `(min, max)`.
+fn ranged_override<T>(lookup: &impl Fn(&str) -> Option<String>, name: &str, (min, max): (T, T)) -> Option<T>
+where
+    T: std::str::FromStr + PartialOrd + std::fmt::Display,
+{
+    let raw = lookup(name)?;
+    match raw.trim().parse::<T>() {
+        Ok(v) if v >= min && v <= max => Some(v),
+        Ok(_) => {
+            eprintln!("[Config] Ignoring {}={}: must be between {} and {}", name, raw.trim(), min, max);
+            None
+        }
+        Err(_) => {
+            eprintln!("[Config] Ignoring {}={}: not a number", name, raw.trim());
+            None
+        }
+    }
+}
+
+fn is_truthy(value: &str) -> bool {
+    matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on")
+}
+
+/// A bare program name is resolved from PATH at run time; anything that looks
+/// like a path must point at an existing file.
+fn validate_tool_path(field: &str, value: Option<&str>) -> Result<(), String> {
+    let Some(value) = value else {
+        return Ok(());
+    };
+    if value.trim().is_empty() {
+        return Err(format!("{} cannot be empty", field));
+    }
+    let path = Path::new(value);
+    if path.components().count() > 1 && !path.is_file() {
+        return Err(format!("{} does not exist: {}", field, value));
+    }
+    Ok(())
+}
+
+fn is_usb_id(value: &str) -> bool {
+    value.len() == 4 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn validate_classification_override(rule: &ClassificationOverride) -> Result<(), String> {
+    if !is_usb_id(&rule.vid) {
+        return Err(format!("classification override vid must be 4 hex digits: '{}'", rule.vid));
+    }
+    if let Some(pid) = rule.pid.as_deref().filter(|pid| !is_usb_id(pid)) {
+        return Err(format!("classification override pid must be 4 hex digits: '{}'", pid));
+    }
+    if !(0.0..=1.0).contains(&rule.forced_confidence) {
+        return Err("classification override confidence must be between 0 and 1".to_string());
+    }
+    // adb, fastboot and idevice_id promote the device once they see it
+    if rule.forced_mode.is_tool_confirmed() {
+        return Err(format!(
+            "classification override mode must be a *_likely, *_detected or unknown_usb mode, not '{}'",
+            rule.forced_mode.as_str()
+        ));
+    }
+    Ok(())
+}
+
+fn same_usb_id(rule: &ClassificationOverride, vid: &str, pid: Option<&str>) -> bool {
+    rule.vid.eq_ignore_ascii_case(vid)
+        && match (rule.pid.as_deref(), pid) {
+            (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+            (None, None) => true,
+            _ => false,
+        }
+}
+
+/// Classifier registry for scans: the built-ins plus the configured overrides.
+pub(crate) fn classifier_registry(state: &AppState) -> ClassifierRegistry {
+    let overrides = state
+        .config
+        .lock()
+        .map(|c| c.classification_overrides.clone())
+        .unwrap_or_default();
+    ClassifierRegistry::with_overrides(overrides)
+}
+
+/// The configured `adb_path`, or `adb` from PATH
+pub(crate) fn adb_program() -> String {
+    bootforgeusb::tools::confirmers::tool_paths()
+        .adb
+        .map_or_else(|| "adb".to_string(), |p| p.to_string_lossy().into_owned())
+}
+
+/// The configured `fastboot_path`, or `fastboot` from PATH
+pub(crate) fn fastboot_program() -> String {
+    bootforgeusb::tools::confirmers::tool_paths()
+        .fastboot
+        .map_or_else(|| "fastboot".to_string(), |p| p.to_string_lossy().into_owned())
+}
+
+/// `<app_data>/config.json`
+pub fn config_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(CONFIG_FILE_NAME)
+}
+
+#[tauri::command]
+pub fn get_config(state: tauri::State<'_, AppState>) -> Result<WorkshopConfig, String> {
+    state
+        .config
+        .lock()
+        .map(|c| c.clone())
+        .map_err(|_| "config mutex poisoned".to_string())
+}
+
+/// Partition names flash jobs may target without a warning.
+#[tauri::command]
+pub fn get_allowed_partitions(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
+    state
+        .config
+        .lock()
+        .map(|c| c.allowed_partitions())
+        .map_err(|_| "config mutex poisoned".to_string())
+}
+
+/// Persist `config` and make it the active configuration. Environment
+/// overrides still take precedence in the active copy.
+#[tauri::command]
+pub fn save_config(
+    state: tauri::State<'_, AppState>,
+    config: WorkshopConfig,
+) -> Result<(), String> {
+    let path = state
+        .config_path
+        .lock()
+        .map_err(|_| "config_path mutex poisoned".to_string())?
+        .clone()
+        .ok_or_else(|| "App data directory is not available".to_string())?;
+    config.save(&path)?;
+
+    let mut active = config;
+    active.apply_env_overrides();
+    if let Ok(mut monitor) = state.device_monitor_config.lock() {
+        monitor.poll_interval_ms = active.device_monitor_poll_ms;
+    }
+    active.apply_scan_settings();
+    if let Ok(mut history) = state.flash_history.lock() {
+        history.truncate(active.max_flash_history_entries);
+    }
+    *state
+        .config
+        .lock()
+        .map_err(|_| "config mutex poisoned".to_string())? = active;
+    Ok(())
+}
+
+/// Apply `change` to both the file on disk and the active configuration,
+/// so environment overrides are not written back to `config.json`.
+fn update_saved_config(
+    state: &AppState,
+    change: impl Fn(&mut WorkshopConfig) -> Result<(), String>,
+) -> Result<(), String> {
+    let path = state
+        .config_path
+        .lock()
+        .map_err(|_| "config_path mutex poisoned".to_string())?
+        .clone()
+        .ok_or_else(|| "App data directory is not available".to_string())?;
+    let mut active = state
+        .config
+        .lock()
+        .map_err(|_| "config mutex poisoned".to_string())?;
+    let mut saved = WorkshopConfig::read(&path);
+    change(&mut saved)?;
+    saved.save(&path)?;
+    change(&mut active)
+}
+
+/// Add a classification override, replacing any existing one for the same
+/// VID/PID. Takes effect from the next scan.
+#[tauri::command]
+pub fn add_classification_override(
+    state: tauri::State<'_, AppState>,
+    rule: ClassificationOverride,
+) -> Result<Vec<ClassificationOverride>, String> {
+    validate_classification_override(&rule)?;
+    update_saved_config(&state, |config| {
+        config
+            .classification_overrides
+            .retain(|r| !same_usb_id(r, &rule.vid, rule.pid.as_deref()));
+        config.classification_overrides.push(rule.clone());
+        Ok(())
+    })?;
+    list_classification_overrides(state)
+}
+
+/// Remove the override for exactly this VID and PID (`None` removes the
+/// vendor-wide override).
+#[tauri::command]
+pub fn remove_classification_override(
+    state: tauri::State<'_, AppState>,
+    vid: String,
+    pid: Option<String>,
+) -> Result<Vec<ClassificationOverride>, String> {
+    let removed = state
+        .config
+        .lock()
+        .map_err(|_| "config mutex poisoned".to_string())?
+        .classification_overrides
+        .iter()
+        .any(|r| same_usb_id(r, &vid, pid.as_deref()));
+    if !removed {
+        return Err(format!(
+            "No classification override for {}{}",
+            vid,
+            pid.as_deref().map(|p| format!(":{}", p)).unwrap_or_default()
+        ));
+    }
+    update_saved_config(&state, |config| {
+        config
+            .classification_overrides
+            .retain(|r| !same_usb_id(r, &vid, pid.as_deref()));
+        Ok(())
+    })?;
+    list_classification_overrides(state)
+}
+
+#[tauri::command]
+pub fn list_classification_overrides(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<ClassificationOverride>, String> {
+    state
+        .config
+        .lock()
+        .map(|c| c.classification_overrides.clone())
+        .map_err(|_| "config mutex poisoned".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_file_uses_defaults_for_missing_keys() {
+        let config: WorkshopConfig =
+            serde_json::from_str(r#"{"device_monitor_poll_ms": 3000}"#).unwrap();
+        assert_eq!(config.device_monitor_poll_ms, 3000);
+        assert_eq!(config.max_flash_history_entries, WorkshopConfig::default().max_flash_history_entries);
+
+        // Config files written before the rename still load
+        let legacy: WorkshopConfig = serde_json::from_str(r#"{"max_flash_history": 50}"#).unwrap();
+        assert_eq!(legacy.max_flash_history_entries, 50);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn env_overrides_win_over_file_values() {
+        let mut config = WorkshopConfig {
+            device_monitor_poll_ms: 3000,
+            ..WorkshopConfig::default()
+        };
+        config.apply_overrides_from(|name| match name {
+            "BW_DISABLE_NODE_BACKEND" => Some("1".to_string()),
+            "BW_DEVICE_MONITOR_POLL_MS" => Some("500".to_string()),
+            "BW_MAX_FLASH_HISTORY" => Some("not-a-number".to_string()),
+            "BW_MAX_FLASH_LOG_LINES" => Some("800".to_string()),
+            "BW_SCAN_TIMEOUT_MS" => Some("2500".to_string()),
+            "BW_ALLOWED_PARTITIONS" => Some("boot, persist_image,".to_string()),
+            _ => None,
+        });
+        assert!(config.disable_node_backend);
+        assert_eq!(config.device_monitor_poll_ms, 500);
+        assert_eq!(config.max_flash_history_entries, 200);
+        assert_eq!(config.max_flash_log_lines, 800);
+        assert_eq!(config.scan_timeout_ms, 2500);
+        assert_eq!(
+            config.allowed_partition_names,
+            Some(vec!["boot".to_string(), "persist_image".to_string()])
+        );
+    }
+
+    #[test]
+    fn out_of_range_env_overrides_are_ignored() {
+        let mut config = WorkshopConfig::default();
+        config.apply_overrides_from(|name| match name {
+            "BW_DEVICE_MONITOR_POLL_MS" => Some("0".to_string()),
+            "BW_MAX_FLASH_HISTORY" => Some("0".to_string()),
+            "BW_MAX_FLASH_LOG_LINES" => Some("50001".to_string()),
+            "BW_SCAN_TIMEOUT_MS" => Some("0".to_string()),
+            _ => None,
+        });
+        assert_eq!(config, WorkshopConfig::default());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn partition_override_replaces_defaults_unless_empty() {
+        let defaults = WorkshopConfig::default().allowed_partitions();
+        assert!(defaults.contains(&"boot".to_string()));
+
+        let empty = WorkshopConfig {
+            allowed_partition_names: Some(vec![]),
+            ..WorkshopConfig::default()
+        };
+        assert_eq!(empty.allowed_partitions(), defaults);
+
+        let custom = WorkshopConfig {
+            allowed_partition_names: Some(vec!["boot".to_string(), "persist_image".to_string()]),
+            ..WorkshopConfig::default()
+        };
+        assert_eq!(custom.allowed_partitions(), vec!["boot", "persist_image"]);
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_values() {
+        let bad = [
+            WorkshopConfig { device_monitor_poll_ms: 10, ..WorkshopConfig::default() },
+            WorkshopConfig { max_flash_history_entries: 9, ..WorkshopConfig::default() },
+            WorkshopConfig { max_flash_log_lines: 50_001, ..WorkshopConfig::default() },
+            WorkshopConfig { scan_timeout_ms: 0, ..WorkshopConfig::default() },
+            WorkshopConfig {
+                allowed_partition_names: Some(vec!["../boot".to_string()]),
+                ..WorkshopConfig::default()
+            },
+            WorkshopConfig {
+                adb_path: Some("/nonexistent/bin/adb".to_string()),
+                ..WorkshopConfig::default()
+            },
+            WorkshopConfig {
+                classification_overrides: vec![ClassificationOverride {
+                    vid: "18d1x".to_string(),
+                    pid: None,
+                    forced_mode: bootforgeusb::model::DeviceMode::AndroidAdbConfirmed,
+                    forced_confidence: 0.9,
+                    note: String::new(),
+                }],
+                ..WorkshopConfig::default()
+            },
+        ];
+        for config in bad {
+            assert!(config.validate().is_err(), "{:?}", config);
+        }
+        let override_mode = |forced_mode| WorkshopConfig {
+            classification_overrides: vec![ClassificationOverride {
+                vid: "18d1".to_string(),
+                pid: None,
+                forced_mode,
+                forced_confidence: 0.9,
+                note: String::new(),
+            }],
+            ..WorkshopConfig::default()
+        };
+        assert!(override_mode(bootforgeusb::model::DeviceMode::AndroidFastbootConfirmed).validate().is_err());
+        assert!(override_mode(bootforgeusb::model::DeviceMode::IosUntrusted).validate().is_err());
+        assert!(override_mode(bootforgeusb::model::DeviceMode::AndroidMtpDetected).validate().is_ok());
+        let bare = WorkshopConfig { adb_path: Some("adb".to_string()), ..WorkshopConfig::default() };
+        assert!(bare.validate().is_ok());
+    }
+}
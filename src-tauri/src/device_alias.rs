@@ -0,0 +1,164 @@
+// Device Aliases
+// Friendly names for device_uids, persisted to <app_data>/device_aliases.json
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::AppState;
+
+pub(crate) const ALIASES_FILE_NAME: &str = "device_aliases.json";
+
+const MAX_ALIAS_CHARS: usize = 64;
+
+/// Characters a shell would interpret; aliases end up in logs and exported
+/// scripts, so they are kept out entirely.
+const SHELL_SPECIAL_CHARS: &[char] = &[
+    ';', '&', '|', '$', '`', '<', '>', '(', ')', '{', '}', '[', ']', '\\', '"', '\'', '*', '?', '!', '~', '#',
+];
+
+/// uid → alias map, written back to disk on every change.
+#[derive(Default)]
+pub(crate) struct DeviceAliasStore {
+    path: Mutex<Option<PathBuf>>,
+    aliases: Mutex<HashMap<String, String>>,
+}
+
+impl DeviceAliasStore {
+    /// Read the aliases at `path` and persist future changes there. A missing
+    /// or unreadable file starts an empty map.
+    pub(crate) fn load(&self, path: PathBuf) {
+        let loaded = match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                eprintln!("[Tauri] Ignoring {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        if let Ok(mut aliases) = self.aliases.lock() {
+            *aliases = loaded;
+        }
+        if let Ok(mut guard) = self.path.lock() {
+            *guard = Some(path);
+        }
+    }
+
+    pub(crate) fn get(&self, device_uid: &str) -> Option<String> {
+        self.aliases.lock().ok()?.get(device_uid).cloned()
+    }
+
+    pub(crate) fn set(&self, device_uid: &str, alias: &str) -> Result<(), String> {
+        let alias = validate_alias(alias)?;
+        self.update(|aliases| {
+            aliases.insert(device_uid.to_string(), alias);
+        })
+    }
+
+    pub(crate) fn remove(&self, device_uid: &str) -> Result<(), String> {
+        self.update(|aliases| {
+            aliases.remove(device_uid);
+        })
+    }
+
+    /// Apply `change` and write the result to disk. The in-memory map is only
+    /// updated once the write succeeds.
+    fn update(&self, change: impl FnOnce(&mut HashMap<String, String>)) -> Result<(), String> {
+        let path = self
+            .path
+            .lock()
+            .map_err(|_| "device alias path mutex poisoned".to_string())?
+            .clone()
+            .ok_or_else(|| "App data directory is not available".to_string())?;
+        let mut aliases = self
+            .aliases
+            .lock()
+            .map_err(|_| "device aliases mutex poisoned".to_string())?;
+        let mut updated = aliases.clone();
+        change(&mut updated);
+        write_aliases(&path, &updated)?;
+        *aliases = updated;
+        Ok(())
+    }
+}
+
+fn write_aliases(path: &Path, aliases: &HashMap<String, String>) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(aliases).map_err(|e| format!("Failed to serialize aliases: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// The trimmed alias, if it is non-empty, at most 64 characters and free of
+/// shell special and control characters.
+fn validate_alias(alias: &str) -> Result<String, String> {
+    let alias = alias.trim();
+    if alias.is_empty() {
+        return Err("alias is required".to_string());
+    }
+    if alias.chars().count() > MAX_ALIAS_CHARS {
+        return Err(format!("alias must be at most {} characters", MAX_ALIAS_CHARS));
+    }
+    if let Some(c) = alias.chars().find(|c| SHELL_SPECIAL_CHARS.contains(c) || c.is_control()) {
+        return Err(format!("alias may not contain '{}'", c.escape_default()));
+    }
+    Ok(alias.to_string())
+}
+
+#[tauri::command]
+pub fn set_device_alias(state: tauri::State<'_, AppState>, device_uid: String, alias: String) -> Result<(), String> {
+    let device_uid = device_uid.trim();
+    if device_uid.is_empty() {
+        return Err("device_uid is required".to_string());
+    }
+    state.device_aliases.set(device_uid, &alias)
+}
+
+#[tauri::command]
+pub fn get_device_alias(state: tauri::State<'_, AppState>, device_uid: String) -> Result<Option<String>, String> {
+    Ok(state.device_aliases.get(device_uid.trim()))
+}
+
+/// Remove a device's alias. Removing one that does not exist is not an error.
+#[tauri::command]
+pub fn delete_device_alias(state: tauri::State<'_, AppState>, device_uid: String) -> Result<(), String> {
+    state.device_aliases.remove(device_uid.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_aliases() {
+        assert_eq!(validate_alias("  Test Pixel 8 ").unwrap(), "Test Pixel 8");
+        assert!(validate_alias("   ").is_err());
+        assert!(validate_alias(&"a".repeat(64)).is_ok());
+        assert!(validate_alias(&"a".repeat(65)).is_err());
+        assert!(validate_alias("pixel; rm -rf /").is_err());
+        assert!(validate_alias("$(whoami)").is_err());
+        assert!(validate_alias("line\nbreak").is_err());
+    }
+
+    #[test]
+    fn persists_aliases_across_loads() {
+        let dir = std::env::temp_dir().join(format!("bw-aliases-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(ALIASES_FILE_NAME);
+
+        let store = DeviceAliasStore::default();
+        assert!(store.set("usb:18d1:4ee7:bus1:addr3", "Bench Pixel").is_err());
+        store.load(path.clone());
+        store.set("usb:18d1:4ee7:bus1:addr3", "Bench Pixel").unwrap();
+        store.set("usb:05ac:12a8:bus1:addr4", "Test iPhone").unwrap();
+        store.remove("usb:05ac:12a8:bus1:addr4").unwrap();
+        assert!(store.set("usb:18d1:4ee7:bus1:addr3", "a|b").is_err());
+
+        let reloaded = DeviceAliasStore::default();
+        reloaded.load(path);
+        assert_eq!(reloaded.get("usb:18d1:4ee7:bus1:addr3").as_deref(), Some("Bench Pixel"));
+        assert_eq!(reloaded.get("usb:05ac:12a8:bus1:addr4"), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,248 @@
+// Device State Export
+// Keeps UnifiedDeviceState per scanned device and shares it as JSON files
+
+use std::collections::HashSet;
+
+use libbootforge::device_state::{
+    ConnectionSpeed as StateSpeed, DeviceMode as StateMode, DeviceStateStore, UnifiedDeviceState,
+};
+
+use bootforgeusb::model::{ConfirmedDeviceRecord, ConnectionSpeed as UsbSpeed, DeviceMode};
+
+use crate::adb_tools::resolve_local_path;
+use crate::{get_log_directory, now_ms, AppState};
+
+fn state_speed(speed: UsbSpeed) -> StateSpeed {
+    match speed {
+        UsbSpeed::Low => StateSpeed::Low,
+        UsbSpeed::Full => StateSpeed::Full,
+        UsbSpeed::High => StateSpeed::High,
+        UsbSpeed::Super => StateSpeed::Super,
+        UsbSpeed::SuperPlus => StateSpeed::SuperPlus,
+        UsbSpeed::SuperPlusPlus => StateSpeed::SuperPlusPlus,
+    }
+}
+
+fn state_mode(mode: &str) -> StateMode {
+    match DeviceMode::parse(mode) {
+        Some(DeviceMode::IosNormalLikely) | Some(DeviceMode::IosUntrusted) => StateMode::Normal,
+        Some(DeviceMode::IosRecoveryLikely) | Some(DeviceMode::AndroidRecoveryAdbConfirmed) => {
+            StateMode::Recovery
+        }
+        Some(DeviceMode::IosDfuLikely) => StateMode::Dfu,
+        Some(DeviceMode::AndroidAdbConfirmed) => StateMode::Adb,
+        Some(DeviceMode::AndroidFastbootConfirmed) => StateMode::Fastboot,
+        Some(DeviceMode::AndroidMtkDownloadLikely) | Some(DeviceMode::AndroidDownloadModeDetected) => {
+            StateMode::Download
+        }
+        Some(DeviceMode::AndroidMtpDetected) => StateMode::Mtp,
+        Some(DeviceMode::UnknownUsb) | None => StateMode::Unknown,
+    }
+}
+
+/// Whether an iOS device has trusted this host. Untrusted and trusted iOS
+/// devices share `StateMode::Normal`, so trust is carried in
+/// `connection.authorized`; other platforms report `None`.
+fn ios_trusted(mode: &str) -> Option<bool> {
+    match DeviceMode::parse(mode) {
+        Some(DeviceMode::IosUntrusted) => Some(false),
+        Some(DeviceMode::IosNormalLikely) => Some(true),
+        _ => None,
+    }
+}
+
+/// Initial `UnifiedDeviceState` for a device seen by a scan.
+pub(crate) fn device_state_from_record(device: &ConfirmedDeviceRecord) -> UnifiedDeviceState {
+    let usb = &device.evidence.usb;
+    let mut state = UnifiedDeviceState::new(
+        device.device_uid.clone(),
+        usb.manufacturer.clone().unwrap_or_else(|| "unknown".to_string()),
+        usb.product.clone().unwrap_or_else(|| "unknown".to_string()),
+        u16::from_str_radix(&usb.vid, 16).unwrap_or(0),
+        u16::from_str_radix(&usb.pid, 16).unwrap_or(0),
+    );
+    state.identity.serial_number = usb.serial.clone();
+    state.identity.marketing_name = device.marketing_name.clone();
+    state.connection.usb_path = Some(format!("bus{}:addr{}", usb.bus, usb.address));
+    state.connection.quality = usb.connection_quality;
+    state.connection.speed = usb.speed.map(state_speed);
+    state.connection.mode = state_mode(&device.mode);
+    state.connection.authorized = ios_trusted(&device.mode).unwrap_or(state.connection.authorized);
+    state.capabilities.file_transfer = state.connection.mode == StateMode::Mtp;
+    state
+}
+
+/// Create or refresh the stored state of every device in a scan result.
+///
+/// Returns the uids of already-known devices whose state changed
+/// significantly (mode, capabilities or iOS trust); newly stored devices are
+/// not listed.
+pub(crate) fn sync_device_states(store: &DeviceStateStore, devices: &[ConfirmedDeviceRecord]) -> HashSet<String> {
+    let mut significant = HashSet::new();
+    for device in devices {
+        let mode = state_mode(&device.mode);
+        let trusted = ios_trusted(&device.mode);
+        let quality = device.evidence.usb.connection_quality;
+        let speed = device.evidence.usb.speed.map(state_speed);
+        let updated = store.update(&device.device_uid, |s| {
+            let before = s.clone();
+            s.connection.quality = quality;
+            s.connection.speed = speed;
+            s.set_mode(mode);
+            s.capabilities.file_transfer |= mode == StateMode::Mtp;
+            if let Some(trusted) = trusted {
+                s.connection.authorized = trusted;
+            }
+            if UnifiedDeviceState::diff(&before, s).is_significant()
+                || before.connection.authorized != s.connection.authorized
+            {
+                significant.insert(device.device_uid.clone());
+            }
+        });
+        if !updated {
+            store.upsert(device_state_from_record(device));
+        }
+    }
+    significant
+}
+
+/// Write the stored state of `device_uid` as JSON and return the file path.
+///
+/// Without `output_path` the file goes to the log directory as
+/// `device-<uid>-<timestamp>.json`. An explicit path must stay inside the
+/// user's home directory.
+#[tauri::command]
+pub fn export_device_state(
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let device_state = state
+        .device_states
+        .get(&device_uid)
+        .ok_or_else(|| format!("No device state recorded for {}", device_uid))?;
+    let json = device_state
+        .to_json()
+        .map_err(|e| format!("Failed to serialize device state: {e}"))?;
+
+    let target = match output_path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(path) => {
+            let home = dirs::home_dir().ok_or_else(|| "Home directory not available".to_string())?;
+            resolve_local_path(&home, path)?
+        }
+        None => {
+            let log_dir = get_log_directory();
+            std::fs::create_dir_all(&log_dir)
+                .map_err(|e| format!("Failed to create log directory: {e}"))?;
+            let safe_uid: String = device_uid
+                .chars()
+                .map(|c| if c.is_alphanumeric() { c } else { '_' })
+                .collect();
+            log_dir.join(format!("device-{}-{}.json", safe_uid, now_ms()))
+        }
+    };
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    std::fs::write(&target, json).map_err(|e| format!("Failed to write {}: {e}", target.display()))?;
+    Ok(target.to_string_lossy().to_string())
+}
+
+fn parse_device_state(json: &str) -> Result<UnifiedDeviceState, String> {
+    let device_state =
+        UnifiedDeviceState::from_json(json).map_err(|e| format!("Invalid device state: {e}"))?;
+    if device_state.id.trim().is_empty() {
+        return Err("Invalid device state: id is empty".to_string());
+    }
+    Ok(device_state)
+}
+
+/// Load a previously exported device state into the store, replacing any
+/// state already held for the same id.
+#[tauri::command]
+pub fn import_device_state(state: tauri::State<'_, AppState>, path: String) -> Result<(), String> {
+    let json = std::fs::read_to_string(path.trim())
+        .map_err(|e| format!("Failed to read {}: {e}", path.trim()))?;
+    state.device_states.upsert(parse_device_state(&json)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bootforgeusb::model::{Evidence, Platform, UsbTransportEvidence};
+    use std::collections::HashMap;
+
+    fn record(mode: DeviceMode) -> ConfirmedDeviceRecord {
+        ConfirmedDeviceRecord {
+            device_uid: "ABC123".to_string(),
+            platform: Platform::from_mode(mode.as_str()),
+            mode: mode.as_str().to_string(),
+            confidence: 0.9,
+            evidence: Evidence {
+                usb: UsbTransportEvidence {
+                    vid: "18d1".to_string(),
+                    pid: "4ee7".to_string(),
+                    manufacturer: Some("Google".to_string()),
+                    product: Some("Pixel 8".to_string()),
+                    serial: Some("ABC123".to_string()),
+                    bus: 1,
+                    address: 4,
+                    connection_quality: Some(90),
+                    ..Default::default()
+                },
+                tools: HashMap::new(),
+            },
+            notes: vec![],
+            matched_tool_ids: vec!["ABC123".to_string()],
+            marketing_name: None,
+            driver_status: None,
+        }
+    }
+
+    #[test]
+    fn scan_results_create_then_update_states() {
+        let store = DeviceStateStore::new();
+        assert!(sync_device_states(&store, &[record(DeviceMode::AndroidAdbConfirmed)]).is_empty());
+        let created = store.get("ABC123").unwrap();
+        assert_eq!(created.identity.usb_vendor_id, 0x18d1);
+        assert_eq!(created.identity.model, "Pixel 8");
+        assert_eq!(created.connection.mode, StateMode::Adb);
+
+        let mut weaker = record(DeviceMode::AndroidAdbConfirmed);
+        weaker.confidence = 0.5;
+        assert!(sync_device_states(&store, &[weaker]).is_empty());
+
+        let significant = sync_device_states(&store, &[record(DeviceMode::AndroidFastbootConfirmed)]);
+        assert!(significant.contains("ABC123"));
+        assert_eq!(store.len(), 1);
+        assert!(store.get("ABC123").unwrap().is_flashable());
+    }
+
+    #[test]
+    fn trusting_an_ios_device_is_a_significant_change() {
+        let store = DeviceStateStore::new();
+        sync_device_states(&store, &[record(DeviceMode::IosUntrusted)]);
+        assert!(!store.get("ABC123").unwrap().connection.authorized);
+        assert!(sync_device_states(&store, &[record(DeviceMode::IosUntrusted)]).is_empty());
+
+        let significant = sync_device_states(&store, &[record(DeviceMode::IosNormalLikely)]);
+        assert!(significant.contains("ABC123"));
+        let trusted = store.get("ABC123").unwrap();
+        assert!(trusted.connection.authorized);
+        assert_eq!(trusted.connection.mode, StateMode::Normal);
+    }
+
+    #[test]
+    fn exported_json_round_trips_and_requires_an_id() {
+        let store = DeviceStateStore::new();
+        sync_device_states(&store, &[record(DeviceMode::AndroidAdbConfirmed)]);
+        let json = store.get("ABC123").unwrap().to_json().unwrap();
+        assert_eq!(parse_device_state(&json).unwrap().id, "ABC123");
+
+        let blank = json.replacen("\"id\": \"ABC123\"", "\"id\": \"\"", 1);
+        assert!(parse_device_state(&blank).unwrap_err().contains("id is empty"));
+        assert!(parse_device_state("{}").is_err());
+    }
+}
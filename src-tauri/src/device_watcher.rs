@@ -0,0 +1,663 @@
+// Device Watcher
+// Typed in-process fan-out of device hotplug events for Rust consumers
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bootforgeusb::classify::ClassifierRegistry;
+use bootforgeusb::model::{ConfirmedDeviceRecord, DeviceMode, Platform};
+use bootforgeusb::DeviceChangeSet;
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::{classifier_registry, WorkshopConfig, POLL_MS_RANGE};
+use crate::{now_ms, restart_device_monitor, start_device_monitor_once, AppState, DeviceHotplugEvent};
+
+/// How much work each monitor poll does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScanDepth {
+    /// USB enumeration only; adb/fastboot/idevice_id are not run
+    UsbOnly,
+    /// USB enumeration plus tool correlation
+    WithTools,
+    /// `WithTools`, plus a Python backend `inspect_basic` for new devices
+    Full,
+}
+
+impl ScanDepth {
+    pub(crate) fn scan(
+        self,
+        registry: &ClassifierRegistry,
+    ) -> Result<Vec<ConfirmedDeviceRecord>, Box<dyn std::error::Error>> {
+        match self {
+            ScanDepth::UsbOnly => bootforgeusb::scan_usb_only_with_registry(registry),
+            ScanDepth::WithTools | ScanDepth::Full => bootforgeusb::scan_with_registry(registry),
+        }
+    }
+}
+
+/// The last scan after a `UsbOnly` poll. Devices still on the same USB
+/// vid:pid keep their earlier record, so the tool confirmations other
+/// commands read from the last scan survive; new or changed devices take the
+/// USB-only record and unplugged ones are dropped.
+pub(crate) fn merge_usb_only_scan(
+    previous: &[ConfirmedDeviceRecord],
+    usb_only: &[ConfirmedDeviceRecord],
+) -> Vec<ConfirmedDeviceRecord> {
+    usb_only
+        .iter()
+        .map(|device| {
+            previous
+                .iter()
+                .find(|p| {
+                    p.device_uid == device.device_uid
+                        && p.evidence.usb.vid == device.evidence.usb.vid
+                        && p.evidence.usb.pid == device.evidence.usb.pid
+                })
+                .unwrap_or(device)
+                .clone()
+        })
+        .collect()
+}
+
+/// Runtime-tunable device monitor settings, read at the start of every poll.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceMonitorConfig {
+    pub poll_interval_ms: u64,
+    pub scan_depth: ScanDepth,
+}
+
+impl DeviceMonitorConfig {
+    pub fn from_workshop_config(config: &WorkshopConfig) -> Self {
+        Self {
+            poll_interval_ms: config.device_monitor_poll_ms,
+            scan_depth: ScanDepth::WithTools,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let (min_poll, max_poll) = POLL_MS_RANGE;
+        if !(min_poll..=max_poll).contains(&self.poll_interval_ms) {
+            return Err(format!(
+                "poll_interval_ms must be between {} and {}",
+                min_poll, max_poll
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Snapshot of the monitor config; the lock is released before returning.
+pub(crate) fn current_monitor_config(state: &AppState) -> DeviceMonitorConfig {
+    state
+        .device_monitor_config
+        .lock()
+        .map(|c| c.clone())
+        .unwrap_or_else(|_| DeviceMonitorConfig {
+            poll_interval_ms: 1500,
+            scan_depth: ScanDepth::WithTools,
+        })
+}
+
+/// Ask the Python backend for lock/MDM flags of newly connected devices and
+/// record them on their stored device state. Skipped when the backend is down.
+pub(crate) fn inspect_connected_devices(state: &AppState, devices: &[ConfirmedDeviceRecord]) {
+    if devices.is_empty() {
+        return;
+    }
+    let Some(client) = state.py_client.lock().ok().and_then(|c| c.clone()) else {
+        return;
+    };
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("[Tauri] Full scan: failed to start runtime: {}", e);
+            return;
+        }
+    };
+    for device in devices {
+        // Each inspection can take seconds; keep the watchdog from counting them as a stall.
+        state.monitor_last_tick.store(now_ms(), Ordering::SeqCst);
+        let result = runtime.block_on(client.inspect_basic(&device.device_uid, device.platform.as_str()));
+        match result {
+            Ok(flags) => {
+                state.device_states.update(&device.device_uid, |s| {
+                    s.security.activation_lock = flags.activation_locked;
+                    s.security.mdm_enrolled = flags.mdm_enrolled;
+                    s.security.frp_enabled = flags.frp_locked;
+                });
+            }
+            Err(e) => eprintln!("[Tauri] inspect_basic failed for {}: {}", device.device_uid, e),
+        }
+    }
+}
+
+/// Pause scanning until `now_ms + duration_ms`, or until resumed when no
+/// duration is given. An existing longer pause is kept.
+pub(crate) fn pause_monitor(paused_until: &AtomicU64, now_ms: u64, duration_ms: Option<u64>) {
+    let until = duration_ms.map_or(u64::MAX, |d| now_ms.saturating_add(d));
+    paused_until.fetch_max(until, Ordering::SeqCst);
+}
+
+pub(crate) fn monitor_paused(paused_until: &AtomicU64, now_ms: u64) -> bool {
+    paused_until.load(Ordering::SeqCst) > now_ms
+}
+
+/// Keeps the device monitor paused while alive: mode changes during a flash
+/// would otherwise flood device-events. Each running flash job holds one for
+/// its whole run; the hold is released on drop, even if the job panics.
+pub(crate) struct FlashMonitorHold(AppHandle);
+
+impl FlashMonitorHold {
+    pub(crate) fn new(app_handle: &AppHandle) -> Self {
+        app_handle.state::<AppState>().flash_monitor_holds.fetch_add(1, Ordering::SeqCst);
+        Self(app_handle.clone())
+    }
+}
+
+impl Drop for FlashMonitorHold {
+    fn drop(&mut self) {
+        self.0.state::<AppState>().flash_monitor_holds.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// How often the watchdog checks the monitor loop
+const MONITOR_WATCHDOG_INTERVAL: Duration = Duration::from_secs(10);
+/// A monitor tick older than this means the loop is stalled or dead, unless
+/// its poll interval and scan timeout allow for a longer gap
+const MONITOR_STALL_MS: u64 = 30_000;
+/// Per-device probe timeouts a single scan may spend, for the stall threshold
+const SCAN_TIMEOUTS_PER_TICK: u64 = 4;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MonitorRestartedEvent {
+    stalled_for_ms: u64,
+}
+
+/// Longest gap between two ticks of a healthy loop: a poll sleep plus a scan
+/// whose device probes run into the scan timeout.
+fn stall_threshold_ms(poll_interval_ms: u64, scan_timeout_ms: u64) -> u64 {
+    MONITOR_STALL_MS.max(
+        poll_interval_ms
+            .saturating_mul(2)
+            .saturating_add(scan_timeout_ms.saturating_mul(SCAN_TIMEOUTS_PER_TICK)),
+    )
+}
+
+/// How long the running monitor has gone without a tick, when that counts as
+/// stalled. A monitor that was stopped on purpose is never stalled.
+fn monitor_stall(started: &AtomicBool, last_tick: &AtomicU64, now_ms: u64, threshold_ms: u64) -> Option<u64> {
+    if !started.load(Ordering::SeqCst) {
+        return None;
+    }
+    let stalled_for = now_ms.saturating_sub(last_tick.load(Ordering::SeqCst));
+    (stalled_for > threshold_ms).then_some(stalled_for)
+}
+
+/// Restart the device monitor whenever its loop stops ticking, e.g. after a
+/// panic inside a scan, and emit `monitor-restarted`. A monitor the user
+/// stopped is not restarted.
+pub(crate) fn start_monitor_watchdog(app_handle: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(MONITOR_WATCHDOG_INTERVAL);
+        let state = app_handle.state::<AppState>();
+        let poll_interval_ms = current_monitor_config(&state).poll_interval_ms;
+        let scan_timeout_ms = state
+            .config
+            .lock()
+            .map(|c| c.scan_timeout_ms)
+            .unwrap_or(bootforgeusb::usb_scan::DEFAULT_PROBE_TIMEOUT_MS);
+        let threshold_ms = stall_threshold_ms(poll_interval_ms, scan_timeout_ms);
+        let Some(stalled_for_ms) =
+            monitor_stall(&state.device_monitor_started, &state.monitor_last_tick, now_ms(), threshold_ms)
+        else {
+            continue;
+        };
+        // The dead loop still holds the start flag.
+        state.device_monitor_started.store(false, Ordering::SeqCst);
+        if state.device_monitor_stop.load(Ordering::SeqCst) {
+            continue;
+        }
+        eprintln!("[Tauri] Device monitor stalled for {} ms; restarting it", stalled_for_ms);
+        restart_device_monitor(&app_handle, state);
+        if let Some(window) = app_handle.get_webview_window("main") {
+            let _ = window.emit("monitor-restarted", MonitorRestartedEvent { stalled_for_ms });
+        }
+    });
+}
+
+/// Stale polls before a device's confidence starts to decay
+const STALE_PROBE_THRESHOLD: u32 = 3;
+/// Confidence lost per stale poll, once past the threshold
+const STALE_CONFIDENCE_STEP: f32 = 0.1;
+const STALE_CONFIDENCE_FLOOR: f32 = 0.3;
+
+/// Whether adb, fastboot or idevice_id should list a device in this mode.
+/// DFU, iOS recovery, MTK and Samsung download and MTP-only Android are only ever visible on USB.
+fn tool_probe_expected(device: &ConfirmedDeviceRecord) -> bool {
+    if device.platform == Platform::Unknown {
+        return false;
+    }
+    !matches!(
+        DeviceMode::parse(&device.mode),
+        Some(DeviceMode::IosDfuLikely)
+            | Some(DeviceMode::IosRecoveryLikely)
+            | Some(DeviceMode::AndroidMtkDownloadLikely)
+            | Some(DeviceMode::AndroidMtpDetected)
+            | Some(DeviceMode::AndroidDownloadModeDetected)
+    )
+}
+
+fn decayed_confidence(confidence: f32, stale_probe_count: u32) -> f32 {
+    (confidence - STALE_CONFIDENCE_STEP * stale_probe_count as f32).max(STALE_CONFIDENCE_FLOOR)
+}
+
+/// Devices the monitor has seen on USB, with the number of consecutive
+/// tool-correlated polls in which no tool reported a matching serial.
+#[derive(Debug, Default)]
+pub(crate) struct SeenDevices {
+    stale_probe_count: HashMap<String, u32>,
+}
+
+impl SeenDevices {
+    /// Update the counters from one tool-correlated scan. Returns the devices
+    /// whose confidence has decayed further, with the decayed confidence set;
+    /// a device that reached the floor is not returned again.
+    pub(crate) fn observe(&mut self, devices: &[ConfirmedDeviceRecord]) -> Vec<ConfirmedDeviceRecord> {
+        self.stale_probe_count.retain(|uid, _| devices.iter().any(|d| &d.device_uid == uid));
+
+        let mut decayed = Vec::new();
+        for device in devices {
+            if !device.matched_tool_ids.is_empty() || !tool_probe_expected(device) {
+                self.stale_probe_count.remove(&device.device_uid);
+                continue;
+            }
+            let count = self.stale_probe_count.entry(device.device_uid.clone()).or_insert(0);
+            *count += 1;
+            if *count < STALE_PROBE_THRESHOLD {
+                continue;
+            }
+            let confidence = decayed_confidence(device.confidence, *count);
+            if *count == STALE_PROBE_THRESHOLD || confidence < decayed_confidence(device.confidence, *count - 1) {
+                let mut device = device.clone();
+                device.confidence = confidence;
+                decayed.push(device);
+            }
+        }
+        decayed
+    }
+}
+
+/// Message sent to `scan_subscribe` channels.
+///
+/// The first message is always the full `deviceList`; later polls only send
+/// a `change` when something differs from the previous scan.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "camelCase")]
+pub enum ScanEvent {
+    DeviceList(Vec<ConfirmedDeviceRecord>),
+    Change(DeviceChangeSet),
+}
+
+/// Set of live subscriber channels. Senders whose receiver was dropped are
+/// pruned on the next broadcast.
+#[derive(Default)]
+pub(crate) struct DeviceEventSubscribers {
+    senders: Mutex<Vec<Sender<DeviceHotplugEvent>>>,
+}
+
+impl DeviceEventSubscribers {
+    pub(crate) fn subscribe(&self) -> Receiver<DeviceHotplugEvent> {
+        let (tx, rx) = channel();
+        if let Ok(mut senders) = self.senders.lock() {
+            senders.push(tx);
+        }
+        rx
+    }
+
+    pub(crate) fn broadcast(&self, event: &DeviceHotplugEvent) {
+        if let Ok(mut senders) = self.senders.lock() {
+            senders.retain(|tx| tx.send(event.clone()).is_ok());
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.senders.lock().map(|s| s.len()).unwrap_or(0)
+    }
+}
+
+/// Owns the device polling loop and hands out typed event receivers.
+///
+/// Managed as Tauri state; the polling loop in `main.rs` forwards every
+/// hotplug event here in addition to emitting it to the frontend.
+pub struct DeviceWatcher {
+    app_handle: AppHandle,
+    subscribers: DeviceEventSubscribers,
+    /// Stop flags of the per-subscriber scan loops started by `scan_subscribe`
+    scan_subscriptions: Mutex<HashMap<u64, Arc<AtomicBool>>>,
+    next_subscription_id: AtomicU64,
+}
+
+impl DeviceWatcher {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            app_handle,
+            subscribers: DeviceEventSubscribers::default(),
+            scan_subscriptions: Mutex::new(HashMap::new()),
+            next_subscription_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Start the polling loop. No-op if it is already running.
+    pub fn start(&self) {
+        start_device_monitor_once(&self.app_handle, self.app_handle.state::<AppState>());
+    }
+
+    /// Ask the polling loop to exit after its current iteration.
+    pub fn stop(&self) {
+        self.app_handle
+            .state::<AppState>()
+            .device_monitor_stop
+            .store(true, Ordering::SeqCst);
+    }
+
+    /// Receive every subsequent hotplug event. Dropping the receiver unsubscribes.
+    pub(crate) fn subscribe(&self) -> Receiver<DeviceHotplugEvent> {
+        self.subscribers.subscribe()
+    }
+
+    pub(crate) fn broadcast(&self, event: &DeviceHotplugEvent) {
+        self.subscribers.broadcast(event);
+    }
+
+    /// Start a scan loop that reports to `channel` until unsubscribed or the
+    /// channel is closed. Returns the subscription id.
+    fn start_scan_subscription(&self, channel: Channel<ScanEvent>) -> Result<u64, String> {
+        let id = self.next_subscription_id.fetch_add(1, Ordering::SeqCst);
+        let stop = Arc::new(AtomicBool::new(false));
+        self.scan_subscriptions
+            .lock()
+            .map_err(|_| "scan_subscriptions mutex poisoned".to_string())?
+            .insert(id, stop.clone());
+
+        let app = self.app_handle.clone();
+        std::thread::spawn(move || {
+            let mut previous: Option<Vec<ConfirmedDeviceRecord>> = None;
+            while !stop.load(Ordering::SeqCst) {
+                let monitor_config = current_monitor_config(&app.state::<AppState>());
+                let registry = classifier_registry(&app.state::<AppState>());
+                match monitor_config.scan_depth.scan(&registry) {
+                    Ok(devices) => {
+                        let event = match &previous {
+                            None => Some(ScanEvent::DeviceList(devices.clone())),
+                            Some(before) => {
+                                let mut changes = bootforgeusb::scan_compare(before, &devices);
+                                changes.pair_reconnects();
+                                (!changes.is_empty()).then_some(ScanEvent::Change(changes))
+                            }
+                        };
+                        if let Some(event) = event {
+                            if channel.send(event).is_err() {
+                                break;
+                            }
+                        }
+                        previous = Some(devices);
+                    }
+                    Err(e) => eprintln!("[Tauri] scan subscription {} scan failed: {}", id, e),
+                }
+
+                std::thread::sleep(Duration::from_millis(monitor_config.poll_interval_ms));
+            }
+            if let Ok(mut subscriptions) = app.state::<DeviceWatcher>().scan_subscriptions.lock() {
+                subscriptions.remove(&id);
+            }
+        });
+        Ok(id)
+    }
+
+    fn stop_scan_subscription(&self, id: u64) -> Result<(), String> {
+        let stop = self
+            .scan_subscriptions
+            .lock()
+            .map_err(|_| "scan_subscriptions mutex poisoned".to_string())?
+            .remove(&id)
+            .ok_or_else(|| format!("No scan subscription with id {}", id))?;
+        stop.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+/// Stream scan results to the frontend over a Tauri channel.
+#[tauri::command]
+pub fn scan_subscribe(
+    watcher: tauri::State<'_, DeviceWatcher>,
+    channel: Channel<ScanEvent>,
+) -> Result<u64, String> {
+    watcher.start_scan_subscription(channel)
+}
+
+#[tauri::command]
+pub fn scan_unsubscribe(
+    watcher: tauri::State<'_, DeviceWatcher>,
+    subscription_id: u64,
+) -> Result<(), String> {
+    watcher.stop_scan_subscription(subscription_id)
+}
+
+/// Stop USB scanning for `duration_ms` (indefinitely without one). Devices
+/// seen before the pause are kept, so the first poll after it only reports
+/// what changed overall.
+#[tauri::command]
+pub fn device_monitor_pause(state: tauri::State<'_, AppState>, duration_ms: Option<u64>) -> Result<(), String> {
+    if duration_ms == Some(0) {
+        return Err("duration_ms must be greater than 0".to_string());
+    }
+    pause_monitor(&state.monitor_paused_until, now_ms(), duration_ms);
+    Ok(())
+}
+
+/// End a `device_monitor_pause`. Running flash jobs keep the monitor paused
+/// until they finish.
+#[tauri::command]
+pub fn device_monitor_resume(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.monitor_paused_until.store(0, Ordering::SeqCst);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_device_monitor_config(state: tauri::State<'_, AppState>) -> Result<DeviceMonitorConfig, String> {
+    state
+        .device_monitor_config
+        .lock()
+        .map(|c| c.clone())
+        .map_err(|_| "device_monitor_config mutex poisoned".to_string())
+}
+
+/// Takes effect on the monitor's next poll.
+#[tauri::command]
+pub fn set_device_monitor_config(
+    state: tauri::State<'_, AppState>,
+    config: DeviceMonitorConfig,
+) -> Result<(), String> {
+    config.validate()?;
+    *state
+        .device_monitor_config
+        .lock()
+        .map_err(|_| "device_monitor_config mutex poisoned".to_string())? = config;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bootforgeusb::model::{Evidence, UsbTransportEvidence};
+
+    fn event(uid: &str) -> DeviceHotplugEvent {
+        DeviceHotplugEvent {
+            event_type: "connected".to_string(),
+            device_uid: uid.to_string(),
+            platform: bootforgeusb::model::Platform::Android,
+            mode: "android_adb_confirmed".to_string(),
+            confidence: 0.9,
+            timestamp: "0".to_string(),
+            display_name: uid.to_string(),
+            matched_tool_ids: vec![],
+            speed: None,
+        }
+    }
+
+    #[test]
+    fn monitor_config_round_trips_and_validates_interval() {
+        let config: DeviceMonitorConfig =
+            serde_json::from_str(r#"{"pollIntervalMs": 500, "scanDepth": "usbOnly"}"#).unwrap();
+        assert_eq!(config.scan_depth, ScanDepth::UsbOnly);
+        assert!(config.validate().is_ok());
+
+        let from_file = DeviceMonitorConfig::from_workshop_config(&WorkshopConfig::default());
+        assert_eq!(from_file.scan_depth, ScanDepth::WithTools);
+        let too_fast = DeviceMonitorConfig { poll_interval_ms: 10, ..from_file };
+        assert!(too_fast.validate().is_err());
+    }
+
+    #[test]
+    fn pause_expires_and_is_never_shortened() {
+        let paused_until = AtomicU64::new(0);
+        assert!(!monitor_paused(&paused_until, 1_000));
+
+        pause_monitor(&paused_until, 1_000, Some(30_000));
+        assert!(monitor_paused(&paused_until, 30_999));
+        assert!(!monitor_paused(&paused_until, 31_000));
+
+        pause_monitor(&paused_until, 1_000, None);
+        pause_monitor(&paused_until, 2_000, Some(5_000));
+        assert!(monitor_paused(&paused_until, u64::MAX - 1));
+    }
+
+    #[test]
+    fn watchdog_reclaims_a_stalled_monitor() {
+        let started = AtomicBool::new(true);
+        let last_tick = AtomicU64::new(100_000);
+        let threshold = stall_threshold_ms(1_000, 2_000);
+        assert_eq!(threshold, MONITOR_STALL_MS);
+        assert_eq!(monitor_stall(&started, &last_tick, 125_000, threshold), None);
+        // Long poll intervals and scan timeouts are not mistaken for a stall
+        let slow = stall_threshold_ms(10_000, 10_000);
+        assert_eq!(slow, 60_000);
+        assert_eq!(monitor_stall(&started, &last_tick, 150_000, slow), None);
+
+        // The loop died without releasing the start flag
+        assert_eq!(monitor_stall(&started, &last_tick, 138_000, threshold), Some(38_000));
+        assert!(!crate::claim_device_monitor_start(&started));
+        started.store(false, Ordering::SeqCst);
+        assert!(crate::claim_device_monitor_start(&started));
+
+        // A deliberately stopped monitor is left alone
+        started.store(false, Ordering::SeqCst);
+        assert_eq!(monitor_stall(&started, &last_tick, 200_000, threshold), None);
+    }
+
+    #[test]
+    fn every_subscriber_receives_the_same_event() {
+        let subscribers = DeviceEventSubscribers::default();
+        let first = subscribers.subscribe();
+        let second = subscribers.subscribe();
+
+        subscribers.broadcast(&event("ABC123"));
+
+        assert_eq!(first.try_recv().unwrap().device_uid, "ABC123");
+        assert_eq!(second.try_recv().unwrap().device_uid, "ABC123");
+    }
+
+    #[test]
+    fn scan_event_is_tagged_for_the_frontend() {
+        let json = serde_json::to_value(ScanEvent::Change(DeviceChangeSet::default())).unwrap();
+        assert_eq!(json["type"], "change");
+        assert!(json["data"]["connected"].as_array().unwrap().is_empty());
+
+        let json = serde_json::to_value(ScanEvent::DeviceList(vec![])).unwrap();
+        assert_eq!(json["type"], "deviceList");
+    }
+
+    fn usb_record(uid: &str, matched: &[&str]) -> ConfirmedDeviceRecord {
+        ConfirmedDeviceRecord {
+            device_uid: uid.to_string(),
+            platform: Platform::Android,
+            mode: DeviceMode::UnknownUsb.as_str().to_string(),
+            confidence: 0.7,
+            evidence: Evidence {
+                usb: UsbTransportEvidence {
+                    vid: "18d1".to_string(),
+                    pid: "4ee7".to_string(),
+                    bus: 1,
+                    address: 4,
+                    ..Default::default()
+                },
+                tools: HashMap::new(),
+            },
+            notes: vec![],
+            matched_tool_ids: matched.iter().map(|s| s.to_string()).collect(),
+            marketing_name: None,
+            driver_status: None,
+        }
+    }
+
+    #[test]
+    fn usb_only_polls_keep_tool_confirmed_records() {
+        let mut confirmed = usb_record("SER1", &["SER1"]);
+        confirmed.mode = DeviceMode::AndroidAdbConfirmed.as_str().to_string();
+        let previous = vec![confirmed, usb_record("SER2", &[])];
+
+        let mut rebooted = usb_record("SER1", &[]);
+        let polled = [usb_record("SER1", &[]), usb_record("SER3", &[])];
+        let merged = merge_usb_only_scan(&previous, &polled);
+        let uids: Vec<_> = merged.iter().map(|d| d.device_uid.as_str()).collect();
+        assert_eq!(uids, ["SER1", "SER3"]);
+        assert_eq!(merged[0].mode, DeviceMode::AndroidAdbConfirmed.as_str());
+        assert_eq!(merged[0].matched_tool_ids, ["SER1"]);
+
+        // A new USB identity (e.g. rebooted into fastboot) replaces the stale record
+        rebooted.evidence.usb.pid = "4ee0".to_string();
+        let merged = merge_usb_only_scan(&previous, &[rebooted]);
+        assert_eq!(merged[0].mode, DeviceMode::UnknownUsb.as_str());
+    }
+
+    #[test]
+    fn confidence_decays_after_repeated_unmatched_probes() {
+        let mut seen = SeenDevices::default();
+        let unmatched = [usb_record("usb:1:4", &[])];
+        assert!(seen.observe(&unmatched).is_empty());
+        assert!(seen.observe(&unmatched).is_empty());
+
+        let decayed = seen.observe(&unmatched);
+        assert_eq!(decayed.len(), 1);
+        assert!((decayed[0].confidence - 0.4).abs() < 1e-6);
+        // 0.3 is the floor: reported once more, then no further updates.
+        assert!((seen.observe(&unmatched)[0].confidence - 0.3).abs() < 1e-6);
+        assert!(seen.observe(&unmatched).is_empty());
+
+        // A successful correlation resets the counter.
+        assert!(seen.observe(&[usb_record("usb:1:4", &["1A2B3C4D"])]).is_empty());
+        assert!(seen.observe(&unmatched).is_empty());
+        assert_eq!(seen.stale_probe_count["usb:1:4"], 1);
+    }
+
+    #[test]
+    fn dropped_subscribers_are_pruned() {
+        let subscribers = DeviceEventSubscribers::default();
+        let kept = subscribers.subscribe();
+        drop(subscribers.subscribe());
+
+        subscribers.broadcast(&event("ABC123"));
+
+        assert_eq!(subscribers.len(), 1);
+        assert!(kept.try_recv().is_ok());
+    }
+}
@@ -0,0 +1,411 @@
+// Qualcomm EDL Engine
+// Loads a firehose programmer over Sahara on a Qualcomm 9008 (EDL) serial port
+
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Serialize;
+
+const SAHARA_HELLO: u32 = 0x01;
+const SAHARA_HELLO_RESP: u32 = 0x02;
+const SAHARA_READ_DATA: u32 = 0x03;
+const SAHARA_END_IMAGE_TX: u32 = 0x04;
+const SAHARA_DONE: u32 = 0x05;
+const SAHARA_DONE_RESP: u32 = 0x06;
+const SAHARA_READ_DATA_64: u32 = 0x12;
+
+/// Hello and hello response are fixed at 0x30 bytes
+const SAHARA_HELLO_LEN: u32 = 0x30;
+/// Packets are small; anything larger means the stream is out of sync
+const SAHARA_MAX_PACKET_LEN: u32 = 0x1000;
+/// Host side of the protocol: version 2, compatible back to 1
+const SAHARA_VERSION: u32 = 2;
+const SAHARA_VERSION_SUPPORTED: u32 = 1;
+const SAHARA_MODE_IMAGE_TX_PENDING: u32 = 0;
+
+const EDL_BAUD_RATE: u32 = 115_200;
+const EDL_IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Hello packet the device sends as soon as the port is opened
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SaharaHello {
+    pub version: u32,
+    pub version_supported: u32,
+    pub max_command_len: u32,
+    pub mode: u32,
+}
+
+impl SaharaHello {
+    fn parse(body: &[u8]) -> Result<Self, String> {
+        if body.len() < 16 {
+            return Err(format!("Sahara hello too short ({} bytes)", body.len() + 8));
+        }
+        Ok(Self {
+            version: le_u32(body, 0),
+            version_supported: le_u32(body, 4),
+            max_command_len: le_u32(body, 8),
+            mode: le_u32(body, 12),
+        })
+    }
+}
+
+/// Host reply to [`SaharaHello`]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SaharaHelloResponse {
+    pub version: u32,
+    pub version_supported: u32,
+    pub status: u32,
+    pub mode: u32,
+}
+
+impl SaharaHelloResponse {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut fields = vec![
+            SAHARA_HELLO_RESP,
+            SAHARA_HELLO_LEN,
+            self.version,
+            self.version_supported,
+            self.status,
+            self.mode,
+        ];
+        fields.resize(SAHARA_HELLO_LEN as usize / 4, 0);
+        fields.iter().flat_map(|f| f.to_le_bytes()).collect()
+    }
+}
+
+/// Firehose XML request sent to a loaded programmer
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum FirehoseCommand {
+    Configure {
+        memory_name: String,
+        max_payload_size: u32,
+    },
+    #[allow(dead_code)] // issued once partition programming is implemented
+    Program {
+        sector_size: u32,
+        num_sectors: u64,
+        physical_partition: u32,
+        start_sector: u64,
+        filename: String,
+    },
+}
+
+impl FirehoseCommand {
+    pub(crate) fn to_xml(&self) -> String {
+        let element = match self {
+            FirehoseCommand::Configure { memory_name, max_payload_size } => format!(
+                "<configure MemoryName=\"{}\" MaxPayloadSizeToTargetInBytes=\"{}\" Verbose=\"0\" \
+                 AlwaysValidate=\"0\" ZLPAwareHost=\"1\" SkipStorageInit=\"0\" />",
+                xml_escape(memory_name),
+                max_payload_size
+            ),
+            FirehoseCommand::Program { sector_size, num_sectors, physical_partition, start_sector, filename } => {
+                format!(
+                    "<program SECTOR_SIZE_IN_BYTES=\"{}\" num_partition_sectors=\"{}\" \
+                     physical_partition_number=\"{}\" start_sector=\"{}\" filename=\"{}\" />",
+                    sector_size,
+                    num_sectors,
+                    physical_partition,
+                    start_sector,
+                    xml_escape(filename)
+                )
+            }
+        };
+        format!("<?xml version=\"1.0\" ?><data>{}</data>", element)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdlStatus {
+    pub port: String,
+    /// `programmer_loaded` once Sahara reports the upload complete
+    pub stage: String,
+    pub sahara_version: u32,
+    pub programmer_bytes: u64,
+    /// The programmer acknowledged a Firehose `<configure>`
+    pub firehose_ready: bool,
+}
+
+fn le_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn le_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from(le_u32(bytes, offset)) | (u64::from(le_u32(bytes, offset + 4)) << 32)
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Next Sahara packet as `(command, body after the 8-byte header)`.
+fn read_sahara_packet<R: Read>(port: &mut R) -> Result<(u32, Vec<u8>), String> {
+    let mut header = [0u8; 8];
+    port.read_exact(&mut header)
+        .map_err(|e| format!("Failed to read Sahara packet: {e}"))?;
+    let command = le_u32(&header, 0);
+    let length = le_u32(&header, 4);
+    if !(8..=SAHARA_MAX_PACKET_LEN).contains(&length) {
+        return Err(format!("Invalid Sahara packet length {:#x} for command {:#x}", length, command));
+    }
+    let mut body = vec![0u8; length as usize - 8];
+    port.read_exact(&mut body)
+        .map_err(|e| format!("Failed to read Sahara packet: {e}"))?;
+    Ok((command, body))
+}
+
+/// `image[offset..offset + length]`, or an error if the device asks past the end.
+fn image_chunk(image: &[u8], offset: u64, length: u64) -> Result<&[u8], String> {
+    let end = offset.checked_add(length).filter(|end| *end <= image.len() as u64);
+    match end {
+        Some(end) => Ok(&image[offset as usize..end as usize]),
+        None => Err(format!(
+            "Device requested bytes {}..{} of a {}-byte programmer",
+            offset,
+            offset.saturating_add(length),
+            image.len()
+        )),
+    }
+}
+
+/// Run the Sahara exchange: hello, hello response, serve read requests from
+/// `image`, then done. Returns the device hello once the upload completes.
+pub(crate) fn sahara_upload<T: Read + Write>(port: &mut T, image: &[u8]) -> Result<SaharaHello, String> {
+    let (command, body) = read_sahara_packet(port)?;
+    if command != SAHARA_HELLO {
+        return Err(format!("Expected Sahara hello, got command {:#x}", command));
+    }
+    let hello = SaharaHello::parse(&body)?;
+    let response = SaharaHelloResponse {
+        version: SAHARA_VERSION,
+        version_supported: SAHARA_VERSION_SUPPORTED,
+        status: 0,
+        mode: SAHARA_MODE_IMAGE_TX_PENDING,
+    };
+    port.write_all(&response.to_bytes())
+        .map_err(|e| format!("Failed to send Sahara hello response: {e}"))?;
+
+    loop {
+        let (command, body) = read_sahara_packet(port)?;
+        match command {
+            SAHARA_READ_DATA if body.len() >= 12 => {
+                let chunk = image_chunk(image, u64::from(le_u32(&body, 4)), u64::from(le_u32(&body, 8)))?;
+                port.write_all(chunk).map_err(|e| format!("Failed to send programmer data: {e}"))?;
+            }
+            SAHARA_READ_DATA_64 if body.len() >= 24 => {
+                let chunk = image_chunk(image, le_u64(&body, 8), le_u64(&body, 16))?;
+                port.write_all(chunk).map_err(|e| format!("Failed to send programmer data: {e}"))?;
+            }
+            SAHARA_END_IMAGE_TX if body.len() >= 8 => {
+                let status = le_u32(&body, 4);
+                if status != 0 {
+                    return Err(format!("Device rejected the programmer (Sahara status {:#x})", status));
+                }
+                let done: Vec<u8> = [SAHARA_DONE, 8].iter().flat_map(|f| f.to_le_bytes()).collect();
+                port.write_all(&done).map_err(|e| format!("Failed to send Sahara done: {e}"))?;
+            }
+            SAHARA_DONE_RESP => return Ok(hello),
+            other => return Err(format!("Unexpected Sahara command {:#x} ({} bytes)", other, body.len() + 8)),
+        }
+    }
+}
+
+/// Send a Firehose command and wait for its `<response>`; true on ACK.
+/// Log elements the programmer emits before the response are skipped.
+fn firehose_request<T: Read + Write>(port: &mut T, command: &FirehoseCommand) -> Result<bool, String> {
+    port.write_all(command.to_xml().as_bytes())
+        .map_err(|e| format!("Failed to send Firehose command: {e}"))?;
+    let mut received = String::new();
+    let mut buffer = [0u8; 4096];
+    loop {
+        let n = port
+            .read(&mut buffer)
+            .map_err(|e| format!("No Firehose response: {e}"))?;
+        if n == 0 {
+            return Err("Port closed before the Firehose response".to_string());
+        }
+        received.push_str(&String::from_utf8_lossy(&buffer[..n]));
+        if let Some(start) = received.find("<response") {
+            if let Some(end) = received[start..].find("/>") {
+                return Ok(received[start..start + end].contains("value=\"ACK\""));
+            }
+        }
+    }
+}
+
+/// Firehose `MemoryName` values; the storage type differs per SoC/board and
+/// the device cannot report it before the programmer is running.
+pub(crate) const EDL_MEMORY_TYPES: &[&str] = &["emmc", "ufs", "nand", "spinor"];
+
+/// The lowercased memory type if it is one Firehose understands.
+pub(crate) fn validate_memory_name(memory_name: &str) -> Result<String, String> {
+    let memory_name = memory_name.trim().to_ascii_lowercase();
+    if !EDL_MEMORY_TYPES.contains(&memory_name.as_str()) {
+        return Err(format!("EDL memory type must be one of: {}", EDL_MEMORY_TYPES.join(", ")));
+    }
+    Ok(memory_name)
+}
+
+/// Loads a firehose programmer on one EDL serial port (`COM5`, `/dev/ttyUSB0`).
+pub(crate) struct QualcommEdlEngine {
+    port: String,
+}
+
+impl QualcommEdlEngine {
+    pub(crate) fn new(port: &str) -> Self {
+        Self { port: port.trim().to_string() }
+    }
+
+    /// Upload `programmer` over Sahara, then configure Firehose for the
+    /// `memory_name` storage (`emmc`, `ufs`, ...). A NAK is an error.
+    pub(crate) fn load_programmer(&self, programmer: &Path, memory_name: &str) -> Result<EdlStatus, String> {
+        let memory_name = validate_memory_name(memory_name)?;
+        let image = std::fs::read(programmer)
+            .map_err(|e| format!("Failed to read programmer {}: {e}", programmer.display()))?;
+        if image.is_empty() {
+            return Err(format!("Programmer {} is empty", programmer.display()));
+        }
+        let mut port = serialport::new(&self.port, EDL_BAUD_RATE)
+            .timeout(EDL_IO_TIMEOUT)
+            .open()
+            .map_err(|e| format!("Failed to open EDL port {}: {e}", self.port))?;
+
+        let hello = sahara_upload(&mut port, &image)?;
+        let configure = FirehoseCommand::Configure {
+            memory_name: memory_name.clone(),
+            max_payload_size: 1024 * 1024,
+        };
+        if !firehose_request(&mut port, &configure)? {
+            return Err(format!(
+                "Programmer loaded but Firehose rejected (NAK) configure for {} storage",
+                memory_name
+            ));
+        }
+
+        Ok(EdlStatus {
+            port: self.port.clone(),
+            stage: "programmer_loaded".to_string(),
+            sahara_version: hello.version,
+            programmer_bytes: image.len() as u64,
+            firehose_ready: true,
+        })
+    }
+}
+
+/// Load a firehose programmer onto the device waiting in EDL mode on `port`
+/// and configure it for `memory_type` storage (`emmc`, `ufs`, `nand`, `spinor`).
+#[tauri::command]
+pub async fn edl_load_programmer(port: String, programmer_path: String, memory_type: String) -> Result<EdlStatus, String> {
+    tokio::task::spawn_blocking(move || {
+        QualcommEdlEngine::new(&port).load_programmer(Path::new(programmer_path.trim()), &memory_type)
+    })
+    .await
+    .map_err(|e| format!("EDL task failed: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Scripted device: reads come from `input`, writes are captured
+    struct MockPort {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl Read for MockPort {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for MockPort {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.output.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn packet(fields: &[u32]) -> Vec<u8> {
+        fields.iter().flat_map(|f| f.to_le_bytes()).collect()
+    }
+
+    fn hello() -> Vec<u8> {
+        let mut fields = vec![SAHARA_HELLO, SAHARA_HELLO_LEN, 2, 1, 0x400, SAHARA_MODE_IMAGE_TX_PENDING];
+        fields.resize(12, 0);
+        packet(&fields)
+    }
+
+    #[test]
+    fn sahara_upload_serves_requested_chunks() {
+        let image = b"ELFPROGRAMMER".to_vec();
+        let mut input = hello();
+        input.extend(packet(&[SAHARA_READ_DATA, 0x14, 13, 0, 4]));
+        input.extend(packet(&[SAHARA_READ_DATA_64, 0x20, 13, 0, 4, 0, 9, 0]));
+        input.extend(packet(&[SAHARA_END_IMAGE_TX, 0x10, 13, 0]));
+        input.extend(packet(&[SAHARA_DONE_RESP, 0x0c, 1]));
+        let mut port = MockPort { input: Cursor::new(input), output: Vec::new() };
+
+        let hello = sahara_upload(&mut port, &image).unwrap();
+        assert_eq!(hello.version, 2);
+        assert_eq!(hello.max_command_len, 0x400);
+
+        let response = SaharaHelloResponse { version: 2, version_supported: 1, status: 0, mode: 0 }.to_bytes();
+        assert_eq!(response.len(), SAHARA_HELLO_LEN as usize);
+        let mut expected = response;
+        expected.extend_from_slice(b"ELFPROGRAMMER");
+        expected.extend(packet(&[SAHARA_DONE, 8]));
+        assert_eq!(port.output, expected);
+    }
+
+    #[test]
+    fn sahara_upload_reports_rejection_and_bad_reads() {
+        let mut input = hello();
+        input.extend(packet(&[SAHARA_END_IMAGE_TX, 0x10, 13, 0x1c]));
+        let mut port = MockPort { input: Cursor::new(input), output: Vec::new() };
+        assert!(sahara_upload(&mut port, b"ELF").unwrap_err().contains("0x1c"));
+
+        let mut input = hello();
+        input.extend(packet(&[SAHARA_READ_DATA, 0x14, 13, 2, 8]));
+        let mut port = MockPort { input: Cursor::new(input), output: Vec::new() };
+        assert!(sahara_upload(&mut port, b"ELF").unwrap_err().contains("3-byte programmer"));
+
+        let mut port = MockPort { input: Cursor::new(packet(&[SAHARA_DONE_RESP, 0x0c, 1])), output: Vec::new() };
+        assert!(sahara_upload(&mut port, b"ELF").unwrap_err().contains("Expected Sahara hello"));
+    }
+
+    #[test]
+    fn firehose_request_skips_logs_until_the_response() {
+        let reply = "<?xml version=\"1.0\" ?><data><log value=\"INFO: Binary build date\" /></data>\
+                     <?xml version=\"1.0\" ?><data><response value=\"ACK\" /></data>";
+        let mut port = MockPort { input: Cursor::new(reply.as_bytes().to_vec()), output: Vec::new() };
+        let configure = FirehoseCommand::Configure { memory_name: "ufs".to_string(), max_payload_size: 1048576 };
+        assert_eq!(firehose_request(&mut port, &configure), Ok(true));
+        let sent = String::from_utf8(port.output).unwrap();
+        assert!(sent.starts_with("<?xml version=\"1.0\" ?><data><configure MemoryName=\"ufs\""));
+        assert!(sent.contains("MaxPayloadSizeToTargetInBytes=\"1048576\""));
+
+        let nak = "<?xml version=\"1.0\" ?><data><response value=\"NAK\" /></data>";
+        let mut port = MockPort { input: Cursor::new(nak.as_bytes().to_vec()), output: Vec::new() };
+        assert_eq!(firehose_request(&mut port, &configure), Ok(false));
+    }
+
+    #[test]
+    fn memory_type_must_be_a_firehose_memory_name() {
+        assert_eq!(validate_memory_name(" eMMC ").unwrap(), "emmc");
+        assert_eq!(validate_memory_name("ufs").unwrap(), "ufs");
+        assert!(validate_memory_name("").is_err());
+        assert!(validate_memory_name("sdcard").is_err());
+    }
+}
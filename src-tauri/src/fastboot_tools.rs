@@ -0,0 +1,908 @@
+// Fastboot Device Tools
+// One-shot fastboot queries that run outside of a flash job
+
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use libbootforge::device_state::SecurityState;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    get_log_directory, is_allowed_partition, iso_now, normalize_slot, partition_allowlist, run_command_with_timeout,
+    spawn_line_reader, terminate_process, validate_partition_name, AppState, CommandRunError,
+};
+use crate::config::fastboot_program;
+use crate::log_rotate::{LogRotator, MAX_LOG_SIZE_BYTES};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+const FASTBOOT_QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+/// Erasing a large userdata partition can take minutes on eMMC devices
+const FASTBOOT_ERASE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// vbmeta images are a few KiB; this only guards against a hung transfer
+const FASTBOOT_VBMETA_TIMEOUT: Duration = Duration::from_secs(2 * 60);
+
+/// Partitions that leave the device unbootable when erased; `fastboot_erase`
+/// refuses them unless `override_safety` is set.
+const PROTECTED_ERASE_PARTITIONS: &[&str] = &["boot", "bootloader", "radio", "vbmeta"];
+
+/// Serials whose vbmeta was last flashed by `fastboot_flash_vbmeta` with
+/// verification disabled. AVB no longer protects those devices, so erasing
+/// vbmeta on them does not need `override_safety`.
+static VERIFICATION_DISABLED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// What the caller must pass to `fastboot_wipe_super`
+const WIPE_SUPER_CONFIRM_TOKEN: &str = "WIPE_SUPER";
+
+/// `fastboot oem` commands that only toggle charging behaviour or read state.
+/// Anything else goes through `fastboot_oem_command_force`.
+const SAFE_OEM_COMMANDS: &[&str] = &[
+    "disable-charger-screen",
+    "enable-charger-screen",
+    "off-mode-charge",
+    "get_unlock_ability",
+];
+
+/// Run `fastboot -s <serial> <args...>` and return combined stdout+stderr.
+///
+/// fastboot writes getvar results to stderr, so both streams are merged.
+fn run_fastboot_query(device_serial: &str, args: &[&str]) -> Result<String, String> {
+    run_fastboot_with_timeout(device_serial, args, FASTBOOT_QUERY_TIMEOUT)
+}
+
+fn run_fastboot_with_timeout(
+    device_serial: &str,
+    args: &[&str],
+    timeout: Duration,
+) -> Result<String, String> {
+    if device_serial.trim().is_empty() {
+        return Err("device_serial is required".to_string());
+    }
+
+    let mut cmd = Command::new(fastboot_program());
+    cmd.arg("-s").arg(device_serial.trim()).args(args);
+    let output = run_command_with_timeout(cmd, timeout).map_err(|e| match e {
+        CommandRunError::Spawn(_) => format!("fastboot not found in PATH: {e}"),
+        CommandRunError::TimedOut(_) => format!("Device {} is not responding to fastboot: {e}", device_serial),
+        CommandRunError::Wait(_) => format!("fastboot failed: {e}"),
+    })?;
+
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    if !output.status.success() {
+        return Err(format!("fastboot {} failed: {}", args.join(" "), combined.trim()));
+    }
+    Ok(combined)
+}
+
+/// Extract `<variable>: <value>` from getvar output.
+///
+/// Handles both plain lines and the `(bootloader) ` prefix some devices emit.
+fn parse_getvar_value(output: &str, variable: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let line = line.trim().trim_start_matches("(bootloader)").trim();
+        let (key, value) = line.split_once(':')?;
+        if key.trim() == variable {
+            Some(value.trim().to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// Parse `fastboot getvar all` output into a variable map.
+///
+/// Keys may themselves contain colons (`partition-size:boot_a: 0x4000000`), so
+/// the split happens at the last `": "` when present, otherwise at the last `:`.
+/// Status lines such as `all:` and `Finished. Total time` are skipped.
+pub(crate) fn parse_getvar_all(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches("(bootloader)").trim();
+            if line.is_empty() || line.starts_with("Finished") || line.starts_with("OKAY") {
+                return None;
+            }
+            let (key, value) = line.rsplit_once(": ").or_else(|| line.rsplit_once(':'))?;
+            let key = key.trim();
+            if key.is_empty() || key == "all" {
+                return None;
+            }
+            Some((key.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+#[tauri::command(async)]
+pub fn fastboot_getvar(device_serial: String, variable: String) -> Result<String, String> {
+    let variable = variable.trim();
+    if variable.is_empty() || variable.contains(char::is_whitespace) {
+        return Err(format!("Invalid fastboot variable name: '{}'", variable));
+    }
+    let output = run_fastboot_query(&device_serial, &["getvar", variable])?;
+    parse_getvar_value(&output, variable)
+        .ok_or_else(|| format!("Device {} did not report {}", device_serial, variable))
+}
+
+#[tauri::command(async)]
+pub fn fastboot_getvar_all(device_serial: String) -> Result<HashMap<String, String>, String> {
+    let output = run_fastboot_query(&device_serial, &["getvar", "all"])?;
+    let vars = parse_getvar_all(&output);
+    if vars.is_empty() {
+        return Err(format!("Device {} returned no variables for getvar all", device_serial));
+    }
+    Ok(vars)
+}
+
+/// Identity reported by the bootloader
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FastbootDeviceInfo {
+    pub product: String,
+    pub variant: Option<String>,
+    pub board: Option<String>,
+    /// 2 on A/B devices
+    pub slot_count: Option<u8>,
+}
+
+pub(crate) fn device_info_from_vars(device_serial: &str, vars: &HashMap<String, String>) -> Result<FastbootDeviceInfo, String> {
+    let get = |key: &str| vars.get(key).map(|v| v.trim()).filter(|v| !v.is_empty());
+    let product = get("product")
+        .ok_or_else(|| format!("Device {} did not report product", device_serial))?
+        .to_string();
+    Ok(FastbootDeviceInfo {
+        product,
+        variant: get("variant").map(str::to_string),
+        board: get("board").map(str::to_string),
+        slot_count: get("slot-count").and_then(|v| v.parse().ok()),
+    })
+}
+
+#[tauri::command(async)]
+pub fn fastboot_getvar_product(device_serial: String) -> Result<FastbootDeviceInfo, String> {
+    let vars = fastboot_getvar_all(device_serial.clone())?;
+    device_info_from_vars(&device_serial, &vars)
+}
+
+/// Bootloader lock state reported by fastboot
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BootloaderStatus {
+    pub unlocked: bool,
+    /// OEM unlocking enabled in developer options (Pixel `unlock-allowed`).
+    /// Always true once the bootloader is unlocked.
+    pub unlock_allowed: bool,
+    /// green / yellow / orange / red
+    pub verified_boot_state: Option<String>,
+}
+
+fn bootloader_status_from_vars(
+    device_serial: &str,
+    vars: &HashMap<String, String>,
+) -> Result<BootloaderStatus, String> {
+    let unlocked = !SecurityState::from_fastboot_vars(vars)
+        .bootloader_locked
+        .ok_or_else(|| format!("Device {} did not report unlocked", device_serial))?;
+    let unlock_allowed = unlocked
+        || vars
+            .get("unlock-allowed")
+            .is_some_and(|v| matches!(v.trim(), "yes" | "true" | "1"));
+    Ok(BootloaderStatus {
+        unlocked,
+        unlock_allowed,
+        verified_boot_state: vars
+            .get("verified-boot-state")
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty()),
+    })
+}
+
+/// Query `unlocked`, `unlock-allowed` and `verified-boot-state`, and record
+/// the result in the stored device state.
+///
+/// Only `unlocked` is required; the other two are vendor-specific and many
+/// bootloaders reject them as unknown variables.
+#[tauri::command(async)]
+pub fn bootloader_unlock_check(
+    state: tauri::State<'_, AppState>,
+    device_serial: String,
+) -> Result<BootloaderStatus, String> {
+    let output = run_fastboot_query(&device_serial, &["getvar", "unlocked"])?;
+    let mut vars = parse_getvar_all(&output);
+    for variable in ["unlock-allowed", "verified-boot-state"] {
+        if let Ok(output) = run_fastboot_query(&device_serial, &["getvar", variable]) {
+            vars.extend(parse_getvar_all(&output));
+        }
+    }
+    let status = bootloader_status_from_vars(&device_serial, &vars)?;
+
+    // Device state is keyed by the scanned device_uid, which may differ from the fastboot serial
+    let serial = device_serial.trim();
+    let device_uid = state
+        .last_scan
+        .lock()
+        .map_err(|_| "last_scan mutex poisoned".to_string())?
+        .iter()
+        .find(|d| d.device_uid == serial || d.matched_tool_ids.iter().any(|id| id == serial))
+        .map_or_else(|| serial.to_string(), |d| d.device_uid.clone());
+    let security = SecurityState::from_fastboot_vars(&vars);
+    state.device_states.update(&device_uid, |s| {
+        s.security.bootloader_locked = security.bootloader_locked;
+        if security.verified_boot.is_some() {
+            s.security.verified_boot = security.verified_boot;
+        }
+    });
+    Ok(status)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FlashRawOutputEvent {
+    device_serial: String,
+    partition: String,
+    line: String,
+}
+
+/// Flash a single image outside of a flash job.
+///
+/// Output is streamed as `flash-raw-output` events and also returned in full.
+/// Only partitions in the active allowlist are accepted. The fastboot pid is tracked per device
+/// so `fastboot_flash_raw_cancel` can terminate it.
+#[tauri::command(async)]
+pub fn fastboot_flash_raw(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+    device_serial: String,
+    partition: String,
+    image_path: String,
+) -> Result<String, String> {
+    let device_serial = device_serial.trim().to_string();
+    let partition = partition.trim().to_string();
+    if device_serial.is_empty() {
+        return Err("device_serial is required".to_string());
+    }
+    validate_partition_name(&partition)?;
+    let (allowed_partitions, _) = partition_allowlist(&state)?;
+    if !is_allowed_partition(&partition, &allowed_partitions) {
+        return Err(format!("Partition '{}' is not in the partition allowlist", partition));
+    }
+    if !Path::new(image_path.trim()).is_file() {
+        return Err(format!("Image file not found: {}", image_path));
+    }
+
+    let mut cmd = Command::new(fastboot_program());
+    cmd.arg("-s")
+        .arg(&device_serial)
+        .arg("flash")
+        .arg(&partition)
+        .arg(image_path.trim());
+    cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    {
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    // Check, spawn and record the pid under one lock so two calls for the
+    // same device cannot both start fastboot.
+    let mut child = {
+        let mut pids = state
+            .raw_flash_pids
+            .lock()
+            .map_err(|_| "raw_flash_pids mutex poisoned".to_string())?;
+        if pids.contains_key(&device_serial) {
+            return Err(format!("A raw flash is already running on {}", device_serial));
+        }
+        let child = cmd.spawn().map_err(|e| format!("fastboot not found in PATH: {e}"))?;
+        pids.insert(device_serial.clone(), child.id());
+        child
+    };
+
+    let (tx, rx) = std::sync::mpsc::channel::<String>();
+    let readers = [
+        child.stdout.take().map(|pipe| spawn_line_reader(pipe, tx.clone())),
+        child.stderr.take().map(|pipe| spawn_line_reader(pipe, tx.clone())),
+    ];
+    drop(tx);
+
+    let mut output = String::new();
+    let window = app_handle.get_webview_window("main");
+    for line in rx {
+        let line = line.trim_end().to_string();
+        if line.is_empty() {
+            continue;
+        }
+        output.push_str(&line);
+        output.push('\n');
+        if let Some(window) = window.as_ref() {
+            let _ = window.emit(
+                "flash-raw-output",
+                FlashRawOutputEvent {
+                    device_serial: device_serial.clone(),
+                    partition: partition.clone(),
+                    line,
+                },
+            );
+        }
+    }
+    for reader in readers.into_iter().flatten() {
+        let _ = reader.join();
+    }
+
+    let status = child.wait();
+    // A missing entry means fastboot_flash_raw_cancel already claimed the pid.
+    let cancelled = state
+        .raw_flash_pids
+        .lock()
+        .map(|mut pids| pids.remove(&device_serial).is_none())
+        .unwrap_or(false);
+    let status = status.map_err(|e| format!("Failed to wait for fastboot: {e}"))?;
+
+    if cancelled {
+        Err(format!("fastboot flash {} cancelled", partition))
+    } else if status.success() {
+        Ok(output)
+    } else {
+        Err(format!("fastboot flash {} failed: {}", partition, output.trim()))
+    }
+}
+
+#[tauri::command]
+pub fn fastboot_flash_raw_cancel(
+    state: tauri::State<'_, AppState>,
+    device_serial: String,
+) -> Result<(), String> {
+    let pid = state
+        .raw_flash_pids
+        .lock()
+        .map_err(|_| "raw_flash_pids mutex poisoned".to_string())?
+        .remove(device_serial.trim())
+        .ok_or_else(|| format!("No raw flash running on {}", device_serial))?;
+    terminate_process(pid)
+}
+
+fn is_protected_from_erase(partition: &str) -> bool {
+    let base = partition
+        .strip_suffix("_a")
+        .or_else(|| partition.strip_suffix("_b"))
+        .unwrap_or(partition);
+    PROTECTED_ERASE_PARTITIONS.contains(&base)
+}
+
+/// vbmeta is safe to erase on a device whose verification was disabled
+fn is_safe_to_erase(device_serial: &str, partition: &str) -> bool {
+    let base = partition
+        .strip_suffix("_a")
+        .or_else(|| partition.strip_suffix("_b"))
+        .unwrap_or(partition);
+    base == "vbmeta"
+        && VERIFICATION_DISABLED
+            .lock()
+            .is_ok_and(|serials| serials.contains(device_serial))
+}
+
+/// Token the caller must echo back to erase `partition`
+fn erase_confirm_token(partition: &str) -> String {
+    format!("ERASE_{}", partition.to_uppercase())
+}
+
+/// Append a line to `<log_dir>/<file_name>`. Logging failures are non-fatal.
+fn append_audit_log(file_name: &str, line: &str) {
+    let log_dir = get_log_directory();
+    if std::fs::create_dir_all(&log_dir).is_err() {
+        return;
+    }
+    let log_file = log_dir.join(file_name);
+    let _ = LogRotator::rotate_if_needed(&log_file, MAX_LOG_SIZE_BYTES);
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_file)
+    {
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+fn log_erase(device_serial: &str, partition: &str, outcome: &str) {
+    append_audit_log(
+        "fastboot-erase.log",
+        &format!("[{}] {} erase {} -> {}", iso_now(), device_serial, partition, outcome),
+    );
+}
+
+fn log_oem_command(device_serial: &str, command_line: &str, outcome: &str) {
+    append_audit_log(
+        "fastboot-oem.log",
+        &format!("[{}] {} oem {} -> {}", iso_now(), device_serial, command_line, outcome),
+    );
+}
+
+fn log_vbmeta_flash(device_serial: &str, command_line: &str, outcome: &str) {
+    append_audit_log(
+        "fastboot-vbmeta.log",
+        &format!("[{}] {} {} -> {}", iso_now(), device_serial, command_line, outcome),
+    );
+}
+
+/// Erase a single partition with `fastboot erase`.
+///
+/// `confirm_token` must be `ERASE_<PARTITION>` (uppercased) so a stray call
+/// cannot wipe anything. boot, bootloader, radio and vbmeta additionally need
+/// `override_safety`, except vbmeta on a device whose verification was
+/// disabled by `fastboot_flash_vbmeta`.
+#[tauri::command(async)]
+pub fn fastboot_erase(
+    device_serial: String,
+    partition: String,
+    confirm_token: String,
+    override_safety: bool,
+) -> Result<String, String> {
+    let partition = partition.trim().to_string();
+    validate_partition_name(&partition)?;
+    if confirm_token != erase_confirm_token(&partition) {
+        return Err(format!(
+            "Confirmation token mismatch: erasing {} needs ERASE_ followed by the partition name in upper case",
+            partition
+        ));
+    }
+    if is_protected_from_erase(&partition) && !override_safety && !is_safe_to_erase(device_serial.trim(), &partition) {
+        return Err(format!(
+            "Refusing to erase protected partition '{}' without override_safety",
+            partition
+        ));
+    }
+
+    let result = run_fastboot_with_timeout(&device_serial, &["erase", &partition], FASTBOOT_ERASE_TIMEOUT);
+    match &result {
+        Ok(_) => log_erase(device_serial.trim(), &partition, "ok"),
+        Err(e) => log_erase(device_serial.trim(), &partition, &format!("error: {}", e)),
+    }
+    result
+}
+
+fn vbmeta_flash_args(vbmeta_path: &str, disable_verity: bool, disable_verification: bool) -> Vec<&str> {
+    let mut args = vec!["flash", "vbmeta"];
+    if disable_verity {
+        args.push("--disable-verity");
+    }
+    if disable_verification {
+        args.push("--disable-verification");
+    }
+    args.push(vbmeta_path);
+    args
+}
+
+/// Flash vbmeta, optionally clearing its verity and verification flags.
+///
+/// The flags are applied by fastboot while flashing, so a stock vbmeta image
+/// works. Disable them when flashing a patched boot or kernel, which
+/// otherwise fails verified boot.
+///
+/// **Warning:** disabling verification voids Android Verified Boot's
+/// guarantees. The device will boot any image, including tampered ones,
+/// until a stock vbmeta is flashed again.
+#[tauri::command(async)]
+pub fn fastboot_flash_vbmeta(
+    device_serial: String,
+    vbmeta_path: String,
+    disable_verity: bool,
+    disable_verification: bool,
+) -> Result<String, String> {
+    let device_serial = device_serial.trim().to_string();
+    if device_serial.is_empty() {
+        return Err("device_serial is required".to_string());
+    }
+    let vbmeta_path = vbmeta_path.trim();
+    if !Path::new(vbmeta_path).is_file() {
+        return Err(format!("vbmeta image not found: {}", vbmeta_path));
+    }
+
+    let args = vbmeta_flash_args(vbmeta_path, disable_verity, disable_verification);
+    let command_line = args.join(" ");
+    log_vbmeta_flash(&device_serial, &command_line, "started");
+    let result = run_fastboot_with_timeout(&device_serial, &args, FASTBOOT_VBMETA_TIMEOUT);
+    match &result {
+        Ok(_) => {
+            if let Ok(mut serials) = VERIFICATION_DISABLED.lock() {
+                if disable_verification {
+                    serials.insert(device_serial.clone());
+                } else {
+                    serials.remove(&device_serial);
+                }
+            }
+            let outcome = match (disable_verity, disable_verification) {
+                (false, false) => "ok (verified boot intact)",
+                _ => "ok (AVB guarantees voided)",
+            };
+            log_vbmeta_flash(&device_serial, &command_line, outcome);
+        }
+        Err(e) => log_vbmeta_flash(&device_serial, &command_line, &format!("error: {}", e)),
+    }
+    result
+}
+
+/// `oem_command` must be a single token; arguments may not contain line breaks.
+fn validate_oem_command(oem_command: &str, args: &[String]) -> Result<(), String> {
+    if oem_command.is_empty()
+        || !oem_command
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    {
+        return Err(format!("Invalid OEM command: '{}'", oem_command));
+    }
+    if args.iter().any(|a| a.chars().any(char::is_control)) {
+        return Err("OEM command arguments may not contain control characters".to_string());
+    }
+    Ok(())
+}
+
+/// Run `fastboot oem <oem_command> <args...>` and audit it. Commands outside
+/// [`SAFE_OEM_COMMANDS`] need `force`.
+fn run_oem_command(device_serial: &str, oem_command: &str, args: &[String], force: bool) -> Result<String, String> {
+    let oem_command = oem_command.trim();
+    validate_oem_command(oem_command, args)?;
+    if !force && !SAFE_OEM_COMMANDS.contains(&oem_command) {
+        return Err(format!(
+            "OEM command '{}' is not in the safe list; use fastboot_oem_command_force",
+            oem_command
+        ));
+    }
+
+    let mut full_args = vec!["oem", oem_command];
+    full_args.extend(args.iter().map(String::as_str));
+    let command_line = full_args[1..].join(" ");
+    let result = run_fastboot_query(device_serial, &full_args);
+    match &result {
+        Ok(_) => log_oem_command(device_serial.trim(), &command_line, if force { "ok (forced)" } else { "ok" }),
+        Err(e) => log_oem_command(device_serial.trim(), &command_line, &format!("error: {}", e)),
+    }
+    result
+}
+
+/// Run an allowlisted `fastboot oem` command (charger screen, off-mode
+/// charging, get_unlock_ability) and return fastboot's full output.
+#[tauri::command(async)]
+pub fn fastboot_oem_command(device_serial: String, oem_command: String, args: Vec<String>) -> Result<String, String> {
+    run_oem_command(&device_serial, &oem_command, &args, false)
+}
+
+/// Run any `fastboot oem` command. `force` must be true to confirm the
+/// command was chosen deliberately.
+#[tauri::command(async)]
+pub fn fastboot_oem_command_force(
+    device_serial: String,
+    oem_command: String,
+    args: Vec<String>,
+    force: bool,
+) -> Result<String, String> {
+    if !force {
+        return Err("force must be true to run an OEM command outside the safe list".to_string());
+    }
+    run_oem_command(&device_serial, &oem_command, &args, true)
+}
+
+/// `true`/`yes`/`1` getvar values
+fn is_truthy_var(value: &str) -> bool {
+    matches!(value.trim().to_ascii_lowercase().as_str(), "true" | "yes" | "1")
+}
+
+/// `getvar partition-size:<partition>` in bytes.
+pub(crate) fn fastboot_partition_size(device_serial: &str, partition: &str) -> Result<u64, String> {
+    let size_var = format!("partition-size:{}", partition);
+    let output = run_fastboot_query(device_serial, &["getvar", &size_var])?;
+    // The variable name itself contains a colon, which parse_getvar_value would split on.
+    parse_getvar_all(&output)
+        .get(&size_var)
+        .and_then(|v| match v.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => v.parse().ok(),
+        })
+        .ok_or_else(|| format!("Device {} did not report {}", device_serial, size_var))
+}
+
+/// Reset the dynamic partition layout with `fastboot wipe-super`.
+///
+/// `wipe-super` writes the empty partition table from `super_empty.img` into
+/// every metadata slot of `super`: system, vendor, product and the other
+/// logical partitions disappear, but the table stays valid so fastbootd can
+/// recreate and flash them. `fastboot erase super` instead erases the raw
+/// block device, metadata included, and the device has no logical partitions
+/// until a complete super image is flashed.
+///
+/// This destroys every logical partition, so `confirm_token` must be
+/// `WIPE_SUPER`. `super_empty_path` must be the super_empty.img from the
+/// device's own factory image: a layout built on the host would lose its
+/// partition groups and virtual A/B metadata. Devices that do not report
+/// `dynamic-partition` are refused. The returned output starts with a
+/// warning naming the slot that stops booting.
+#[tauri::command(async)]
+pub fn fastboot_wipe_super(
+    device_serial: String,
+    super_empty_path: Option<String>,
+    confirm_token: String,
+) -> Result<String, String> {
+    let device_serial = device_serial.trim().to_string();
+    if device_serial.is_empty() {
+        return Err("device_serial is required".to_string());
+    }
+    if confirm_token != WIPE_SUPER_CONFIRM_TOKEN {
+        return Err("Confirmation token mismatch: wipe-super needs the wipe-super confirmation token".to_string());
+    }
+    let image = match super_empty_path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(path) if Path::new(path).is_file() => std::path::PathBuf::from(path),
+        Some(path) => return Err(format!("super_empty image not found: {}", path)),
+        None => {
+            return Err(
+                "super_empty_path is required: use super_empty.img from the device's factory image".to_string(),
+            )
+        }
+    };
+
+    let output = run_fastboot_query(&device_serial, &["getvar", "dynamic-partition"])?;
+    if !parse_getvar_value(&output, "dynamic-partition").is_some_and(|v| is_truthy_var(&v)) {
+        return Err(format!("Device {} does not use dynamic partitions", device_serial));
+    }
+    let vars = ["super-partition-name", "current-slot"]
+        .iter()
+        .filter_map(|variable| {
+            let output = run_fastboot_query(&device_serial, &["getvar", variable]).ok()?;
+            Some((*variable, parse_getvar_value(&output, variable)?))
+        })
+        .collect::<HashMap<_, _>>();
+    let super_name = vars.get("super-partition-name").map(String::as_str).unwrap_or("super");
+
+    let warning = match vars.get("current-slot").filter(|s| !s.is_empty()) {
+        Some(slot) => format!(
+            "WARNING: wipe-super empties both slots; the active slot {} will not boot until its logical partitions are flashed",
+            slot.trim_start_matches('_')
+        ),
+        None => "WARNING: the device will not boot until its logical partitions are flashed".to_string(),
+    };
+    let image_arg = image.to_string_lossy().to_string();
+    let result = run_fastboot_with_timeout(&device_serial, &["wipe-super", &image_arg], FASTBOOT_ERASE_TIMEOUT);
+    match &result {
+        Ok(_) => log_erase(&device_serial, super_name, "ok (wipe-super)"),
+        Err(e) => log_erase(&device_serial, super_name, &format!("wipe-super error: {}", e)),
+    }
+    Ok(format!("{}\n{}", warning, result?))
+}
+
+#[tauri::command(async)]
+pub fn flash_get_current_slot(device_serial: String) -> Result<String, String> {
+    let output = run_fastboot_query(&device_serial, &["getvar", "current-slot"])?;
+    let slot = parse_getvar_value(&output, "current-slot")
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| format!("Device {} did not report current-slot (not an A/B device?)", device_serial))?;
+    Ok(slot.trim_start_matches('_').to_string())
+}
+
+/// A/B slot state reported by the bootloader
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotInfo {
+    pub count: u8,
+    /// "a" or "b", without the leading underscore
+    pub current: String,
+    pub a_successful: bool,
+    pub b_successful: bool,
+}
+
+fn slot_info_from_vars(device_serial: &str, vars: &HashMap<String, String>) -> Result<SlotInfo, String> {
+    let get = |key: &str| vars.get(key).map(|v| v.trim()).filter(|v| !v.is_empty());
+    let count = get("slot-count")
+        .and_then(|v| v.parse::<u8>().ok())
+        .filter(|count| *count >= 2)
+        .ok_or_else(|| format!("Device {} is not an A/B device (no slot-count)", device_serial))?;
+    let current = get("current-slot")
+        .ok_or_else(|| format!("Device {} did not report current-slot", device_serial))?
+        .trim_start_matches('_')
+        .to_string();
+    Ok(SlotInfo {
+        count,
+        current,
+        a_successful: get("slot-successful:a").is_some_and(is_truthy_var),
+        b_successful: get("slot-successful:b").is_some_and(is_truthy_var),
+    })
+}
+
+/// Slot count, active slot and whether each slot has booted successfully.
+#[tauri::command(async)]
+pub fn fastboot_get_slot_info(device_serial: String) -> Result<SlotInfo, String> {
+    let mut vars = HashMap::new();
+    for variable in ["slot-count", "current-slot", "slot-successful:a", "slot-successful:b"] {
+        // slot-successful is optional on some bootloaders; a missing value reads as false.
+        match run_fastboot_query(&device_serial, &["getvar", variable]) {
+            Ok(output) => vars.extend(parse_getvar_all(&output)),
+            Err(e) if variable == "slot-count" => return Err(e),
+            Err(_) => {}
+        }
+    }
+    slot_info_from_vars(&device_serial, &vars)
+}
+
+/// Make `slot` ("a" or "b") the slot the device boots next.
+#[tauri::command(async)]
+pub fn fastboot_set_active_slot(device_serial: String, slot: String) -> Result<(), String> {
+    let slot = normalize_slot(&slot)?;
+    run_fastboot_query(&device_serial, &["set_active", &slot]).map(|_| ())
+}
+
+/// Activate `slot` and mark it as successfully booted with
+/// `fastboot oem mark_boot_successful`, so the bootloader stops counting
+/// boot attempts. Bootloaders without that command leave the flag to the
+/// booted OS (`bootctl mark-successful`); that is reported as an error after
+/// the slot was activated.
+#[tauri::command(async)]
+pub fn fastboot_mark_slot_successful(device_serial: String, slot: String) -> Result<(), String> {
+    fastboot_set_active_slot(device_serial.clone(), slot.clone())?;
+    run_oem_command(&device_serial, "mark_boot_successful", &[], true)
+        .map(|_| ())
+        .map_err(|e| {
+            format!(
+                "Slot {} is active, but the bootloader could not mark it successful; boot it and run `bootctl mark-successful`: {}",
+                slot.trim(),
+                e
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slot_info_reads_successful_flags() {
+        let vars = parse_getvar_all(
+            "(bootloader) slot-count:2\n(bootloader) current-slot:_b\n\
+             (bootloader) slot-successful:a:yes\n(bootloader) slot-successful:b:no\n",
+        );
+        assert_eq!(
+            slot_info_from_vars("ABC123", &vars).unwrap(),
+            SlotInfo { count: 2, current: "b".to_string(), a_successful: true, b_successful: false }
+        );
+        let single = parse_getvar_all("(bootloader) slot-count:1\n");
+        assert!(slot_info_from_vars("ABC123", &single).unwrap_err().contains("not an A/B"));
+    }
+
+    #[test]
+    fn wipe_super_needs_token_and_factory_image_before_running() {
+        let wipe = |serial: &str, path: Option<&str>, token: &str| {
+            fastboot_wipe_super(serial.to_string(), path.map(str::to_string), token.to_string()).unwrap_err()
+        };
+        assert!(wipe("  ", None, "WIPE_SUPER").contains("device_serial"));
+        let mismatch = wipe("ABC123", Some("super_empty.img"), "wipe_super");
+        assert!(mismatch.contains("token") && !mismatch.contains("WIPE_SUPER"));
+        assert!(wipe("ABC123", None, "WIPE_SUPER").contains("super_empty_path is required"));
+        assert!(wipe("ABC123", Some("/nonexistent/super_empty.img"), "WIPE_SUPER").contains("not found"));
+        assert!(is_truthy_var(" true"));
+        assert!(!is_truthy_var("no"));
+    }
+
+    #[test]
+    fn getvar_value_handles_bootloader_prefix() {
+        let out = "(bootloader) unlocked: yes\nFinished. Total time: 0.001s\n";
+        assert_eq!(parse_getvar_value(out, "unlocked").as_deref(), Some("yes"));
+        assert_eq!(parse_getvar_value("product: sargo\n", "product").as_deref(), Some("sargo"));
+        assert_eq!(parse_getvar_value(out, "product"), None);
+    }
+
+    #[test]
+    fn getvar_all_keeps_colons_in_keys() {
+        let out = "(bootloader) current-slot:a\n\
+                   (bootloader) partition-size:boot_a: 0x4000000\n\
+                   (bootloader) partition-type:system_a:raw\n\
+                   all:\n\
+                   Finished. Total time: 0.042s\n";
+        let vars = parse_getvar_all(out);
+        assert_eq!(vars.len(), 3);
+        assert_eq!(vars["current-slot"], "a");
+        assert_eq!(vars["partition-size:boot_a"], "0x4000000");
+        assert_eq!(vars["partition-type:system_a"], "raw");
+    }
+
+    #[test]
+    fn device_info_requires_product() {
+        let vars = parse_getvar_all(
+            "(bootloader) product:sargo\n(bootloader) variant:SM7150 UFS\n(bootloader) slot-count:2\n",
+        );
+        let info = device_info_from_vars("ABC123", &vars).unwrap();
+        assert_eq!(info.product, "sargo");
+        assert_eq!(info.variant.as_deref(), Some("SM7150 UFS"));
+        assert_eq!(info.board, None);
+        assert_eq!(info.slot_count, Some(2));
+
+        assert!(device_info_from_vars("ABC123", &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn bootloader_status_from_pixel_and_samsung_output() {
+        // Pixel 7, locked with OEM unlocking enabled
+        let pixel = "(bootloader) unlocked:no\nFinished. Total time: 0.001s\n\
+                     (bootloader) unlock-allowed:yes\nFinished. Total time: 0.001s\n\
+                     (bootloader) verified-boot-state:green\nFinished. Total time: 0.001s\n";
+        let status = bootloader_status_from_vars("ABC123", &parse_getvar_all(pixel)).unwrap();
+        assert!(!status.unlocked);
+        assert!(status.unlock_allowed);
+        assert_eq!(status.verified_boot_state.as_deref(), Some("green"));
+
+        // Samsung fastbootd, unlocked; unlock-allowed and verified-boot-state are unknown
+        let samsung = "unlocked: yes\nFinished. Total time: 0.003s\n";
+        let status = bootloader_status_from_vars("R58M12", &parse_getvar_all(samsung)).unwrap();
+        assert!(status.unlocked);
+        assert!(status.unlock_allowed);
+        assert_eq!(status.verified_boot_state, None);
+
+        let missing = "getvar:unlocked FAILED (remote: 'unknown variable')\n";
+        assert!(bootloader_status_from_vars("R58M12", &parse_getvar_all(missing)).is_err());
+    }
+
+    #[test]
+    fn oem_commands_outside_the_safe_list_need_force() {
+        let err = fastboot_oem_command("ABC123".to_string(), "unlock".to_string(), vec![]).unwrap_err();
+        assert!(err.contains("fastboot_oem_command_force"));
+        let err = fastboot_oem_command_force("ABC123".to_string(), "unlock".to_string(), vec![], false).unwrap_err();
+        assert!(err.contains("force must be true"));
+        assert!(validate_oem_command("off-mode-charge", &["0".to_string()]).is_ok());
+        assert!(validate_oem_command("off-mode-charge 0", &[]).is_err());
+        assert!(validate_oem_command("off-mode-charge", &["0\nreboot".to_string()]).is_err());
+    }
+
+    #[test]
+    fn erase_requires_exact_token_before_running() {
+        assert_eq!(erase_confirm_token("userdata"), "ERASE_USERDATA");
+        let err = fastboot_erase(
+            "ABC123".to_string(),
+            "userdata".to_string(),
+            "ERASE_userdata".to_string(),
+            false,
+        )
+        .unwrap_err();
+        assert!(err.contains("Confirmation token mismatch"));
+        assert!(!err.contains("ERASE_USERDATA"));
+    }
+
+    #[test]
+    fn erase_refuses_protected_partitions_without_override() {
+        assert!(is_protected_from_erase("boot_a"));
+        assert!(is_protected_from_erase("vbmeta"));
+        assert!(!is_protected_from_erase("metadata"));
+        let err = fastboot_erase(
+            "ABC123".to_string(),
+            "bootloader".to_string(),
+            "ERASE_BOOTLOADER".to_string(),
+            false,
+        )
+        .unwrap_err();
+        assert!(err.contains("override_safety"));
+    }
+
+    #[test]
+    fn vbmeta_flags_are_passed_to_fastboot_flash() {
+        assert_eq!(vbmeta_flash_args("vbmeta.img", false, false), ["flash", "vbmeta", "vbmeta.img"]);
+        assert_eq!(
+            vbmeta_flash_args("vbmeta.img", true, true),
+            ["flash", "vbmeta", "--disable-verity", "--disable-verification", "vbmeta.img"]
+        );
+        assert_eq!(
+            vbmeta_flash_args("vbmeta.img", false, true),
+            ["flash", "vbmeta", "--disable-verification", "vbmeta.img"]
+        );
+    }
+
+    #[test]
+    fn vbmeta_is_safe_to_erase_only_once_verification_is_disabled() {
+        assert!(!is_safe_to_erase("VBMETA-TEST", "vbmeta_a"));
+        VERIFICATION_DISABLED.lock().unwrap().insert("VBMETA-TEST".to_string());
+        assert!(is_safe_to_erase("VBMETA-TEST", "vbmeta_a"));
+        assert!(!is_safe_to_erase("VBMETA-TEST", "boot"));
+        assert!(!is_safe_to_erase("OTHER", "vbmeta"));
+        VERIFICATION_DISABLED.lock().unwrap().remove("VBMETA-TEST");
+    }
+}
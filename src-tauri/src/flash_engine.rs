@@ -0,0 +1,826 @@
+// Flash Engine
+// Runs one admitted fastboot/sideload/spflashtool/edl flash job against its shared runtime
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tauri::AppHandle;
+
+use crate::edl_engine::QualcommEdlEngine;
+use crate::fastboot_tools::parse_getvar_all;
+use crate::scatter_file::select_partitions;
+use crate::{
+    emit_flash_update, get_log_directory, now_ms, parse_sideload_xfer, slot_partition_name, spawn_line_reader,
+    wait_for_device_mode, FlashJobConfig, FlashJobRuntime, PartitionStatus,
+};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+/// The job being executed
+struct ActiveJob {
+    id: String,
+    runtime: Arc<Mutex<FlashJobRuntime>>,
+}
+
+impl ActiveJob {
+    fn update(&self, f: impl FnOnce(&mut FlashJobRuntime)) {
+        if let Ok(mut job) = self.runtime.lock() {
+            f(&mut job);
+        }
+    }
+}
+
+/// Owns a running tool process and kills it on drop unless [`Self::release`]
+/// was called, so an early return or panic never leaves a flashing tool
+/// running after the job has ended.
+struct ProcessGuard {
+    child: Option<Child>,
+}
+
+impl ProcessGuard {
+    fn new(child: Child) -> Self {
+        Self { child: Some(child) }
+    }
+
+    fn child_mut(&mut self) -> &mut Child {
+        self.child.as_mut().expect("process guard already released")
+    }
+
+    fn id(&self) -> u32 {
+        self.child.as_ref().map(Child::id).unwrap_or_default()
+    }
+
+    fn kill(&mut self) {
+        let _ = self.child_mut().kill();
+    }
+
+    fn wait(&mut self) -> std::io::Result<ExitStatus> {
+        self.child_mut().wait()
+    }
+
+    /// Stop managing the child; it is no longer killed on drop.
+    fn release(mut self) {
+        self.child = None;
+    }
+}
+
+impl Drop for ProcessGuard {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            if matches!(child.try_wait(), Ok(None)) {
+                let _ = child.kill();
+            }
+            let _ = child.wait();
+        }
+    }
+}
+
+/// SP Flash Tool command-line binary
+pub(crate) const FLASH_TOOL: &str = "flash_tool";
+
+/// How long an autoReboot job waits for the device to boot back into adb;
+/// the first boot after flashing system images can take several minutes
+const REBOOT_WAIT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Last `NN%` token of an SP Flash Tool output line.
+fn parse_spflash_percent(line: &str) -> Option<u64> {
+    line.split_whitespace()
+        .rev()
+        .find_map(|token| token.strip_suffix('%')?.parse::<u64>().ok())
+        .filter(|pct| *pct <= 100)
+}
+
+pub(crate) struct FastbootFlashEngine {
+    /// `None` runs the job without emitting `flash-progress` events
+    app_handle: Option<AppHandle>,
+    fastboot_program: String,
+    adb_program: String,
+    flash_tool_program: String,
+    /// Where per-job scatter files for SP Flash Tool are written
+    work_dir: PathBuf,
+    /// How long to wait for the device in adb after the reboot step
+    reboot_timeout: Duration,
+}
+
+impl FastbootFlashEngine {
+    pub(crate) fn new(app_handle: AppHandle) -> Self {
+        Self {
+            app_handle: Some(app_handle),
+            fastboot_program: crate::config::fastboot_program(),
+            adb_program: crate::config::adb_program(),
+            flash_tool_program: FLASH_TOOL.to_string(),
+            work_dir: get_log_directory().join("spflash"),
+            reboot_timeout: REBOOT_WAIT_TIMEOUT,
+        }
+    }
+
+    /// Engine that runs the given tool binaries and emits no events.
+    #[cfg(test)]
+    fn detached(fastboot_program: &str, adb_program: &str) -> Self {
+        Self {
+            app_handle: None,
+            fastboot_program: fastboot_program.to_string(),
+            adb_program: adb_program.to_string(),
+            flash_tool_program: FLASH_TOOL.to_string(),
+            work_dir: std::env::temp_dir().join(format!("bw-spflash-{}", std::process::id())),
+            reboot_timeout: Duration::ZERO,
+        }
+    }
+
+    fn emit(&self, job: &ActiveJob, kind: &str, data: serde_json::Value) {
+        if let Some(app_handle) = &self.app_handle {
+            emit_flash_update(app_handle, &job.id, kind, data);
+        }
+    }
+
+    fn set_job_status(&self, job: &ActiveJob, status: &str, step: &str) {
+        job.update(|runtime| {
+            runtime.status = status.to_string();
+            runtime.current_step = step.to_string();
+            if status == "completed" || status == "failed" || status == "cancelled" {
+                runtime.end_time_ms = Some(now_ms());
+                runtime.close_log_tail();
+            }
+        });
+        self.emit(job, "status", serde_json::json!({ "status": status, "message": step }));
+    }
+
+    fn push_log(&self, job: &ActiveJob, line: &str) {
+        job.update(|runtime| runtime.push_log(line.to_string()));
+        self.emit(job, "log", serde_json::json!({ "message": line }));
+    }
+
+    fn complete_step(&self, job: &ActiveJob, completed: u64, total: u64) {
+        let pct = (completed * 100).checked_div(total).unwrap_or(0).min(100);
+        job.update(|runtime| {
+            runtime.completed_steps = completed;
+            runtime.progress = pct;
+        });
+        self.emit(job, "progress", serde_json::json!({ "progress": pct }));
+    }
+
+    fn set_partition_result(
+        &self,
+        job: &ActiveJob,
+        index: usize,
+        status: PartitionStatus,
+        bytes_written: u64,
+        duration_ms: u64,
+    ) {
+        let mut snapshot = None;
+        job.update(|runtime| {
+            if let Some(result) = runtime.partition_results.get_mut(index) {
+                result.status = status;
+                result.bytes_written = bytes_written;
+                result.duration_ms = duration_ms;
+                snapshot = Some(result.clone());
+            }
+        });
+        // Only terminal partition states are broadcast; Flashing is covered by the status event.
+        if let Some(result) = snapshot {
+            if matches!(result.status, PartitionStatus::Done | PartitionStatus::Failed(_)) {
+                self.emit(job, "partition_done", serde_json::to_value(&result).unwrap_or_default());
+            }
+        }
+    }
+
+    fn cancel_requested(&self, job: &ActiveJob) -> bool {
+        job.runtime.lock().map(|runtime| runtime.cancel_requested).unwrap_or(false)
+    }
+
+    fn set_active_pid(&self, job: &ActiveJob, pid: Option<u32>) {
+        job.update(|runtime| runtime.active_pid = pid);
+    }
+
+    /// End the job after a failed step: cancelled if that was requested,
+    /// otherwise failed with `step` as the final status message.
+    fn fail(&self, job: &ActiveJob, step: &str, message: String) {
+        if self.cancel_requested(job) {
+            self.set_job_status(job, "cancelled", "Cancelled");
+            return;
+        }
+        self.set_job_status(job, "failed", step);
+        self.emit(job, "error", serde_json::json!({ "message": message }));
+    }
+
+    /// Runs `program -s <serial> <args...>`; see [`Self::run_tool_step_with`].
+    fn run_tool_step(&self, job: &ActiveJob, serial: &str, program: &str, args: &[&str]) -> Result<Vec<String>, String> {
+        let mut full_args = vec!["-s", serial];
+        full_args.extend_from_slice(args);
+        self.run_tool_step_with(job, program, &full_args, |_| {})
+    }
+
+    /// Runs one flashing tool invocation as a managed child process. stdout/stderr
+    /// are streamed line by line into the job log and `on_line` (and returned), and
+    /// the pid is published so that flash_cancel can terminate the tool mid-transfer.
+    fn run_tool_step_with(
+        &self,
+        job: &ActiveJob,
+        program: &str,
+        args: &[&str],
+        mut on_line: impl FnMut(&str),
+    ) -> Result<Vec<String>, String> {
+        let mut cmd = Command::new(program);
+        cmd.args(args);
+        cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+        #[cfg(target_os = "windows")]
+        {
+            cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+        }
+        let mut child = ProcessGuard::new(
+            cmd.spawn()
+                .map_err(|e| format!("Failed to run {} {}: {e}", program, args.join(" ")))?,
+        );
+        self.set_active_pid(job, Some(child.id()));
+        // Cancellation may have landed before the pid was visible to flash_cancel.
+        if self.cancel_requested(job) {
+            child.kill();
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel::<String>();
+        let readers = [
+            child.child_mut().stdout.take().map(|pipe| spawn_line_reader(pipe, tx.clone())),
+            child.child_mut().stderr.take().map(|pipe| spawn_line_reader(pipe, tx.clone())),
+        ];
+        drop(tx);
+        let mut output = Vec::new();
+        for line in rx {
+            let line = line.trim();
+            if !line.is_empty() {
+                self.push_log(job, line);
+                on_line(line);
+                output.push(line.to_string());
+            }
+        }
+        for reader in readers.into_iter().flatten() {
+            let _ = reader.join();
+        }
+
+        let status = child.wait();
+        self.set_active_pid(job, None);
+        if status.is_ok() {
+            child.release();
+        }
+        let status = status.map_err(|e| format!("Failed to wait for {} {}: {e}", program, args.join(" ")))?;
+        if status.success() {
+            Ok(output)
+        } else if self.cancel_requested(job) {
+            Err(format!("{} {} cancelled", program, args.join(" ")))
+        } else {
+            Err(format!("{} {} failed", program, args.join(" ")))
+        }
+    }
+
+    /// Whether the device keeps an A and a B copy of `partition`
+    /// (`getvar has-slot:<partition>`). Unanswered queries count as no: the
+    /// bare name then goes to the slot selected with `--set-active`.
+    fn has_slot(&self, job: &ActiveJob, serial: &str, fastboot: &str, partition: &str) -> bool {
+        let variable = format!("has-slot:{}", partition);
+        match self.run_tool_step(job, serial, fastboot, &["getvar", &variable]) {
+            Ok(lines) => parse_getvar_all(&lines.join("\n"))
+                .get(&variable)
+                .is_some_and(|value| value.eq_ignore_ascii_case("yes")),
+            Err(message) => {
+                self.push_log(
+                    job,
+                    &format!("[tauri-fastboot] {}; flashing {} without a slot suffix", message, partition),
+                );
+                false
+            }
+        }
+    }
+
+    /// Run the job to completion, failure or cancellation. The outcome is left
+    /// in `runtime.status`.
+    pub(crate) fn execute(&self, job_id: String, runtime: Arc<Mutex<FlashJobRuntime>>) {
+        let (config, total_steps) = match runtime.lock() {
+            Ok(runtime) => (runtime.config.clone(), runtime.total_steps),
+            Err(_) => return,
+        };
+        let job = ActiveJob { id: job_id, runtime };
+        let serial = config.deviceSerial.as_str();
+        let fastboot = self.fastboot_program.as_str();
+
+        let sideload = config.flashMethod == "sideload";
+        let tag = if sideload { "[tauri-sideload]" } else { "[tauri-fastboot]" };
+
+        self.set_job_status(&job, "running", "Preparing");
+        self.push_log(&job, &format!("{} Starting {} flash job", tag, config.flashMethod));
+        if config.verifyAfterFlash {
+            self.push_log(
+                &job,
+                &format!("{} NOTE: verifyAfterFlash is not implemented for {} backend", tag, config.flashMethod),
+            );
+        }
+
+        if config.flashMethod == "spflashtool" {
+            self.execute_spflashtool(&job, &config, total_steps);
+            return;
+        }
+        if config.flashMethod == "edl" {
+            self.execute_edl(&job, &config, total_steps);
+            return;
+        }
+
+        let mut completed_steps: u64 = 0;
+        let slot = config.activeSlot.clone();
+
+        // Select the target slot up front so slot-less images land on the right side.
+        if let Some(slot) = slot.as_deref() {
+            if self.cancel_requested(&job) {
+                self.set_job_status(&job, "cancelled", "Cancelled");
+                return;
+            }
+
+            self.set_job_status(&job, "running", &format!("Selecting slot {}", slot));
+            self.push_log(&job, &format!("[tauri-fastboot] fastboot --set-active={}", slot));
+            if let Err(message) = self.run_tool_step(&job, serial, fastboot, &[&format!("--set-active={}", slot)]) {
+                self.fail(&job, "Slot selection failed", message);
+                return;
+            }
+            completed_steps += 1;
+            self.complete_step(&job, completed_steps, total_steps);
+        }
+
+        // Optional wipe
+        if config.wipeUserData {
+            if self.cancel_requested(&job) {
+                self.set_job_status(&job, "cancelled", "Cancelled");
+                return;
+            }
+
+            self.set_job_status(&job, "running", "Wiping userdata (-w)");
+            self.push_log(&job, "[tauri-fastboot] fastboot -w");
+            if let Err(message) = self.run_tool_step(&job, serial, fastboot, &["-w"]) {
+                self.fail(&job, "Wipe failed", message);
+                return;
+            }
+            completed_steps += 1;
+            self.complete_step(&job, completed_steps, total_steps);
+        }
+
+        // Flash partitions
+        for (index, p) in config.partitions.iter().enumerate() {
+            if self.cancel_requested(&job) {
+                self.set_job_status(&job, "cancelled", "Cancelled");
+                return;
+            }
+
+            let partition_name = match slot.as_deref() {
+                Some(_) if !sideload => {
+                    let has_slot = self.has_slot(&job, serial, fastboot, &p.name);
+                    slot_partition_name(&p.name, slot.as_deref(), has_slot)
+                }
+                _ => p.name.clone(),
+            };
+            job.update(|runtime| {
+                if let Some(result) = runtime.partition_results.get_mut(index) {
+                    result.name = partition_name.clone();
+                }
+            });
+            self.set_partition_result(&job, index, PartitionStatus::Flashing, 0, 0);
+            let partition_started = Instant::now();
+
+            let result = if sideload {
+                self.set_job_status(&job, "running", "Sideloading OTA package");
+                self.push_log(&job, &format!("{} adb sideload {}", tag, p.imagePath));
+                self.run_tool_step(&job, serial, &self.adb_program, &["sideload", &p.imagePath])
+                    .and_then(|lines| {
+                        let ratio = parse_sideload_xfer(&lines)
+                            .ok_or_else(|| "adb sideload finished without reporting Total xfer".to_string())?;
+                        self.push_log(&job, &format!("{} Sideload complete (transfer ratio {:.2}x)", tag, ratio));
+                        Ok(())
+                    })
+            } else {
+                self.set_job_status(&job, "running", &format!("Flashing {}", partition_name));
+                self.push_log(&job, &format!("{} fastboot flash {} {}", tag, partition_name, p.imagePath));
+                self.run_tool_step(&job, serial, fastboot, &["flash", &partition_name, &p.imagePath])
+                    .map(|_| ())
+            };
+
+            let duration_ms = partition_started.elapsed().as_millis() as u64;
+            if let Err(message) = result {
+                self.set_partition_result(&job, index, PartitionStatus::Failed(message.clone()), 0, duration_ms);
+                self.fail(&job, &format!("Flash failed: {}", partition_name), message);
+                return;
+            }
+
+            self.set_partition_result(&job, index, PartitionStatus::Done, p.size, duration_ms);
+            completed_steps += 1;
+            self.complete_step(&job, completed_steps, total_steps);
+        }
+
+        // Optional reboot
+        if config.autoReboot {
+            if self.cancel_requested(&job) {
+                self.set_job_status(&job, "cancelled", "Cancelled");
+                return;
+            }
+
+            self.set_job_status(&job, "running", "Rebooting");
+            let program = if sideload { self.adb_program.as_str() } else { fastboot };
+            self.push_log(&job, &format!("{} {} reboot", tag, program));
+            match self.run_tool_step(&job, serial, program, &["reboot"]) {
+                Ok(_) if config.wipeUserData => {
+                    // A wiped device boots with adb disabled, so it never reappears.
+                    self.push_log(&job, &format!("{} userdata was wiped; not waiting for adb", tag));
+                }
+                Ok(_) => {
+                    self.wait_for_boot(&job, serial, tag);
+                    if self.cancel_requested(&job) {
+                        self.set_job_status(&job, "cancelled", "Cancelled");
+                        return;
+                    }
+                }
+                Err(message) => self.push_log(&job, &format!("{} {}", tag, message)),
+            }
+            completed_steps += 1;
+            self.complete_step(&job, completed_steps, total_steps);
+        }
+
+        self.set_job_status(&job, "completed", "Completed");
+        self.emit(&job, "log", serde_json::json!({ "message": format!("{} Job complete", tag) }));
+    }
+
+    /// Wait for the rebooted device to show up in `adb devices`. A device that
+    /// does not come back (adb disabled, bootloop) is logged, not failed: the
+    /// images were already written.
+    fn wait_for_boot(&self, job: &ActiveJob, serial: &str, tag: &str) {
+        self.set_job_status(job, "running", "Waiting for device to boot");
+        let booted = ["adb".to_string(), "unauthorized".to_string()];
+        match wait_for_device_mode(
+            &self.adb_program,
+            &self.fastboot_program,
+            serial,
+            &booted,
+            self.reboot_timeout,
+            || self.cancel_requested(job),
+        ) {
+            Ok(mode) => self.push_log(job, &format!("{} Device {} is back ({})", tag, serial, mode)),
+            Err(message) => self.push_log(job, &format!("{} WARNING: {}", tag, message)),
+        }
+    }
+
+    /// SP Flash Tool downloads every selected partition in one run, driven by
+    /// a copy of the scatter file that only enables the job's partitions.
+    fn execute_spflashtool(&self, job: &ActiveJob, config: &FlashJobConfig, total_steps: u64) {
+        let tag = "[tauri-spflash]";
+        if config.autoReboot {
+            self.push_log(job, &format!("{} NOTE: autoReboot is ignored; unplug the device to reboot it", tag));
+        }
+
+        let scatter_path = config.scatterFile.clone().unwrap_or_default();
+        let job_scatter = self.work_dir.join(format!("{}_scatter.txt", job.id));
+        let images: HashMap<String, String> = config
+            .partitions
+            .iter()
+            .map(|p| (p.name.trim().to_string(), p.imagePath.clone()))
+            .collect();
+        let written = std::fs::read_to_string(&scatter_path)
+            .map_err(|e| format!("Failed to read scatter file {}: {e}", scatter_path))
+            .and_then(|contents| {
+                std::fs::create_dir_all(&self.work_dir)
+                    .and_then(|_| std::fs::write(&job_scatter, select_partitions(&contents, &images)))
+                    .map_err(|e| format!("Failed to write {}: {e}", job_scatter.display()))
+            });
+        if let Err(message) = written {
+            self.fail(job, "Scatter file preparation failed", message);
+            return;
+        }
+
+        if self.cancel_requested(job) {
+            self.set_job_status(job, "cancelled", "Cancelled");
+            return;
+        }
+        for index in 0..config.partitions.len() {
+            self.set_partition_result(job, index, PartitionStatus::Flashing, 0, 0);
+        }
+        self.set_job_status(
+            job,
+            "running",
+            &format!("Downloading {} partition(s) with SP Flash Tool", config.partitions.len()),
+        );
+        let scatter_arg = job_scatter.to_string_lossy().to_string();
+        self.push_log(job, &format!("{} {} -s {} -c download", tag, FLASH_TOOL, scatter_arg));
+
+        let started = Instant::now();
+        let mut last_pct = 0;
+        let result = self.run_tool_step_with(
+            job,
+            &self.flash_tool_program,
+            &["-s", &scatter_arg, "-c", "download"],
+            |line| {
+                if let Some(pct) = parse_spflash_percent(line).filter(|pct| *pct > last_pct) {
+                    last_pct = pct;
+                    job.update(|runtime| runtime.progress = pct);
+                    self.emit(job, "progress", serde_json::json!({ "progress": pct }));
+                }
+            },
+        );
+        let duration_ms = started.elapsed().as_millis() as u64;
+        let _ = std::fs::remove_file(&job_scatter);
+
+        if let Err(message) = result {
+            for index in 0..config.partitions.len() {
+                self.set_partition_result(job, index, PartitionStatus::Failed(message.clone()), 0, duration_ms);
+            }
+            self.fail(job, "SP Flash Tool download failed", message);
+            return;
+        }
+        for (index, p) in config.partitions.iter().enumerate() {
+            self.set_partition_result(job, index, PartitionStatus::Done, p.size, duration_ms);
+        }
+        self.complete_step(job, config.partitions.len() as u64, total_steps);
+
+        self.set_job_status(job, "completed", "Completed");
+        self.emit(job, "log", serde_json::json!({ "message": format!("{} Job complete", tag) }));
+    }
+
+    /// Loads the firehose programmer over Sahara. Partition programming over
+    /// Firehose is not implemented yet, so the job stops once the programmer
+    /// is running and its partitions are reported as not flashed.
+    fn execute_edl(&self, job: &ActiveJob, config: &FlashJobConfig, total_steps: u64) {
+        let tag = "[tauri-edl]";
+        if config.autoReboot {
+            self.push_log(job, &format!("{} NOTE: autoReboot is ignored; the device stays in firehose", tag));
+        }
+        if self.cancel_requested(job) {
+            self.set_job_status(job, "cancelled", "Cancelled");
+            return;
+        }
+
+        let programmer = config.edlProgrammer.clone().unwrap_or_default();
+        let memory = config.edlMemory.clone().unwrap_or_default();
+        self.set_job_status(job, "running", "Loading firehose programmer");
+        self.push_log(job, &format!("{} Sahara upload of {} on {}", tag, programmer, config.deviceSerial));
+        let status = match QualcommEdlEngine::new(&config.deviceSerial).load_programmer(Path::new(programmer.trim()), &memory)
+        {
+            Ok(status) => status,
+            Err(message) => {
+                self.fail(job, "Programmer upload failed", message);
+                return;
+            }
+        };
+        self.push_log(
+            job,
+            &format!(
+                "{} Programmer loaded ({} bytes, Sahara v{}), firehose configured for {}",
+                tag, status.programmer_bytes, status.sahara_version, memory
+            ),
+        );
+        self.complete_step(job, 1, total_steps);
+        self.set_job_status(job, "completed", "Programmer loaded");
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::{terminate_process, FlashJobConfig, FlashLogTail, FlashPartition, PartitionResult, RingBuffer};
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// fastboot stand-in: `flash system` fails, `flash slow` blocks, every
+    /// partition but userdata has slots, anything else succeeds.
+    const STUB_FASTBOOT: &str = "#!/bin/sh\n\
+        if [ \"$3\" = getvar ]; then case \"$4\" in has-slot:userdata) echo \"$4: no\" >&2;; *) echo \"$4: yes\" >&2;; esac; exit 0; fi\n\
+        if [ \"$3\" = flash ] && [ \"$4\" = system ]; then echo \"FAILED (remote: 'partition table doesn't exist')\" >&2; exit 1; fi\n\
+        if [ \"$3\" = flash ] && [ \"$4\" = slow ]; then exec sleep 30; fi\n\
+        echo OKAY\n";
+
+    /// The stub inside a fresh directory, removed when the `TempDir` drops
+    fn stub_fastboot() -> (TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fastboot");
+        std::fs::write(&path, STUB_FASTBOOT).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        (dir, path)
+    }
+
+    fn runtime(partitions: &[&str]) -> Arc<Mutex<FlashJobRuntime>> {
+        let config = FlashJobConfig {
+            deviceSerial: "ABC123".to_string(),
+            deviceSerials: vec![],
+            deviceBrand: "google".to_string(),
+            flashMethod: "fastboot".to_string(),
+            partitions: partitions
+                .iter()
+                .map(|name| FlashPartition {
+                    name: name.to_string(),
+                    imagePath: format!("/images/{}.img", name),
+                    size: 1024,
+                })
+                .collect(),
+            verifyAfterFlash: false,
+            autoReboot: true,
+            wipeUserData: false,
+            activeSlot: None,
+            allowQueue: false,
+            scatterFile: None,
+            edlProgrammer: None,
+            edlMemory: None,
+        };
+        Arc::new(Mutex::new(FlashJobRuntime {
+            status: "queued".to_string(),
+            progress: 0,
+            current_step: "Queued".to_string(),
+            total_steps: partitions.len() as u64 + 1,
+            completed_steps: 0,
+            logs: RingBuffer::new(100),
+            log_times_ms: RingBuffer::new(100),
+            start_time_ms: now_ms(),
+            end_time_ms: None,
+            total_bytes: 1024 * partitions.len() as u64,
+            cancel_requested: false,
+            active_pid: None,
+            partition_results: partitions
+                .iter()
+                .map(|name| PartitionResult {
+                    name: name.to_string(),
+                    status: PartitionStatus::Pending,
+                    bytes_written: 0,
+                    duration_ms: 0,
+                })
+                .collect(),
+            config,
+            log_tail: FlashLogTail::default(),
+        }))
+    }
+
+    fn engine(fastboot: &std::path::Path) -> FastbootFlashEngine {
+        FastbootFlashEngine::detached(fastboot.to_str().unwrap(), "adb")
+    }
+
+    #[test]
+    fn completes_all_steps() {
+        let (_dir, fastboot) = stub_fastboot();
+        let runtime = runtime(&["boot", "vendor_boot"]);
+        engine(&fastboot).execute("job-1".to_string(), runtime.clone());
+
+        let job = runtime.lock().unwrap();
+        assert_eq!(job.status, "completed");
+        assert_eq!(job.progress, 100);
+        assert_eq!(job.completed_steps, 3);
+        assert!(job.end_time_ms.is_some());
+        assert!(job.partition_results.iter().all(|r| matches!(r.status, PartitionStatus::Done)));
+        assert!(job.logs.iter().any(|line| line == "OKAY"));
+    }
+
+    #[test]
+    fn auto_reboot_waits_for_the_device_in_adb() {
+        let (_dir, fastboot) = stub_fastboot();
+        let adb = fastboot.with_file_name("adb");
+        std::fs::write(&adb, "#!/bin/sh\necho 'List of devices attached'\necho 'ABC123\tdevice'\n").unwrap();
+        std::fs::set_permissions(&adb, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let runtime = runtime(&["boot"]);
+        let mut engine = FastbootFlashEngine::detached(fastboot.to_str().unwrap(), adb.to_str().unwrap());
+        engine.reboot_timeout = Duration::from_secs(10);
+        engine.execute("job-5".to_string(), runtime.clone());
+
+        let job = runtime.lock().unwrap();
+        assert_eq!(job.status, "completed");
+        assert!(job.logs.iter().any(|line| line == "[tauri-fastboot] Device ABC123 is back (adb)"));
+    }
+
+    #[test]
+    fn slot_suffix_is_added_only_to_slotted_partitions() {
+        let (_dir, fastboot) = stub_fastboot();
+        let runtime = runtime(&["boot", "userdata"]);
+        if let Ok(mut job) = runtime.lock() {
+            job.config.activeSlot = Some("b".to_string());
+            job.config.autoReboot = false;
+            job.total_steps = 3;
+        }
+        engine(&fastboot).execute("job-6".to_string(), runtime.clone());
+
+        let job = runtime.lock().unwrap();
+        assert_eq!(job.status, "completed");
+        assert_eq!(job.completed_steps, 3);
+        let names: Vec<_> = job.partition_results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["boot_b", "userdata"]);
+        assert!(job.logs.iter().any(|line| line == "[tauri-fastboot] fastboot flash userdata /images/userdata.img"));
+        assert!(!job.logs.iter().any(|line| line.contains("set_active")));
+    }
+
+    #[test]
+    fn partition_failure_stops_the_job() {
+        let (_dir, fastboot) = stub_fastboot();
+        let runtime = runtime(&["boot", "system", "vendor"]);
+        engine(&fastboot).execute("job-2".to_string(), runtime.clone());
+
+        let job = runtime.lock().unwrap();
+        assert_eq!(job.status, "failed");
+        assert_eq!(job.current_step, "Flash failed: system");
+        assert!(matches!(job.partition_results[0].status, PartitionStatus::Done));
+        assert!(matches!(job.partition_results[1].status, PartitionStatus::Failed(_)));
+        assert!(matches!(job.partition_results[2].status, PartitionStatus::Pending));
+        assert!(job.logs.iter().any(|line| line.contains("partition table doesn't exist")));
+    }
+
+    #[test]
+    fn spflashtool_downloads_only_selected_partitions() {
+        let (temp, _) = stub_fastboot();
+        let dir = temp.path().to_path_buf();
+        let flash_tool = dir.join("flash_tool");
+        // Records the scatter it was given, then reports progress
+        std::fs::write(
+            &flash_tool,
+            format!(
+                "#!/bin/sh\ncp \"$2\" {}/used_scatter.txt\n\
+                 echo 'Download Flash 40%'\necho 'Download Flash 100%'\necho 'All command exec done!'\n",
+                dir.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&flash_tool, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let runtime = runtime(&["boot"]);
+        {
+            let mut job = runtime.lock().unwrap();
+            job.config.flashMethod = "spflashtool".to_string();
+            // flash_start does not count a reboot step for spflashtool
+            job.total_steps = 1;
+            job.config.scatterFile = Some(
+                std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                    .join("tests/fixtures/MT6765_Android_scatter.txt")
+                    .to_string_lossy()
+                    .to_string(),
+            );
+        }
+        let mut engine = FastbootFlashEngine::detached("fastboot", "adb");
+        engine.flash_tool_program = flash_tool.to_string_lossy().to_string();
+        engine.work_dir = dir.clone();
+        engine.execute("job-4".to_string(), runtime.clone());
+
+        let job = runtime.lock().unwrap();
+        assert_eq!(job.status, "completed");
+        assert_eq!(job.progress, 100);
+        assert!(matches!(job.partition_results[0].status, PartitionStatus::Done));
+        let used = crate::scatter_file::ScatterFile::load(&dir.join("used_scatter.txt")).unwrap();
+        let downloads: Vec<_> = used.entries.iter().filter(|e| e.is_download).collect();
+        assert_eq!(downloads.len(), 1);
+        assert_eq!(downloads[0].file.as_deref(), Some("/images/boot.img"));
+        assert_eq!(parse_spflash_percent("Format Flash 7%"), Some(7));
+        assert_eq!(parse_spflash_percent("Connecting to BROM..."), None);
+    }
+
+    #[test]
+    fn cancellation_mid_run_stops_the_tool() {
+        let (_dir, fastboot) = stub_fastboot();
+        let runtime = runtime(&["boot", "slow", "vendor"]);
+        let worker = {
+            let engine = engine(&fastboot);
+            let runtime = runtime.clone();
+            std::thread::spawn(move || engine.execute("job-3".to_string(), runtime))
+        };
+
+        // Wait for the blocking `flash slow` step, then cancel it as flash_cancel does.
+        let started = Instant::now();
+        let pid = loop {
+            let pid = {
+                let job = runtime.lock().unwrap();
+                job.active_pid.filter(|_| job.current_step == "Flashing slow")
+            };
+            if let Some(pid) = pid {
+                break pid;
+            }
+            assert!(started.elapsed() < Duration::from_secs(10), "flash slow never started");
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        runtime.lock().unwrap().cancel_requested = true;
+        let cancelled_at = Instant::now();
+        terminate_process(pid).unwrap();
+        worker.join().unwrap();
+        // The stub sleeps for 30s, so this only passes if the signal killed it
+        assert!(cancelled_at.elapsed() < Duration::from_secs(5));
+
+        let job = runtime.lock().unwrap();
+        assert_eq!(job.status, "cancelled");
+        assert!(job.active_pid.is_none());
+        assert!(matches!(job.partition_results[0].status, PartitionStatus::Done));
+        assert!(matches!(job.partition_results[1].status, PartitionStatus::Failed(_)));
+        assert!(matches!(job.partition_results[2].status, PartitionStatus::Pending));
+    }
+
+    #[test]
+    fn dropping_an_unreleased_process_guard_kills_the_child() {
+        let guard = ProcessGuard::new(Command::new("sleep").arg("30").spawn().unwrap());
+        let pid = guard.id();
+        let started = Instant::now();
+        drop(guard);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        // The child was reaped, so its pid no longer names a live process
+        let alive = Command::new("kill").args(["-0", &pid.to_string()]).status().unwrap();
+        assert!(!alive.success());
+
+        let mut guard = ProcessGuard::new(Command::new("true").spawn().unwrap());
+        assert!(guard.wait().unwrap().success());
+        guard.release();
+    }
+}
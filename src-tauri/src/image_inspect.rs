@@ -0,0 +1,200 @@
+// Image Inspection
+// Identifies flashable image files from their header before a flash job is built
+
+use std::io::Read;
+use std::path::Path;
+
+use bootforgeusb::model::UsbTransportEvidence;
+use libbootforge::imaging::boot_profiles::{USB2_ASSUMED_SPEED_BPS, USB3_ASSUMED_SPEED_BPS};
+use libbootforge::utils::ChecksumVerifier;
+use serde::Serialize;
+
+use crate::AppState;
+
+/// Bytes read from the start of the file for magic detection
+const HEADER_LEN: usize = 256;
+
+/// Android sparse image header magic (0xed26ff3a, little-endian)
+const SPARSE_MAGIC: [u8; 4] = [0x3a, 0xff, 0x26, 0xed];
+
+/// A header signature and what it says about the image
+struct ImageMagic {
+    magic: &'static [u8],
+    /// Reported as `android_magic`
+    label: &'static str,
+    format: &'static str,
+    partition: &'static str,
+}
+
+const IMAGE_MAGICS: &[ImageMagic] = &[
+    ImageMagic { magic: b"ANDROID!", label: "ANDROID!", format: "android_boot", partition: "boot" },
+    ImageMagic { magic: b"VNDRBOOT", label: "VNDRBOOT", format: "android_vendor_boot", partition: "vendor_boot" },
+    // Samsung/MTK signed boot header in front of an ANDROID! image
+    ImageMagic { magic: b"DHTB", label: "DHTB", format: "dhtb_boot", partition: "boot" },
+    ImageMagic { magic: b"AVB0", label: "AVB0", format: "vbmeta", partition: "vbmeta" },
+    // DT table header, big-endian 0xd7b7ab1e
+    ImageMagic { magic: &[0xd7, 0xb7, 0xab, 0x1e], label: "d7b7ab1e", format: "dtbo", partition: "dtbo" },
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageFileInfo {
+    pub format: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+    pub is_sparse: bool,
+    pub android_magic: Option<String>,
+    pub partition_hint: Option<String>,
+    /// Assumed transfer rate to the requested device, from its negotiated USB speed
+    pub expected_transfer_bps: Option<u64>,
+    /// `size_bytes` at `expected_transfer_bps`, rounded up
+    pub expected_transfer_secs: Option<u64>,
+}
+
+/// `(format, is_sparse, android_magic, partition_hint)` for an image header.
+/// Sparse images carry no partition-specific magic in their header.
+pub(crate) fn detect_image_header(header: &[u8]) -> (&'static str, bool, Option<&'static str>, Option<&'static str>) {
+    if header.starts_with(&SPARSE_MAGIC) {
+        return ("android_sparse", true, None, None);
+    }
+    match IMAGE_MAGICS.iter().find(|m| header.starts_with(m.magic)) {
+        Some(m) => (m.format, false, Some(m.label), Some(m.partition)),
+        None => ("raw", false, None, None),
+    }
+}
+
+pub(crate) fn read_header(path: &Path) -> Result<Vec<u8>, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    file.take(HEADER_LEN as u64)
+        .read_to_end(&mut header)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    Ok(header)
+}
+
+/// Assumed transfer rate for a device: the USB3 figure on SuperSpeed links,
+/// the USB2 figure otherwise (including when the speed is unknown).
+fn expected_transfer_bps(usb: &UsbTransportEvidence) -> u64 {
+    if usb.is_high_speed() {
+        USB3_ASSUMED_SPEED_BPS
+    } else {
+        USB2_ASSUMED_SPEED_BPS
+    }
+}
+
+/// Describe an image file: detected format, size and SHA-256. With
+/// `device_uid`, also the expected transfer time to that device from the
+/// last scan.
+#[tauri::command]
+pub async fn image_info(
+    state: tauri::State<'_, AppState>,
+    path: String,
+    device_uid: Option<String>,
+) -> Result<ImageFileInfo, String> {
+    let transfer_bps = match device_uid.as_deref().map(str::trim).filter(|uid| !uid.is_empty()) {
+        Some(uid) => {
+            let last = state
+                .last_scan
+                .lock()
+                .map_err(|_| "last_scan mutex poisoned".to_string())?;
+            let device = last
+                .iter()
+                .find(|d| d.device_uid == uid)
+                .ok_or_else(|| format!("Device not found in last scan: {}", uid))?;
+            Some(expected_transfer_bps(&device.evidence.usb))
+        }
+        None => None,
+    };
+    let mut info = inspect_image(&path).await?;
+    info.expected_transfer_bps = transfer_bps;
+    info.expected_transfer_secs = transfer_bps.map(|bps| info.size_bytes.div_ceil(bps));
+    Ok(info)
+}
+
+async fn inspect_image(path: &str) -> Result<ImageFileInfo, String> {
+    let path = Path::new(path.trim());
+    let metadata = std::fs::metadata(path).map_err(|e| format!("Image not found: {} ({e})", path.display()))?;
+    if !metadata.is_file() {
+        return Err(format!("Not a file: {}", path.display()));
+    }
+    let header = read_header(path)?;
+    let (format, is_sparse, android_magic, partition_hint) = detect_image_header(&header);
+    let sha256 = ChecksumVerifier::compute_sha256(path)
+        .await
+        .map_err(|e| format!("Failed to hash {}: {e}", path.display()))?;
+
+    Ok(ImageFileInfo {
+        format: format.to_string(),
+        size_bytes: metadata.len(),
+        sha256,
+        is_sparse,
+        android_magic: android_magic.map(str::to_string),
+        partition_hint: partition_hint.map(str::to_string),
+        expected_transfer_bps: None,
+        expected_transfer_secs: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bootforgeusb::model::ConnectionSpeed;
+
+    fn header(magic: &[u8]) -> Vec<u8> {
+        let mut bytes = magic.to_vec();
+        bytes.resize(HEADER_LEN, 0);
+        bytes
+    }
+
+    #[test]
+    fn detects_each_image_magic() {
+        assert_eq!(
+            detect_image_header(&header(b"ANDROID!")),
+            ("android_boot", false, Some("ANDROID!"), Some("boot"))
+        );
+        assert_eq!(
+            detect_image_header(&header(b"VNDRBOOT")),
+            ("android_vendor_boot", false, Some("VNDRBOOT"), Some("vendor_boot"))
+        );
+        assert_eq!(detect_image_header(&header(b"DHTB")), ("dhtb_boot", false, Some("DHTB"), Some("boot")));
+        assert_eq!(detect_image_header(&header(b"AVB0")), ("vbmeta", false, Some("AVB0"), Some("vbmeta")));
+        assert_eq!(
+            detect_image_header(&header(&[0xd7, 0xb7, 0xab, 0x1e])),
+            ("dtbo", false, Some("d7b7ab1e"), Some("dtbo"))
+        );
+        assert_eq!(detect_image_header(&header(&SPARSE_MAGIC)), ("android_sparse", true, None, None));
+        assert_eq!(detect_image_header(&header(b"\x7fELF")), ("raw", false, None, None));
+        assert_eq!(detect_image_header(b"AND"), ("raw", false, None, None));
+    }
+
+    #[test]
+    fn image_info_reports_size_and_checksum() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vbmeta.img");
+        std::fs::write(&path, b"AVB0").unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let info = runtime.block_on(inspect_image(&path.to_string_lossy()));
+
+        let info = info.unwrap();
+        assert_eq!(info.size_bytes, 4);
+        assert_eq!(info.partition_hint.as_deref(), Some("vbmeta"));
+        assert_eq!(info.sha256.len(), 64);
+        assert!(runtime.block_on(inspect_image("/nonexistent/boot.img")).is_err());
+    }
+
+    #[test]
+    fn transfer_rate_follows_usb_speed() {
+        let mut usb = UsbTransportEvidence {
+            vid: "18d1".to_string(),
+            pid: "4ee0".to_string(),
+            bus: 1,
+            address: 2,
+            ..Default::default()
+        };
+        assert_eq!(expected_transfer_bps(&usb), USB2_ASSUMED_SPEED_BPS);
+        usb.speed = Some(ConnectionSpeed::High);
+        assert_eq!(expected_transfer_bps(&usb), USB2_ASSUMED_SPEED_BPS);
+        usb.speed = Some(ConnectionSpeed::Super);
+        assert_eq!(expected_transfer_bps(&usb), USB3_ASSUMED_SPEED_BPS);
+    }
+}
@@ -0,0 +1,831 @@
+// iOS Device Tools
+// libimobiledevice queries against devices confirmed by the most recent scan
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use bootforgeusb::model::DeviceMode;
+use libbootforge::device_state::{
+    BatteryState, CpuArchitecture, DeviceMode as StateMode, OperatingSystem, UnifiedDeviceState,
+};
+use plist::{Dictionary, Value};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::adb_tools::{create_new_output, resolve_local_path, transfer_base_dir};
+use crate::device_state_io::device_state_from_record;
+use crate::{get_log_directory, now_ms, run_command_with_timeout, spawn_line_reader, AppState, CommandRunError};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+const IDEVICEINFO_TIMEOUT: Duration = Duration::from_secs(15);
+
+const IDEVICESCREENSHOT: &str = "idevicescreenshot";
+const IDEVICESCREENSHOT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// lockdownd domain holding the battery keys
+const BATTERY_DOMAIN: &str = "com.apple.mobile.battery";
+
+/// Final hold of every DFU guide; if the device has not shown up in DFU by
+/// the end of it, the button timing was off
+const DFU_FINAL_HOLD_MS: u64 = 10_000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IosBackupResult {
+    /// Backup directory; idevicebackup2 keeps each device in a `<udid>` subdirectory
+    pub path: String,
+    pub device_udid: String,
+    pub size_bytes: u64,
+    pub duration_ms: u64,
+}
+
+/// Payload of the `ios-backup-progress` and `ios-restore-progress` events
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IosBackupProgressEvent {
+    device_uid: String,
+    percent: f32,
+    /// Latest status line from idevicebackup2
+    message: String,
+}
+
+/// Resolve the UDID for a device_uid from the most recent scan.
+fn resolve_ios_udid(state: &AppState, device_uid: &str) -> Result<String, String> {
+    let last = state
+        .last_scan
+        .lock()
+        .map_err(|_| "last_scan mutex poisoned".to_string())?;
+    let record = last
+        .iter()
+        .find(|d| d.device_uid == device_uid)
+        .ok_or_else(|| format!("Device {} not found in the most recent scan", device_uid))?;
+
+    if record.mode == DeviceMode::IosUntrusted.as_str() {
+        return Err(untrusted_error(device_uid));
+    }
+    if record.mode != DeviceMode::IosNormalLikely.as_str() {
+        return Err(format!(
+            "Device {} is in mode '{}', expected {}",
+            device_uid,
+            record.mode,
+            DeviceMode::IosNormalLikely.as_str()
+        ));
+    }
+
+    Ok(record
+        .matched_tool_ids
+        .first()
+        .cloned()
+        .unwrap_or_else(|| record.device_uid.clone()))
+}
+
+/// Like [`resolve_ios_udid`], but also requires that idevice_id reported the
+/// device in the scan, for tools that talk to it over usbmuxd.
+fn resolve_correlated_ios_udid(state: &AppState, device_uid: &str) -> Result<String, String> {
+    let udid = resolve_ios_udid(state, device_uid)?;
+    let correlated = state
+        .last_scan
+        .lock()
+        .map_err(|_| "last_scan mutex poisoned".to_string())?
+        .iter()
+        .any(|d| d.device_uid == device_uid && !d.matched_tool_ids.is_empty());
+    if !correlated {
+        return Err(format!(
+            "Device {} was not reported by idevice_id; check that usbmuxd is running and scan again",
+            device_uid
+        ));
+    }
+    Ok(udid)
+}
+
+fn untrusted_error(device_uid: &str) -> String {
+    format!(
+        "Device {} has not trusted this computer: unlock it, tap \"Trust\" and scan again",
+        device_uid
+    )
+}
+
+/// Run `ideviceinfo -u <udid> -x [-q <domain>]` and return the raw XML plist.
+fn run_ideviceinfo(udid: &str, domain: Option<&str>) -> Result<Vec<u8>, String> {
+    let mut cmd = Command::new("ideviceinfo");
+    cmd.args(["-u", udid, "-x"]);
+    if let Some(domain) = domain {
+        cmd.args(["-q", domain]);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    let output = run_command_with_timeout(cmd, IDEVICEINFO_TIMEOUT).map_err(|e| match e {
+        CommandRunError::Spawn(_) => format!("ideviceinfo not found in PATH (install libimobiledevice): {e}"),
+        _ => format!("ideviceinfo failed: {e}"),
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ideviceinfo_error(udid, stderr.trim()));
+    }
+    Ok(output.stdout)
+}
+
+/// Turn ideviceinfo's stderr into something a user can act on. Pairing and
+/// lockdownd failures all mean the trust prompt was not accepted.
+fn ideviceinfo_error(udid: &str, stderr: &str) -> String {
+    libimobiledevice_error("ideviceinfo", udid, stderr)
+}
+
+fn libimobiledevice_error(tool: &str, udid: &str, stderr: &str) -> String {
+    let lower = stderr.to_ascii_lowercase();
+    if lower.contains("pair") || lower.contains("lockdownd") || lower.contains("password protected") {
+        untrusted_error(udid)
+    } else if lower.contains("no device found") {
+        format!("Device {} is no longer connected", udid)
+    } else {
+        format!("{} failed for {}: {}", tool, udid, stderr)
+    }
+}
+
+fn parse_ideviceinfo_plist(xml: &[u8]) -> Result<Dictionary, String> {
+    match Value::from_reader_xml(xml).map_err(|e| format!("Invalid ideviceinfo output: {e}"))? {
+        Value::Dictionary(dict) => Ok(dict),
+        _ => Err("Invalid ideviceinfo output: expected a dictionary".to_string()),
+    }
+}
+
+fn plist_string(info: &Dictionary, key: &str) -> Option<String> {
+    info.get(key)
+        .and_then(Value::as_string)
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+fn architecture_from_cpu(cpu: Option<&str>) -> CpuArchitecture {
+    match cpu.unwrap_or_default() {
+        a if a.starts_with("arm64") => CpuArchitecture::Arm64,
+        a if a.starts_with("armv") => CpuArchitecture::Arm,
+        _ => CpuArchitecture::Unknown,
+    }
+}
+
+/// Battery from the `com.apple.mobile.battery` domain. `None` without a level.
+fn battery_from_plist(info: &Dictionary) -> Option<BatteryState> {
+    let level = info.get("BatteryCurrentCapacity").and_then(Value::as_unsigned_integer)?;
+    Some(BatteryState {
+        level: level.min(100) as u8,
+        charging: info
+            .get("BatteryIsCharging")
+            .and_then(Value::as_boolean)
+            .unwrap_or(false),
+        temperature: None,
+        health: None,
+        voltage_mv: None,
+    })
+}
+
+/// Copy the lockdownd values into `state`.
+fn apply_ideviceinfo(state: &mut UnifiedDeviceState, info: &Dictionary, battery: Option<&Dictionary>) {
+    state.identity.manufacturer = "Apple".to_string();
+    if let Some(product_type) = plist_string(info, "ProductType") {
+        state.identity.model = product_type;
+    }
+    if let Some(class) = plist_string(info, "DeviceClass") {
+        state.identity.device_family = class.to_ascii_lowercase();
+    }
+    state.identity.serial_number = plist_string(info, "SerialNumber").or(state.identity.serial_number.take());
+    state.identity.imei = plist_string(info, "InternationalMobileEquipmentIdentity");
+    state.identity.meid = plist_string(info, "MobileEquipmentIdentifier");
+
+    state.software.os = match plist_string(info, "DeviceClass").as_deref() {
+        Some("iPad") => OperatingSystem::Ipados,
+        _ => OperatingSystem::Ios,
+    };
+    state.software.os_version = plist_string(info, "ProductVersion").unwrap_or_default();
+    state.software.build_number = plist_string(info, "BuildVersion");
+    state.software.firmware_version = plist_string(info, "FirmwareVersion");
+
+    state.hardware.architecture = architecture_from_cpu(plist_string(info, "CPUArchitecture").as_deref());
+    state.hardware.soc = plist_string(info, "HardwarePlatform");
+    state.hardware.hardware_rev = plist_string(info, "HardwareModel");
+    state.hardware.baseband = plist_string(info, "BasebandVersion");
+
+    if let Some(battery) = battery.and_then(battery_from_plist) {
+        state.battery = Some(battery);
+    }
+
+    // A successful lockdownd query means the device is paired.
+    state.connection.authorized = true;
+    state.connection.mode = StateMode::Normal;
+    state.capabilities.screenshot = true;
+    state.capabilities.backup = true;
+    state.capabilities.restore = true;
+    state.capabilities.dfu = true;
+}
+
+/// Full device info for a trusted iOS device from `ideviceinfo`.
+///
+/// The stored device state is updated and returned. Battery values are
+/// best-effort; a device that refuses the battery domain still returns.
+#[tauri::command(async)]
+pub fn ios_device_info_deep(
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+) -> Result<UnifiedDeviceState, String> {
+    let udid = resolve_ios_udid(&state, &device_uid)?;
+    let info = parse_ideviceinfo_plist(&run_ideviceinfo(&udid, None)?)?;
+    let battery = run_ideviceinfo(&udid, Some(BATTERY_DOMAIN))
+        .and_then(|xml| parse_ideviceinfo_plist(&xml))
+        .ok();
+
+    let mut device_state = match state.device_states.get(&device_uid) {
+        Some(existing) => existing,
+        None => {
+            let last = state
+                .last_scan
+                .lock()
+                .map_err(|_| "last_scan mutex poisoned".to_string())?;
+            let record = last
+                .iter()
+                .find(|d| d.device_uid == device_uid)
+                .ok_or_else(|| format!("Device {} not found in the most recent scan", device_uid))?;
+            device_state_from_record(record)
+        }
+    };
+    apply_ideviceinfo(&mut device_state, &info, battery.as_ref());
+    device_state.capabilities.screenshot &= crate::diagnostics::on_path(IDEVICESCREENSHOT);
+    device_state.touch();
+    state.device_states.upsert(device_state.clone());
+    Ok(device_state)
+}
+
+/// `<log_dir>/ios-screenshot-<udid>-<ms>.png`
+fn default_screenshot_path(log_dir: &Path, udid: &str, now_ms: u64) -> PathBuf {
+    let safe_udid: String = udid
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    log_dir.join(format!("ios-screenshot-{}-{}.png", safe_udid, now_ms))
+}
+
+/// Capture the screen of a trusted iOS device with `idevicescreenshot`.
+///
+/// Without `output_path` the image goes to a timestamped file in the log
+/// directory; otherwise the path is resolved inside the transfer directory
+/// and must not exist yet. Returns the absolute path, which is also emitted
+/// as an `ios-screenshot` event. iOS 16 and earlier need the Developer Disk
+/// Image mounted first.
+#[tauri::command(async)]
+pub fn ios_screenshot(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let udid = resolve_correlated_ios_udid(&state, &device_uid)?;
+
+    let target = match output_path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(path) => resolve_local_path(&transfer_base_dir(), path)?,
+        None => {
+            let log_dir = get_log_directory();
+            std::fs::create_dir_all(&log_dir).map_err(|e| format!("Failed to create log directory: {e}"))?;
+            default_screenshot_path(&log_dir, &udid, now_ms())
+        }
+    };
+    // Claim the name first so an existing file is never overwritten or removed
+    drop(create_new_output(&target)?);
+
+    let mut cmd = Command::new(IDEVICESCREENSHOT);
+    cmd.arg("-u").arg(&udid).arg(&target);
+    #[cfg(target_os = "windows")]
+    {
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    let captured = run_command_with_timeout(cmd, IDEVICESCREENSHOT_TIMEOUT)
+        .map_err(|e| match e {
+            CommandRunError::Spawn(_) => format!(
+                "idevicescreenshot not found in PATH; install libimobiledevice to take iOS screenshots: {e}"
+            ),
+            _ => format!("idevicescreenshot failed: {e}"),
+        })
+        .and_then(|output| {
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let message = if stderr.trim().is_empty() { stdout.trim() } else { stderr.trim() };
+                return Err(libimobiledevice_error(IDEVICESCREENSHOT, &udid, message));
+            }
+            if std::fs::metadata(&target).map(|m| m.len()).unwrap_or(0) == 0 {
+                return Err("idevicescreenshot produced no image data".to_string());
+            }
+            Ok(())
+        });
+    if let Err(e) = captured {
+        let _ = std::fs::remove_file(&target);
+        return Err(e);
+    }
+
+    let absolute = std::fs::canonicalize(&target).unwrap_or(target);
+    let path = absolute.to_string_lossy().to_string();
+    if let Some(mut device_state) = state.device_states.get(&device_uid) {
+        device_state.capabilities.screenshot = true;
+        device_state.touch();
+        state.device_states.upsert(device_state);
+    }
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.emit(
+            "ios-screenshot",
+            serde_json::json!({ "deviceUid": device_uid, "path": path }),
+        );
+    }
+    Ok(path)
+}
+
+fn backup_args(udid: &str, backup_dir: &Path) -> Vec<String> {
+    vec![
+        "-u".to_string(),
+        udid.to_string(),
+        "backup".to_string(),
+        "--full".to_string(),
+        backup_dir.to_string_lossy().to_string(),
+    ]
+}
+
+fn encryption_args(udid: &str, password: &str, backup_dir: &Path) -> Vec<String> {
+    vec![
+        "-u".to_string(),
+        udid.to_string(),
+        "encryption".to_string(),
+        "on".to_string(),
+        password.to_string(),
+        backup_dir.to_string_lossy().to_string(),
+    ]
+}
+
+fn restore_args(udid: &str, password: Option<&str>, backup_dir: &Path) -> Vec<String> {
+    let mut args = vec!["-u".to_string(), udid.to_string(), "restore".to_string(), "--system".to_string()];
+    if let Some(password) = password {
+        args.push("--password".to_string());
+        args.push(password.to_string());
+    }
+    args.push(backup_dir.to_string_lossy().to_string());
+    args
+}
+
+/// `RESTORE_<UDID>`, which `ios_restore` needs before it overwrites a device
+fn restore_confirm_token(udid: &str) -> String {
+    format!("RESTORE_{}", udid)
+}
+
+/// Percentage from an idevicebackup2 progress line such as
+/// `[=====               ]  25% Finished`.
+fn parse_backup_percent(line: &str) -> Option<f32> {
+    line.split_whitespace()
+        .find_map(|token| token.strip_suffix('%'))
+        .and_then(|n| n.parse::<f32>().ok())
+        .filter(|p| (0.0..=100.0).contains(p))
+}
+
+/// Status lines worth showing while the backup runs.
+fn is_backup_status_line(line: &str) -> bool {
+    ["Backup directory is", "Backup will be encrypted", "Starting backup for device", "Starting Restore"]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+}
+
+/// Run idevicebackup2, emitting `event` for each progress or status line.
+/// Returns the combined output.
+fn run_idevicebackup2(app_handle: &AppHandle, event: &str, device_uid: &str, args: &[String]) -> Result<String, String> {
+    let mut cmd = Command::new("idevicebackup2");
+    cmd.args(args).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    {
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("idevicebackup2 not found in PATH (install libimobiledevice): {e}"))?;
+
+    let (tx, rx) = std::sync::mpsc::channel::<String>();
+    let readers = [
+        child.stdout.take().map(|pipe| spawn_line_reader(pipe, tx.clone())),
+        child.stderr.take().map(|pipe| spawn_line_reader(pipe, tx.clone())),
+    ];
+    drop(tx);
+
+    let window = app_handle.get_webview_window("main");
+    let mut output = String::new();
+    let mut percent = 0.0;
+    let mut message = String::new();
+    for line in rx {
+        let line = line.trim().to_string();
+        if line.is_empty() {
+            continue;
+        }
+        output.push_str(&line);
+        output.push('\n');
+
+        let new_percent = parse_backup_percent(&line);
+        if new_percent.is_none() && !is_backup_status_line(&line) {
+            continue;
+        }
+        match new_percent {
+            Some(p) => percent = p,
+            None => message = line,
+        }
+        if let Some(window) = window.as_ref() {
+            let _ = window.emit(
+                event,
+                IosBackupProgressEvent {
+                    device_uid: device_uid.to_string(),
+                    percent,
+                    message: message.clone(),
+                },
+            );
+        }
+    }
+    for reader in readers.into_iter().flatten() {
+        let _ = reader.join();
+    }
+
+    let status = child.wait().map_err(|e| format!("Failed to wait for idevicebackup2: {e}"))?;
+    if status.success() {
+        Ok(output)
+    } else {
+        Err(libimobiledevice_error("idevicebackup2", device_uid, output.trim()))
+    }
+}
+
+/// Total size of the files under `path`.
+fn directory_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => directory_size(&entry.path()),
+            _ => entry.metadata().map(|m| m.len()).unwrap_or(0),
+        })
+        .sum()
+}
+
+fn resolve_backup_dir(backup_path: &str) -> Result<PathBuf, String> {
+    let dir = resolve_local_path(&transfer_base_dir(), backup_path)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create directory {}: {e}", dir.display()))?;
+    Ok(dir)
+}
+
+/// Full backup of a trusted iOS device with `idevicebackup2`.
+///
+/// With `encrypted`, backup encryption is turned on with `password` first;
+/// a device that already encrypts its backups keeps its existing password.
+/// Progress is emitted as `ios-backup-progress` events.
+#[tauri::command(async)]
+pub fn ios_backup(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+    backup_path: String,
+    encrypted: bool,
+    password: Option<String>,
+) -> Result<IosBackupResult, String> {
+    let password = password.filter(|p| !p.is_empty());
+    if encrypted && password.is_none() {
+        return Err("A password is required for an encrypted backup".to_string());
+    }
+    let udid = resolve_ios_udid(&state, &device_uid)?;
+    let backup_dir = resolve_backup_dir(&backup_path)?;
+
+    let started = Instant::now();
+    if let (true, Some(password)) = (encrypted, password.as_deref()) {
+        if let Err(e) = run_idevicebackup2(
+            &app_handle,
+            "ios-backup-progress",
+            &device_uid,
+            &encryption_args(&udid, password, &backup_dir),
+        ) {
+            if !e.contains("already enabled") {
+                return Err(e);
+            }
+        }
+    }
+    run_idevicebackup2(&app_handle, "ios-backup-progress", &device_uid, &backup_args(&udid, &backup_dir))?;
+
+    Ok(IosBackupResult {
+        path: backup_dir.to_string_lossy().to_string(),
+        size_bytes: directory_size(&backup_dir.join(&udid)),
+        device_udid: udid,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Restore a backup made by `ios_backup` to the same device.
+///
+/// The restore replaces everything on the device, so `confirm_token` must be
+/// `RESTORE_<UDID>`. `password` is needed when the backup is encrypted. The
+/// device reboots when the restore completes. Progress is emitted as
+/// `ios-restore-progress` events.
+#[tauri::command(async)]
+pub fn ios_restore(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+    backup_path: String,
+    password: Option<String>,
+    confirm_token: String,
+) -> Result<IosBackupResult, String> {
+    let udid = resolve_ios_udid(&state, &device_uid)?;
+    if confirm_token.trim() != restore_confirm_token(&udid) {
+        return Err("Confirmation token mismatch: a restore needs RESTORE_ followed by the device UDID".to_string());
+    }
+    let backup_dir = resolve_local_path(&transfer_base_dir(), &backup_path)?;
+    if !backup_dir.join(&udid).is_dir() {
+        return Err(format!("No backup for {} in {}", udid, backup_dir.display()));
+    }
+
+    let started = Instant::now();
+    let password = password.filter(|p| !p.is_empty());
+    run_idevicebackup2(
+        &app_handle,
+        "ios-restore-progress",
+        &device_uid,
+        &restore_args(&udid, password.as_deref(), &backup_dir),
+    )?;
+
+    Ok(IosBackupResult {
+        path: backup_dir.to_string_lossy().to_string(),
+        size_bytes: directory_size(&backup_dir.join(&udid)),
+        device_udid: udid,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// One button instruction of a DFU guide
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DfuStep {
+    pub instruction: String,
+    /// How long to give the user for this step before scanning
+    pub wait_ms: u64,
+    /// Mode a scan should show once the step is done; empty when the step has
+    /// no visible effect on its own
+    pub detect_mode: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DfuGuideSession {
+    pub session_id: String,
+    /// ProductType the guide was chosen for, when it could be determined
+    pub product_type: Option<String>,
+    pub steps: Vec<DfuStep>,
+    /// device_uid of the device seen in DFU mode; it differs from the one
+    /// the guide started with because DFU enumerates as a new USB device
+    pub dfu_device_uid: String,
+}
+
+/// Payload of the `dfu-guide-step` event, sent when a step starts and again
+/// after the scan that follows it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DfuGuideStepEvent {
+    session_id: String,
+    step_index: usize,
+    instruction: String,
+    /// Mode of the Apple device found by the scan, `disconnected` when there
+    /// was none; absent when the step has just started
+    detected_mode: Option<String>,
+}
+
+fn dfu_step(instruction: &str, wait_ms: u64, detect_mode: &str) -> DfuStep {
+    DfuStep {
+        instruction: instruction.to_string(),
+        wait_ms,
+        detect_mode: detect_mode.to_string(),
+    }
+}
+
+/// Button sequence for entering DFU on the given ProductType (e.g. `iPhone9,3`).
+/// Home-button iPhones are iPhone8,x and earlier (6s/SE), iPhone9,x is the
+/// iPhone 7, and everything from the iPhone 8 (iPhone10,x) on uses the
+/// volume-button sequence, which is also the fallback for unknown models.
+fn dfu_steps_for(product_type: Option<&str>) -> Vec<DfuStep> {
+    let dfu = DeviceMode::IosDfuLikely.as_str();
+    let generation = product_type
+        .and_then(|p| p.strip_prefix("iPhone"))
+        .and_then(|p| p.split(',').next())
+        .and_then(|major| major.parse::<u32>().ok());
+    match generation {
+        Some(major) if major <= 8 => vec![
+            dfu_step("Press and hold the Home button and the Side (or Top) button together", 10_000, ""),
+            dfu_step("Release the Side button but keep holding the Home button", 5_000, ""),
+            dfu_step("Keep holding the Home button until the device is detected", DFU_FINAL_HOLD_MS, dfu),
+        ],
+        Some(9) => vec![
+            dfu_step("Press and hold the Side button and the Volume Down button together", 10_000, ""),
+            dfu_step("Release the Side button but keep holding Volume Down", 5_000, ""),
+            dfu_step("Keep holding Volume Down until the device is detected", DFU_FINAL_HOLD_MS, dfu),
+        ],
+        _ => vec![
+            dfu_step("Press and quickly release Volume Up", 1_000, ""),
+            dfu_step("Press and quickly release Volume Down", 1_000, ""),
+            dfu_step("Press and hold the Side button until the screen goes black", 10_000, ""),
+            dfu_step("Keep holding the Side button and also press and hold Volume Down", 5_000, ""),
+            dfu_step("Release the Side button but keep holding Volume Down", DFU_FINAL_HOLD_MS, dfu),
+        ],
+    }
+}
+
+/// ProductType of a scanned iOS device: asked from the device when it is
+/// booted and trusted, otherwise taken from an earlier ios_device_info_deep.
+fn ios_product_type(state: &AppState, device_uid: &str) -> Result<Option<String>, String> {
+    let record = {
+        let last = state
+            .last_scan
+            .lock()
+            .map_err(|_| "last_scan mutex poisoned".to_string())?;
+        last.iter()
+            .find(|d| d.device_uid == device_uid)
+            .cloned()
+            .ok_or_else(|| format!("Device {} not found in the most recent scan", device_uid))?
+    };
+    if !record.mode.starts_with("ios") {
+        return Err(format!("Device {} is not an iOS device (mode '{}')", device_uid, record.mode));
+    }
+
+    if record.mode == DeviceMode::IosNormalLikely.as_str() {
+        let udid = record.matched_tool_ids.first().unwrap_or(&record.device_uid);
+        if let Ok(product_type) = run_ideviceinfo(udid, None)
+            .and_then(|xml| parse_ideviceinfo_plist(&xml))
+            .map(|info| plist_string(&info, "ProductType"))
+        {
+            return Ok(product_type);
+        }
+    }
+    Ok(state
+        .device_states
+        .get(device_uid)
+        .map(|s| s.identity.model)
+        .filter(|model| model.starts_with("iPhone")))
+}
+
+/// Walk the user through the button sequence that puts their iPhone into
+/// DFU mode.
+///
+/// The sequence depends on the generation: Home button (6s and earlier),
+/// iPhone 7, or iPhone 8 and later. Each step is announced with a
+/// `dfu-guide-step` event, then after `wait_ms` a USB scan reports the mode it
+/// sees in a second event. Returns as soon as a device shows up in
+/// `ios_dfu_likely`; fails if none has by the end of the sequence.
+#[tauri::command(async)]
+pub fn ios_enter_dfu_guide(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+) -> Result<DfuGuideSession, String> {
+    let product_type = ios_product_type(&state, &device_uid)?;
+    let steps = dfu_steps_for(product_type.as_deref());
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let registry = crate::config::classifier_registry(&state);
+    let window = app_handle.get_webview_window("main");
+    let emit = |step_index: usize, step: &DfuStep, detected_mode: Option<String>| {
+        if let Some(window) = window.as_ref() {
+            let _ = window.emit(
+                "dfu-guide-step",
+                DfuGuideStepEvent {
+                    session_id: session_id.clone(),
+                    step_index,
+                    instruction: step.instruction.clone(),
+                    detected_mode,
+                },
+            );
+        }
+    };
+
+    let mut last_mode = String::new();
+    for (index, step) in steps.iter().enumerate() {
+        emit(index, step, None);
+        std::thread::sleep(Duration::from_millis(step.wait_ms));
+
+        // DFU is recognised from the USB product id alone, so the cheap scan is enough
+        let records = bootforgeusb::scan_usb_only_with_registry(&registry)
+            .map_err(|e| format!("USB scan failed: {e}"))?;
+        let ios: Vec<_> = records.iter().filter(|r| r.mode.starts_with("ios")).collect();
+        if let Some(dfu) = ios.iter().find(|r| r.mode == DeviceMode::IosDfuLikely.as_str()) {
+            emit(index, step, Some(dfu.mode.clone()));
+            return Ok(DfuGuideSession {
+                session_id,
+                product_type,
+                steps,
+                dfu_device_uid: dfu.device_uid.clone(),
+            });
+        }
+        last_mode = ios.first().map_or_else(|| "disconnected".to_string(), |r| r.mode.clone());
+        emit(index, step, Some(last_mode.clone()));
+    }
+
+    Err(format!(
+        "The device did not enter DFU mode (last seen: {}). If the Apple logo appeared, the buttons were held too long; start again",
+        last_mode
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> Vec<u8> {
+        std::fs::read(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name))
+            .unwrap()
+    }
+
+    #[test]
+    fn maps_ideviceinfo_plist_into_device_state() {
+        let info = parse_ideviceinfo_plist(&fixture("ideviceinfo_iphone14.xml")).unwrap();
+        let battery = parse_ideviceinfo_plist(&fixture("ideviceinfo_battery.xml")).unwrap();
+        let mut state = UnifiedDeviceState::new(
+            "00008110-001A2B3C4D5E6F70".to_string(),
+            "unknown".to_string(),
+            "unknown".to_string(),
+            0x05ac,
+            0x12a8,
+        );
+
+        apply_ideviceinfo(&mut state, &info, Some(&battery));
+
+        assert_eq!(state.identity.manufacturer, "Apple");
+        assert_eq!(state.identity.model, "iPhone14,5");
+        assert_eq!(state.identity.serial_number.as_deref(), Some("F2LXK0ABCD12"));
+        assert_eq!(state.identity.device_family, "iphone");
+        assert_eq!(state.software.os, OperatingSystem::Ios);
+        assert_eq!(state.software.os_version, "17.4.1");
+        assert_eq!(state.software.build_number.as_deref(), Some("21E236"));
+        assert_eq!(state.hardware.architecture, CpuArchitecture::Arm64);
+        assert_eq!(state.hardware.soc.as_deref(), Some("t8110"));
+        let battery = state.battery.unwrap();
+        assert_eq!(battery.level, 87);
+        assert!(battery.charging);
+        assert!(state.connection.authorized);
+        assert!(state.capabilities.backup && state.capabilities.restore && state.capabilities.screenshot);
+    }
+
+    #[test]
+    fn pairing_failures_are_reported_as_untrusted() {
+        let err = ideviceinfo_error(
+            "00008110-001A2B3C4D5E6F70",
+            "ERROR: Could not connect to lockdownd: Pairing dialog response pending (-19)",
+        );
+        assert!(err.contains("has not trusted this computer"));
+        assert!(ideviceinfo_error("X", "No device found with udid X.").contains("no longer connected"));
+        assert!(parse_ideviceinfo_plist(b"not a plist").is_err());
+    }
+
+    #[test]
+    fn parses_idevicebackup2_progress_lines() {
+        assert_eq!(parse_backup_percent("[=====               ]  25% Finished"), Some(25.0));
+        assert_eq!(parse_backup_percent("[====================] 100% Finished"), Some(100.0));
+        assert_eq!(parse_backup_percent("Receiving files"), None);
+        assert!(is_backup_status_line("Backup directory is \"/tmp/backup\""));
+        assert!(is_backup_status_line("Starting backup for device 00008110-001A2B3C4D5E6F70..."));
+        assert!(!is_backup_status_line("Receiving files"));
+    }
+
+    #[test]
+    fn builds_idevicebackup2_arguments() {
+        let dir = Path::new("/tmp/backups");
+        assert_eq!(backup_args("UDID", dir), ["-u", "UDID", "backup", "--full", "/tmp/backups"]);
+        assert_eq!(
+            encryption_args("UDID", "secret", dir),
+            ["-u", "UDID", "encryption", "on", "secret", "/tmp/backups"]
+        );
+        assert_eq!(
+            restore_args("UDID", Some("secret"), dir),
+            ["-u", "UDID", "restore", "--system", "--password", "secret", "/tmp/backups"]
+        );
+        assert_eq!(restore_confirm_token("00008030-001A2B3C4D5E"), "RESTORE_00008030-001A2B3C4D5E");
+    }
+
+    #[test]
+    fn names_default_screenshots_after_the_udid() {
+        assert_eq!(
+            default_screenshot_path(Path::new("/tmp/logs"), "00008030-001A2B3C", 1700000000000),
+            Path::new("/tmp/logs/ios-screenshot-00008030_001A2B3C-1700000000000.png")
+        );
+    }
+
+    #[test]
+    fn picks_the_dfu_sequence_by_generation() {
+        let first = |product_type: Option<&str>| dfu_steps_for(product_type)[0].instruction.clone();
+        assert!(first(Some("iPhone8,1")).contains("Home"), "iPhone 6s");
+        assert!(first(Some("iPhone9,3")).contains("Side button and the Volume Down"), "iPhone 7");
+        assert!(first(Some("iPhone10,4")).contains("Volume Up"), "iPhone 8");
+        assert!(first(Some("iPhone15,2")).contains("Volume Up"), "iPhone 14 Pro");
+        assert!(first(None).contains("Volume Up"));
+
+        let steps = dfu_steps_for(Some("iPhone14,5"));
+        assert_eq!(steps.last().unwrap().detect_mode, "ios_dfu_likely");
+        assert!(steps[..steps.len() - 1].iter().all(|s| s.detect_mode.is_empty()));
+    }
+}
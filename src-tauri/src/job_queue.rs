@@ -0,0 +1,145 @@
+// Flash Job Queue
+// At most one active flash job per device serial; later jobs wait their turn
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// The job currently flashing a device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobHandle {
+    pub job_id: String,
+}
+
+/// Outcome of submitting a job to the queue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Admission {
+    /// The device was idle; the caller should start the job now
+    Start,
+    /// Another job is active; `position` is this job's 1-based place in line
+    Queued { position: usize },
+}
+
+#[derive(Default)]
+struct Slots {
+    /// device serial -> active job (`None` once the device went idle)
+    active: HashMap<String, Option<JobHandle>>,
+    /// device serial -> job ids waiting for the active one to finish
+    waiting: HashMap<String, VecDeque<String>>,
+}
+
+#[derive(Default)]
+pub struct JobQueue {
+    slots: Mutex<Slots>,
+}
+
+impl JobQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Claim `device_serial` for `job_id`, or queue behind the active job
+    /// when `allow_queue` is set.
+    pub fn admit(&self, device_serial: &str, job_id: &str, allow_queue: bool) -> Result<Admission, String> {
+        let serial = device_serial.trim();
+        let mut slots = self.slots.lock().map_err(|_| "job_queue mutex poisoned".to_string())?;
+        let active = slots.active.get(serial).cloned().flatten();
+        match active {
+            None => {
+                slots.active.insert(
+                    serial.to_string(),
+                    Some(JobHandle { job_id: job_id.to_string() }),
+                );
+                Ok(Admission::Start)
+            }
+            Some(handle) if !allow_queue => Err(format!(
+                "Device {} already has an active flash job ({})",
+                serial, handle.job_id
+            )),
+            Some(_) => {
+                let waiting = slots.waiting.entry(serial.to_string()).or_default();
+                waiting.push_back(job_id.to_string());
+                Ok(Admission::Queued { position: waiting.len() })
+            }
+        }
+    }
+
+    /// Release the device after `job_id` ended and hand it to the next
+    /// waiting job, whose id is returned so the caller can start it.
+    pub fn finish(&self, device_serial: &str, job_id: &str) -> Option<String> {
+        let serial = device_serial.trim();
+        let mut slots = self.slots.lock().ok()?;
+        let is_active = matches!(
+            slots.active.get(serial),
+            Some(Some(handle)) if handle.job_id == job_id
+        );
+        if !is_active {
+            return None;
+        }
+
+        let next = slots.waiting.get_mut(serial).and_then(|w| w.pop_front());
+        if slots.waiting.get(serial).is_some_and(|w| w.is_empty()) {
+            slots.waiting.remove(serial);
+        }
+        slots.active.insert(
+            serial.to_string(),
+            next.clone().map(|job_id| JobHandle { job_id }),
+        );
+        next
+    }
+
+    /// Drop a job that is still waiting. Returns false if it was not queued.
+    pub fn remove_queued(&self, device_serial: &str, job_id: &str) -> bool {
+        let serial = device_serial.trim();
+        let Ok(mut slots) = self.slots.lock() else {
+            return false;
+        };
+        let Some(waiting) = slots.waiting.get_mut(serial) else {
+            return false;
+        };
+        let before = waiting.len();
+        waiting.retain(|id| id != job_id);
+        before != waiting.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_job_is_rejected_without_allow_queue() {
+        let queue = JobQueue::new();
+        assert_eq!(queue.admit("ABC123", "job-1", false), Ok(Admission::Start));
+        let err = queue.admit("ABC123", "job-2", false).unwrap_err();
+        assert!(err.contains("job-1"));
+        // Other devices are unaffected.
+        assert_eq!(queue.admit("XYZ789", "job-3", false), Ok(Admission::Start));
+    }
+
+    #[test]
+    fn queued_jobs_start_in_order_when_the_active_one_finishes() {
+        let queue = JobQueue::new();
+        assert_eq!(queue.admit("ABC123", "job-1", true), Ok(Admission::Start));
+        assert_eq!(queue.admit("ABC123", "job-2", true), Ok(Admission::Queued { position: 1 }));
+        assert_eq!(queue.admit("ABC123", "job-3", true), Ok(Admission::Queued { position: 2 }));
+
+        // Only the active job can release the device.
+        assert_eq!(queue.finish("ABC123", "job-3"), None);
+        assert_eq!(queue.finish("ABC123", "job-1").as_deref(), Some("job-2"));
+        assert_eq!(queue.finish("ABC123", "job-2").as_deref(), Some("job-3"));
+        assert_eq!(queue.finish("ABC123", "job-3"), None);
+        assert_eq!(queue.admit("ABC123", "job-4", false), Ok(Admission::Start));
+    }
+
+    #[test]
+    fn cancelled_queued_job_is_skipped() {
+        let queue = JobQueue::new();
+        queue.admit("ABC123", "job-1", true).unwrap();
+        queue.admit("ABC123", "job-2", true).unwrap();
+        queue.admit("ABC123", "job-3", true).unwrap();
+
+        assert!(queue.remove_queued("ABC123", "job-2"));
+        assert!(!queue.remove_queued("ABC123", "job-1"));
+        assert_eq!(queue.finish("ABC123", "job-1").as_deref(), Some("job-3"));
+    }
+}
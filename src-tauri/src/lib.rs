@@ -1,5 +1,7 @@
 // Bobby's Workshop - Tauri Library
 // Module declarations
 
+pub mod net_utils;
+pub mod py_client;
 pub mod python_backend;
-pub mod py_client;
\ No newline at end of file
+pub mod ring_buffer;
@@ -0,0 +1,235 @@
+// Log Rotation
+// Size-based rotation for the logs in get_log_directory(), plus commands to list and delete them
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::get_log_directory;
+
+/// Logs larger than this are rotated before they are written to again
+pub(crate) const MAX_LOG_SIZE_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Node backend stdout/stderr, in the log directory
+pub(crate) const BACKEND_LOG: &str = "backend.log";
+
+/// Append-only audit trails in the log directory; neither they nor their
+/// rotated copies can be deleted through [`log_delete_file`]
+pub(crate) const AUDIT_LOGS: &[&str] = &[
+    "fastboot-erase.log",
+    "fastboot-oem.log",
+    "fastboot-vbmeta.log",
+    "device-shell.log",
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFileInfo {
+    pub path: String,
+    pub size: u64,
+    /// RFC 3339; the modification time where the filesystem has no creation time
+    pub created_at: String,
+}
+
+/// Rotates log files aside, so the next writer starts fresh.
+pub(crate) struct LogRotator;
+
+impl LogRotator {
+    /// Rename `log_path` to `<stem>-<timestamp>.log` next to it when it is
+    /// larger than `max_size_bytes`. Returns the rotated file, or `None` when
+    /// the log is missing or small enough.
+    pub(crate) fn rotate_if_needed(log_path: &Path, max_size_bytes: u64) -> io::Result<Option<PathBuf>> {
+        let size = match fs::metadata(log_path) {
+            Ok(meta) => meta.len(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if size <= max_size_bytes {
+            return Ok(None);
+        }
+        let target = rotated_path(log_path, Utc::now());
+        fs::rename(log_path, &target)?;
+        Ok(Some(target))
+    }
+
+    /// Copy a non-empty `log_path` to `<stem>-<timestamp>.log` and truncate it
+    /// in place, for logs another process still holds open in append mode.
+    pub(crate) fn copy_truncate(log_path: &Path) -> io::Result<Option<PathBuf>> {
+        match fs::metadata(log_path) {
+            Ok(meta) if meta.len() > 0 => {}
+            Ok(_) => return Ok(None),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let target = rotated_path(log_path, Utc::now());
+        fs::copy(log_path, &target)?;
+        fs::OpenOptions::new().write(true).open(log_path)?.set_len(0)?;
+        Ok(Some(target))
+    }
+}
+
+/// `<dir>/<stem>-<YYYYmmdd-HHMMSS>.log`, numbered if that name is taken
+fn rotated_path(log_path: &Path, now: DateTime<Utc>) -> PathBuf {
+    let stem = log_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "log".to_string());
+    let dir = log_path.parent().unwrap_or_else(|| Path::new("."));
+    let base = format!("{}-{}", stem, now.format("%Y%m%d-%H%M%S"));
+    let mut candidate = dir.join(format!("{}.log", base));
+    let mut n = 1;
+    while candidate.exists() {
+        candidate = dir.join(format!("{}-{}.log", base, n));
+        n += 1;
+    }
+    candidate
+}
+
+fn file_info(path: &Path) -> io::Result<LogFileInfo> {
+    let meta = fs::metadata(path)?;
+    let created = meta.created().or_else(|_| meta.modified())?;
+    Ok(LogFileInfo {
+        path: path.to_string_lossy().to_string(),
+        size: meta.len(),
+        created_at: DateTime::<Utc>::from(created).to_rfc3339(),
+    })
+}
+
+/// Every file directly inside `dir`, oldest first.
+fn list_files(dir: &Path) -> io::Result<Vec<LogFileInfo>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            files.push(file_info(&entry.path())?);
+        }
+    }
+    files.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.path.cmp(&b.path)));
+    Ok(files)
+}
+
+/// The live backend log, or an audit log or one of its rotated copies.
+fn is_protected(file_name: &str) -> bool {
+    file_name == BACKEND_LOG
+        || AUDIT_LOGS.iter().any(|audit| {
+            let stem = audit.trim_end_matches(".log");
+            file_name == *audit || (file_name.starts_with(&format!("{stem}-")) && file_name.ends_with(".log"))
+        })
+}
+
+/// Delete `path` if it is a file directly inside `dir` that is not protected.
+fn delete_file_in(dir: &Path, path: &Path) -> Result<(), String> {
+    let dir = fs::canonicalize(dir).map_err(|e| format!("Log directory unavailable: {e}"))?;
+    let file = fs::canonicalize(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    if file.parent() != Some(dir.as_path()) || !file.is_file() {
+        return Err(format!("{} is not a file in the log directory", path.display()));
+    }
+    let name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    if is_protected(&name) {
+        return Err(format!("{} is in use or an audit log and cannot be deleted", name));
+    }
+    fs::remove_file(&file).map_err(|e| format!("Failed to delete {}: {e}", file.display()))
+}
+
+/// Rotate the backend log now, whatever its size. The backend keeps writing
+/// to the same file, so it is copied aside and truncated rather than renamed.
+/// Returns the rotated file, or `None` when there is no log or it is empty.
+#[tauri::command]
+pub fn log_rotate_now() -> Result<Option<String>, String> {
+    LogRotator::copy_truncate(&get_log_directory().join(BACKEND_LOG))
+        .map(|rotated| rotated.map(|p| p.to_string_lossy().to_string()))
+        .map_err(|e| format!("Failed to rotate {}: {e}", BACKEND_LOG))
+}
+
+#[tauri::command]
+pub fn log_list_files() -> Result<Vec<LogFileInfo>, String> {
+    list_files(&get_log_directory()).map_err(|e| format!("Failed to list logs: {e}"))
+}
+
+/// Delete one file from the log directory; paths anywhere else, the live
+/// backend log and the audit logs are rejected.
+#[tauri::command]
+pub fn log_delete_file(path: String) -> Result<(), String> {
+    delete_file_in(&get_log_directory(), Path::new(path.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_oversized_logs_and_only_deletes_inside_the_log_dir() {
+        let dir = std::env::temp_dir().join(format!("bw-logrotate-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let log = dir.join(BACKEND_LOG);
+
+        assert!(LogRotator::rotate_if_needed(&log, 10).unwrap().is_none());
+        fs::write(&log, b"0123456789").unwrap();
+        assert!(LogRotator::rotate_if_needed(&log, 10).unwrap().is_none());
+        fs::write(&log, b"0123456789ab").unwrap();
+        let rotated = LogRotator::rotate_if_needed(&log, 10).unwrap().unwrap();
+        assert!(!log.exists());
+        let name = rotated.file_name().unwrap().to_string_lossy().to_string();
+        assert!(name.starts_with("backend-") && name.ends_with(".log"), "{name}");
+
+        // A second rotation within the same second gets its own name
+        fs::write(&log, b"0123456789ab").unwrap();
+        let again = LogRotator::rotate_if_needed(&log, 10).unwrap().unwrap();
+        assert_ne!(again, rotated);
+
+        let files = list_files(&dir).unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|f| f.size == 12));
+
+        let outside = std::env::temp_dir().join(format!("bw-logrotate-outside-{}", uuid::Uuid::new_v4()));
+        fs::write(&outside, b"keep").unwrap();
+        assert!(delete_file_in(&dir, &outside).is_err());
+        assert!(delete_file_in(&dir, &dir.join("..").join(outside.file_name().unwrap())).is_err());
+        assert!(outside.exists());
+        delete_file_in(&dir, &rotated).unwrap();
+        assert_eq!(list_files(&dir).unwrap().len(), 1);
+
+        fs::write(&log, b"live").unwrap();
+        assert!(delete_file_in(&dir, &log).is_err());
+        let erase_log = dir.join("fastboot-erase.log");
+        fs::write(&erase_log, b"audit").unwrap();
+        assert!(delete_file_in(&dir, &erase_log).is_err());
+        let rotated_erase_log = dir.join("fastboot-erase-20260101-000000.log");
+        fs::write(&rotated_erase_log, b"audit").unwrap();
+        assert!(delete_file_in(&dir, &rotated_erase_log).is_err());
+        assert!(log.exists() && erase_log.exists() && rotated_erase_log.exists());
+
+        let _ = fs::remove_file(&outside);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn manual_rotation_keeps_the_open_log_file() {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("bw-logrotate-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let log = dir.join(BACKEND_LOG);
+        assert!(LogRotator::copy_truncate(&log).unwrap().is_none());
+
+        let mut writer = fs::OpenOptions::new().create(true).append(true).open(&log).unwrap();
+        writer.write_all(b"before").unwrap();
+        let rotated = LogRotator::copy_truncate(&log).unwrap().unwrap();
+        assert_eq!(fs::read(&rotated).unwrap(), b"before");
+        assert!(LogRotator::copy_truncate(&log).unwrap().is_none());
+
+        // The writer's handle still points at the log, which starts over
+        writer.write_all(b"after").unwrap();
+        assert_eq!(fs::read(&log).unwrap(), b"after");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
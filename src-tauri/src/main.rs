@@ -7,24 +7,51 @@
 )]
 
 use std::process::{Command, Child, Stdio};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use tauri::{Manager, AppHandle, Emitter};
 use std::path::PathBuf;
 use std::env;
 use std::collections::{HashMap, HashSet};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 
 mod python_backend;
 mod py_client;
 mod fastapi_backend;
+mod adb_tools;
+mod fastboot_tools;
+mod ios_tools;
+mod device_watcher;
+mod config;
+mod net_utils;
+mod device_state_io;
+mod job_queue;
+mod flash_engine;
+mod edl_engine;
+mod scatter_file;
+mod image_inspect;
+mod preflight;
+mod diagnostics;
+mod audit_log;
+mod ws_device_server;
+mod profile_prefetcher;
+mod device_alias;
+mod throughput;
+mod log_rotate;
+mod platform_checks;
+#[allow(dead_code)] // full API is exported through lib.rs
+mod ring_buffer;
 use python_backend::{launch_python_backend, shutdown_python_backend};
 use py_client::PyWorkerClient;
 use fastapi_backend::{launch_fastapi_backend, shutdown_fastapi_backend};
+use job_queue::{Admission, JobQueue};
+use flash_engine::FastbootFlashEngine;
+use ring_buffer::RingBuffer;
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
 use serde::{Deserialize, Serialize};
+use bootforgeusb::model::Platform;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct FlashPartition {
@@ -35,18 +62,81 @@ struct FlashPartition {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct FlashJobConfig {
+    /// Deprecated: single-device form of `deviceSerials`. Each admitted job
+    /// carries its own serial here.
+    #[serde(default)]
     deviceSerial: String,
+    /// Devices to flash with the same images; one independent job per serial
+    #[serde(default)]
+    deviceSerials: Vec<String>,
     deviceBrand: String,
     flashMethod: String,
     partitions: Vec<FlashPartition>,
     verifyAfterFlash: bool,
     autoReboot: bool,
     wipeUserData: bool,
+    /// A/B slot to flash and activate ("a" or "b"). None leaves slots untouched.
+    #[serde(default)]
+    activeSlot: Option<String>,
+    /// Wait behind an active job on the same device instead of failing
+    #[serde(default)]
+    allowQueue: bool,
+    /// MTK scatter file, required by the spflashtool method
+    #[serde(default)]
+    scatterFile: Option<String>,
+    /// Qualcomm firehose programmer (.elf/.mbn), required by the edl method.
+    /// EDL jobs use the 9008 serial port (`COM5`, `/dev/ttyUSB0`) as the device serial.
+    #[serde(default)]
+    edlProgrammer: Option<String>,
+    /// Firehose storage type for the edl method (`emmc`, `ufs`, `nand`, `spinor`)
+    #[serde(default)]
+    edlMemory: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct FlashStartResponse {
+    /// First entry of `jobIds`
     jobId: String,
+    jobIds: Vec<String>,
+    /// Set when several devices were started; see flash_group_status
+    #[serde(skip_serializing_if = "Option::is_none")]
+    groupId: Option<String>,
+}
+
+impl FlashJobConfig {
+    /// `deviceSerials`, or the legacy `deviceSerial` when that list is empty.
+    fn target_serials(&self) -> Result<Vec<String>, String> {
+        let serials: Vec<String> = if self.deviceSerials.is_empty() {
+            vec![self.deviceSerial.trim().to_string()]
+        } else {
+            self.deviceSerials.iter().map(|s| s.trim().to_string()).collect()
+        };
+        if serials.iter().any(|s| s.is_empty()) {
+            return Err("deviceSerial is required".to_string());
+        }
+        for (i, serial) in serials.iter().enumerate() {
+            if serials[..i].contains(serial) {
+                return Err(format!("Device {} is listed more than once", serial));
+            }
+        }
+        Ok(serials)
+    }
+}
+
+/// Aggregate view of the jobs started by one multi-device flash_start call
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GroupFlashStatus {
+    group_id: String,
+    /// running while any job is queued/running; otherwise completed when all
+    /// jobs completed, failed when any failed, else cancelled
+    status: String,
+    /// Mean progress of the jobs
+    progress: u64,
+    completed: usize,
+    failed: usize,
+    cancelled: usize,
+    jobs: Vec<FlashOperationStatus>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,16 +149,19 @@ struct RealTimeFlashUpdate {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct DeviceHotplugEvent {
+pub(crate) struct DeviceHotplugEvent {
     #[serde(rename = "type")]
-    event_type: String,
-    device_uid: String,
-    platform_hint: String,
-    mode: String,
-    confidence: f32,
-    timestamp: String,
-    display_name: String,
-    matched_tool_ids: Vec<String>,
+    pub(crate) event_type: String,
+    pub(crate) device_uid: String,
+    #[serde(rename = "platform_hint")]
+    pub(crate) platform: Platform,
+    pub(crate) mode: String,
+    pub(crate) confidence: f32,
+    pub(crate) timestamp: String,
+    pub(crate) display_name: String,
+    pub(crate) matched_tool_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) speed: Option<bootforgeusb::model::ConnectionSpeed>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -91,6 +184,9 @@ struct FlashHistoryEntry {
     duration: u64,
     bytesWritten: u64,
     averageSpeed: u64,
+    /// Config the job ran with, for flash_job_clone; not sent to the UI
+    #[serde(skip)]
+    jobConfig: Option<FlashJobConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,6 +204,7 @@ struct FlashOperationStatus {
     timeRemaining: u64,
     logs: Vec<String>,
     startTime: u64,
+    partitionResults: Vec<PartitionResult>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -142,6 +239,24 @@ struct FlashOperationModel {
     canCancel: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum PartitionStatus {
+    Pending,
+    Flashing,
+    Done,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PartitionResult {
+    name: String,
+    status: PartitionStatus,
+    bytes_written: u64,
+    duration_ms: u64,
+}
+
 #[derive(Debug, Clone)]
 struct FlashJobRuntime {
     status: String,
@@ -149,13 +264,58 @@ struct FlashJobRuntime {
     current_step: String,
     total_steps: u64,
     completed_steps: u64,
-    logs: Vec<String>,
+    logs: RingBuffer<String>,
+    /// When each `logs` entry was added (ms since epoch), in the same order
+    log_times_ms: RingBuffer<u64>,
     start_time_ms: u64,
     end_time_ms: Option<u64>,
     total_bytes: u64,
     cancel_requested: bool,
     active_pid: Option<u32>,
+    partition_results: Vec<PartitionResult>,
     config: FlashJobConfig,
+    log_tail: FlashLogTail,
+}
+
+/// Channels registered through `flash_log_tail`; each new log line is sent to every one.
+#[derive(Default)]
+struct FlashLogTail(Vec<tauri::ipc::Channel<String>>);
+
+// A cloned runtime is a status snapshot and does not take over the subscribers.
+impl Clone for FlashLogTail {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl std::fmt::Debug for FlashLogTail {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "FlashLogTail({} subscribers)", self.0.len())
+    }
+}
+
+impl FlashJobRuntime {
+    fn push_log(&mut self, line: String) {
+        // Forwarded under the job lock so a tail never sees a line twice or misses one.
+        self.log_tail.0.retain(|channel| channel.send(line.clone()).is_ok());
+        self.logs.push(line);
+        self.log_times_ms.push(now_ms());
+    }
+
+    /// Drops every `flash_log_tail` channel; called once the job reaches a terminal status.
+    fn close_log_tail(&mut self) {
+        self.log_tail.0.clear();
+    }
+
+    /// Log lines added strictly after `since_ms`, oldest first.
+    fn logs_since(&self, since_ms: u64) -> Vec<String> {
+        self.log_times_ms
+            .iter()
+            .zip(self.logs.iter())
+            .filter(|(time, _)| **time > since_ms)
+            .map(|(_, line)| line.clone())
+            .collect()
+    }
 }
 
 fn to_bootforge_status(raw: &str) -> String {
@@ -171,6 +331,82 @@ fn to_bootforge_status(raw: &str) -> String {
     .to_string()
 }
 
+/// Normalize a user-supplied slot ("a", "_b", "B") to "a" or "b".
+fn normalize_slot(raw: &str) -> Result<String, String> {
+    let slot = raw.trim().trim_start_matches('_').to_ascii_lowercase();
+    match slot.as_str() {
+        "a" | "b" => Ok(slot),
+        _ => Err(format!("Invalid slot '{}': expected 'a' or 'b'", raw)),
+    }
+}
+
+/// Append the slot suffix to a partition the device reports as slotted
+/// (`has-slot:<name>` is `yes`) unless the name already carries one.
+/// userdata, metadata, persist and other single-copy partitions keep their name.
+fn slot_partition_name(name: &str, slot: Option<&str>, has_slot: bool) -> String {
+    match slot {
+        Some(slot) if has_slot && !name.ends_with("_a") && !name.ends_with("_b") => format!("{}_{}", name, slot),
+        _ => name.to_string(),
+    }
+}
+
+/// Standard Android partitions (without slot suffix).
+const STANDARD_PARTITIONS: &[&str] = &[
+    "boot", "init_boot", "vendor_boot", "system", "vendor", "userdata", "cache", "recovery",
+    "bootloader", "radio", "aboot", "vbmeta", "dtbo", "persist",
+];
+
+/// Partition names may only contain alphanumerics, dots, dashes and underscores.
+fn validate_partition_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Partition name cannot be empty".to_string());
+    }
+    if !name.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '-' || c == '_') {
+        return Err(format!("Invalid partition name format: {}", name));
+    }
+    Ok(())
+}
+
+/// Whether `name` (optionally slot-suffixed) is a standard Android partition.
+#[cfg(test)]
+fn is_standard_partition(name: &str) -> bool {
+    is_allowed_partition(name, STANDARD_PARTITIONS)
+}
+
+/// Whether `name` (optionally slot-suffixed) appears in `allowed`.
+fn is_allowed_partition<S: AsRef<str>>(name: &str, allowed: &[S]) -> bool {
+    let base = name
+        .strip_suffix("_a")
+        .or_else(|| name.strip_suffix("_b"))
+        .unwrap_or(name);
+    allowed.iter().any(|p| p.as_ref() == name || p.as_ref() == base)
+}
+
+/// Fill in `p.size` from the image file when the caller sent 0.
+///
+/// Fails when the image cannot be read; returns a warning when the size is
+/// still unknown (empty file and no size given).
+fn resolve_partition_size(p: &mut FlashPartition) -> Result<Option<String>, String> {
+    let metadata =
+        std::fs::metadata(&p.imagePath).map_err(|_| format!("Image file not found: {}", p.imagePath))?;
+    if p.size == 0 {
+        p.size = metadata.len();
+    }
+    if p.size == 0 {
+        return Ok(Some(format!("Size of partition {} is unknown ({} is empty)", p.name, p.imagePath)));
+    }
+    Ok(None)
+}
+
+/// The active partition allowlist and whether it is enforced.
+fn partition_allowlist(state: &AppState) -> Result<(Vec<String>, bool), String> {
+    let config = state
+        .config
+        .lock()
+        .map_err(|_| "config mutex poisoned".to_string())?;
+    Ok((config.allowed_partitions(), config.strict_partition_allowlist))
+}
+
 fn job_to_operation(job_id: &str, job: &FlashJobRuntime) -> FlashOperationModel {
     let status = to_bootforge_status(&job.status);
     let stage = job.current_step.clone();
@@ -198,7 +434,7 @@ fn job_to_operation(job_id: &str, job: &FlashJobRuntime) -> FlashOperationModel
             error: None,
             warnings: vec![],
         },
-        logs: job.logs.clone(),
+        logs: job.logs.to_vec(),
         canPause: false,
         canResume: false,
         canCancel: job.status == "running" || job.status == "queued",
@@ -219,6 +455,16 @@ fn iso_now() -> String {
     format!("{}", now_ms())
 }
 
+/// Queue an emitted event for the audit log, if it is available.
+fn record_audit_event(app_handle: &AppHandle, event: &str, payload: &impl Serialize) {
+    let Some(state) = app_handle.try_state::<AppState>() else {
+        return;
+    };
+    if let Some(logger) = state.audit_log.as_ref() {
+        logger.record(event, payload);
+    }
+}
+
 fn emit_flash_update(app_handle: &AppHandle, job_id: &str, kind: &str, data: serde_json::Value) {
     let payload = RealTimeFlashUpdate {
         kind: kind.to_string(),
@@ -227,17 +473,33 @@ fn emit_flash_update(app_handle: &AppHandle, job_id: &str, kind: &str, data: ser
         data,
     };
 
+    let event = format!("flash-progress:{}", job_id);
+    record_audit_event(app_handle, &event, &payload);
+
     // Per-job channel. In Tauri v2, emit to all windows.
     if let Some(window) = app_handle.get_webview_window("main") {
-        let _ = window.emit(&format!("flash-progress:{}", job_id), &payload);
+        let _ = window.emit(&event, &payload);
     }
 }
 
-fn emit_device_event(app_handle: &AppHandle, event: DeviceHotplugEvent) {
+fn emit_device_event(app_handle: &AppHandle, mut event: DeviceHotplugEvent) {
+    if let Some(alias) = app_handle
+        .try_state::<AppState>()
+        .and_then(|state| state.device_aliases.get(&event.device_uid))
+    {
+        event.display_name = alias;
+    }
+
+    // Typed fan-out for in-process Rust consumers.
+    if let Some(watcher) = app_handle.try_state::<device_watcher::DeviceWatcher>() {
+        watcher.broadcast(&event);
+    }
+
     let envelope = DeviceEventEnvelope {
         kind: "device_event".to_string(),
         event,
     };
+    record_audit_event(app_handle, "device-events", &envelope);
 
     // In Tauri v2, emit to all windows.
     if let Some(window) = app_handle.get_webview_window("main") {
@@ -266,8 +528,85 @@ fn run_command_capture_lines(mut cmd: Command) -> Result<Vec<String>, String> {
         .collect())
 }
 
+#[derive(Debug)]
+enum CommandRunError {
+    /// The program could not be started (usually not installed / not in PATH).
+    Spawn(std::io::Error),
+    /// The program did not exit within the allotted time and was killed.
+    TimedOut(std::time::Duration),
+    /// Waiting on the child process failed.
+    Wait(std::io::Error),
+}
+
+impl std::fmt::Display for CommandRunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandRunError::Spawn(e) => write!(f, "Failed to spawn: {e}"),
+            CommandRunError::TimedOut(t) => write!(f, "Timed out after {}s", t.as_secs()),
+            CommandRunError::Wait(e) => write!(f, "Failed to wait for process: {e}"),
+        }
+    }
+}
+
+/// Run a command to completion, killing it if it exceeds `timeout`.
+///
+/// stdout/stderr are drained on helper threads so a chatty child cannot
+/// block on a full pipe while we poll for exit.
+fn run_command_with_timeout(
+    mut cmd: Command,
+    timeout: std::time::Duration,
+) -> Result<std::process::Output, CommandRunError> {
+    use std::io::Read;
+
+    #[cfg(target_os = "windows")]
+    {
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = cmd.spawn().map_err(CommandRunError::Spawn)?;
+
+    let mut stdout_pipe = child.stdout.take();
+    let mut stderr_pipe = child.stderr.take();
+    let stdout_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(pipe) = stdout_pipe.as_mut() {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    });
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(pipe) = stderr_pipe.as_mut() {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    });
+
+    let started = std::time::Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => {
+                if started.elapsed() >= timeout {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(CommandRunError::TimedOut(timeout));
+                }
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+            Err(e) => return Err(CommandRunError::Wait(e)),
+        }
+    };
+
+    Ok(std::process::Output {
+        status,
+        stdout: stdout_reader.join().unwrap_or_default(),
+        stderr: stderr_reader.join().unwrap_or_default(),
+    })
+}
+
 fn fastboot_exists() -> bool {
-    let mut cmd = Command::new("fastboot");
+    let mut cmd = Command::new(config::fastboot_program());
     cmd.arg("--version")
         .stdout(Stdio::null())
         .stderr(Stdio::null());
@@ -280,8 +619,26 @@ fn fastboot_exists() -> bool {
         .unwrap_or(false)
 }
 
+/// SP Flash Tool's CLI has no version flag, so look it up on PATH instead.
+fn flash_tool_exists() -> bool {
+    #[cfg(target_os = "windows")]
+    let mut cmd = Command::new("where");
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = Command::new("which");
+    cmd.arg(flash_engine::FLASH_TOOL)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(target_os = "windows")]
+    {
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    cmd.status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
 fn adb_exists() -> bool {
-    let mut cmd = Command::new("adb");
+    let mut cmd = Command::new(config::adb_program());
     cmd.arg("version")
         .stdout(Stdio::null())
         .stderr(Stdio::null());
@@ -295,7 +652,13 @@ fn adb_exists() -> bool {
 }
 
 fn adb_list_serials() -> Vec<String> {
-    let mut cmd = Command::new("adb");
+    // accept device/unauthorized/recovery etc as "present" for hotplug
+    adb_device_states(&config::adb_program()).into_iter().map(|(serial, _)| serial).collect()
+}
+
+/// `(serial, state)` pairs from `<program> devices`; empty when adb cannot run.
+fn adb_device_states(program: &str) -> Vec<(String, String)> {
+    let mut cmd = Command::new(program);
     cmd.args(["devices"]);
     #[cfg(target_os = "windows")]
     {
@@ -319,14 +682,17 @@ fn adb_list_serials() -> Vec<String> {
             if serial.is_empty() || state.is_empty() {
                 return None;
             }
-            // accept device/unauthorized/recovery etc as "present" for hotplug
-            Some(serial.to_string())
+            Some((serial.to_string(), state.to_string()))
         })
         .collect()
 }
 
 fn fastboot_list_serials() -> Vec<String> {
-    let mut cmd = Command::new("fastboot");
+    fastboot_serials(&config::fastboot_program())
+}
+
+fn fastboot_serials(program: &str) -> Vec<String> {
+    let mut cmd = Command::new(program);
     cmd.args(["devices"]);
     #[cfg(target_os = "windows")]
     {
@@ -351,33 +717,189 @@ fn fastboot_list_serials() -> Vec<String> {
         .collect()
 }
 
+/// How often `wait_for_device_mode` re-runs `adb devices` and `fastboot devices`
+const DEVICE_MODE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Longest wait monitor_device_mode_changes accepts; longer timeouts are clamped
+const MAX_DEVICE_MODE_WAIT_MS: u64 = 10 * 60 * 1000;
+
+/// Mode `serial` is currently in: "fastboot", "adb" for a booted device
+/// (adb state `device`), or the adb state itself ("recovery", "sideload",
+/// "unauthorized", ...). `None` when neither tool lists it.
+fn current_device_mode(adb_program: &str, fastboot_program: &str, serial: &str) -> Option<String> {
+    if let Some((_, state)) = adb_device_states(adb_program).into_iter().find(|(s, _)| s == serial) {
+        return Some(if state == "device" { "adb".to_string() } else { state });
+    }
+    fastboot_serials(fastboot_program)
+        .iter()
+        .any(|s| s == serial)
+        .then(|| "fastboot".to_string())
+}
+
+/// Poll until `serial` is seen in one of `expected_modes` and return that mode.
+/// Fails on timeout, or as soon as `should_stop` returns true.
+fn wait_for_device_mode(
+    adb_program: &str,
+    fastboot_program: &str,
+    serial: &str,
+    expected_modes: &[String],
+    timeout: std::time::Duration,
+    should_stop: impl Fn() -> bool,
+) -> Result<String, String> {
+    let started = std::time::Instant::now();
+    let mut last_seen: Option<String> = None;
+    loop {
+        let mode = current_device_mode(adb_program, fastboot_program, serial);
+        if let Some(mode) = mode.as_ref().filter(|m| expected_modes.contains(m)) {
+            return Ok(mode.clone());
+        }
+        if mode.is_some() {
+            last_seen = mode;
+        }
+        if should_stop() {
+            return Err(format!("Stopped waiting for device {}", serial));
+        }
+        if started.elapsed() >= timeout {
+            return Err(format!(
+                "Device {} did not appear in {} within {} ms (last seen: {})",
+                serial,
+                expected_modes.join("/"),
+                timeout.as_millis(),
+                last_seen.as_deref().unwrap_or("not connected")
+            ));
+        }
+        std::thread::sleep(DEVICE_MODE_POLL_INTERVAL);
+    }
+}
+
+/// Wait for a device to reach one of `expected_modes` (see `current_device_mode`
+/// for the mode names), e.g. to follow it through a reboot. `timeout_ms` is
+/// capped at ten minutes; `monitor_device_mode_cancel` ends the wait early.
+#[tauri::command]
+async fn monitor_device_mode_changes(
+    state: tauri::State<'_, AppState>,
+    device_serial: String,
+    expected_modes: Vec<String>,
+    timeout_ms: u64,
+) -> Result<String, String> {
+    let serial = device_serial.trim().to_string();
+    if serial.is_empty() {
+        return Err("device_serial is required".to_string());
+    }
+    if expected_modes.is_empty() {
+        return Err("At least one expected mode is required".to_string());
+    }
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut waits = state
+            .device_mode_waits
+            .lock()
+            .map_err(|_| "device_mode_waits mutex poisoned".to_string())?;
+        if waits.contains_key(&serial) {
+            return Err(format!("Already waiting for a mode change on {}", serial));
+        }
+        waits.insert(serial.clone(), cancel.clone());
+    }
+
+    let timeout = std::time::Duration::from_millis(timeout_ms.min(MAX_DEVICE_MODE_WAIT_MS));
+    let waiting_for = serial.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        wait_for_device_mode(
+            &config::adb_program(),
+            &config::fastboot_program(),
+            &waiting_for,
+            &expected_modes,
+            timeout,
+            || cancel.load(Ordering::SeqCst),
+        )
+    })
+    .await;
+    if let Ok(mut waits) = state.device_mode_waits.lock() {
+        waits.remove(&serial);
+    }
+    result.map_err(|e| format!("Device monitor task failed: {}", e))?
+}
+
+/// Stop a running monitor_device_mode_changes wait for `device_serial`.
+#[tauri::command]
+fn monitor_device_mode_cancel(state: tauri::State<'_, AppState>, device_serial: String) -> Result<(), String> {
+    state
+        .device_mode_waits
+        .lock()
+        .map_err(|_| "device_mode_waits mutex poisoned".to_string())?
+        .get(device_serial.trim())
+        .ok_or_else(|| format!("No mode change wait running on {}", device_serial))?
+        .store(true, Ordering::SeqCst);
+    Ok(())
+}
+
 struct AppState {
     backend_server: Mutex<Option<Child>>,
-    flash_jobs: Mutex<HashMap<String, FlashJobRuntime>>,
+    /// Set while backend_restart is stopping and relaunching the Node backend
+    backend_restarting: AtomicBool,
+    flash_jobs: Mutex<HashMap<String, Arc<Mutex<FlashJobRuntime>>>>,
+    /// Job ids started together by a multi-device flash_start, keyed by group id
+    flash_groups: Mutex<HashMap<String, Vec<String>>>,
+    /// One active flash job per device serial
+    job_queue: JobQueue,
     flash_history: Mutex<Vec<FlashHistoryEntry>>,
     job_counter: AtomicU64,
-    device_monitor_started: Mutex<bool>,
-    py_client: Mutex<Option<PyWorkerClient>>,
+    device_monitor_started: AtomicBool,
+    device_monitor_stop: AtomicBool,
+    /// Monitor polls are skipped until this time (ms since epoch); 0 when not paused
+    monitor_paused_until: AtomicU64,
+    /// Running flash jobs; monitor polls are skipped while any is running.
+    /// Kept apart from `monitor_paused_until` so a job ending never cancels a manual pause.
+    flash_monitor_holds: AtomicUsize,
+    /// When the device monitor loop last started an iteration (ms since epoch); see the monitor watchdog
+    monitor_last_tick: AtomicU64,
+    /// Bumped on every monitor start; a loop that sees a newer generation has been replaced and exits
+    monitor_generation: AtomicU64,
+    py_client: Mutex<Option<Arc<PyWorkerClient>>>,
     py_backend_port: Mutex<Option<u16>>,
     fastapi_backend: Mutex<Option<Child>>,
+    last_scan: Mutex<Vec<bootforgeusb::model::ConfirmedDeviceRecord>>,
+    shell_cmd_allowlist: Mutex<Option<Vec<String>>>,
+    /// fastboot pids of in-flight fastboot_flash_raw calls, keyed by device serial
+    raw_flash_pids: Mutex<HashMap<String, u32>>,
+    /// Cancel flags of in-flight monitor_device_mode_changes calls, keyed by device serial
+    device_mode_waits: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    scan_history: Option<bootforgeusb::history::ScanHistoryLogger>,
+    /// Every emitted flash update and hotplug event, for debugging failed flashes
+    audit_log: Option<audit_log::EventAuditLogger>,
+    /// Latest UnifiedDeviceState per device_uid, fed by scans and device_info_deep
+    device_states: libbootforge::DeviceStateStore,
+    config: Mutex<config::WorkshopConfig>,
+    /// `<app_data>/config.json`, known once the app has been set up
+    config_path: Mutex<Option<PathBuf>>,
+    device_monitor_config: Mutex<device_watcher::DeviceMonitorConfig>,
+    /// Built-in boot profiles plus any imported with import_profiles
+    boot_profiles: Mutex<libbootforge::imaging::BootProfileRegistry>,
+    /// Boot profiles resolved for the devices in the last bootforgeusb_scan
+    profile_prefetcher: profile_prefetcher::ProfilePrefetcher,
+    /// User-assigned names shown instead of device_uids in hotplug events
+    device_aliases: device_alias::DeviceAliasStore,
+    /// Port the WebSocket device server is listening on, if it was started
+    ws_server_port: Mutex<Option<u16>>,
 }
 
-fn env_var_truthy(name: &str) -> bool {
-    match env::var(name) {
-        Ok(v) => matches!(v.to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"),
-        Err(_) => false,
-    }
-}
-
-fn should_start_node_backend() -> bool {
+fn should_start_node_backend(state: &AppState) -> bool {
     // ALWAYS AUTO-START backend for complete standalone experience
     // Backend is required for full functionality
-    // Set BW_DISABLE_NODE_BACKEND=1 to disable (not recommended)
-    !env_var_truthy("BW_DISABLE_NODE_BACKEND")
+    // Set disable_node_backend in config.json or BW_DISABLE_NODE_BACKEND=1 to disable (not recommended)
+    state
+        .config
+        .lock()
+        .map(|c| !c.disable_node_backend)
+        .unwrap_or(true)
 }
 
 #[tauri::command]
 fn get_backend_status(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    if state.backend_restarting.load(Ordering::Acquire) {
+        return Ok("Backend restarting...".to_string());
+    }
+
     let is_running = {
         let backend = state
             .backend_server
@@ -390,14 +912,14 @@ fn get_backend_status(state: tauri::State<'_, AppState>) -> Result<String, Strin
         return Ok("Backend running on http://localhost:3001".to_string());
     }
 
-    if should_start_node_backend() {
+    if should_start_node_backend(&state) {
         Ok(
             "Backend server is enabled but not running. Ensure Node.js is installed and check app logs for startup errors."
                 .to_string(),
         )
     } else {
         Ok(
-            "Backend server disabled. To enable the Node backend, unset BW_DISABLE_NODE_BACKEND or set it to 0."
+            "Backend server disabled. To enable the Node backend, set disable_node_backend to false in config.json and unset BW_DISABLE_NODE_BACKEND."
                 .to_string(),
         )
     }
@@ -408,380 +930,697 @@ fn get_app_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
 
+/// Scan for devices and start prefetching their boot profiles.
+#[tauri::command(async)]
+fn bootforgeusb_scan(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<bootforgeusb::model::ConfirmedDeviceRecord>, String> {
+    bootforgeusb_scan_grouped(app_handle, state).map(|result| result.devices)
+}
+
+/// Like bootforgeusb_scan, but also returns the devices grouped by USB hub.
+#[tauri::command(async)]
+fn bootforgeusb_scan_grouped(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<bootforgeusb::model::ScanGroupResult, String> {
+    let result = run_scan(&state)?;
+    state
+        .profile_prefetcher
+        .prefetch_in_background(app_handle, result.devices.clone());
+    Ok(result)
+}
+
+/// Scan, rank and group devices, recording the result as the last scan.
+pub(crate) fn run_scan(state: &AppState) -> Result<bootforgeusb::model::ScanGroupResult, String> {
+    // Ranked: highest confidence first, then ios/android/unknown, then uid. Grouped by USB hub.
+    let mut devices = bootforgeusb::scan_with_registry(&config::classifier_registry(state))
+        .map_err(|e| format!("USB scan failed: {e}"))?;
+    bootforgeusb::rank_devices(&mut devices);
+    let groups = bootforgeusb::group_by_hub(&devices);
+    let mut result = bootforgeusb::model::ScanGroupResult { devices, groups };
+    platform_checks::annotate_driver_status(&mut result.devices);
+    if let Ok(mut last) = state.last_scan.lock() {
+        *last = result.devices.clone();
+    }
+    device_state_io::sync_device_states(&state.device_states, &result.devices);
+    record_scan_history(state, &result.devices);
+    Ok(result)
+}
+
+/// Run a full scan and report how long each pipeline stage took. The
+/// devices are returned as well but do not replace the last scan.
+#[tauri::command(async)]
+fn scan_benchmark(state: tauri::State<'_, AppState>) -> Result<bootforgeusb::model::ScanBenchmark, String> {
+    bootforgeusb::scan_benchmark_with_registry(&config::classifier_registry(&state))
+        .map_err(|e| format!("USB scan failed: {e}"))
+}
+
+/// Explain how a device from the most recent scan was classified.
 #[tauri::command]
-fn bootforgeusb_scan() -> Result<Vec<bootforgeusb::model::DeviceRecord>, String> {
-    bootforgeusb::scan().map_err(|e| format!("USB scan failed: {e}"))
+fn scan_explain(
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+) -> Result<bootforgeusb::explain::ClassificationExplanation, String> {
+    let last = state
+        .last_scan
+        .lock()
+        .map_err(|_| "last_scan mutex poisoned".to_string())?;
+    let record = last
+        .iter()
+        .find(|r| r.device_uid == device_uid)
+        .ok_or_else(|| format!("Device {} not found in the last scan; run a scan first", device_uid))?;
+    Ok(bootforgeusb::explain::explain(record))
 }
 
+/// Id of the boot profile that best fits a device, as resolved for the last scan.
 #[tauri::command]
-fn flash_start(app_handle: AppHandle, state: tauri::State<'_, AppState>, config: FlashJobConfig) -> Result<FlashStartResponse, String> {
-    if config.flashMethod != "fastboot" {
-        return Err("Only fastboot is supported by the in-process (Tauri) flash backend".to_string());
+fn suggest_boot_profile(
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+) -> Result<Option<String>, String> {
+    Ok(state.profile_prefetcher.get(&device_uid)?.map(|p| p.id))
+}
+
+/// `<app_data>/boot_profiles`: profiles imported with import_profiles, loaded at startup
+const IMPORTED_PROFILES_DIR: &str = "boot_profiles";
+
+/// Write the given boot profiles to a ZIP bundle inside the user's home
+/// directory. Returns the bundle size in bytes.
+#[tauri::command(async)]
+fn export_profiles(
+    state: tauri::State<'_, AppState>,
+    profile_ids: Vec<String>,
+    output_path: String,
+) -> Result<u64, String> {
+    let home = dirs::home_dir().ok_or_else(|| "Home directory not available".to_string())?;
+    let target = adb_tools::resolve_local_path(&home, output_path.trim())?;
+    let registry = state
+        .boot_profiles
+        .lock()
+        .map_err(|_| "boot_profiles mutex poisoned".to_string())?;
+    registry
+        .export_bundle(&profile_ids, &target)
+        .map_err(|e| format!("Failed to export profiles: {e}"))
+}
+
+/// Load every profile from a bundle in the user's home directory made by
+/// export_profiles, and keep them for later runs. Ids that already exist,
+/// built-in ones included, are refused unless `overwrite` is set. Returns
+/// the imported ids.
+#[tauri::command(async)]
+fn import_profiles(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+    zip_path: String,
+    overwrite: bool,
+) -> Result<Vec<String>, String> {
+    let home = dirs::home_dir().ok_or_else(|| "Home directory not available".to_string())?;
+    let source = adb_tools::resolve_local_path(&home, zip_path.trim())?;
+    let profiles_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("App data directory is not available: {e}"))?
+        .join(IMPORTED_PROFILES_DIR);
+    let mut registry = state
+        .boot_profiles
+        .lock()
+        .map_err(|_| "boot_profiles mutex poisoned".to_string())?;
+    let ids = registry
+        .import_bundle(&source, overwrite)
+        .map_err(|e| format!("Failed to import profiles: {e}"))?;
+    registry
+        .save_profiles(&ids, &profiles_dir)
+        .map_err(|e| format!("Imported profiles could not be saved to {}: {e}", profiles_dir.display()))?;
+    Ok(ids)
+}
+
+/// Append a scan result to the on-disk scan history. Failures are logged, never fatal.
+fn record_scan_history(state: &AppState, devices: &[bootforgeusb::model::ConfirmedDeviceRecord]) {
+    if let Some(logger) = state.scan_history.as_ref() {
+        if let Err(e) = logger.record(devices) {
+            eprintln!("[Tauri] Failed to record scan history: {}", e);
+        }
     }
+}
 
-    if !fastboot_exists() {
-        return Err("fastboot not found in PATH".to_string());
+#[tauri::command]
+fn scan_history_path(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    let logger = state
+        .scan_history
+        .as_ref()
+        .ok_or_else(|| "Scan history is not available".to_string())?;
+    Ok(logger.current_path().to_string_lossy().to_string())
+}
+
+#[tauri::command]
+fn scan_history_recent(
+    state: tauri::State<'_, AppState>,
+    limit: usize,
+) -> Result<Vec<bootforgeusb::history::ScanHistoryEntry>, String> {
+    let logger = state
+        .scan_history
+        .as_ref()
+        .ok_or_else(|| "Scan history is not available".to_string())?;
+    logger
+        .recent(limit)
+        .map_err(|e| format!("Failed to read scan history: {e}"))
+}
+
+/// Partition name a sideload job must use for its single OTA package
+const SIDELOAD_PARTITION: &str = "ota";
+
+// Checks shared by flash_start and the flash_preflight_check checklist
+
+fn validate_flash_method(method: &str) -> Result<(), String> {
+    if matches!(method, "fastboot" | "sideload" | "spflashtool" | "edl") {
+        Ok(())
+    } else {
+        Err("Only fastboot, sideload, spflashtool and edl are supported by the in-process (Tauri) flash backend"
+            .to_string())
     }
+}
 
-    if config.deviceSerial.trim().is_empty() {
-        return Err("deviceSerial is required".to_string());
+/// The external tool `method` drives must be installed; edl talks to the port itself.
+fn check_flash_tool(method: &str) -> Result<(), String> {
+    match method {
+        "sideload" if !adb_exists() => Err("adb not found in PATH".to_string()),
+        "spflashtool" if !flash_tool_exists() => {
+            Err(format!("{} (SP Flash Tool) not found in PATH", flash_engine::FLASH_TOOL))
+        }
+        "fastboot" if !fastboot_exists() => Err("fastboot not found in PATH".to_string()),
+        _ => Ok(()),
     }
+}
 
-    if config.partitions.is_empty() {
-        return Err("At least one partition is required".to_string());
+/// Partition count and the options specific to the config's method.
+fn validate_flash_options(config: &FlashJobConfig) -> Result<(), String> {
+    match config.flashMethod.as_str() {
+        "edl" => return validate_edl_config(config),
+        _ if config.partitions.is_empty() => return Err("At least one partition is required".to_string()),
+        "sideload" => validate_sideload_config(config)?,
+        "spflashtool" => validate_spflashtool_config(config)?,
+        _ => {}
     }
+    Ok(())
+}
 
-    // Partition name allowlist (standard Android partitions only)
-    let allowed_partitions = [
-        "boot", "system", "vendor", "userdata", "cache", "recovery",
-        "bootloader", "radio", "aboot", "vbmeta", "dtbo", "persist"
-    ];
-    
-    for p in &config.partitions {
-        let partition_name = p.name.trim();
-        if partition_name.is_empty() {
-            return Err("Partition name cannot be empty".to_string());
+/// A valid partition name in the allowlist. Outside a non-strict allowlist
+/// the name is accepted with a warning; sideload's `ota` entry is exempt.
+fn check_partition_name(
+    name: &str,
+    allowed: &[String],
+    strict_allowlist: bool,
+    sideload: bool,
+) -> Result<Option<String>, String> {
+    validate_partition_name(name)?;
+    if sideload || is_allowed_partition(name, allowed) {
+        return Ok(None);
+    }
+    let message = format!("Partition '{}' is not in the partition allowlist", name);
+    if strict_allowlist {
+        Err(message)
+    } else {
+        Ok(Some(message))
+    }
+}
+
+/// A sideload job applies exactly one OTA zip; fastboot-only options are rejected.
+fn validate_sideload_config(config: &FlashJobConfig) -> Result<(), String> {
+    match config.partitions.as_slice() {
+        [p] if p.name.trim() == SIDELOAD_PARTITION => {}
+        _ => {
+            return Err(format!(
+                "sideload requires exactly one partition entry named '{}'",
+                SIDELOAD_PARTITION
+            ))
         }
-        // Validate partition name format (alphanumeric, dots, dashes, underscores)
-        if !partition_name.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '-' || c == '_') {
-            return Err(format!("Invalid partition name format: {}", partition_name));
+    }
+    if config.wipeUserData {
+        return Err("wipeUserData is not supported for sideload; wipe from recovery instead".to_string());
+    }
+    if config.activeSlot.as_deref().is_some_and(|s| !s.trim().is_empty()) {
+        return Err("activeSlot is not supported for sideload".to_string());
+    }
+    Ok(())
+}
+
+/// An spflashtool job downloads partitions named in its scatter file; the
+/// fastboot-only options are rejected.
+fn validate_spflashtool_config(config: &FlashJobConfig) -> Result<(), String> {
+    let scatter_path = config
+        .scatterFile
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| "scatterFile is required for spflashtool".to_string())?;
+    let scatter = scatter_file::ScatterFile::load(std::path::Path::new(scatter_path))?;
+    for p in &config.partitions {
+        if scatter.entry(p.name.trim()).is_none() {
+            return Err(format!("Partition '{}' is not in scatter file {}", p.name.trim(), scatter_path));
         }
-        // Additional check: warn if partition is not in allowlist (but allow it anyway for flexibility)
-        if !allowed_partitions.contains(&partition_name) {
-            eprintln!("WARNING: Partition '{}' is not in the standard allowlist", partition_name);
+    }
+    if config.wipeUserData {
+        return Err("wipeUserData is not supported for spflashtool; flash userdata from the scatter instead".to_string());
+    }
+    if config.activeSlot.as_deref().is_some_and(|s| !s.trim().is_empty()) {
+        return Err("activeSlot is not supported for spflashtool".to_string());
+    }
+    Ok(())
+}
+
+/// An edl job only loads and configures a firehose programmer: Firehose
+/// partition programming is not implemented, so partitions are refused, and
+/// slot and wipe options are fastboot-only.
+fn validate_edl_config(config: &FlashJobConfig) -> Result<(), String> {
+    if !config.partitions.is_empty() {
+        return Err("edl jobs only load the firehose programmer; partition programming is not supported".to_string());
+    }
+    edl_engine::validate_memory_name(config.edlMemory.as_deref().unwrap_or_default())?;
+    let programmer = config
+        .edlProgrammer
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| "edlProgrammer is required for edl".to_string())?;
+    if !std::path::Path::new(programmer).is_file() {
+        return Err(format!("Programmer file not found: {}", programmer));
+    }
+    if config.wipeUserData {
+        return Err("wipeUserData is not supported for edl".to_string());
+    }
+    if config.activeSlot.as_deref().is_some_and(|s| !s.trim().is_empty()) {
+        return Err("activeSlot is not supported for edl".to_string());
+    }
+    Ok(())
+}
+
+/// Transfer ratio from adb's final `Total xfer: 1.00x` line.
+fn parse_sideload_xfer(lines: &[String]) -> Option<f64> {
+    lines.iter().rev().find_map(|line| {
+        let rest = &line[line.find("Total xfer:")? + "Total xfer:".len()..];
+        rest.trim().trim_end_matches('x').trim().parse().ok()
+    })
+}
+
+#[tauri::command]
+fn flash_start(app_handle: AppHandle, state: tauri::State<'_, AppState>, mut config: FlashJobConfig) -> Result<FlashStartResponse, String> {
+    let method = config.flashMethod.as_str();
+    validate_flash_method(method)?;
+    let sideload = method == "sideload";
+    let spflashtool = method == "spflashtool";
+    let edl = method == "edl";
+
+    check_flash_tool(method)?;
+    let serials = config.target_serials()?;
+    validate_flash_options(&config)?;
+
+    config.activeSlot = match config.activeSlot.as_deref() {
+        Some(raw) if !raw.trim().is_empty() => Some(normalize_slot(raw)?),
+        _ => None,
+    };
+
+    let (allowed_partitions, strict_allowlist) = partition_allowlist(&state)?;
+    // Reported in every job's log as well as the backend log
+    let mut warnings = Vec::new();
+    for p in &mut config.partitions {
+        // Partitions outside the allowlist are only warned about unless the config makes it strict
+        if let Some(warning) = check_partition_name(p.name.trim(), &allowed_partitions, strict_allowlist, sideload)? {
+            warnings.push(warning);
         }
         if p.imagePath.trim().is_empty() {
             return Err(format!("imagePath missing for partition {}", p.name));
         }
-        let pb = PathBuf::from(&p.imagePath);
-        if !pb.exists() {
-            return Err(format!("Image file not found: {}", p.imagePath));
+        if let Some(warning) = resolve_partition_size(p)? {
+            warnings.push(warning);
         }
     }
-
-    let id = {
-        let next = state.job_counter.fetch_add(1, Ordering::SeqCst) + 1;
-        format!("tauri-{}-{}", now_ms(), next)
-    };
+    for warning in &warnings {
+        eprintln!("[Tauri] flash_start: {}", warning);
+    }
 
     let total_bytes: u64 = config.partitions.iter().map(|p| p.size).sum();
     let total_steps = config.partitions.len() as u64
         + if config.wipeUserData { 1 } else { 0 }
-        + if config.autoReboot { 1 } else { 0 };
-
-    let runtime = FlashJobRuntime {
-        status: "queued".to_string(),
-        progress: 0,
-        current_step: "Queued".to_string(),
-        total_steps,
-        completed_steps: 0,
-        logs: vec![],
-        start_time_ms: now_ms(),
-        end_time_ms: None,
-        total_bytes,
-        cancel_requested: false,
-        active_pid: None,
-        config: config.clone(),
-    };
-
-    {
-        let mut jobs = state.flash_jobs.lock().map_err(|_| "flash_jobs mutex poisoned".to_string())?;
-        jobs.insert(id.clone(), runtime);
-    }
-
-    emit_flash_update(
-        &app_handle,
-        &id,
-        "status",
-        serde_json::json!({
-            "status": "preparing",
-            "progress": 0,
-            "message": "Queued"
-        }),
-    );
-
-    // Run the job on a background thread.
-    let app_for_thread = app_handle.clone();
-    let id_for_thread = id.clone();
-
-    std::thread::spawn(move || {
-        let mut set_job_status = |status: &str, step: &str| {
-            let state = app_for_thread.state::<AppState>();
-            if let Ok(mut jobs) = state.flash_jobs.lock() {
-                if let Some(job) = jobs.get_mut(&id_for_thread) {
-                    job.status = status.to_string();
-                    job.current_step = step.to_string();
-                    if status == "completed" || status == "failed" || status == "cancelled" {
-                        job.end_time_ms = Some(now_ms());
-                    }
-                }
-            }
-            emit_flash_update(
-                &app_for_thread,
-                &id_for_thread,
-                "status",
-                serde_json::json!({ "status": status, "message": step }),
-            );
+        // SP Flash Tool cannot reboot the device (it restarts when unplugged); EDL jobs leave it in firehose.
+        + if config.autoReboot && !spflashtool && !edl { 1 } else { 0 }
+        // Sahara upload of the firehose programmer
+        + if edl { 1 } else { 0 }
+        // `fastboot --set-active` before the partitions
+        + if config.activeSlot.is_some() { 1 } else { 0 };
+
+    let log_capacity = state
+        .config
+        .lock()
+        .map(|c| c.max_flash_log_lines)
+        .unwrap_or(config::DEFAULT_MAX_FLASH_LOG_LINES);
+
+    // Every job is registered before any is admitted: a queued job can be
+    // handed its device as soon as it is admitted.
+    let mut job_ids = Vec::with_capacity(serials.len());
+    for serial in &serials {
+        let id = {
+            let next = state.job_counter.fetch_add(1, Ordering::SeqCst) + 1;
+            format!("tauri-{}-{}", now_ms(), next)
         };
-
-        let mut push_log = |line: &str| {
-            let state = app_for_thread.state::<AppState>();
-            if let Ok(mut jobs) = state.flash_jobs.lock() {
-                if let Some(job) = jobs.get_mut(&id_for_thread) {
-                    job.logs.push(line.to_string());
-                    if job.logs.len() > 5000 {
-                        let drain = job.logs.len() - 5000;
-                        job.logs.drain(0..drain);
-                    }
-                }
-            }
-            emit_flash_update(
-                &app_for_thread,
-                &id_for_thread,
-                "log",
-                serde_json::json!({ "message": line }),
-            );
+        let mut job_config = config.clone();
+        job_config.deviceSerial = serial.clone();
+        job_config.deviceSerials = vec![serial.clone()];
+
+        let mut runtime = FlashJobRuntime {
+            status: "queued".to_string(),
+            progress: 0,
+            current_step: "Queued".to_string(),
+            total_steps,
+            completed_steps: 0,
+            logs: RingBuffer::new(log_capacity),
+            log_times_ms: RingBuffer::new(log_capacity),
+            start_time_ms: now_ms(),
+            end_time_ms: None,
+            total_bytes,
+            cancel_requested: false,
+            active_pid: None,
+            partition_results: job_config
+                .partitions
+                .iter()
+                .map(|p| PartitionResult {
+                    name: p.name.clone(),
+                    status: PartitionStatus::Pending,
+                    bytes_written: 0,
+                    duration_ms: 0,
+                })
+                .collect(),
+            config: job_config,
+            log_tail: FlashLogTail::default(),
         };
-
-        let mut complete_step = |completed: u64, total: u64| {
-            let pct = if total == 0 { 0 } else { ((completed * 100) / total).min(100) };
-            let state = app_for_thread.state::<AppState>();
-            if let Ok(mut jobs) = state.flash_jobs.lock() {
-                if let Some(job) = jobs.get_mut(&id_for_thread) {
-                    job.completed_steps = completed;
-                    job.progress = pct;
-                }
-            }
-            emit_flash_update(
-                &app_for_thread,
-                &id_for_thread,
-                "progress",
-                serde_json::json!({ "progress": pct }),
-            );
-        };
-
-        let cancel_requested = || -> bool {
-            let state = app_for_thread.state::<AppState>();
-            if let Ok(jobs) = state.flash_jobs.lock() {
-                if let Some(job) = jobs.get(&id_for_thread) {
-                    return job.cancel_requested;
-                }
-            }
-            false
-        };
-
-        set_job_status("running", "Preparing");
-        push_log("[tauri-fastboot] Starting fastboot flash job");
-        if config.verifyAfterFlash {
-            push_log("[tauri-fastboot] NOTE: verifyAfterFlash is not implemented for fastboot backend");
+        for warning in &warnings {
+            runtime.push_log(format!("[tauri-fastboot] WARNING: {}", warning));
         }
+        let mut jobs = state.flash_jobs.lock().map_err(|_| "flash_jobs mutex poisoned".to_string())?;
+        jobs.insert(id.clone(), Arc::new(Mutex::new(runtime)));
+        job_ids.push(id);
+    }
 
-        let mut completed_steps: u64 = 0;
-        let total_steps_local = total_steps;
-
-        // Optional wipe
-        if config.wipeUserData {
-            if cancel_requested() {
-                set_job_status("cancelled", "Cancelled");
-                return;
-            }
-
-            set_job_status("running", "Wiping userdata (-w)");
-            push_log("[tauri-fastboot] fastboot -w");
-            let mut cmd = Command::new("fastboot");
-            cmd.arg("-s").arg(&config.deviceSerial).arg("-w");
-            #[cfg(target_os = "windows")]
-            {
-                cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-            }
-            match cmd.output() {
-                Ok(out) => {
-                    let combined = format!("{}{}", String::from_utf8_lossy(&out.stdout), String::from_utf8_lossy(&out.stderr));
-                    for line in combined.lines() {
-                        let line = line.trim();
-                        if !line.is_empty() {
-                            push_log(line);
+    // A busy device fails the whole call instead of leaving a partial group behind.
+    let mut admissions = Vec::with_capacity(job_ids.len());
+    for (serial, id) in serials.iter().zip(&job_ids) {
+        match state.job_queue.admit(serial, id, config.allowQueue) {
+            Ok(admission) => admissions.push(admission),
+            Err(e) => {
+                for ((serial, id), admission) in serials.iter().zip(&job_ids).zip(admissions) {
+                    match admission {
+                        Admission::Start => {
+                            if let Some(next_id) = state.job_queue.finish(serial, id) {
+                                spawn_flash_job(app_handle.clone(), next_id);
+                            }
                         }
-                    }
-                    if !out.status.success() {
-                        set_job_status("failed", "Wipe failed");
-                        emit_flash_update(
-                            &app_for_thread,
-                            &id_for_thread,
-                            "error",
-                            serde_json::json!({ "message": "fastboot -w failed" }),
-                        );
-                        return;
-                    }
-                }
-                Err(e) => {
-                    set_job_status("failed", "Wipe failed");
-                    emit_flash_update(
-                        &app_for_thread,
-                        &id_for_thread,
-                        "error",
-                        serde_json::json!({ "message": format!("Failed to run fastboot -w: {e}") }),
-                    );
-                    return;
-                }
-            }
-            completed_steps += 1;
-            complete_step(completed_steps, total_steps_local);
-        }
-
-        // Flash partitions
-        for p in &config.partitions {
-            if cancel_requested() {
-                set_job_status("cancelled", "Cancelled");
-                return;
-            }
-
-            set_job_status("running", &format!("Flashing {}", p.name));
-            push_log(&format!("[tauri-fastboot] fastboot flash {} {}", p.name, p.imagePath));
-
-            let mut cmd = Command::new("fastboot");
-            cmd.arg("-s").arg(&config.deviceSerial);
-            cmd.arg("flash").arg(&p.name).arg(&p.imagePath);
-            #[cfg(target_os = "windows")]
-            {
-                cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-            }
-
-            match cmd.output() {
-                Ok(out) => {
-                    let combined = format!("{}{}", String::from_utf8_lossy(&out.stdout), String::from_utf8_lossy(&out.stderr));
-                    for line in combined.lines() {
-                        let line = line.trim();
-                        if !line.is_empty() {
-                            push_log(line);
+                        Admission::Queued { .. } => {
+                            state.job_queue.remove_queued(serial, id);
                         }
                     }
-                    if !out.status.success() {
-                        set_job_status("failed", &format!("Flash failed: {}", p.name));
-                        emit_flash_update(
-                            &app_for_thread,
-                            &id_for_thread,
-                            "error",
-                            serde_json::json!({ "message": format!("fastboot flash {} failed", p.name) }),
-                        );
-                        return;
-                    }
                 }
-                Err(e) => {
-                    set_job_status("failed", &format!("Flash failed: {}", p.name));
-                    emit_flash_update(
-                        &app_for_thread,
-                        &id_for_thread,
-                        "error",
-                        serde_json::json!({ "message": format!("Failed to run fastboot flash {}: {e}", p.name) }),
-                    );
-                    return;
+                if let Ok(mut jobs) = state.flash_jobs.lock() {
+                    for id in &job_ids {
+                        jobs.remove(id);
+                    }
                 }
+                return Err(e);
             }
-
-            completed_steps += 1;
-            complete_step(completed_steps, total_steps_local);
         }
-
-        // Optional reboot
-        if config.autoReboot {
-            if cancel_requested() {
-                set_job_status("cancelled", "Cancelled");
-                return;
-            }
-
-            set_job_status("running", "Rebooting");
-            push_log("[tauri-fastboot] fastboot reboot");
-            let mut cmd = Command::new("fastboot");
-            cmd.arg("-s").arg(&config.deviceSerial).arg("reboot");
-            #[cfg(target_os = "windows")]
-            {
-                cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-            }
-            let _ = cmd.output().map(|out| {
-                let combined = format!("{}{}", String::from_utf8_lossy(&out.stdout), String::from_utf8_lossy(&out.stderr));
-                for line in combined.lines() {
-                    let line = line.trim();
-                    if !line.is_empty() {
-                        push_log(line);
-                    }
-                }
-            });
-            completed_steps += 1;
-            complete_step(completed_steps, total_steps_local);
+    }
+
+    // Registered before any job starts so the last one to finish can prune it
+    let group_id = if job_ids.len() > 1 {
+        let next = state.job_counter.fetch_add(1, Ordering::SeqCst) + 1;
+        let id = format!("tauri-group-{}-{}", now_ms(), next);
+        if let Ok(mut groups) = state.flash_groups.lock() {
+            groups.insert(id.clone(), job_ids.clone());
         }
+        Some(id)
+    } else {
+        None
+    };
 
-        set_job_status("completed", "Completed");
+    for ((serial, id), admission) in serials.iter().zip(&job_ids).zip(admissions) {
         emit_flash_update(
-            &app_for_thread,
-            &id_for_thread,
+            &app_handle,
+            id,
             "status",
-            serde_json::json!({ "status": "completed", "message": "Completed" }),
-        );
-        emit_flash_update(
-            &app_for_thread,
-            &id_for_thread,
-            "log",
-            serde_json::json!({ "message": "[tauri-fastboot] Job complete" }),
+            serde_json::json!({
+                "status": "preparing",
+                "progress": 0,
+                "message": "Queued",
+                "totalBytes": total_bytes
+            }),
         );
+        match admission {
+            Admission::Start => spawn_flash_job(app_handle.clone(), id.clone()),
+            Admission::Queued { position } => {
+                emit_flash_update(
+                    &app_handle,
+                    id,
+                    "log",
+                    serde_json::json!({
+                        "message": format!(
+                            "[tauri-fastboot] Waiting for the active job on {} (position {} in queue)",
+                            serial, position
+                        )
+                    }),
+                );
+            }
+        }
+    }
 
-        // Ensure no closures keep borrowing `state` before we lock other mutexes.
-        drop(set_job_status);
-        drop(push_log);
-        drop(complete_step);
-        drop(cancel_requested);
-
-        // Save a lightweight history entry for flash-api consumers
-        let end = now_ms();
-        let start = {
-            let state = app_for_thread.state::<AppState>();
-            let jobs = state.flash_jobs.lock().ok();
-            jobs.and_then(|j| j.get(&id_for_thread).map(|r| r.start_time_ms)).unwrap_or(end)
-        };
-        let duration = end.saturating_sub(start);
-        let entry = FlashHistoryEntry {
-            jobId: id_for_thread.clone(),
-            deviceSerial: config.deviceSerial.clone(),
-            deviceBrand: Some(config.deviceBrand.clone()),
-            flashMethod: config.flashMethod.clone(),
-            partitions: config.partitions.iter().map(|p| p.name.clone()).collect(),
-            status: "completed".to_string(),
-            startTime: start,
-            endTime: end,
-            duration,
-            bytesWritten: 0,
-            averageSpeed: 0,
+    Ok(FlashStartResponse {
+        jobId: job_ids[0].clone(),
+        jobIds: job_ids,
+        groupId: group_id,
+    })
+}
+
+/// Run an admitted job on a background thread. When it ends, the next job
+/// queued for the same device (if any) is started.
+fn spawn_flash_job(app_handle: AppHandle, job_id: String) {
+    let runtime = {
+        let state = app_handle.state::<AppState>();
+        let jobs = state.flash_jobs.lock();
+        jobs.ok().and_then(|jobs| jobs.get(&job_id).cloned())
+    };
+    let Some(runtime) = runtime else {
+        eprintln!("[Tauri] spawn_flash_job: unknown job {}", job_id);
+        return;
+    };
+    let Some(serial) = runtime.lock().ok().map(|job| job.config.deviceSerial.clone()) else {
+        return;
+    };
+
+    // Released after the next queued job has taken its own hold
+    let monitor_hold = device_watcher::FlashMonitorHold::new(&app_handle);
+    std::thread::spawn(move || {
+        let _monitor_hold = monitor_hold;
+        let _device_slot = DeviceJobSlot {
+            app_handle: app_handle.clone(),
+            serial,
+            job_id: job_id.clone(),
         };
-        let state = app_for_thread.state::<AppState>();
-        if let Ok(mut hist) = state.flash_history.lock() {
-            hist.insert(0, entry);
-            if hist.len() > 200 {
-                hist.truncate(200);
-            }
+        FastbootFlashEngine::new(app_handle.clone()).execute(job_id.clone(), runtime.clone());
+        record_flash_history(&app_handle, &job_id, &runtime);
+        let state = app_handle.state::<AppState>();
+        if let (Ok(mut groups), Ok(jobs)) = (state.flash_groups.lock(), state.flash_jobs.lock()) {
+            prune_finished_groups(&mut groups, &jobs);
         };
     });
+}
+
+/// Drop the groups whose jobs have all completed, failed or been cancelled.
+/// Their jobs stay readable through flash_status.
+fn prune_finished_groups(
+    groups: &mut HashMap<String, Vec<String>>,
+    jobs: &HashMap<String, Arc<Mutex<FlashJobRuntime>>>,
+) {
+    groups.retain(|_, job_ids| {
+        job_ids.iter().any(|id| {
+            jobs.get(id).and_then(|job| job.lock().ok()).is_some_and(|job| {
+                job.status != "completed" && job.status != "failed" && job.status != "cancelled"
+            })
+        })
+    });
+}
+
+/// A job's claim on its device in the job queue. Dropping it, even while a
+/// panicking flash thread unwinds, frees the device and starts the next
+/// queued job.
+struct DeviceJobSlot {
+    app_handle: AppHandle,
+    serial: String,
+    job_id: String,
+}
+
+impl Drop for DeviceJobSlot {
+    fn drop(&mut self) {
+        let next = self.app_handle.state::<AppState>().job_queue.finish(&self.serial, &self.job_id);
+        if let Some(next_id) = next {
+            spawn_flash_job(self.app_handle.clone(), next_id);
+        }
+    }
+}
+
+/// Save a lightweight history entry for flash-api consumers once a job completes.
+fn record_flash_history(app_handle: &AppHandle, job_id: &str, runtime: &Mutex<FlashJobRuntime>) {
+    let Some((config, start)) = runtime
+        .lock()
+        .ok()
+        .filter(|job| job.status == "completed")
+        .map(|job| (job.config.clone(), job.start_time_ms))
+    else {
+        return;
+    };
+    let end = now_ms();
+    let duration = end.saturating_sub(start);
+    let entry = FlashHistoryEntry {
+        jobId: job_id.to_string(),
+        deviceSerial: config.deviceSerial.clone(),
+        deviceBrand: Some(config.deviceBrand.clone()),
+        flashMethod: config.flashMethod.clone(),
+        partitions: config.partitions.iter().map(|p| p.name.clone()).collect(),
+        status: "completed".to_string(),
+        startTime: start,
+        endTime: end,
+        duration,
+        bytesWritten: 0,
+        averageSpeed: 0,
+        jobConfig: Some(config),
+    };
+    let state = app_handle.state::<AppState>();
+    let max_history = state
+        .config
+        .lock()
+        .map(|c| c.max_flash_history_entries)
+        .unwrap_or_else(|_| config::WorkshopConfig::default().max_flash_history_entries);
+    if let Ok(mut hist) = state.flash_history.lock() {
+        hist.insert(0, entry);
+        if hist.len() > max_history {
+            hist.truncate(max_history);
+        }
+    };
+}
+
+/// Start a job on `new_device_serial` with the config of an earlier job, found
+/// among the current jobs or the flash history. Image paths are reused as-is.
+#[tauri::command]
+fn flash_job_clone(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+    original_job_id: String,
+    new_device_serial: String,
+) -> Result<FlashStartResponse, String> {
+    let new_serial = new_device_serial.trim().to_string();
+    if new_serial.is_empty() {
+        return Err("new_device_serial is required".to_string());
+    }
+    let running = {
+        let jobs = state.flash_jobs.lock().map_err(|_| "flash_jobs mutex poisoned".to_string())?;
+        match jobs.get(&original_job_id) {
+            Some(job) => Some(job.lock().map_err(|_| "flash job mutex poisoned".to_string())?.config.clone()),
+            None => None,
+        }
+    };
+    let original = match running {
+        Some(config) => config,
+        None => state
+            .flash_history
+            .lock()
+            .map_err(|_| "flash_history mutex poisoned".to_string())?
+            .iter()
+            .find(|entry| entry.jobId == original_job_id)
+            .and_then(|entry| entry.jobConfig.clone())
+            .ok_or_else(|| "Unknown jobId".to_string())?,
+    };
 
-    Ok(FlashStartResponse { jobId: id })
+    if let Some(missing) = original.partitions.iter().find(|p| !std::path::Path::new(&p.imagePath).is_file()) {
+        return Err(format!("Image file for {} no longer exists: {}", missing.name, missing.imagePath));
+    }
+    let config = FlashJobConfig {
+        deviceSerial: new_serial,
+        deviceSerials: vec![],
+        ..original
+    };
+    flash_start(app_handle, state, config)
 }
 
 #[tauri::command]
 fn flash_cancel(state: tauri::State<'_, AppState>, jobId: String) -> Result<(), String> {
     let mut jobs = state.flash_jobs.lock().map_err(|_| "flash_jobs mutex poisoned".to_string())?;
-    let job = jobs.get_mut(&jobId).ok_or_else(|| "Unknown jobId".to_string())?;
+    let job = jobs.get(&jobId).ok_or_else(|| "Unknown jobId".to_string())?;
+    let mut job = job.lock().map_err(|_| "flash job mutex poisoned".to_string())?;
     job.cancel_requested = true;
     job.status = "cancelled".to_string();
     job.end_time_ms = Some(now_ms());
+    job.close_log_tail();
+    // A job still waiting for its device never starts.
+    state.job_queue.remove_queued(&job.config.deviceSerial, &jobId);
+    // Stop the running fastboot process; the flash thread notices the failed step and exits.
+    if let Some(pid) = job.active_pid {
+        // The process may already have exited between the pid snapshot and the signal.
+        if let Err(e) = terminate_process(pid) {
+            eprintln!("[Tauri] flash_cancel: {}", e);
+        }
+    }
     Ok(())
 }
 
+/// Ask a process to terminate: SIGTERM on Unix, taskkill on Windows.
+fn terminate_process(pid: u32) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = Command::new("taskkill");
+        cmd.args(["/PID", &pid.to_string(), "/T", "/F"]);
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+        cmd
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        let mut cmd = Command::new("kill");
+        cmd.arg("-TERM").arg(pid.to_string());
+        cmd
+    };
+    let status = cmd
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("Failed to signal process {pid}: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("Failed to terminate process {pid}"))
+    }
+}
+
+/// Forward each line of a child's output pipe to `tx` from a dedicated thread.
+fn spawn_line_reader<R: std::io::Read + Send + 'static>(
+    pipe: R,
+    tx: std::sync::mpsc::Sender<String>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        use std::io::BufRead;
+        for line in std::io::BufReader::new(pipe).lines().map_while(Result::ok) {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    })
+}
+
 #[tauri::command]
 fn bootforge_flash_history(state: tauri::State<'_, AppState>, limit: Option<usize>) -> Result<Vec<FlashOperationModel>, String> {
     let jobs = state.flash_jobs.lock().map_err(|_| "flash_jobs mutex poisoned".to_string())?;
     let mut items: Vec<(u64, String, FlashOperationModel)> = Vec::new();
     for (job_id, job) in jobs.iter() {
+        let Ok(job) = job.lock() else { continue };
         if job.status == "completed" || job.status == "failed" || job.status == "cancelled" {
             let sort_key = job.end_time_ms.unwrap_or(job.start_time_ms);
-            items.push((sort_key, job_id.clone(), job_to_operation(job_id, job)));
+            items.push((sort_key, job_id.clone(), job_to_operation(job_id, &job)));
         }
     }
     items.sort_by(|a, b| b.0.cmp(&a.0));
@@ -794,20 +1633,42 @@ fn bootforge_flash_active(state: tauri::State<'_, AppState>) -> Result<Vec<Flash
     let jobs = state.flash_jobs.lock().map_err(|_| "flash_jobs mutex poisoned".to_string())?;
     let mut out = Vec::new();
     for (job_id, job) in jobs.iter() {
+        let Ok(job) = job.lock() else { continue };
         if job.status == "running" || job.status == "queued" || job.status == "paused" {
-            out.push(job_to_operation(job_id, job));
+            out.push(job_to_operation(job_id, &job));
         }
     }
     Ok(out)
 }
 
+/// Point-in-time copy of every queued, running or paused job, for a frontend
+/// that missed `flash-progress:` events. With `since_ms`, each job's logs are
+/// limited to lines added after that time.
 #[tauri::command]
-fn flash_status(state: tauri::State<'_, AppState>, jobId: String) -> Result<FlashOperationStatus, String> {
+fn flash_progress_snapshot(
+    state: tauri::State<'_, AppState>,
+    since_ms: Option<u64>,
+) -> Result<Vec<FlashOperationModel>, String> {
+    // Holding the map lock keeps jobs from being added or removed mid-snapshot.
     let jobs = state.flash_jobs.lock().map_err(|_| "flash_jobs mutex poisoned".to_string())?;
-    let job = jobs.get(&jobId).ok_or_else(|| "Unknown jobId".to_string())?;
-    let elapsed = now_ms().saturating_sub(job.start_time_ms);
-    Ok(FlashOperationStatus {
-        jobId: jobId.clone(),
+    let mut out = Vec::new();
+    for (job_id, job) in jobs.iter() {
+        let job = job.lock().map_err(|_| "flash job mutex poisoned".to_string())?;
+        if job.status == "running" || job.status == "queued" || job.status == "paused" {
+            let mut operation = job_to_operation(job_id, &job);
+            if let Some(since_ms) = since_ms {
+                operation.logs = job.logs_since(since_ms);
+            }
+            out.push(operation);
+        }
+    }
+    out.sort_by(|a, b| a.progress.startedAt.cmp(&b.progress.startedAt).then_with(|| a.id.cmp(&b.id)));
+    Ok(out)
+}
+
+fn operation_status(job_id: &str, job: &FlashJobRuntime, include_logs: bool) -> FlashOperationStatus {
+    FlashOperationStatus {
+        jobId: job_id.to_string(),
         status: job.status.clone(),
         progress: job.progress,
         currentStep: job.current_step.clone(),
@@ -816,11 +1677,93 @@ fn flash_status(state: tauri::State<'_, AppState>, jobId: String) -> Result<Flas
         bytesWritten: 0,
         totalBytes: job.total_bytes,
         speed: 0,
-        timeElapsed: elapsed,
+        timeElapsed: now_ms().saturating_sub(job.start_time_ms),
         timeRemaining: 0,
-        logs: job.logs.clone(),
+        logs: if include_logs { job.logs.to_vec() } else { vec![] },
         startTime: job.start_time_ms,
-    })
+        partitionResults: job.partition_results.clone(),
+    }
+}
+
+#[tauri::command]
+fn flash_status(state: tauri::State<'_, AppState>, jobId: String) -> Result<FlashOperationStatus, String> {
+    let jobs = state.flash_jobs.lock().map_err(|_| "flash_jobs mutex poisoned".to_string())?;
+    let job = jobs.get(&jobId).ok_or_else(|| "Unknown jobId".to_string())?;
+    let job = job.lock().map_err(|_| "flash job mutex poisoned".to_string())?;
+    Ok(operation_status(&jobId, &job, true))
+}
+
+/// Streams a job's log over `channel`: the buffered lines first, then each new line
+/// as it is logged. The channel is dropped once the job completes, fails or is cancelled.
+#[tauri::command]
+fn flash_log_tail(
+    state: tauri::State<'_, AppState>,
+    job_id: String,
+    channel: tauri::ipc::Channel<String>,
+) -> Result<(), String> {
+    let jobs = state.flash_jobs.lock().map_err(|_| "flash_jobs mutex poisoned".to_string())?;
+    let job = jobs.get(&job_id).ok_or_else(|| "Unknown jobId".to_string())?;
+    let mut job = job.lock().map_err(|_| "flash job mutex poisoned".to_string())?;
+    for line in job.logs.iter() {
+        channel.send(line.clone()).map_err(|e| format!("Failed to send log line: {}", e))?;
+    }
+    if job.end_time_ms.is_none() {
+        job.log_tail.0.push(channel);
+    }
+    Ok(())
+}
+
+/// Stops every `flash_log_tail` stream for the job.
+#[tauri::command]
+fn flash_log_unsubscribe(state: tauri::State<'_, AppState>, job_id: String) -> Result<(), String> {
+    let jobs = state.flash_jobs.lock().map_err(|_| "flash_jobs mutex poisoned".to_string())?;
+    let job = jobs.get(&job_id).ok_or_else(|| "Unknown jobId".to_string())?;
+    job.lock().map_err(|_| "flash job mutex poisoned".to_string())?.close_log_tail();
+    Ok(())
+}
+
+fn aggregate_group_status(group_id: &str, jobs: Vec<FlashOperationStatus>) -> GroupFlashStatus {
+    let count = |status: &str| jobs.iter().filter(|j| j.status == status).count();
+    let (completed, failed, cancelled) = (count("completed"), count("failed"), count("cancelled"));
+    let status = if completed + failed + cancelled < jobs.len() {
+        "running"
+    } else if completed == jobs.len() {
+        "completed"
+    } else if failed > 0 {
+        "failed"
+    } else {
+        "cancelled"
+    };
+    let progress = jobs.iter().map(|j| j.progress).sum::<u64>() / jobs.len().max(1) as u64;
+    GroupFlashStatus {
+        group_id: group_id.to_string(),
+        status: status.to_string(),
+        progress,
+        completed,
+        failed,
+        cancelled,
+        jobs,
+    }
+}
+
+/// Combined status of the jobs started by one multi-device flash_start.
+#[tauri::command]
+fn flash_group_status(state: tauri::State<'_, AppState>, group_id: String) -> Result<GroupFlashStatus, String> {
+    let job_ids = state
+        .flash_groups
+        .lock()
+        .map_err(|_| "flash_groups mutex poisoned".to_string())?
+        .get(&group_id)
+        .cloned()
+        .ok_or_else(|| "Unknown groupId".to_string())?;
+    let jobs = state.flash_jobs.lock().map_err(|_| "flash_jobs mutex poisoned".to_string())?;
+    let mut statuses = Vec::new();
+    for job_id in &job_ids {
+        let Some(job) = jobs.get(job_id) else { continue };
+        let job = job.lock().map_err(|_| "flash job mutex poisoned".to_string())?;
+        statuses.push(operation_status(job_id, &job, false));
+    }
+    Ok(aggregate_group_status(&group_id, statuses))
 }
 
 #[tauri::command]
@@ -835,104 +1778,232 @@ fn flash_active(state: tauri::State<'_, AppState>) -> Result<Vec<FlashOperationS
     let jobs = state.flash_jobs.lock().map_err(|_| "flash_jobs mutex poisoned".to_string())?;
     let mut out = Vec::new();
     for (job_id, job) in jobs.iter() {
+        let Ok(job) = job.lock() else { continue };
         if job.status == "running" || job.status == "queued" || job.status == "paused" {
-            let elapsed = now_ms().saturating_sub(job.start_time_ms);
-            out.push(FlashOperationStatus {
-                jobId: job_id.clone(),
-                status: job.status.clone(),
-                progress: job.progress,
-                currentStep: job.current_step.clone(),
-                totalSteps: job.total_steps,
-                completedSteps: job.completed_steps,
-                bytesWritten: 0,
-                totalBytes: job.total_bytes,
-                speed: 0,
-                timeElapsed: elapsed,
-                timeRemaining: 0,
-                logs: vec![],
-                startTime: job.start_time_ms,
-            });
+            out.push(operation_status(job_id, &job, false));
         }
     }
     Ok(out)
 }
 
-fn start_device_monitor_once(app_handle: &AppHandle, state: tauri::State<'_, AppState>) {
-    let should_start = {
-        let mut started_guard = state.device_monitor_started.lock().unwrap_or_else(|p| p.into_inner());
-        if *started_guard {
-            false
-        } else {
-            *started_guard = true;
-            true
-        }
+#[tauri::command]
+fn estimate_flash_duration(
+    state: tauri::State<'_, AppState>,
+    profile_id: String,
+    total_bytes: u64,
+    usb_speed: String,
+) -> Result<u64, String> {
+    use libbootforge::imaging::boot_profiles::{USB2_ASSUMED_SPEED_BPS, USB3_ASSUMED_SPEED_BPS};
+
+    let speed_bps = match usb_speed.trim().to_ascii_lowercase().as_str() {
+        "usb2" | "usb2.0" | "2.0" | "high" => USB2_ASSUMED_SPEED_BPS,
+        "usb3" | "usb3.0" | "3.0" | "super" | "superplus" => USB3_ASSUMED_SPEED_BPS,
+        other => return Err(format!("Unknown usb_speed '{}' (expected usb2 or usb3)", other)),
     };
 
-    if !should_start {
+    let registry = state
+        .boot_profiles
+        .lock()
+        .map_err(|_| "boot_profiles mutex poisoned".to_string())?;
+    let profile = registry
+        .get_profile(profile_id.trim())
+        .ok_or_else(|| format!("Unknown boot profile: {}", profile_id))?;
+    let estimate = profile.estimate_duration(total_bytes, speed_bps);
+    // Round up so a non-zero estimate never reports as 0 seconds.
+    Ok(estimate.as_secs() + u64::from(estimate.subsec_nanos() > 0))
+}
+
+/// Best-effort platform guess for monitor uids (scan uids or `adb:`/`fastboot:` fallbacks).
+fn platform_from_uid(uid: &str) -> Platform {
+    if uid.contains("ios") {
+        Platform::Ios
+    } else if uid.contains("android") || uid.starts_with("adb:") || uid.starts_with("fastboot:") {
+        Platform::Android
+    } else {
+        Platform::Unknown
+    }
+}
+
+/// Atomically claim the right to start the device monitor. Only the first caller wins.
+fn claim_device_monitor_start(started: &AtomicBool) -> bool {
+    started
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_ok()
+}
+
+/// Checked at the top of every monitor iteration. Once stop_device_monitor
+/// has been called, releases the start flag so the monitor can be started
+/// again and tells the loop to exit.
+fn monitor_stop_requested(stop: &AtomicBool, started: &AtomicBool) -> bool {
+    if !stop.load(Ordering::SeqCst) {
+        return false;
+    }
+    started.store(false, Ordering::SeqCst);
+    true
+}
+
+fn start_device_monitor_once(app_handle: &AppHandle, state: tauri::State<'_, AppState>) {
+    if !claim_device_monitor_start(&state.device_monitor_started) {
+        return;
+    }
+    state.device_monitor_stop.store(false, Ordering::SeqCst);
+    spawn_device_monitor(app_handle, &state, Vec::new());
+}
+
+/// Replace a stalled monitor loop. Devices from the last scan are carried
+/// over so they are not announced as connected again, and a pending stop
+/// request is left in place.
+fn restart_device_monitor(app_handle: &AppHandle, state: tauri::State<'_, AppState>) {
+    if !claim_device_monitor_start(&state.device_monitor_started) {
         return;
     }
+    let seen = state.last_scan.lock().map(|last| last.clone()).unwrap_or_default();
+    spawn_device_monitor(app_handle, &state, seen);
+}
+
+fn spawn_device_monitor(
+    app_handle: &AppHandle,
+    state: &AppState,
+    seen: Vec<bootforgeusb::model::ConfirmedDeviceRecord>,
+) {
+    let generation = state.monitor_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    state.monitor_last_tick.store(now_ms(), Ordering::SeqCst);
 
     let app = app_handle.clone();
     std::thread::spawn(move || {
-        let mut seen: HashSet<String> = HashSet::new();
+        // Last BootForgeUSB scan, diffed with scan_compare.
+        let mut seen_records = seen;
+        // uids seen through the adb/fastboot fallback when the USB scan is unavailable.
+        let mut seen_fallback: HashSet<String> = HashSet::new();
+        // Unmatched tool probes per device, for confidence decay.
+        let mut seen_devices = device_watcher::SeenDevices::default();
         loop {
-            // Prefer BootForgeUSB scan (includes libusb enumeration + tool confirmers).
-            let mut current: HashSet<String> = HashSet::new();
-            let scan = bootforgeusb::scan().ok();
-            if let Some(devs) = scan {
-                for d in devs {
-                    current.insert(d.device_uid.clone());
+            if monitor_stop_requested(
+                &app.state::<AppState>().device_monitor_stop,
+                &app.state::<AppState>().device_monitor_started,
+            ) {
+                println!("[Tauri] Device monitor stopped");
+                break;
+            }
+            // The watchdog started a replacement while this loop was stalled.
+            if app.state::<AppState>().monitor_generation.load(Ordering::SeqCst) != generation {
+                break;
+            }
+            app.state::<AppState>().monitor_last_tick.store(now_ms(), Ordering::SeqCst);
+
+            let monitor_config = device_watcher::current_monitor_config(&app.state::<AppState>());
+            if device_watcher::monitor_paused(&app.state::<AppState>().monitor_paused_until, now_ms())
+                || app.state::<AppState>().flash_monitor_holds.load(Ordering::SeqCst) > 0
+            {
+                std::thread::sleep(std::time::Duration::from_millis(monitor_config.poll_interval_ms));
+                continue;
+            }
+
+            // Prefer BootForgeUSB scan (libusb enumeration, plus tool confirmers unless UsbOnly).
+            let registry = config::classifier_registry(&app.state::<AppState>());
+            if let Ok(devs) = monitor_config.scan_depth.scan(&registry) {
+                app.state::<AppState>().monitor_last_tick.store(now_ms(), Ordering::SeqCst);
+                let mut changes = bootforgeusb::scan_compare(&seen_records, &devs);
+                // A replug that changed the uid is one reconnect, not a disconnect plus a connect.
+                changes.pair_reconnects();
+                // Only persist polls that changed something to keep history readable.
+                if !changes.is_empty() {
+                    record_scan_history(&app.state::<AppState>(), &devs);
+                }
+                for device in &changes.connected {
+                    emit_device_event(&app, hotplug_event_from_record("connected", device));
                 }
+                for device in &changes.disconnected {
+                    emit_device_event(&app, hotplug_event_from_record("disconnected", device));
+                }
+                for (_, device) in &changes.reconnected {
+                    emit_device_event(&app, hotplug_event_from_record("reconnected", device));
+                }
+                // Confidence jitter alone is not worth an event; only mode or capability changes are.
+                let significant = device_state_io::sync_device_states(&app.state::<AppState>().device_states, &devs);
+                for (_, device) in &changes.changed {
+                    if significant.contains(&device.device_uid) {
+                        emit_device_event(&app, hotplug_event_from_record("changed", device));
+                    }
+                }
+                if monitor_config.scan_depth != device_watcher::ScanDepth::UsbOnly {
+                    for device in seen_devices.observe(&devs) {
+                        emit_device_event(&app, hotplug_event_from_record("changed", &device));
+                    }
+                }
+                if let Ok(mut last) = app.state::<AppState>().last_scan.lock() {
+                    *last = if monitor_config.scan_depth == device_watcher::ScanDepth::UsbOnly {
+                        device_watcher::merge_usb_only_scan(&last, &devs)
+                    } else {
+                        devs.clone()
+                    };
+                }
+                if monitor_config.scan_depth == device_watcher::ScanDepth::Full {
+                    device_watcher::inspect_connected_devices(&app.state::<AppState>(), &changes.connected);
+                }
+                seen_records = devs;
             } else {
                 // Fall back to tool lists.
+                let mut current: HashSet<String> = HashSet::new();
                 for s in adb_list_serials() {
                     current.insert(format!("adb:{}", s));
                 }
                 for s in fastboot_list_serials() {
                     current.insert(format!("fastboot:{}", s));
                 }
-            }
-
-            // Connected
-            for uid in current.difference(&seen) {
-                emit_device_event(
-                    &app,
-                    DeviceHotplugEvent {
-                        event_type: "connected".to_string(),
-                        device_uid: uid.to_string(),
-                        platform_hint: if uid.contains("ios") { "ios".to_string() } else if uid.contains("android") || uid.starts_with("adb:") || uid.starts_with("fastboot:") { "android".to_string() } else { "unknown".to_string() },
-                        mode: if uid.contains("fastboot") { "fastboot".to_string() } else { "normal".to_string() },
-                        confidence: 0.85,
-                        timestamp: iso_now(),
-                        display_name: uid.to_string(),
-                        matched_tool_ids: vec![],
-                    },
-                );
-            }
 
-            // Disconnected
-            for uid in seen.difference(&current) {
-                emit_device_event(
-                    &app,
-                    DeviceHotplugEvent {
-                        event_type: "disconnected".to_string(),
-                        device_uid: uid.to_string(),
-                        platform_hint: if uid.contains("ios") { "ios".to_string() } else if uid.contains("android") || uid.starts_with("adb:") || uid.starts_with("fastboot:") { "android".to_string() } else { "unknown".to_string() },
-                        mode: if uid.contains("fastboot") { "fastboot".to_string() } else { "normal".to_string() },
-                        confidence: 0.85,
-                        timestamp: iso_now(),
-                        display_name: uid.to_string(),
-                        matched_tool_ids: vec![],
-                    },
-                );
+                for uid in current.difference(&seen_fallback) {
+                    emit_device_event(&app, hotplug_event_from_uid("connected", uid));
+                }
+                for uid in seen_fallback.difference(&current) {
+                    emit_device_event(&app, hotplug_event_from_uid("disconnected", uid));
+                }
+                seen_fallback = current;
             }
 
-            seen = current;
-            std::thread::sleep(std::time::Duration::from_millis(1500));
+            std::thread::sleep(std::time::Duration::from_millis(monitor_config.poll_interval_ms));
         }
     });
 }
 
+fn hotplug_event_from_record(event_type: &str, device: &bootforgeusb::model::ConfirmedDeviceRecord) -> DeviceHotplugEvent {
+    DeviceHotplugEvent {
+        event_type: event_type.to_string(),
+        device_uid: device.device_uid.clone(),
+        platform: device.platform,
+        mode: device.mode.clone(),
+        confidence: device.confidence,
+        timestamp: iso_now(),
+        display_name: device
+            .marketing_name
+            .clone()
+            .or_else(|| device.evidence.usb.product.clone())
+            .unwrap_or_else(|| device.device_uid.clone()),
+        matched_tool_ids: device.matched_tool_ids.clone(),
+        speed: device.evidence.usb.speed,
+    }
+}
+
+fn hotplug_event_from_uid(event_type: &str, uid: &str) -> DeviceHotplugEvent {
+    DeviceHotplugEvent {
+        event_type: event_type.to_string(),
+        device_uid: uid.to_string(),
+        platform: platform_from_uid(uid),
+        mode: if uid.contains("fastboot") { "fastboot".to_string() } else { "normal".to_string() },
+        confidence: 0.85,
+        timestamp: iso_now(),
+        display_name: uid.to_string(),
+        matched_tool_ids: vec![],
+        speed: None,
+    }
+}
+
+#[tauri::command]
+fn stop_device_monitor(watcher: tauri::State<'_, device_watcher::DeviceWatcher>) -> Result<(), String> {
+    watcher.stop();
+    Ok(())
+}
+
 fn get_log_directory() -> PathBuf {
     #[cfg(target_os = "windows")]
     {
@@ -1053,6 +2124,11 @@ fn find_node_executable(app_handle: &AppHandle) -> Option<PathBuf> {
     None
 }
 
+/// Port the bundled Node backend listens on
+const NODE_BACKEND_PORT: u16 = 3001;
+/// Launch attempts made by backend_restart before giving up
+const BACKEND_RESTART_ATTEMPTS: u32 = 3;
+
 fn start_backend_server(app_handle: &AppHandle) -> Result<Child, std::io::Error> {
     println!("[Tauri] Starting backend API server...");
     
@@ -1138,8 +2214,7 @@ fn start_backend_server(app_handle: &AppHandle) -> Result<Child, std::io::Error>
         ));
     }
     
-    // Use port 3001 for the backend server
-    let port = 3001;
+    let port = NODE_BACKEND_PORT;
     
     // Get log directory for backend logs
     let log_dir = get_log_directory();
@@ -1168,11 +2243,14 @@ fn start_backend_server(app_handle: &AppHandle) -> Result<Child, std::io::Error>
     }
     #[cfg(not(debug_assertions))]
     {
-        let log_file = log_dir.join("backend.log");
-        if let (Ok(stdout_file), Ok(stderr_file)) = (
-            std::fs::File::create(&log_file),
-            std::fs::File::create(&log_file)
-        ) {
+        let log_file = log_dir.join(log_rotate::BACKEND_LOG);
+        if let Err(e) = log_rotate::LogRotator::rotate_if_needed(&log_file, log_rotate::MAX_LOG_SIZE_BYTES) {
+            eprintln!("[Tauri] Failed to rotate {}: {}", log_file.display(), e);
+        }
+        // Append mode, so a manual rotation that truncates the log in place
+        // (log_rotate_now) does not leave the backend writing past the end
+        let open_log = || std::fs::OpenOptions::new().create(true).append(true).open(&log_file);
+        if let (Ok(stdout_file), Ok(stderr_file)) = (open_log(), open_log()) {
             cmd.stdout(Stdio::from(stdout_file)).stderr(Stdio::from(stderr_file));
         } else {
             cmd.stdout(Stdio::inherit()).stderr(Stdio::inherit());
@@ -1185,30 +2263,11 @@ fn start_backend_server(app_handle: &AppHandle) -> Result<Child, std::io::Error>
     println!("[Tauri] Server PID: {}", child.id());
     
     // Give the server time to start up and bind to the port
-    // Check if port is listening by attempting a TCP connection
-    let mut attempts = 0;
-    let max_attempts = 30; // 15 seconds total (30 * 500ms)
-    let mut server_ready = false;
-    
-    while attempts < max_attempts && !server_ready {
-        std::thread::sleep(std::time::Duration::from_millis(500));
-        attempts += 1;
-        
-        // Try to connect to the port to see if server is listening
-        match std::net::TcpStream::connect(format!("127.0.0.1:{}", port)) {
-            Ok(_) => {
-                server_ready = true;
-                println!("[Tauri] Backend server confirmed ready after {}ms", attempts * 500);
-                break;
-            }
-            Err(_) => {
-                // Port not ready yet, continue waiting
-            }
-        }
-    }
-    
-    if !server_ready {
-        println!("[Tauri] Warning: Backend server may not be fully ready after {}ms, but continuing...", attempts * 500);
+    let ready_timeout = std::time::Duration::from_secs(15);
+    if net_utils::wait_for_port(port, ready_timeout, std::time::Duration::from_millis(500)) {
+        println!("[Tauri] Backend server confirmed ready on port {}", port);
+    } else {
+        println!("[Tauri] Warning: Backend server may not be fully ready after {}s, but continuing...", ready_timeout.as_secs());
     }
     
     Ok(child)
@@ -1234,17 +2293,147 @@ fn stop_backend_server(app_handle: &AppHandle) {
     }
 }
 
+/// Delay before launch attempt `attempt` (1-based) of a backend restart:
+/// none for the first, then 1s, 2s, 4s, ...
+fn backend_restart_backoff(attempt: u32) -> std::time::Duration {
+    if attempt <= 1 {
+        std::time::Duration::ZERO
+    } else {
+        std::time::Duration::from_secs(1 << (attempt - 2))
+    }
+}
+
+fn emit_backend_status(app_handle: &AppHandle, status: &str, attempt: Option<u32>, message: Option<&str>) {
+    let _ = app_handle.emit(
+        "backend-status",
+        serde_json::json!({ "status": status, "attempt": attempt, "message": message }),
+    );
+}
+
+/// Stop the Node backend, then relaunch it with retries until it accepts
+/// connections on NODE_BACKEND_PORT.
+fn restart_backend_server(app_handle: &AppHandle) -> Result<String, String> {
+    emit_backend_status(app_handle, "stopping", None, None);
+    stop_backend_server(app_handle);
+    std::thread::sleep(std::time::Duration::from_secs(1));
+
+    let mut last_error = String::new();
+    for attempt in 1..=BACKEND_RESTART_ATTEMPTS {
+        std::thread::sleep(backend_restart_backoff(attempt));
+        emit_backend_status(app_handle, "starting", Some(attempt), None);
+
+        match start_backend_server(app_handle) {
+            Ok(mut child) => {
+                // start_backend_server only warns when the port stays closed
+                let ready = net_utils::wait_for_port(
+                    NODE_BACKEND_PORT,
+                    std::time::Duration::from_secs(2),
+                    std::time::Duration::from_millis(250),
+                );
+                if ready {
+                    let state: tauri::State<'_, AppState> = app_handle.state();
+                    let mut backend = match state.backend_server.lock() {
+                        Ok(guard) => guard,
+                        Err(poisoned) => poisoned.into_inner(),
+                    };
+                    *backend = Some(child);
+                    drop(backend);
+
+                    let message = format!("Backend restarted on port {}", NODE_BACKEND_PORT);
+                    emit_backend_status(app_handle, "ready", Some(attempt), Some(&message));
+                    return Ok(message);
+                }
+                let _ = child.kill();
+                let _ = child.wait();
+                last_error = format!("backend did not open port {}", NODE_BACKEND_PORT);
+            }
+            Err(e) => last_error = e.to_string(),
+        }
+        eprintln!("[Tauri] Backend restart attempt {} failed: {}", attempt, last_error);
+    }
+
+    let message = format!(
+        "Backend restart failed after {} attempts: {}",
+        BACKEND_RESTART_ATTEMPTS, last_error
+    );
+    emit_backend_status(app_handle, "failed", None, Some(&message));
+    Err(message)
+}
+
+#[tauri::command]
+async fn backend_restart(app_handle: AppHandle) -> Result<String, String> {
+    {
+        let state: tauri::State<'_, AppState> = app_handle.state();
+        if !should_start_node_backend(&state) {
+            return Err("Node backend is disabled by configuration".to_string());
+        }
+        if state.backend_restarting.swap(true, Ordering::AcqRel) {
+            return Err("Backend restart already in progress".to_string());
+        }
+    }
+
+    let handle = app_handle.clone();
+    let result = tokio::task::spawn_blocking(move || restart_backend_server(&handle))
+        .await
+        .map_err(|e| format!("backend restart task failed: {}", e))
+        .and_then(|r| r);
+
+    app_handle.state::<AppState>().backend_restarting.store(false, Ordering::Release);
+    result
+}
+
 fn main() {
     // Initialize app state
     let app_state = AppState {
         backend_server: Mutex::new(None),
+        backend_restarting: AtomicBool::new(false),
         flash_jobs: Mutex::new(HashMap::new()),
+        flash_groups: Mutex::new(HashMap::new()),
+        job_queue: JobQueue::new(),
         flash_history: Mutex::new(vec![]),
         job_counter: AtomicU64::new(0),
-        device_monitor_started: Mutex::new(false),
+        device_monitor_started: AtomicBool::new(false),
+        device_monitor_stop: AtomicBool::new(false),
+        monitor_paused_until: AtomicU64::new(0),
+        flash_monitor_holds: AtomicUsize::new(0),
+        monitor_last_tick: AtomicU64::new(0),
+        monitor_generation: AtomicU64::new(0),
         py_client: Mutex::new(None),
         py_backend_port: Mutex::new(None),
         fastapi_backend: Mutex::new(None),
+        last_scan: Mutex::new(vec![]),
+        shell_cmd_allowlist: Mutex::new(None),
+        raw_flash_pids: Mutex::new(HashMap::new()),
+        device_mode_waits: Mutex::new(HashMap::new()),
+        scan_history: match bootforgeusb::history::ScanHistoryLogger::new(get_log_directory().join("scan-history")) {
+            Ok(logger) => Some(logger),
+            Err(e) => {
+                eprintln!("[Tauri] Scan history disabled: {}", e);
+                None
+            }
+        },
+        audit_log: match audit_log::EventAuditLogger::new(get_log_directory().join("audit")) {
+            Ok(logger) => Some(logger),
+            Err(e) => {
+                eprintln!("[Tauri] Event audit log disabled: {}", e);
+                None
+            }
+        },
+        device_states: libbootforge::DeviceStateStore::new(),
+        config: Mutex::new({
+            let mut defaults = config::WorkshopConfig::default();
+            defaults.apply_env_overrides();
+            defaults
+        }),
+        config_path: Mutex::new(None),
+        device_monitor_config: Mutex::new(device_watcher::DeviceMonitorConfig {
+            poll_interval_ms: 1500,
+            scan_depth: device_watcher::ScanDepth::WithTools,
+        }),
+        boot_profiles: Mutex::new(libbootforge::imaging::BootProfileRegistry::new()),
+        profile_prefetcher: profile_prefetcher::ProfilePrefetcher::default(),
+        device_aliases: device_alias::DeviceAliasStore::default(),
+        ws_server_port: Mutex::new(None),
     };
 
     tauri::Builder::default()
@@ -1253,8 +2442,43 @@ fn main() {
             let state = app.state::<AppState>();
             let handle = app.handle();
 
-            // Start in-process device monitor (Tauri events)
-            start_device_monitor_once(&handle, state.clone());
+            // Load <app_data>/config.json, device aliases and imported profiles before anything that depends on them
+            if let Ok(app_data_dir) = handle.path().app_data_dir() {
+                let path = config::config_path(&app_data_dir);
+                let loaded = config::WorkshopConfig::load(&path);
+                if let Ok(mut guard) = state.device_monitor_config.lock() {
+                    *guard = device_watcher::DeviceMonitorConfig::from_workshop_config(&loaded);
+                }
+                if let Ok(mut guard) = state.config.lock() {
+                    *guard = loaded;
+                }
+                if let Ok(mut guard) = state.config_path.lock() {
+                    *guard = Some(path);
+                }
+                state
+                    .device_aliases
+                    .load(app_data_dir.join(device_alias::ALIASES_FILE_NAME));
+                if let Ok(mut registry) = state.boot_profiles.lock() {
+                    if let Err(e) = registry.load_profile_dir(&app_data_dir.join(IMPORTED_PROFILES_DIR)) {
+                        eprintln!("[Tauri] Failed to load imported boot profiles: {}", e);
+                    }
+                }
+            }
+            if let Ok(config) = state.config.lock() {
+                config.apply_scan_settings();
+            }
+
+            // Start in-process device monitor (Tauri events + typed subscribers)
+            app.manage(device_watcher::DeviceWatcher::new(handle.clone()));
+            app.state::<device_watcher::DeviceWatcher>().start();
+            device_watcher::start_monitor_watchdog(handle.clone());
+
+            // Report missing prerequisites (tools, libusb, drivers) once, off the startup path
+            let diagnostics_handle = handle.clone();
+            std::thread::spawn(move || diagnostics::log_startup_report(&diagnostics_handle));
+
+            // Optional WebSocket feed for external tools (BW_WS_PORT)
+            ws_device_server::WebSocketDeviceServer::start_from_env(handle.clone());
 
             // Launch Python backend service (legacy)
             if let Ok(resource_dir) = handle.path().resource_dir() {
@@ -1273,12 +2497,12 @@ fn main() {
                             
                             match client.health().await {
                                 Ok(health) => {
-                                    println!("[Tauri] Python backend healthy: {} (uptime: {}ms)", 
-                                        health.version, health.uptime_ms);
+                                    println!("[Tauri] Python backend {}: {} (uptime: {}ms)", 
+                                        health.status, health.version, health.uptime_ms);
                                     
                                     // Store client and port in state
                                     if let Ok(mut py_client_guard) = state_for_client.py_client.lock() {
-                                        *py_client_guard = Some(client);
+                                        *py_client_guard = Some(Arc::new(client));
                                     }
                                     if let Ok(mut port_guard) = state_for_client.py_backend_port.lock() {
                                         *port_guard = Some(port);
@@ -1311,7 +2535,7 @@ fn main() {
             }
 
             // Start legacy Node backend only when explicitly enabled.
-            if should_start_node_backend() {
+            if should_start_node_backend(&state) {
                 match start_backend_server(&handle) {
                     Ok(child) => {
                         if let Ok(mut guard) = state.backend_server.lock() {
@@ -1321,13 +2545,10 @@ fn main() {
                     }
                     Err(e) => {
                         eprintln!("[Tauri] Failed to start backend server: {}", e);
-                        eprintln!("[Tauri] Node backend is required for full functionality");
-                        eprintln!("[Tauri] Ensure Node.js is installed from https://nodejs.org/");
-                        eprintln!("[Tauri] Or set BW_DISABLE_NODE_BACKEND=1 to use in-process backend only");
                     }
                 }
             } else {
-                println!("[Tauri] Node backend disabled by BW_DISABLE_NODE_BACKEND environment variable");
+                println!("[Tauri] Node backend disabled by configuration (disable_node_backend / BW_DISABLE_NODE_BACKEND)");
             }
             
             Ok(())
@@ -1349,16 +2570,306 @@ fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             get_backend_status,
+            backend_restart,
             get_app_version,
             bootforgeusb_scan,
+            bootforgeusb_scan_grouped,
+            profile_prefetcher::get_prefetched_profile,
+            device_alias::set_device_alias,
+            device_alias::get_device_alias,
+            device_alias::delete_device_alias,
+            throughput::measure_usb_throughput,
+            log_rotate::log_rotate_now,
+            log_rotate::log_list_files,
+            log_rotate::log_delete_file,
+            scan_benchmark,
+            scan_history_path,
+            scan_history_recent,
+            audit_log::audit_log_path,
+            audit_log::audit_log_tail,
+            scan_explain,
+            suggest_boot_profile,
+            export_profiles,
+            import_profiles,
+            platform_checks::platform_health_check,
+            diagnostics::workbench_diagnostics,
+            diagnostics::validate_tool_path,
+            platform_checks::check_driver_updates,
+            config::get_allowed_partitions,
+            fastboot_tools::fastboot_getvar_product,
+            fastboot_tools::bootloader_unlock_check,
+            fastboot_tools::fastboot_oem_command,
+            fastboot_tools::fastboot_oem_command_force,
+            ios_tools::ios_device_info_deep,
+            ios_tools::ios_screenshot,
+            ios_tools::ios_backup,
+            ios_tools::ios_restore,
+            ios_tools::ios_enter_dfu_guide,
+            adb_tools::device_enter_sideload,
+            adb_tools::android_backup,
+            device_watcher::get_device_monitor_config,
+            device_watcher::set_device_monitor_config,
+            device_watcher::device_monitor_pause,
+            device_watcher::device_monitor_resume,
+            device_state_io::export_device_state,
+            device_state_io::import_device_state,
             flash_start,
             flash_cancel,
+            flash_job_clone,
             flash_status,
+            flash_log_tail,
+            flash_log_unsubscribe,
+            monitor_device_mode_changes,
+            monitor_device_mode_cancel,
+            flash_group_status,
             flash_history,
             flash_active,
+            estimate_flash_duration,
+            image_inspect::image_info,
+            preflight::flash_preflight_check,
+            edl_engine::edl_load_programmer,
             bootforge_flash_history,
             bootforge_flash_active,
+            flash_progress_snapshot,
+            adb_tools::device_screenshot,
+            adb_tools::device_shell,
+            adb_tools::device_shell_set_allowlist,
+            adb_tools::device_info_deep,
+            adb_tools::adb_list_files,
+            adb_tools::adb_pull,
+            adb_tools::adb_push,
+            adb_tools::adb_install_apk,
+            adb_tools::android_list_packages,
+            adb_tools::android_uninstall_package,
+            adb_tools::android_lock_screen,
+            adb_tools::android_unlock_screen,
+            adb_tools::get_unauthorized_adb_devices,
+            fastboot_tools::flash_get_current_slot,
+            fastboot_tools::fastboot_getvar,
+            fastboot_tools::fastboot_getvar_all,
+            fastboot_tools::fastboot_flash_raw,
+            fastboot_tools::fastboot_flash_raw_cancel,
+            fastboot_tools::fastboot_erase,
+            fastboot_tools::fastboot_flash_vbmeta,
+            fastboot_tools::fastboot_wipe_super,
+            fastboot_tools::fastboot_get_slot_info,
+            fastboot_tools::fastboot_set_active_slot,
+            fastboot_tools::fastboot_mark_slot_successful,
+            ws_device_server::get_ws_server_status,
+            stop_device_monitor,
+            device_watcher::scan_subscribe,
+            device_watcher::scan_unsubscribe,
+            config::get_config,
+            config::save_config,
+            config::add_classification_override,
+            config::remove_classification_override,
+            config::list_classification_overrides,
         ])
         .run(tauri::generate_context!())
         .expect("error while building tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn device_monitor_start_is_claimed_once() {
+        let started = AtomicBool::new(false);
+        assert!(claim_device_monitor_start(&started));
+        assert!(!claim_device_monitor_start(&started));
+        assert!(started.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn stopped_monitor_exits_and_can_be_started_again() {
+        let (started, stop) = (AtomicBool::new(false), AtomicBool::new(false));
+        assert!(claim_device_monitor_start(&started));
+        assert!(!monitor_stop_requested(&stop, &started));
+
+        // What stop_device_monitor does
+        stop.store(true, Ordering::SeqCst);
+        assert!(monitor_stop_requested(&stop, &started));
+        assert!(!started.load(Ordering::SeqCst));
+        assert!(claim_device_monitor_start(&started));
+    }
+
+    #[test]
+    fn sideload_xfer_ratio_is_read_from_the_last_line() {
+        let lines = vec![
+            "serving: 'ota.zip'  (~47%)".to_string(),
+            "Total xfer: 1.00x".to_string(),
+        ];
+        assert_eq!(parse_sideload_xfer(&lines), Some(1.0));
+        assert_eq!(parse_sideload_xfer(&lines[..1]), None);
+    }
+
+    fn flash_config(serial: &str, serials: &[&str]) -> FlashJobConfig {
+        serde_json::from_value(serde_json::json!({
+            "deviceSerial": serial,
+            "deviceSerials": serials,
+            "deviceBrand": "google",
+            "flashMethod": "fastboot",
+            "partitions": [],
+            "verifyAfterFlash": false,
+            "autoReboot": true,
+            "wipeUserData": false
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn target_serials_prefer_the_list_over_the_legacy_field() {
+        assert_eq!(flash_config(" ABC123 ", &[]).target_serials().unwrap(), vec!["ABC123"]);
+        assert_eq!(
+            flash_config("ABC123", &["XYZ789", "DEF456"]).target_serials().unwrap(),
+            vec!["XYZ789", "DEF456"]
+        );
+        assert!(flash_config("", &[]).target_serials().is_err());
+        assert!(flash_config("", &["ABC123", " ABC123"]).target_serials().is_err());
+    }
+
+    #[test]
+    fn group_status_aggregates_job_states() {
+        let job = |id: &str, status: &str, progress: u64| {
+            let mut runtime = FlashJobRuntime {
+                status: status.to_string(),
+                progress,
+                current_step: String::new(),
+                total_steps: 1,
+                completed_steps: 0,
+                logs: RingBuffer::new(config::DEFAULT_MAX_FLASH_LOG_LINES),
+                log_times_ms: RingBuffer::new(config::DEFAULT_MAX_FLASH_LOG_LINES),
+                start_time_ms: now_ms(),
+                end_time_ms: None,
+                total_bytes: 0,
+                cancel_requested: false,
+                active_pid: None,
+                partition_results: vec![],
+                config: flash_config(id, &[]),
+                log_tail: FlashLogTail::default(),
+            };
+            runtime.push_log("line".to_string());
+            operation_status(id, &runtime, false)
+        };
+
+        let running = aggregate_group_status("g1", vec![job("a", "completed", 100), job("b", "running", 40)]);
+        assert_eq!(running.status, "running");
+        assert_eq!(running.progress, 70);
+        assert_eq!(running.completed, 1);
+        assert!(running.jobs[0].logs.is_empty());
+
+        let failed = aggregate_group_status("g1", vec![job("a", "completed", 100), job("b", "failed", 40)]);
+        assert_eq!(failed.status, "failed");
+        let done = aggregate_group_status("g1", vec![job("a", "completed", 100), job("b", "completed", 100)]);
+        assert_eq!(done.status, "completed");
+    }
+
+    #[test]
+    fn finished_groups_are_pruned() {
+        let job = |status: &str| {
+            Arc::new(Mutex::new(FlashJobRuntime {
+                status: status.to_string(),
+                progress: 0,
+                current_step: String::new(),
+                total_steps: 1,
+                completed_steps: 0,
+                logs: RingBuffer::new(1),
+                log_times_ms: RingBuffer::new(1),
+                start_time_ms: now_ms(),
+                end_time_ms: None,
+                total_bytes: 0,
+                cancel_requested: false,
+                active_pid: None,
+                partition_results: vec![],
+                config: flash_config("ABC123", &[]),
+                log_tail: FlashLogTail::default(),
+            }))
+        };
+        let jobs = HashMap::from([
+            ("a".to_string(), job("completed")),
+            ("b".to_string(), job("running")),
+            ("c".to_string(), job("failed")),
+            ("d".to_string(), job("cancelled")),
+        ]);
+        let mut groups = HashMap::from([
+            ("active".to_string(), vec!["a".to_string(), "b".to_string()]),
+            ("done".to_string(), vec!["a".to_string(), "c".to_string(), "d".to_string()]),
+        ]);
+
+        prune_finished_groups(&mut groups, &jobs);
+        assert_eq!(groups.keys().collect::<Vec<_>>(), ["active"]);
+    }
+
+    #[test]
+    fn partition_size_falls_back_to_the_image_file() {
+        let path = std::env::temp_dir().join(format!("bw-partition-size-{}.img", std::process::id()));
+        std::fs::write(&path, [0u8; 16]).unwrap();
+        let mut partition = FlashPartition {
+            name: "boot".to_string(),
+            imagePath: path.to_string_lossy().to_string(),
+            size: 0,
+        };
+        assert_eq!(resolve_partition_size(&mut partition), Ok(None));
+        assert_eq!(partition.size, 16);
+
+        partition.size = 4096;
+        assert_eq!(resolve_partition_size(&mut partition), Ok(None));
+        assert_eq!(partition.size, 4096);
+
+        std::fs::write(&path, []).unwrap();
+        partition.size = 0;
+        let warning = resolve_partition_size(&mut partition).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(warning.unwrap().contains("unknown"));
+        assert!(resolve_partition_size(&mut partition).unwrap_err().contains("not found"));
+    }
+
+    #[test]
+    fn logs_since_filters_by_push_time() {
+        let mut runtime = FlashJobRuntime {
+            status: "running".to_string(),
+            progress: 0,
+            current_step: String::new(),
+            total_steps: 1,
+            completed_steps: 0,
+            logs: RingBuffer::new(2),
+            log_times_ms: RingBuffer::new(2),
+            start_time_ms: now_ms(),
+            end_time_ms: None,
+            total_bytes: 0,
+            cancel_requested: false,
+            active_pid: None,
+            partition_results: vec![],
+            config: flash_config("ABC123", &[]),
+            log_tail: FlashLogTail::default(),
+        };
+        runtime.push_log("first".to_string());
+        runtime.push_log("second".to_string());
+        runtime.push_log("third".to_string());
+
+        assert_eq!(runtime.logs_since(0), vec!["second", "third"]);
+        assert!(runtime.logs_since(u64::MAX).is_empty());
+        assert_eq!(job_to_operation("job", &runtime).logs, vec!["second", "third"]);
+    }
+
+    #[test]
+    fn backend_restart_backoff_doubles_after_first_attempt() {
+        let secs: Vec<u64> = (1..=BACKEND_RESTART_ATTEMPTS).map(|a| backend_restart_backoff(a).as_secs()).collect();
+        assert_eq!(secs, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn partition_allowlist_accepts_slot_suffixes() {
+        assert!(is_standard_partition("init_boot"));
+        assert!(is_standard_partition("boot_a"));
+        assert!(is_standard_partition("vbmeta_b"));
+        assert!(!is_standard_partition("modem_a"));
+        let custom = ["persist_image".to_string()];
+        assert!(is_allowed_partition("persist_image_b", &custom));
+        assert!(!is_allowed_partition("boot", &custom));
+        assert!(validate_partition_name("boot_a").is_ok());
+        assert!(validate_partition_name("boot;reboot").is_err());
+        assert!(validate_partition_name("").is_err());
+    }
+}
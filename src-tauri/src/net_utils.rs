@@ -0,0 +1,103 @@
+// Network Utilities
+// Readiness probes for locally spawned backend servers
+
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Poll interval while the server is most likely still starting up
+const FAST_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How long to poll at `FAST_POLL_INTERVAL` before backing off
+const FAST_POLL_WINDOW: Duration = Duration::from_secs(2);
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Wait until something accepts TCP connections on `127.0.0.1:port`.
+///
+/// Polls every 100 ms for the first 2 seconds, then doubles the interval on
+/// each attempt up to `initial_interval`. Returns `false` if the port is
+/// still closed once `timeout` has elapsed.
+pub fn wait_for_port(port: u16, timeout: Duration, initial_interval: Duration) -> bool {
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let start = Instant::now();
+    let deadline = start + timeout;
+    let mut interval = FAST_POLL_INTERVAL;
+
+    loop {
+        let now = Instant::now();
+        let remaining = deadline.saturating_duration_since(now);
+        let connect_timeout = remaining.min(CONNECT_TIMEOUT);
+        if !connect_timeout.is_zero() && TcpStream::connect_timeout(&addr, connect_timeout).is_ok()
+        {
+            return true;
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return false;
+        }
+        thread::sleep(interval.min(remaining));
+
+        if start.elapsed() >= FAST_POLL_WINDOW {
+            interval = (interval * 2).min(initial_interval.max(FAST_POLL_INTERVAL));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn returns_true_once_port_is_listening() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let start = Instant::now();
+        assert!(wait_for_port(
+            port,
+            Duration::from_secs(5),
+            Duration::from_millis(500)
+        ));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn waits_for_a_server_that_starts_late() {
+        let port = {
+            let probe = TcpListener::bind("127.0.0.1:0").unwrap();
+            probe.local_addr().unwrap().port()
+        };
+        let server = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(300));
+            let listener = TcpListener::bind(("127.0.0.1", port)).unwrap();
+            thread::sleep(Duration::from_millis(1000));
+            drop(listener);
+        });
+
+        assert!(wait_for_port(
+            port,
+            Duration::from_secs(5),
+            Duration::from_millis(500)
+        ));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn returns_false_after_timeout_when_nothing_listens() {
+        let port = {
+            let probe = TcpListener::bind("127.0.0.1:0").unwrap();
+            probe.local_addr().unwrap().port()
+        };
+
+        let start = Instant::now();
+        assert!(!wait_for_port(
+            port,
+            Duration::from_millis(300),
+            Duration::from_millis(500)
+        ));
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(300));
+        assert!(elapsed < Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,321 @@
+// Platform Health Checks
+// OS-level prerequisites for direct USB access, reported as warning strings
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use std::process::Command;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use std::time::Duration;
+
+use libbootforge::drivers::{DriverPackRegistry, DriverPackUpdate};
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::run_command_with_timeout;
+use crate::AppState;
+
+#[cfg(any(target_os = "macos", test))]
+const USB_ENTITLEMENT: &str = "com.apple.security.device.usb";
+#[cfg(any(target_os = "macos", test))]
+const ICLOUD_CONTAINER_ENTITLEMENT: &str = "com.apple.developer.icloud.container-identifiers";
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Whether the entitlements plist sets `key` to `<true/>`.
+#[cfg(any(target_os = "macos", test))]
+fn entitlement_enabled(plist: &str, key: &str) -> bool {
+    let tag = format!("<key>{}</key>", key);
+    plist
+        .find(&tag)
+        .map(|i| plist[i + tag.len()..].trim_start().starts_with("<true/>"))
+        .unwrap_or(false)
+}
+
+/// Warnings for the entitlements printed by `codesign -d --entitlements :-`.
+#[cfg(any(target_os = "macos", test))]
+fn entitlement_warnings(plist: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if !entitlement_enabled(plist, USB_ENTITLEMENT) {
+        warnings.push(format!(
+            "The app is missing the {} entitlement; direct USB access may be blocked on macOS 12+",
+            USB_ENTITLEMENT
+        ));
+    }
+    if plist.contains(&format!("<key>{}</key>", ICLOUD_CONTAINER_ENTITLEMENT)) {
+        warnings.push(format!(
+            "The app declares {}, which it does not use; check the signing configuration",
+            ICLOUD_CONTAINER_ENTITLEMENT
+        ));
+    }
+    warnings
+}
+
+/// Inspect the running executable's code signature for the USB entitlement.
+#[cfg(target_os = "macos")]
+pub fn check_macos_usb_entitlements() -> Vec<String> {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => return vec![format!("Could not locate the app executable: {}", e)],
+    };
+    let mut cmd = Command::new("codesign");
+    cmd.args(["-d", "--entitlements", ":-"]).arg(&exe);
+    let output = match run_command_with_timeout(cmd, CHECK_TIMEOUT) {
+        Ok(output) => output,
+        Err(e) => return vec![format!("Could not run codesign: {}", e)],
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        if stderr.contains("not signed") {
+            return vec!["The app is not code signed; USB entitlements cannot be verified".to_string()];
+        }
+        return vec![format!("codesign failed: {}", stderr.trim())];
+    }
+    entitlement_warnings(&stdout)
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn check_macos_usb_entitlements() -> Vec<String> {
+    Vec::new()
+}
+
+/// Android devices are only accessible to regular users when a udev rule
+/// grants access to their vendor IDs (Google's 18d1 is the usual marker).
+#[cfg(target_os = "linux")]
+pub(crate) fn check_linux_usb_access() -> Vec<String> {
+    let mut warnings = Vec::new();
+    if !std::path::Path::new("/dev/bus/usb").is_dir() {
+        warnings.push("/dev/bus/usb is not available; USB devices cannot be enumerated".to_string());
+    }
+    let has_android_rule = ["/etc/udev/rules.d", "/lib/udev/rules.d", "/usr/lib/udev/rules.d"]
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "rules"))
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .any(|rules| rules.to_ascii_lowercase().contains("18d1"));
+    if !has_android_rule {
+        warnings.push(
+            "No udev rule for Android devices found; install android-udev-rules or add a rule for your device's vendor ID"
+                .to_string(),
+        );
+    }
+    warnings
+}
+
+/// Driver bound to a USB device on Windows. libusb (and so the USB scan)
+/// can only open devices bound to WinUSB or libusbK.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowsDriverStatus {
+    WinUsbBound,
+    LibUsbKBound,
+    UnknownDriver(String),
+    NotBound,
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+impl WindowsDriverStatus {
+    pub fn is_libusb_compatible(&self) -> bool {
+        matches!(self, WindowsDriverStatus::WinUsbBound | WindowsDriverStatus::LibUsbKBound)
+    }
+
+    /// Value used for `ConfirmedDeviceRecord::driver_status`
+    pub fn label(&self) -> String {
+        match self {
+            WindowsDriverStatus::WinUsbBound => "WinUSB".to_string(),
+            WindowsDriverStatus::LibUsbKBound => "libusbK".to_string(),
+            WindowsDriverStatus::UnknownDriver(name) => name.clone(),
+            WindowsDriverStatus::NotBound => "not bound".to_string(),
+        }
+    }
+}
+
+/// Find the device with `vid`/`pid` in `pnputil /enum-devices /properties`
+/// output and classify its `DEVPKEY_Device_Service` (the bound driver).
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_pnputil_driver(output: &str, vendor_id: u16, product_id: u16) -> WindowsDriverStatus {
+    let needle = format!("VID_{:04X}&PID_{:04X}", vendor_id, product_id);
+    let mut lines = output.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        let is_match = line
+            .strip_prefix("Instance ID:")
+            .is_some_and(|id| id.to_ascii_uppercase().contains(&needle));
+        if !is_match {
+            continue;
+        }
+        // Properties of this device run until the next "Instance ID:" line.
+        let mut service = None;
+        for line in lines.by_ref() {
+            if line.starts_with("Instance ID:") {
+                break;
+            }
+            if line.starts_with("DEVPKEY_Device_Service") {
+                service = lines.by_ref().map(str::trim).find(|l| !l.is_empty()).map(str::to_string);
+                break;
+            }
+        }
+        return match service {
+            Some(name) if name.eq_ignore_ascii_case("winusb") => WindowsDriverStatus::WinUsbBound,
+            Some(name) if name.eq_ignore_ascii_case("libusbk") => WindowsDriverStatus::LibUsbKBound,
+            Some(name) => WindowsDriverStatus::UnknownDriver(name),
+            None => WindowsDriverStatus::NotBound,
+        };
+    }
+    WindowsDriverStatus::NotBound
+}
+
+#[cfg(target_os = "windows")]
+fn pnputil_usb_devices() -> Result<String, String> {
+    let mut cmd = Command::new("pnputil.exe");
+    cmd.args(["/enum-devices", "/class", "USB", "/properties"]);
+    let output = run_command_with_timeout(cmd, CHECK_TIMEOUT)
+        .map_err(|e| format!("Could not run pnputil: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "pnputil failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Which driver Windows has bound to the USB device `vendor_id:product_id`.
+#[cfg(target_os = "windows")]
+pub fn check_windows_usb_driver(vendor_id: u16, product_id: u16) -> WindowsDriverStatus {
+    match pnputil_usb_devices() {
+        Ok(output) => parse_pnputil_driver(&output, vendor_id, product_id),
+        Err(e) => WindowsDriverStatus::UnknownDriver(e),
+    }
+}
+
+/// Fill in `driver_status` for each scanned device (Windows only; a no-op
+/// elsewhere). pnputil is run once for the whole list.
+pub fn annotate_driver_status(devices: &mut [bootforgeusb::model::ConfirmedDeviceRecord]) {
+    #[cfg(target_os = "windows")]
+    {
+        let Ok(output) = pnputil_usb_devices() else {
+            return;
+        };
+        for device in devices.iter_mut() {
+            let usb = &device.evidence.usb;
+            if let (Ok(vid), Ok(pid)) = (u16::from_str_radix(&usb.vid, 16), u16::from_str_radix(&usb.pid, 16)) {
+                device.driver_status = Some(parse_pnputil_driver(&output, vid, pid).label());
+            }
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = devices;
+}
+
+/// Devices from the last scan that libusb cannot open because of their driver.
+fn driver_warnings(devices: &[bootforgeusb::model::ConfirmedDeviceRecord]) -> Vec<String> {
+    devices
+        .iter()
+        .filter_map(|device| {
+            let status = device.driver_status.as_deref()?;
+            if status == "WinUSB" || status == "libusbK" {
+                return None;
+            }
+            let usb = &device.evidence.usb;
+            Some(format!(
+                "USB device {}:{} ({}) is bound to '{}'; install the WinUSB driver (e.g. Google USB Driver or Zadig) for direct USB access",
+                usb.vid,
+                usb.pid,
+                device.device_uid,
+                status
+            ))
+        })
+        .collect()
+}
+
+/// Fastboot and most flashing tools need the WinUSB driver service.
+#[cfg(target_os = "windows")]
+pub(crate) fn check_winusb_service() -> Vec<String> {
+    let mut cmd = Command::new("sc");
+    cmd.args(["query", "WinUSB"]);
+    match run_command_with_timeout(cmd, CHECK_TIMEOUT) {
+        Ok(output) if output.status.success() => Vec::new(),
+        Ok(_) => vec!["The WinUSB driver service is not installed; fastboot devices may not be detected".to_string()],
+        Err(e) => vec![format!("Could not query the WinUSB driver service: {}", e)],
+    }
+}
+
+/// Run the checks for the current OS. An empty list means nothing to report.
+///
+/// Per-device driver problems come from the `driver_status` of the most
+/// recent scan.
+#[tauri::command(async)]
+pub fn platform_health_check(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
+    let mut warnings = check_macos_usb_entitlements();
+    #[cfg(target_os = "linux")]
+    warnings.extend(check_linux_usb_access());
+    #[cfg(target_os = "windows")]
+    warnings.extend(check_winusb_service());
+    let last = state
+        .last_scan
+        .lock()
+        .map_err(|_| "last_scan mutex poisoned".to_string())?;
+    warnings.extend(driver_warnings(&last));
+    Ok(warnings)
+}
+
+/// Driver packs for this OS that have a newer version in the update
+/// manifest at `manifest_url`.
+#[tauri::command]
+pub async fn check_driver_updates(manifest_url: String) -> Result<Vec<DriverPackUpdate>, String> {
+    tokio::task::spawn_blocking(move || DriverPackRegistry::new().check_updates(&manifest_url))
+        .await
+        .map_err(|e| format!("driver update check failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usb_entitlement_must_be_true() {
+        let granted = r#"<plist><dict>
+            <key>com.apple.security.device.usb</key>
+            <true/>
+        </dict></plist>"#;
+        assert!(entitlement_warnings(granted).is_empty());
+
+        let denied = granted.replace("<true/>", "<false/>");
+        let warnings = entitlement_warnings(&denied);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains(USB_ENTITLEMENT));
+    }
+
+    #[test]
+    fn pnputil_output_maps_to_driver_status() {
+        let output = "Microsoft PnP Utility\n\n\
+            Instance ID:                USB\\VID_18D1&PID_4EE0\\ABC123\n\
+            Device Description:         Android Bootloader Interface\n\
+            DEVPKEY_Device_Service [String]:\n\
+                WinUSB\n\n\
+            Instance ID:                USB\\VID_04E8&PID_6860\\R58M12\n\
+            DEVPKEY_Device_Service [String]:\n\
+                dg_ssudbus\n\n\
+            Instance ID:                USB\\VID_05AC&PID_12A8\\00008030\n\
+            Device Description:         Apple Mobile Device USB Composite Device\n";
+        assert_eq!(parse_pnputil_driver(output, 0x18d1, 0x4ee0), WindowsDriverStatus::WinUsbBound);
+        assert_eq!(
+            parse_pnputil_driver(output, 0x04e8, 0x6860),
+            WindowsDriverStatus::UnknownDriver("dg_ssudbus".to_string())
+        );
+        assert_eq!(parse_pnputil_driver(output, 0x05ac, 0x12a8), WindowsDriverStatus::NotBound);
+        assert_eq!(parse_pnputil_driver(output, 0x2717, 0xff48), WindowsDriverStatus::NotBound);
+        assert!(!WindowsDriverStatus::NotBound.is_libusb_compatible());
+    }
+
+    #[test]
+    fn icloud_container_entitlement_is_flagged() {
+        let plist = r#"<plist><dict>
+            <key>com.apple.security.device.usb</key><true/>
+            <key>com.apple.developer.icloud.container-identifiers</key><array/>
+        </dict></plist>"#;
+        let warnings = entitlement_warnings(plist);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains(ICLOUD_CONTAINER_ENTITLEMENT));
+    }
+}
@@ -0,0 +1,458 @@
+// Flash Preflight
+// Checks a flash job config against the tools, devices and images it needs before flash_start
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use bootforgeusb::model::{ConfirmedDeviceRecord, DeviceMode};
+use libbootforge::imaging::{minimum_image_flash_size, PartitionFS};
+use libbootforge::{DeviceCapabilities, DeviceStateStore};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::fastboot_tools::{self, FastbootDeviceInfo};
+use crate::image_inspect::{detect_image_header, read_header};
+use crate::{
+    check_flash_tool, check_partition_name, partition_allowlist, validate_flash_method, validate_flash_options,
+    AppState, FlashJobConfig, FlashPartition,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreflightSeverity {
+    /// flash_start would refuse the job, or the flash would fail
+    Error,
+    /// The job can start but may not do what the user expects
+    Warning,
+    Info,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightItem {
+    pub check_name: String,
+    pub passed: bool,
+    pub message: String,
+    /// `Info` for passed checks; how serious the failure is otherwise
+    pub severity: PreflightSeverity,
+}
+
+impl PreflightItem {
+    fn pass(check_name: &str, message: String) -> Self {
+        Self { check_name: check_name.to_string(), passed: true, message, severity: PreflightSeverity::Info }
+    }
+
+    fn fail(check_name: &str, severity: PreflightSeverity, message: String) -> Self {
+        Self { check_name: check_name.to_string(), passed: false, message, severity }
+    }
+
+    fn from_result(check_name: &str, result: Result<(), String>, ok_message: String) -> Self {
+        match result {
+            Ok(()) => Self::pass(check_name, ok_message),
+            Err(e) => Self::fail(check_name, PreflightSeverity::Error, e),
+        }
+    }
+}
+
+/// Mode a device must be in for `method`; `None` for edl, whose target is a
+/// serial port rather than a scanned USB device.
+fn expected_mode(method: &str) -> Option<DeviceMode> {
+    match method {
+        "fastboot" => Some(DeviceMode::AndroidFastbootConfirmed),
+        "sideload" => Some(DeviceMode::AndroidRecoveryAdbConfirmed),
+        "spflashtool" => Some(DeviceMode::AndroidMtkDownloadLikely),
+        _ => None,
+    }
+}
+
+fn tool_check(method: &str) -> PreflightItem {
+    PreflightItem::from_result("tool_available", check_flash_tool(method), format!("Tools for {} found", method))
+}
+
+fn method_options_check(config: &FlashJobConfig) -> PreflightItem {
+    PreflightItem::from_result(
+        "method_options",
+        validate_flash_options(config),
+        format!("Partitions and options are valid for {}", config.flashMethod),
+    )
+}
+
+/// The scanned device a flash serial refers to: by uid, tool serial or USB serial.
+fn find_device<'a>(records: &'a [ConfirmedDeviceRecord], serial: &str) -> Option<&'a ConfirmedDeviceRecord> {
+    records.iter().find(|d| {
+        d.device_uid == serial
+            || d.matched_tool_ids.iter().any(|id| id == serial)
+            || d.evidence.usb.serial.as_deref() == Some(serial)
+    })
+}
+
+fn device_checks(records: &[ConfirmedDeviceRecord], serial: &str, expected: &DeviceMode) -> Vec<PreflightItem> {
+    let Some(device) = find_device(records, serial) else {
+        return vec![PreflightItem::fail(
+            "device_found",
+            PreflightSeverity::Error,
+            format!("Device {} not found in the most recent scan", serial),
+        )];
+    };
+    let found = PreflightItem::pass("device_found", format!("Device {} found", serial));
+    let mode = if device.mode == expected.as_str() {
+        PreflightItem::pass("device_mode", format!("Device {} is in {}", serial, device.mode))
+    } else {
+        PreflightItem::fail(
+            "device_mode",
+            PreflightSeverity::Error,
+            format!("Device {} is in mode '{}', expected {}", serial, device.mode, expected.as_str()),
+        )
+    };
+    vec![found, mode]
+}
+
+fn partition_name_check(name: &str, allowed: &[String], strict_allowlist: bool, sideload: bool) -> PreflightItem {
+    match check_partition_name(name, allowed, strict_allowlist, sideload) {
+        Ok(None) => PreflightItem::pass("partition_name", format!("Partition {} is valid", name)),
+        Ok(Some(warning)) => PreflightItem::fail("partition_name", PreflightSeverity::Warning, warning),
+        Err(e) => PreflightItem::fail("partition_name", PreflightSeverity::Error, e),
+    }
+}
+
+/// Existence, size and header checks for one partition's image. Returns the
+/// image size alongside, 0 when the file cannot be read.
+fn image_checks(partition: &FlashPartition) -> (Vec<PreflightItem>, u64) {
+    let name = partition.name.trim();
+    let path = Path::new(partition.imagePath.trim());
+    let metadata = match std::fs::metadata(path) {
+        Ok(m) if m.is_file() => m,
+        _ => {
+            let message = if partition.imagePath.trim().is_empty() {
+                format!("imagePath missing for partition {}", name)
+            } else {
+                format!("Image file not found: {}", path.display())
+            };
+            return (vec![PreflightItem::fail("image_exists", PreflightSeverity::Error, message)], 0);
+        }
+    };
+    let mut items = vec![PreflightItem::pass("image_exists", format!("{} exists", path.display()))];
+
+    let size = metadata.len();
+    items.push(if size > 0 {
+        PreflightItem::pass("image_size", format!("{} is {} bytes", path.display(), size))
+    } else {
+        PreflightItem::fail("image_size", PreflightSeverity::Warning, format!("{} is empty", path.display()))
+    });
+
+    items.push(match read_header(path) {
+        Ok(header) => image_format_check(name, &header),
+        Err(e) => PreflightItem::fail("image_format", PreflightSeverity::Error, e),
+    });
+    (items, size)
+}
+
+/// Warns when the header identifies an image for a different partition.
+/// Sparse and raw images carry no partition hint and always pass.
+fn image_format_check(partition: &str, header: &[u8]) -> PreflightItem {
+    let (format, _, _, hint) = detect_image_header(header);
+    let base = partition
+        .strip_suffix("_a")
+        .or_else(|| partition.strip_suffix("_b"))
+        .unwrap_or(partition);
+    match hint {
+        Some(hint) if hint != base => PreflightItem::fail(
+            "image_format",
+            PreflightSeverity::Warning,
+            format!("Image for {} looks like a {} image ({})", partition, hint, format),
+        ),
+        _ => PreflightItem::pass("image_format", format!("Image for {} is {}", partition, format)),
+    }
+}
+
+/// `max-download-size` as reported by getvar: hex with a `0x` prefix, or decimal.
+fn parse_download_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+/// Lowercase alphanumerics only, so "OnePlus 7T" and "oneplus7t" compare equal.
+fn normalize_identity(value: &str) -> String {
+    value.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// Loose match: the brand and one of product/variant/board contain each other.
+fn brand_matches_device(brand: &str, info: &FastbootDeviceInfo) -> bool {
+    let brand = normalize_identity(brand);
+    if brand.is_empty() {
+        return false;
+    }
+    [Some(&info.product), info.variant.as_ref(), info.board.as_ref()]
+        .into_iter()
+        .flatten()
+        .map(|v| normalize_identity(v))
+        .any(|v| !v.is_empty() && (v.contains(&brand) || brand.contains(&v)))
+}
+
+/// The config's brand and slot against what the bootloader reports.
+fn device_identity_checks(config: &FlashJobConfig, serial: &str, info: &FastbootDeviceInfo) -> Vec<PreflightItem> {
+    let brand = config.deviceBrand.trim();
+    let mut items = vec![if brand.is_empty() {
+        PreflightItem::fail(
+            "device_brand",
+            PreflightSeverity::Warning,
+            format!("No device brand given; {} reports fastboot product '{}'", serial, info.product),
+        )
+    } else if !brand_matches_device(brand, info) {
+        PreflightItem::fail(
+            "device_brand",
+            PreflightSeverity::Warning,
+            format!(
+                "Device brand '{}' does not match fastboot product '{}' of {}; make sure the images are built for this device",
+                brand, info.product, serial
+            ),
+        )
+    } else {
+        PreflightItem::pass("device_brand", format!("{} is a {} ({})", serial, brand, info.product))
+    }];
+    if let (Some(_), Some(slot_count)) = (&config.activeSlot, info.slot_count) {
+        items.push(if slot_count < 2 {
+            PreflightItem::fail(
+                "active_slot",
+                PreflightSeverity::Warning,
+                format!("A slot was requested but {} reports slot-count {}", serial, slot_count),
+            )
+        } else {
+            PreflightItem::pass("active_slot", format!("{} has {} slots", serial, slot_count))
+        });
+    }
+    items
+}
+
+/// fastboot sends each image separately, so the largest image is compared to
+/// the device's download buffer. Larger images are split into sparse chunks,
+/// which the bootloader may not accept.
+fn download_size_check(serial: &str, vars: &HashMap<String, String>, largest_image: u64) -> PreflightItem {
+    let limit = vars
+        .get("max-download-size")
+        .ok_or_else(|| format!("{} did not report max-download-size", serial))
+        .and_then(|v| parse_download_size(v).ok_or_else(|| format!("Unrecognised max-download-size: {}", v)));
+    match limit {
+        Ok(limit) if largest_image <= limit => PreflightItem::pass(
+            "download_size",
+            format!("Largest image ({} bytes) fits max-download-size of {} ({} bytes)", largest_image, serial, limit),
+        ),
+        Ok(limit) => PreflightItem::fail(
+            "download_size",
+            PreflightSeverity::Warning,
+            format!(
+                "Largest image ({} bytes) exceeds max-download-size of {} ({} bytes); fastboot will split it",
+                largest_image, serial, limit
+            ),
+        ),
+        Err(e) => PreflightItem::fail("download_size", PreflightSeverity::Warning, e),
+    }
+}
+
+/// The image must fit the partition it is flashed to, measured as it will
+/// be written: sparse images expanded, filesystem images block-rounded.
+fn partition_space_check(serial: &str, partition: &FlashPartition) -> Option<PreflightItem> {
+    let name = partition.name.trim();
+    let path = Path::new(partition.imagePath.trim());
+    let needed = minimum_image_flash_size(path, PartitionFS::Unknown).ok()?;
+    Some(match fastboot_tools::fastboot_partition_size(serial, name) {
+        Ok(available) if needed <= available => PreflightItem::pass(
+            "partition_space",
+            format!("{} needs {} bytes; {} on {} has {}", path.display(), needed, name, serial, available),
+        ),
+        Ok(available) => PreflightItem::fail(
+            "partition_space",
+            PreflightSeverity::Error,
+            format!(
+                "{} needs {} bytes but {} on {} is only {} bytes",
+                path.display(),
+                needed,
+                name,
+                serial,
+                available
+            ),
+        ),
+        // Slotted names without a suffix and some bootloaders do not answer
+        Err(e) => PreflightItem::fail(
+            "partition_space",
+            PreflightSeverity::Warning,
+            format!("Space on {} not checked: {}", name, e),
+        ),
+    })
+}
+
+/// Bootloader checks for a device confirmed in fastboot mode, from one
+/// `getvar all`. The capabilities the variables imply are added to the
+/// stored state of the scanned device `device_uid`.
+fn fastboot_device_checks(
+    config: &FlashJobConfig,
+    serial: &str,
+    device_uid: &str,
+    largest_image: u64,
+    device_states: &DeviceStateStore,
+) -> Vec<PreflightItem> {
+    let vars = match fastboot_tools::fastboot_getvar_all(serial.to_string()) {
+        Ok(vars) => vars,
+        Err(e) => return vec![PreflightItem::fail("device_brand", PreflightSeverity::Warning, e)],
+    };
+    let capabilities = DeviceCapabilities::from_fastboot_vars(&vars);
+    device_states.update(device_uid, |s| s.capabilities.merge(&capabilities));
+
+    let mut items = match fastboot_tools::device_info_from_vars(serial, &vars) {
+        Ok(info) => device_identity_checks(config, serial, &info),
+        Err(e) => vec![PreflightItem::fail("device_brand", PreflightSeverity::Warning, e)],
+    };
+    items.push(download_size_check(serial, &vars, largest_image));
+    items.extend(config.partitions.iter().filter_map(|p| partition_space_check(serial, p)));
+    items
+}
+
+/// Run every check flash_start depends on, plus the bootloader's view of the
+/// device, and report them all so the UI can show a checklist before the
+/// user confirms the flash.
+#[tauri::command]
+pub async fn flash_preflight_check(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+    config: FlashJobConfig,
+) -> Result<Vec<PreflightItem>, String> {
+    let records = state
+        .last_scan
+        .lock()
+        .map_err(|_| "last_scan mutex poisoned".to_string())?
+        .clone();
+    let (allowed, strict_allowlist) = partition_allowlist(&state)?;
+    tokio::task::spawn_blocking(move || {
+        let state = app_handle.state::<AppState>();
+        run_checks(&config, &records, &allowed, strict_allowlist, &state.device_states)
+    })
+    .await
+    .map_err(|e| format!("Preflight check task failed: {}", e))
+}
+
+fn run_checks(
+    config: &FlashJobConfig,
+    records: &[ConfirmedDeviceRecord],
+    allowed: &[String],
+    strict_allowlist: bool,
+    device_states: &DeviceStateStore,
+) -> Vec<PreflightItem> {
+    let method = config.flashMethod.as_str();
+    if let Err(e) = validate_flash_method(method) {
+        return vec![PreflightItem::fail("flash_method", PreflightSeverity::Error, e)];
+    }
+
+    let mut items = vec![tool_check(method), method_options_check(config)];
+
+    let serials = match config.target_serials() {
+        Ok(serials) => serials,
+        Err(e) => {
+            items.push(PreflightItem::fail("device_found", PreflightSeverity::Error, e));
+            vec![]
+        }
+    };
+    let mut ready_for_fastboot = Vec::new();
+    for serial in &serials {
+        match expected_mode(method) {
+            Some(mode) => {
+                let checks = device_checks(records, serial, &mode);
+                if method == "fastboot" && checks.iter().all(|c| c.passed) {
+                    if let Some(device) = find_device(records, serial) {
+                        ready_for_fastboot.push((serial.as_str(), device.device_uid.as_str()));
+                    }
+                }
+                items.extend(checks);
+            }
+            None => items.push(PreflightItem::pass(
+                "device_found",
+                format!("{} is a serial port; edl targets are not matched against the USB scan", serial),
+            )),
+        }
+    }
+
+    let mut largest_image = 0;
+    for partition in &config.partitions {
+        items.push(partition_name_check(partition.name.trim(), allowed, strict_allowlist, method == "sideload"));
+        let (checks, size) = image_checks(partition);
+        largest_image = largest_image.max(size.max(partition.size));
+        items.extend(checks);
+    }
+
+    // Only devices already confirmed in fastboot mode are queried; getvar waits for absent ones.
+    for (serial, device_uid) in ready_for_fastboot {
+        items.extend(fastboot_device_checks(config, serial, device_uid, largest_image, device_states));
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_checks_cover_existence_size_and_format() {
+        let path = std::env::temp_dir().join(format!("bw-preflight-{}.img", std::process::id()));
+        std::fs::write(&path, b"AVB0").unwrap();
+        let partition = |name: &str, path: &Path| FlashPartition {
+            name: name.to_string(),
+            imagePath: path.to_string_lossy().to_string(),
+            size: 0,
+        };
+
+        let (vbmeta, size) = image_checks(&partition("vbmeta_a", &path));
+        let (boot, _) = image_checks(&partition("boot", &path));
+        std::fs::remove_file(&path).unwrap();
+        let (missing, missing_size) = image_checks(&partition("boot", &path));
+
+        assert_eq!(size, 4);
+        assert!(vbmeta.iter().all(|c| c.passed));
+        let format = boot.iter().find(|c| c.check_name == "image_format").unwrap();
+        assert!(!format.passed);
+        assert_eq!(format.severity, PreflightSeverity::Warning);
+        assert_eq!(missing_size, 0);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].severity, PreflightSeverity::Error);
+    }
+
+    #[test]
+    fn compares_brand_loosely() {
+        let info = FastbootDeviceInfo {
+            product: "OnePlus7T".to_string(),
+            variant: None,
+            board: Some("msmnile".to_string()),
+            slot_count: Some(1),
+        };
+        assert!(brand_matches_device("oneplus", &info));
+        assert!(brand_matches_device("MSM-Nile", &info));
+        assert!(!brand_matches_device("Samsung", &info));
+        assert!(!brand_matches_device("  ", &info));
+
+        let mut config: FlashJobConfig = serde_json::from_value(serde_json::json!({
+            "deviceSerial": "ABC123",
+            "deviceBrand": "Samsung",
+            "flashMethod": "fastboot",
+            "partitions": [],
+            "verifyAfterFlash": false,
+            "autoReboot": true,
+            "wipeUserData": false,
+            "activeSlot": "a"
+        }))
+        .unwrap();
+        let items = device_identity_checks(&config, "ABC123", &info);
+        assert!(items.iter().all(|i| !i.passed && i.severity == PreflightSeverity::Warning));
+        assert_eq!(items.len(), 2);
+        config.deviceBrand = "oneplus".to_string();
+        config.activeSlot = None;
+        assert!(device_identity_checks(&config, "ABC123", &info).iter().all(|i| i.passed));
+    }
+
+    #[test]
+    fn parses_hex_and_decimal_download_sizes() {
+        assert_eq!(parse_download_size("0x10000000"), Some(0x1000_0000));
+        assert_eq!(parse_download_size(" 536870912 "), Some(536_870_912));
+        assert_eq!(parse_download_size("unknown"), None);
+    }
+}
@@ -0,0 +1,159 @@
+// Boot Profile Prefetcher
+// Resolves the boot profile for every scanned device in the background so the UI can read it instantly
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bootforgeusb::model::ConfirmedDeviceRecord;
+use libbootforge::imaging::{BootProfile, BootProfileRegistry};
+use tauri::{AppHandle, Manager};
+
+use crate::AppState;
+
+/// Cache of `BootProfileRegistry::for_device` results keyed by device_uid.
+/// `None` entries record devices that have no matching profile.
+#[derive(Default)]
+pub(crate) struct ProfilePrefetcher {
+    cache: Arc<Mutex<HashMap<String, Option<BootProfile>>>>,
+    /// Bumped by every prefetch; results from an older one are discarded
+    generation: Arc<AtomicU64>,
+}
+
+impl ProfilePrefetcher {
+    /// Resolve profiles for `devices` on a background thread, using the
+    /// registry in `AppState` so imported profiles are taken into account.
+    pub(crate) fn prefetch_in_background(&self, app_handle: AppHandle, devices: Vec<ConfirmedDeviceRecord>) {
+        let cache = self.cache.clone();
+        let generation = self.generation.clone();
+        let this_generation = generation.fetch_add(1, Ordering::SeqCst) + 1;
+        std::thread::spawn(move || {
+            let state = app_handle.state::<AppState>();
+            let Ok(registry) = state.boot_profiles.lock() else {
+                return;
+            };
+            let resolved = resolve_profiles(&registry, &devices);
+            drop(registry);
+            store(&cache, &generation, this_generation, resolved);
+        });
+    }
+
+    /// Resolve profiles for `devices` on the calling thread.
+    #[cfg(test)]
+    fn prefetch(&self, registry: &BootProfileRegistry, devices: &[ConfirmedDeviceRecord]) {
+        let this_generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        store(&self.cache, &self.generation, this_generation, resolve_profiles(registry, devices));
+    }
+
+    /// The cached profile for a device, or an error if it has not been
+    /// prefetched by a scan yet.
+    pub(crate) fn get(&self, device_uid: &str) -> Result<Option<BootProfile>, String> {
+        let cache = self
+            .cache
+            .lock()
+            .map_err(|_| "profile cache mutex poisoned".to_string())?;
+        cache
+            .get(device_uid)
+            .cloned()
+            .ok_or_else(|| format!("No profile prefetched for {}; run a scan first", device_uid))
+    }
+}
+
+fn resolve_profiles(
+    registry: &BootProfileRegistry,
+    devices: &[ConfirmedDeviceRecord],
+) -> HashMap<String, Option<BootProfile>> {
+    devices
+        .iter()
+        .map(|device| (device.device_uid.clone(), registry.for_device(device).cloned()))
+        .collect()
+}
+
+/// Replace the cache with the latest scan, dropping devices that are gone.
+/// Does nothing if a newer prefetch has started since `this_generation`.
+fn store(
+    cache: &Mutex<HashMap<String, Option<BootProfile>>>,
+    generation: &AtomicU64,
+    this_generation: u64,
+    resolved: HashMap<String, Option<BootProfile>>,
+) {
+    if let Ok(mut cache) = cache.lock() {
+        if generation.load(Ordering::SeqCst) == this_generation {
+            *cache = resolved;
+        }
+    }
+}
+
+/// The boot profile prefetched for a device by the last bootforgeusb_scan.
+#[tauri::command]
+pub fn get_prefetched_profile(
+    state: tauri::State<'_, AppState>,
+    device_uid: String,
+) -> Result<Option<BootProfile>, String> {
+    state.profile_prefetcher.get(&device_uid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bootforgeusb::model::{Evidence, Platform, UsbTransportEvidence};
+
+    fn record(uid: &str, platform: Platform, product: &str) -> ConfirmedDeviceRecord {
+        ConfirmedDeviceRecord {
+            device_uid: uid.to_string(),
+            platform,
+            mode: String::new(),
+            confidence: 0.9,
+            evidence: Evidence {
+                usb: UsbTransportEvidence {
+                    vid: "0000".to_string(),
+                    pid: "0000".to_string(),
+                    product: Some(product.to_string()),
+                    bus: 1,
+                    address: 2,
+                    ..Default::default()
+                },
+                tools: HashMap::new(),
+            },
+            notes: vec![],
+            matched_tool_ids: vec![],
+            marketing_name: None,
+            driver_status: None,
+        }
+    }
+
+    #[test]
+    fn caches_profiles_per_device_from_the_latest_scan() {
+        let registry = BootProfileRegistry::new();
+        let prefetcher = ProfilePrefetcher::default();
+        assert!(prefetcher.get("pixel").is_err());
+
+        prefetcher.prefetch(
+            &registry,
+            &[
+                record("pixel", Platform::Android, "Pixel 8"),
+                record("mystery", Platform::Unknown, "Gadget"),
+            ],
+        );
+        assert_eq!(prefetcher.get("pixel").unwrap().unwrap().id, "google-pixel-android14");
+        assert!(prefetcher.get("mystery").unwrap().is_none());
+
+        // Unplugged devices drop out on the next scan
+        prefetcher.prefetch(&registry, &[record("iphone", Platform::Ios, "iPhone")]);
+        assert!(prefetcher.get("pixel").is_err());
+        assert_eq!(prefetcher.get("iphone").unwrap().unwrap().id, "iphone-ios");
+    }
+
+    #[test]
+    fn results_from_an_older_prefetch_are_dropped() {
+        let registry = BootProfileRegistry::new();
+        let prefetcher = ProfilePrefetcher::default();
+        let stale = resolve_profiles(&registry, &[record("pixel", Platform::Android, "Pixel 8")]);
+        let stale_generation = prefetcher.generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+        prefetcher.prefetch(&registry, &[record("iphone", Platform::Ios, "iPhone")]);
+        store(&prefetcher.cache, &prefetcher.generation, stale_generation, stale);
+        assert!(prefetcher.get("pixel").is_err());
+        assert!(prefetcher.get("iphone").is_ok());
+    }
+}
@@ -3,7 +3,9 @@
 
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, Context};
-use std::time::Duration;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 #[derive(Serialize)]
 pub struct PyInspectRequest<T> {
@@ -16,7 +18,6 @@ pub struct PyInspectRequest<T> {
 pub struct PyResponse<T> {
     pub ok: bool,
     pub data: Option<T>,
-    pub warnings: Vec<String>,
 }
 
 #[derive(Deserialize, Default)]
@@ -24,7 +25,6 @@ pub struct InspectFlags {
     pub activation_locked: Option<bool>,
     pub mdm_enrolled: Option<bool>,
     pub frp_locked: Option<bool>,
-    pub efi_locked: Option<bool>,
 }
 
 #[derive(Deserialize)]
@@ -34,10 +34,141 @@ pub struct HealthResponse {
     pub uptime_ms: u64,
 }
 
+/// Retry policy for transient Python backend failures (connection refused, timeout)
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u8,
+    pub base_delay_ms: u64,
+    pub backoff_factor: f32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 250,
+            backoff_factor: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `retry` (1-based): base * factor^(retry-1)
+    fn delay_for(&self, retry: u8) -> Duration {
+        let factor = self.backoff_factor.max(1.0).powi(i32::from(retry.saturating_sub(1)));
+        Duration::from_millis((self.base_delay_ms as f32 * factor) as u64)
+    }
+}
+
+/// True if the error chain contains a network-level reqwest failure worth retrying
+fn is_transient(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_connect() || e.is_timeout())
+    })
+}
+
+/// Run `f`, re-attempting transient failures with exponential backoff
+pub async fn with_retry<F, Fut, T>(policy: &RetryPolicy, mut f: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let max_attempts = policy.max_attempts.max(1);
+    let mut attempt: u8 = 1;
+    loop {
+        match f().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < max_attempts && is_transient(&e) => {
+                tokio::time::sleep(policy.delay_for(attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+/// Circuit breaker guarding the Python backend
+///
+/// Trips to `Open` after `failure_threshold` consecutive transient failures and
+/// rejects calls without touching the network. After `reset_timeout` a single
+/// trial call is allowed (`HalfOpen`); its outcome closes or re-opens the circuit.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    state: CircuitState,
+    consecutive_failures: u32,
+    failure_threshold: u32,
+    reset_timeout: Duration,
+    opened_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, reset_timeout: Duration) -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            failure_threshold: failure_threshold.max(1),
+            reset_timeout,
+            opened_at: None,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn state(&self) -> CircuitState {
+        self.state
+    }
+
+    /// Whether a request may proceed. Moves `Open` to `HalfOpen` once the reset timeout elapses.
+    pub fn allow_request(&mut self) -> bool {
+        match self.state {
+            CircuitState::Closed | CircuitState::HalfOpen => true,
+            CircuitState::Open => {
+                let elapsed = self.opened_at.map(|t| t.elapsed()).unwrap_or_default();
+                if elapsed >= self.reset_timeout {
+                    self.state = CircuitState::HalfOpen;
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        self.state = CircuitState::Closed;
+        self.consecutive_failures = 0;
+        self.opened_at = None;
+    }
+
+    pub fn record_failure(&mut self) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        if self.state == CircuitState::HalfOpen || self.consecutive_failures >= self.failure_threshold {
+            self.state = CircuitState::Open;
+            self.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(30))
+    }
+}
+
 /// Python worker HTTP client
 pub struct PyWorkerClient {
     base_url: String,
     client: reqwest::Client,
+    retry_policy: RetryPolicy,
+    breaker: Mutex<CircuitBreaker>,
 }
 
 impl PyWorkerClient {
@@ -49,11 +180,70 @@ impl PyWorkerClient {
                 .timeout(Duration::from_secs(30))
                 .build()
                 .expect("Failed to create HTTP client"),
+            retry_policy: RetryPolicy::default(),
+            breaker: Mutex::new(CircuitBreaker::default()),
         }
     }
 
+    /// Override the retry policy used for every request
+    #[cfg(test)]
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Current circuit breaker state
+    #[cfg(test)]
+    pub fn circuit_state(&self) -> CircuitState {
+        self.breaker
+            .lock()
+            .map(|b| b.state())
+            .unwrap_or(CircuitState::Closed)
+    }
+
+    /// Run a request through the circuit breaker and retry policy
+    async fn call<F, Fut, T>(&self, f: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let allowed = self
+            .breaker
+            .lock()
+            .map(|mut b| b.allow_request())
+            .unwrap_or(true);
+        if !allowed {
+            anyhow::bail!("Python backend circuit open: skipping request after repeated failures");
+        }
+
+        let result = with_retry(&self.retry_policy, f).await;
+
+        // Only network-level failures count against the backend; an HTTP error
+        // response still proves the backend is reachable.
+        if let Ok(mut breaker) = self.breaker.lock() {
+            match &result {
+                Err(e) if is_transient(e) => breaker.record_failure(),
+                _ => breaker.record_success(),
+            }
+        }
+        result
+    }
+
     /// Check Python backend health
     pub async fn health(&self) -> Result<HealthResponse> {
+        self.call(|| self.health_once()).await
+    }
+
+    /// Basic device inspection
+    pub async fn inspect_basic(
+        &self,
+        device_id: &str,
+        platform: &str,
+    ) -> Result<InspectFlags> {
+        self.call(|| self.inspect_basic_once(device_id, platform)).await
+    }
+
+    async fn health_once(&self) -> Result<HealthResponse> {
         let url = format!("{}/health", self.base_url);
         let res = self.client
             .get(&url)
@@ -71,8 +261,7 @@ impl PyWorkerClient {
         Ok(health)
     }
 
-    /// Basic device inspection
-    pub async fn inspect_basic(
+    async fn inspect_basic_once(
         &self,
         device_id: &str,
         platform: &str,
@@ -108,38 +297,100 @@ impl PyWorkerClient {
 
         Ok(py_res.data.unwrap_or_default())
     }
+}
 
-    /// Deep device inspection
-    pub async fn inspect_deep(
-        &self,
-        device_id: &str,
-        platform: &str,
-    ) -> Result<serde_json::Value> {
-        let req = PyInspectRequest {
-            device_id: device_id.to_string(),
-            platform: platform.to_string(),
-            payload: serde_json::json!({}),
-        };
-
-        let url = format!("{}/inspect/deep", self.base_url);
-        let res = self.client
-            .post(&url)
-            .json(&req)
-            .send()
-            .await
-            .context("Failed to send deep inspect request")?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
 
-        if !res.status().is_success() {
-            anyhow::bail!("Deep inspect failed: HTTP {}", res.status());
+    /// Minimal HTTP server answering every request with a fixed JSON body
+    async fn serve_json(listener: TcpListener, body: &'static str) {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else { return };
+            tokio::spawn(async move {
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
         }
+    }
 
-        let py_res: PyResponse<serde_json::Value> = res.json().await
-            .context("Failed to parse deep inspect response")?;
+    async fn unused_port() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap().port()
+    }
 
-        if !py_res.ok {
-            anyhow::bail!("Deep inspect returned error");
+    const HEALTH_BODY: &str = r#"{"status":"ok","version":"test","uptime_ms":5}"#;
+
+    #[tokio::test]
+    async fn health_succeeds_against_mock_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(serve_json(listener, HEALTH_BODY));
+
+        let client = PyWorkerClient::new(port);
+        let health = client.health().await.unwrap();
+        assert_eq!(health.version, "test");
+        assert_eq!(client.circuit_state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn retries_until_backend_starts_listening() {
+        let port = unused_port().await;
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            let listener = TcpListener::bind(("127.0.0.1", port)).await.unwrap();
+            serve_json(listener, HEALTH_BODY).await;
+        });
+
+        let client = PyWorkerClient::new(port).with_retry_policy(RetryPolicy {
+            max_attempts: 10,
+            base_delay_ms: 100,
+            backoff_factor: 1.0,
+        });
+        assert!(client.health().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn circuit_opens_after_consecutive_failures() {
+        let port = unused_port().await;
+        let client = PyWorkerClient::new(port).with_retry_policy(RetryPolicy {
+            max_attempts: 1,
+            base_delay_ms: 0,
+            backoff_factor: 1.0,
+        });
+
+        for _ in 0..5 {
+            assert!(client.health().await.is_err());
         }
+        assert_eq!(client.circuit_state(), CircuitState::Open);
+
+        let err = client.health().await.err().expect("circuit should reject the call");
+        assert!(err.to_string().contains("circuit open"));
+    }
+
+    #[test]
+    fn circuit_half_open_trial_decides_next_state() {
+        let mut breaker = CircuitBreaker::new(2, Duration::ZERO);
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        assert!(breaker.allow_request());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
 
-        Ok(py_res.data.unwrap_or(serde_json::json!({})))
+        assert!(breaker.allow_request());
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
     }
 }
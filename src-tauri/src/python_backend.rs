@@ -5,13 +5,18 @@ use std::process::{Child, Command, Stdio};
 use std::sync::Mutex;
 use std::path::PathBuf;
 use std::io::{BufRead, BufReader};
+use std::time::Duration;
 use anyhow::{Result, Context};
 
+use crate::net_utils::wait_for_port;
+
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
 static PY_PROCESS: Mutex<Option<Child>> = Mutex::new(None);
 
+const READY_TIMEOUT: Duration = Duration::from_secs(15);
+
 /// Launch Python backend service
 pub fn launch_python_backend(app_dir: &PathBuf) -> Result<u16> {
     // Find Python executable in bundled resources
@@ -46,21 +51,21 @@ pub fn launch_python_backend(app_dir: &PathBuf) -> Result<u16> {
     let mut child = cmd.spawn()
         .context("Failed to spawn Python backend")?;
     
-    // Read port from stdout (Python prints it)
+    // Read port from stdout (Python prints it); otherwise use the one we passed
+    let mut port = port;
     if let Some(stdout) = child.stdout.take() {
         let reader = BufReader::new(stdout);
         if let Some(Ok(line)) = reader.lines().next() {
             if let Ok(parsed_port) = line.trim().parse::<u16>() {
-                // Python printed the port, use it
-                let port = parsed_port;
-                *PY_PROCESS.lock().unwrap() = Some(child);
-                return Ok(port);
+                port = parsed_port;
             }
         }
     }
-    
-    // If Python didn't print port, use the one we passed
     *PY_PROCESS.lock().unwrap() = Some(child);
+    
+    if !wait_for_port(port, READY_TIMEOUT, Duration::from_millis(500)) {
+        eprintln!("[Python] Backend not accepting connections on port {} after {}s", port, READY_TIMEOUT.as_secs());
+    }
     Ok(port)
 }
 
@@ -0,0 +1,79 @@
+// Ring Buffer
+// Fixed-capacity FIFO that overwrites its oldest entry when full
+
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+pub struct RingBuffer<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    /// A capacity of 0 is treated as 1.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Append `item`, dropping the oldest entry if the buffer is full. O(1).
+    pub fn push(&mut self, item: T) {
+        if self.items.len() == self.capacity {
+            self.items.pop_front();
+        }
+        self.items.push_back(item);
+    }
+
+    /// Oldest to newest
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl<T: Clone> RingBuffer<T> {
+    /// Copy of the contents, oldest first
+    pub fn to_vec(&self) -> Vec<T> {
+        self.items.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overwrites_oldest_when_full() {
+        let mut buffer = RingBuffer::new(3);
+        assert!(buffer.is_empty());
+        for i in 0..5 {
+            buffer.push(i);
+        }
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.to_vec(), vec![2, 3, 4]);
+        assert_eq!(buffer.iter().next(), Some(&2));
+    }
+
+    #[test]
+    fn zero_capacity_keeps_latest_entry() {
+        let mut buffer = RingBuffer::new(0);
+        buffer.push("a");
+        buffer.push("b");
+        assert_eq!(buffer.capacity(), 1);
+        assert_eq!(buffer.to_vec(), vec!["b"]);
+    }
+}
@@ -0,0 +1,187 @@
+// MTK Scatter File
+// Parses MediaTek scatter files (v1.1+ layout) for SP Flash Tool jobs
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// One partition from the layout section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScatterEntry {
+    pub name: String,
+    /// Image file next to the scatter file; `None` for `NONE`
+    pub file: Option<String>,
+    pub linear_start_addr: u64,
+    pub is_download: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScatterFile {
+    /// e.g. `MT6765`
+    pub platform: Option<String>,
+    pub entries: Vec<ScatterEntry>,
+}
+
+/// `key: value` of a scatter line, with the list marker (`- `) removed.
+fn scatter_field(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.starts_with('#') {
+        return None;
+    }
+    let line = line.strip_prefix("- ").unwrap_or(line);
+    let (key, value) = line.split_once(':')?;
+    Some((key.trim(), value.trim()))
+}
+
+fn parse_address(value: &str) -> Option<u64> {
+    let value = value.trim();
+    match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+impl ScatterFile {
+    /// Parse the YAML-like layout written by MTK build systems. Every
+    /// `partition_index` line starts a new entry.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut scatter = ScatterFile::default();
+        let mut current: Option<ScatterEntry> = None;
+        for (number, line) in contents.lines().enumerate() {
+            let Some((key, value)) = scatter_field(line) else {
+                continue;
+            };
+            match key {
+                "platform" if scatter.platform.is_none() => scatter.platform = Some(value.to_string()),
+                "partition_index" => {
+                    scatter.entries.extend(current.take());
+                    current = Some(ScatterEntry {
+                        name: String::new(),
+                        file: None,
+                        linear_start_addr: 0,
+                        is_download: false,
+                    });
+                }
+                "partition_name" => {
+                    if let Some(entry) = current.as_mut() {
+                        entry.name = value.to_string();
+                    }
+                }
+                "file_name" => {
+                    if let Some(entry) = current.as_mut() {
+                        entry.file = (value != "NONE" && !value.is_empty()).then(|| value.to_string());
+                    }
+                }
+                "linear_start_addr" => {
+                    if let Some(entry) = current.as_mut() {
+                        entry.linear_start_addr = parse_address(value)
+                            .ok_or_else(|| format!("line {}: invalid linear_start_addr '{}'", number + 1, value))?;
+                    }
+                }
+                "is_download" => {
+                    if let Some(entry) = current.as_mut() {
+                        entry.is_download = value.eq_ignore_ascii_case("true");
+                    }
+                }
+                _ => {}
+            }
+        }
+        scatter.entries.extend(current);
+
+        if let Some(entry) = scatter.entries.iter().find(|e| e.name.is_empty()) {
+            return Err(format!(
+                "scatter entry at 0x{:x} has no partition_name",
+                entry.linear_start_addr
+            ));
+        }
+        if scatter.entries.is_empty() {
+            return Err("scatter file has no partitions (only the v1.1+ layout is supported)".to_string());
+        }
+        Ok(scatter)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read scatter file {}: {e}", path.display()))?;
+        Self::parse(&contents).map_err(|e| format!("Invalid scatter file {}: {}", path.display(), e))
+    }
+
+    pub fn entry(&self, name: &str) -> Option<&ScatterEntry> {
+        self.entries.iter().find(|e| e.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Rewrite scatter `contents` so SP Flash Tool downloads only the partitions
+/// in `images` (name -> image path) from the given files; every other entry
+/// gets `is_download: false`.
+pub fn select_partitions(contents: &str, images: &HashMap<String, String>) -> String {
+    let mut selected: Option<&String> = None;
+    let mut out = String::with_capacity(contents.len());
+    for line in contents.lines() {
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let rewritten = match scatter_field(line) {
+            Some(("partition_index", _)) => {
+                selected = None;
+                None
+            }
+            Some(("partition_name", name)) => {
+                selected = images
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                    .map(|(_, path)| path);
+                None
+            }
+            Some(("file_name", _)) => selected.map(|path| format!("{}file_name: {}", indent, path)),
+            Some(("is_download", _)) => Some(format!("{}is_download: {}", indent, selected.is_some())),
+            _ => None,
+        };
+        out.push_str(rewritten.as_deref().unwrap_or(line));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> String {
+        std::fs::read_to_string(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/MT6765_Android_scatter.txt"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_layout_entries() {
+        let scatter = ScatterFile::parse(&fixture()).unwrap();
+        assert_eq!(scatter.platform.as_deref(), Some("MT6765"));
+        assert_eq!(scatter.entries.len(), 5);
+
+        let preloader = scatter.entry("preloader").unwrap();
+        assert_eq!(preloader.file.as_deref(), Some("preloader_k65v1_64_bsp.bin"));
+        assert_eq!(preloader.linear_start_addr, 0);
+
+        let boot = scatter.entry("BOOT").unwrap();
+        assert_eq!(boot.linear_start_addr, 0x2_2d80_0000);
+        assert!(boot.is_download);
+
+        let nvram = scatter.entry("nvram").unwrap();
+        assert_eq!(nvram.file, None);
+        assert!(!nvram.is_download);
+
+        assert!(ScatterFile::parse("- general: MTK_PLATFORM_CFG\n").is_err());
+    }
+
+    #[test]
+    fn selecting_partitions_disables_the_rest() {
+        let images = HashMap::from([("boot".to_string(), "/images/boot-patched.img".to_string())]);
+        let rewritten = select_partitions(&fixture(), &images);
+        let scatter = ScatterFile::parse(&rewritten).unwrap();
+
+        let boot = scatter.entry("boot").unwrap();
+        assert!(boot.is_download);
+        assert_eq!(boot.file.as_deref(), Some("/images/boot-patched.img"));
+        assert_eq!(scatter.entries.iter().filter(|e| e.is_download).count(), 1);
+        assert_eq!(scatter.entry("lk").unwrap().file.as_deref(), Some("lk.img"));
+    }
+}
@@ -0,0 +1,235 @@
+// USB Throughput
+// Times real transfers to a device so slow flashes can be blamed on the cable/port or the device
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use bootforgeusb::model::{ConfirmedDeviceRecord, ConnectionSpeed};
+use serde::Serialize;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::adb_tools::adb_command;
+use crate::config::{adb_program, fastboot_program};
+use crate::{current_device_mode, run_command_with_timeout, AppState, CommandRunError};
+
+/// Transfers per direction; the median is reported
+const RUNS: usize = 3;
+const SIZE_MB_RANGE: (u32, u32) = (1, 256);
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const SHELL_TIMEOUT: Duration = Duration::from_secs(30);
+const REMOTE_TEST_FILE: &str = "/sdcard/throughput_test";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThroughputResult {
+    /// Median host → device rate, bytes per second
+    pub write_speed_bps: u64,
+    /// Median device → host rate; `None` in fastboot, which cannot read back
+    pub read_speed_bps: Option<u64>,
+    /// Negotiated USB speed from the last scan, e.g. "USB 2.0 High Speed (480 Mbps)"
+    pub usb_speed_class: String,
+}
+
+fn speed_class_label(speed: Option<ConnectionSpeed>) -> String {
+    match speed {
+        Some(ConnectionSpeed::Low) => "USB 1.0 Low Speed (1.5 Mbps)",
+        Some(ConnectionSpeed::Full) => "USB 1.1 Full Speed (12 Mbps)",
+        Some(ConnectionSpeed::High) => "USB 2.0 High Speed (480 Mbps)",
+        Some(ConnectionSpeed::Super) => "USB 3.0 SuperSpeed (5 Gbps)",
+        Some(ConnectionSpeed::SuperPlus) => "USB 3.1 SuperSpeed+ (10 Gbps)",
+        Some(ConnectionSpeed::SuperPlusPlus) => "USB 3.2 SuperSpeed+ (20 Gbps)",
+        None => "unknown",
+    }
+    .to_string()
+}
+
+/// Speed of the scanned device whose adb/fastboot serial is `serial`
+fn scanned_speed(records: &[ConfirmedDeviceRecord], serial: &str) -> Option<ConnectionSpeed> {
+    records
+        .iter()
+        .find(|r| {
+            r.matched_tool_ids.iter().any(|id| id == serial) || r.evidence.usb.serial.as_deref() == Some(serial)
+        })
+        .and_then(|r| r.evidence.usb.speed)
+}
+
+fn median(mut samples: Vec<u64>) -> u64 {
+    samples.sort_unstable();
+    samples.get(samples.len() / 2).copied().unwrap_or(0)
+}
+
+fn bytes_per_second(bytes: u64, elapsed: Duration) -> u64 {
+    (u128::from(bytes) * 1_000_000_000 / elapsed.as_nanos().max(1)) as u64
+}
+
+/// Write `size_mb` MiB of pseudo-random bytes to `path`. Random data keeps
+/// adb's transfer compression from inflating the numbers.
+fn write_test_file(path: &Path, size_mb: u32) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    let mut x: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut chunk = vec![0u8; 1024 * 1024];
+    for _ in 0..size_mb {
+        for word in chunk.chunks_exact_mut(8) {
+            // xorshift64
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            word.copy_from_slice(&x.to_le_bytes());
+        }
+        file.write_all(&chunk)?;
+    }
+    file.sync_all()
+}
+
+/// Run `cmd` and return how long it took, or why it failed.
+fn timed(cmd: Command, what: &str) -> Result<Duration, String> {
+    let started = Instant::now();
+    let output = run_command_with_timeout(cmd, TRANSFER_TIMEOUT).map_err(|e| match e {
+        CommandRunError::Spawn(_) => format!("{} not found in PATH: {e}", what),
+        _ => format!("{} failed: {e}", what),
+    })?;
+    let elapsed = started.elapsed();
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}{}",
+            what,
+            String::from_utf8_lossy(&output.stdout).trim(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(elapsed)
+}
+
+/// `fastboot stage` uploads into the bootloader's download buffer without
+/// writing any partition, so the device is left exactly as it was.
+fn measure_fastboot(serial: &str, local: &Path, bytes: u64) -> Result<ThroughputResult, String> {
+    let mut writes = Vec::with_capacity(RUNS);
+    for _ in 0..RUNS {
+        let mut cmd = Command::new(fastboot_program());
+        cmd.arg("-s").arg(serial).arg("stage").arg(local);
+        #[cfg(target_os = "windows")]
+        {
+            cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+        }
+        writes.push(bytes_per_second(bytes, timed(cmd, "fastboot stage")?));
+    }
+    Ok(ThroughputResult {
+        write_speed_bps: median(writes),
+        read_speed_bps: None,
+        usb_speed_class: String::new(),
+    })
+}
+
+fn measure_adb(serial: &str, local: &Path, pulled: &Path, bytes: u64) -> Result<ThroughputResult, String> {
+    let mut writes = Vec::with_capacity(RUNS);
+    let mut reads = Vec::with_capacity(RUNS);
+    for _ in 0..RUNS {
+        let mut push = adb_command(serial);
+        push.arg("push").arg(local).arg(REMOTE_TEST_FILE);
+        writes.push(bytes_per_second(bytes, timed(push, "adb push")?));
+
+        let mut pull = adb_command(serial);
+        pull.arg("pull").arg(REMOTE_TEST_FILE).arg(pulled);
+        reads.push(bytes_per_second(bytes, timed(pull, "adb pull")?));
+    }
+    Ok(ThroughputResult {
+        write_speed_bps: median(writes),
+        read_speed_bps: Some(median(reads)),
+        usb_speed_class: String::new(),
+    })
+}
+
+fn remove_remote_test_file(serial: &str) {
+    let mut cmd = adb_command(serial);
+    cmd.args(["shell", "rm", "-f", REMOTE_TEST_FILE]);
+    if let Err(e) = run_command_with_timeout(cmd, SHELL_TIMEOUT) {
+        eprintln!("[Tauri] Failed to remove {} from {}: {}", REMOTE_TEST_FILE, serial, e);
+    }
+}
+
+/// Measure transfer speed to a device over its current USB connection.
+///
+/// A `size_mb` MiB file (1–256) is sent three times and the median rate is
+/// reported. In ADB mode it is pushed to `/sdcard/throughput_test` and pulled
+/// back, then deleted from the device. In fastboot mode it is sent with
+/// `fastboot stage`, which only fills RAM, so there is no read speed.
+/// Compare the result with `usb_speed_class`: a USB 3 link near USB 2 rates
+/// points at the cable or hub, a slow rate on a fast link at the device.
+#[tauri::command(async)]
+pub fn measure_usb_throughput(
+    state: tauri::State<'_, AppState>,
+    device_serial: String,
+    size_mb: u32,
+) -> Result<ThroughputResult, String> {
+    let serial = device_serial.trim().to_string();
+    if serial.is_empty() {
+        return Err("device_serial is required".to_string());
+    }
+    let (min_mb, max_mb) = SIZE_MB_RANGE;
+    if !(min_mb..=max_mb).contains(&size_mb) {
+        return Err(format!("size_mb must be between {} and {}", min_mb, max_mb));
+    }
+    let mode = current_device_mode(&adb_program(), &fastboot_program(), &serial)
+        .ok_or_else(|| format!("Device {} is not listed by adb or fastboot", serial))?;
+    if mode != "adb" && mode != "fastboot" {
+        return Err(format!("Device {} is in adb state '{}'; it must be booted or in fastboot", serial, mode));
+    }
+
+    let work_dir: PathBuf = std::env::temp_dir().join(format!("bw-throughput-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&work_dir).map_err(|e| format!("Failed to create {}: {e}", work_dir.display()))?;
+    let local = work_dir.join("throughput_test.bin");
+    let bytes = u64::from(size_mb) * 1024 * 1024;
+
+    let result = write_test_file(&local, size_mb)
+        .map_err(|e| format!("Failed to create test file: {e}"))
+        .and_then(|_| {
+            if mode == "fastboot" {
+                measure_fastboot(&serial, &local, bytes)
+            } else {
+                let measured = measure_adb(&serial, &local, &work_dir.join("pulled.bin"), bytes);
+                remove_remote_test_file(&serial);
+                measured
+            }
+        });
+    let _ = std::fs::remove_dir_all(&work_dir);
+
+    let speed = state
+        .last_scan
+        .lock()
+        .map_err(|_| "last_scan mutex poisoned".to_string())
+        .map(|last| scanned_speed(&last, &serial))?;
+    result.map(|r| ThroughputResult {
+        usb_speed_class: speed_class_label(speed),
+        ..r
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_median_rate_of_the_runs() {
+        assert_eq!(bytes_per_second(50 * 1024 * 1024, Duration::from_secs(2)), 25 * 1024 * 1024);
+        assert_eq!(bytes_per_second(1, Duration::ZERO), 1_000_000_000);
+        assert_eq!(median(vec![30, 10, 20]), 20);
+        assert_eq!(median(vec![5, 500, 6]), 6);
+        assert_eq!(speed_class_label(Some(ConnectionSpeed::High)), "USB 2.0 High Speed (480 Mbps)");
+        assert_eq!(speed_class_label(None), "unknown");
+    }
+
+    #[test]
+    fn test_file_has_the_requested_size_and_is_not_compressible() {
+        let path = std::env::temp_dir().join(format!("bw-throughput-test-{}", uuid::Uuid::new_v4()));
+        write_test_file(&path, 2).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        assert_eq!(data.len(), 2 * 1024 * 1024);
+        assert!(data[..4096].iter().filter(|&&b| b == 0).count() < 64);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,228 @@
+// WebSocket Device Server
+// Streams hotplug events to local tools over ws://127.0.0.1:<BW_WS_PORT> and answers scan requests
+
+use std::sync::Arc;
+
+use bootforgeusb::model::ScanGroupResult;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::device_watcher::DeviceWatcher;
+use crate::{run_scan, AppState, DeviceHotplugEvent};
+
+/// Port to listen on; the server only starts when this is set
+pub(crate) const WS_PORT_ENV: &str = "BW_WS_PORT";
+
+/// Hotplug events buffered per client before a slow client starts missing them
+const EVENT_BUFFER: usize = 64;
+
+/// Frames sent to clients
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "camelCase")]
+enum ServerFrame {
+    Hotplug(DeviceHotplugEvent),
+    ScanResult(ScanGroupResult),
+    Error(String),
+}
+
+/// Requests clients may send, e.g. `{"type": "scan"}`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum ClientRequest {
+    Scan,
+}
+
+pub(crate) struct WebSocketDeviceServer {
+    app_handle: AppHandle,
+    token: String,
+    events: broadcast::Sender<DeviceHotplugEvent>,
+}
+
+impl WebSocketDeviceServer {
+    /// Start the server if `BW_WS_PORT` holds a port. Clients must present
+    /// `ws_token` from the workshop config, so without one the server stays off.
+    pub(crate) fn start_from_env(app_handle: AppHandle) {
+        let Ok(raw) = std::env::var(WS_PORT_ENV) else {
+            return;
+        };
+        let Ok(port) = raw.trim().parse::<u16>() else {
+            eprintln!("[WsServer] Ignoring {}={}: not a port number", WS_PORT_ENV, raw);
+            return;
+        };
+        let token = app_handle
+            .state::<AppState>()
+            .config
+            .lock()
+            .ok()
+            .and_then(|c| c.ws_token.clone());
+        let Some(token) = token else {
+            eprintln!("[WsServer] {} is set but no ws_token is configured; not starting", WS_PORT_ENV);
+            return;
+        };
+
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        let server = Arc::new(Self { app_handle, token, events });
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = server.run(port).await {
+                eprintln!("[WsServer] {}", e);
+            }
+        });
+    }
+
+    async fn run(self: Arc<Self>, port: u16) -> Result<(), String> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .map_err(|e| format!("Failed to bind 127.0.0.1:{}: {}", port, e))?;
+        let bound = listener.local_addr().map_err(|e| e.to_string())?.port();
+        if let Ok(mut guard) = self.app_handle.state::<AppState>().ws_server_port.lock() {
+            *guard = Some(bound);
+        }
+        println!("[WsServer] Listening on ws://127.0.0.1:{}", bound);
+        self.forward_hotplug_events();
+
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    eprintln!("[WsServer] Accept failed: {}", e);
+                    continue;
+                }
+            };
+            let server = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.serve_client(stream).await {
+                    eprintln!("[WsServer] Client {}: {}", peer, e);
+                }
+            });
+        }
+    }
+
+    /// Relay DeviceWatcher events into the broadcast channel every client reads from.
+    fn forward_hotplug_events(&self) {
+        let receiver = self.app_handle.state::<DeviceWatcher>().subscribe();
+        let events = self.events.clone();
+        std::thread::spawn(move || {
+            for event in receiver {
+                // An error only means no client is connected right now
+                let _ = events.send(event);
+            }
+        });
+    }
+
+    async fn serve_client(&self, stream: TcpStream) -> Result<(), String> {
+        let token = self.token.clone();
+        // The error type is fixed by tungstenite's handshake callback
+        #[allow(clippy::result_large_err)]
+        let authorize = move |request: &Request, response: Response| -> Result<Response, ErrorResponse> {
+            let authorization = request
+                .headers()
+                .get("authorization")
+                .and_then(|v| v.to_str().ok());
+            if is_authorized(request.uri().query(), authorization, &token) {
+                Ok(response)
+            } else {
+                let mut rejection = ErrorResponse::new(Some("invalid or missing token".to_string()));
+                *rejection.status_mut() = StatusCode::UNAUTHORIZED;
+                Err(rejection)
+            }
+        };
+        let socket = tokio_tungstenite::accept_hdr_async(stream, authorize)
+            .await
+            .map_err(|e| format!("handshake failed: {}", e))?;
+        let (mut outgoing, mut incoming) = socket.split();
+        let mut events = self.events.subscribe();
+
+        loop {
+            let frame = tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => ServerFrame::Hotplug(event),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        ServerFrame::Error(format!("{} hotplug events were dropped", missed))
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                message = incoming.next() => match message {
+                    Some(Ok(Message::Text(text))) => self.handle_request(&text).await,
+                    Some(Ok(Message::Close(_))) | None => break,
+                    // Pings are answered by tungstenite itself
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(e.to_string()),
+                },
+            };
+            let json = serde_json::to_string(&frame).map_err(|e| e.to_string())?;
+            outgoing.send(Message::text(json)).await.map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    async fn handle_request(&self, text: &str) -> ServerFrame {
+        match serde_json::from_str::<ClientRequest>(text) {
+            Ok(ClientRequest::Scan) => {
+                let app_handle = self.app_handle.clone();
+                let scan = tokio::task::spawn_blocking(move || run_scan(&app_handle.state::<AppState>())).await;
+                match scan {
+                    Ok(Ok(result)) => ServerFrame::ScanResult(result),
+                    Ok(Err(e)) => ServerFrame::Error(e),
+                    Err(e) => ServerFrame::Error(format!("Scan task failed: {}", e)),
+                }
+            }
+            Err(e) => ServerFrame::Error(format!("Unrecognized request: {}", e)),
+        }
+    }
+}
+
+/// A client is authorized by `?token=<token>` on the URL or an
+/// `Authorization: Bearer <token>` header.
+fn is_authorized(query: Option<&str>, authorization: Option<&str>, token: &str) -> bool {
+    let from_query = query
+        .into_iter()
+        .flat_map(|q| q.split('&'))
+        .filter_map(|pair| pair.strip_prefix("token="));
+    let from_header = authorization.and_then(|v| v.trim().strip_prefix("Bearer ")).map(str::trim);
+    from_query.chain(from_header).any(|candidate| tokens_match(candidate, token))
+}
+
+/// Compare without returning early, so response timing does not leak a prefix match.
+fn tokens_match(candidate: &str, token: &str) -> bool {
+    candidate.len() == token.len()
+        && candidate
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// The port the WebSocket device server is listening on, or `None` when it
+/// was not started.
+#[tauri::command]
+pub fn get_ws_server_status(state: tauri::State<'_, AppState>) -> Result<Option<u16>, String> {
+    state
+        .ws_server_port
+        .lock()
+        .map(|port| *port)
+        .map_err(|_| "ws_server_port mutex poisoned".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_the_token_from_the_query_or_bearer_header() {
+        let token = "s3cret-token";
+        assert!(is_authorized(Some("client=cli&token=s3cret-token"), None, token));
+        assert!(is_authorized(None, Some("Bearer s3cret-token"), token));
+        assert!(!is_authorized(Some("token=s3cret"), None, token));
+        assert!(!is_authorized(None, Some("Basic s3cret-token"), token));
+        assert!(!is_authorized(None, None, token));
+
+        assert_eq!(serde_json::from_str::<ClientRequest>(r#"{"type": "scan"}"#).unwrap(), ClientRequest::Scan);
+        assert!(serde_json::from_str::<ClientRequest>(r#"{"type": "flash"}"#).is_err());
+    }
+}